@@ -6,6 +6,7 @@ pub type Result<T> = std::result::Result<T, Error>;
 #[derive(thiserror::Error, Debug, Serialize, strum_macros::Display)]
 pub enum Error {
     PwdWithSchemeFailedParse,
+    PwdNotHtpasswd,
     FailSpawnBlockForValidate,
     FailSpawnBlockForHash,
     FailSpawnBlockForSalt,
@@ -12,6 +12,8 @@
 //! - The `Scheme` trait exposes sync functions `hash` and `validate` to be implemented for each scheme.
 //! - The two public async functions `hash_pwd(...)` and `validate_pwd(...)` call the scheme using
 //!   `spawn_blocking` to ensure that long hashing/validation processes do not hinder the execution of smaller tasks.
+//! - `hash_htpasswd(...)` hashes with a scheme nginx can check (`auth_basic_user_file`),
+//!   `htpasswd_hash(...)` gives back the hash to write in the user file.
 //! - Schemes are designed to be agnostic of whether they are in an async or sync context, hence they are async-free.
 
 // region:    --- Modules;
@@ -22,7 +24,7 @@ mod scheme;
 pub use self::error::{Error, Result};
 pub use scheme::SchemeStatus;
 
-use crate::pwd::scheme::{DEFAULT_SCHEME, Scheme, get_scheme};
+use crate::pwd::scheme::{DEFAULT_SCHEME, HTPASSWD_SCHEME, Scheme, get_scheme};
 use lazy_regex::regex_captures;
 use std::str::FromStr;
 use uuid::Uuid;
@@ -96,6 +98,30 @@ pub async fn validate_pwd(
     // validate_for_scheme(&scheme_name, to_hash, &hashed).await?;
     Ok(scheme_status)
 }
+
+/// Hash the password with the scheme readable by nginx, for the
+/// `auth_basic_user_file` of the access lists.
+pub async fn hash_htpasswd(to_hash: ContentToHash) -> Result<String> {
+    tokio::task::spawn_blocking(move || {
+        hash_for_scheme(HTPASSWD_SCHEME, to_hash)
+    })
+    .await
+    .map_err(|_| Error::FailSpawnBlockForHash)?
+}
+
+/// The `crypt(3)` hash of a `hash_htpasswd` password, as written after
+/// `user:` in the user file.
+pub fn htpasswd_hash(pwd_ref: &str) -> Result<String> {
+    let PwdParts {
+        scheme_name,
+        hashed,
+    } = pwd_ref.parse()?;
+    if scheme_name != HTPASSWD_SCHEME {
+        return Err(Error::PwdNotHtpasswd);
+    }
+
+    Ok(hashed)
+}
 // endregion: --- Public Functions
 
 // region:    --- Privates
@@ -172,5 +198,32 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_htpasswd_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_to_hash = ContentToHash {
+            content: "hello world".to_string(),
+            salt: Uuid::parse_str("f05e8961-d6ad-4086-9e78-a6de065e5453")?,
+        };
+
+        // -- Exec
+        let pwd_hashed = hash_htpasswd(fx_to_hash.clone()).await?;
+        let pwd_validate =
+            validate_pwd(fx_to_hash.clone(), pwd_hashed.clone()).await?;
+
+        // -- Check
+        assert!(matches!(pwd_validate, SchemeStatus::Outdated));
+        assert!(
+            htpasswd_hash(&pwd_hashed)?.starts_with("$6$f05e8961d6ad4086$")
+        );
+        let default_hashed = hash_pwd(fx_to_hash).await?;
+        assert!(matches!(
+            htpasswd_hash(&default_hashed),
+            Err(super::Error::PwdNotHtpasswd)
+        ));
+
+        Ok(())
+    }
 }
 // endregion: --- Tests
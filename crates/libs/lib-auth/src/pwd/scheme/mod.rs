@@ -3,6 +3,7 @@
 mod error;
 mod scheme_01;
 mod scheme_02;
+mod scheme_03;
 
 pub use self::error::{Error, Result};
 
@@ -12,6 +13,8 @@ use enum_dispatch::enum_dispatch;
 // endregion: --- Modules
 
 pub const DEFAULT_SCHEME: &str = "02";
+/// Scheme of the passwords checked by nginx, see `scheme_03`.
+pub const HTPASSWD_SCHEME: &str = "03";
 
 #[derive(Debug)]
 pub enum SchemeStatus {
@@ -30,12 +33,14 @@ pub trait Scheme {
 pub enum SchemeDispatcher {
     Scheme01(scheme_01::Scheme01),
     Scheme02(scheme_02::Scheme02),
+    Scheme03(scheme_03::Scheme03),
 }
 
 pub fn get_scheme(scheme_name: &str) -> Result<impl Scheme> {
     match scheme_name {
         "01" => Ok(SchemeDispatcher::Scheme01(scheme_01::Scheme01)),
         "02" => Ok(SchemeDispatcher::Scheme02(scheme_02::Scheme02)),
+        "03" => Ok(SchemeDispatcher::Scheme03(scheme_03::Scheme03)),
         _ => Err(Error::SchemeNotFound(scheme_name.to_string())),
     }
 }
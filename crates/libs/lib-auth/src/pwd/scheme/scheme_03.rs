@@ -0,0 +1,69 @@
+//! SHA-512 crypt (`$6$`), the strongest hash nginx reads from an
+//! `auth_basic_user_file` on every platform (through `crypt(3)`).
+//!
+//! No server key here, nginx could not check the passwords otherwise.
+//! Only used for the users of the access lists, see `hash_htpasswd`.
+
+use super::{Error, Result};
+use crate::pwd::ContentToHash;
+use crate::pwd::scheme::Scheme;
+use sha_crypt::{ROUNDS_DEFAULT, Sha512Params, sha512_check, sha512_crypt_b64};
+
+/// Salt length of the format (the first hex digits of the uuid salt).
+const SALT_LEN: usize = 16;
+
+pub struct Scheme03;
+
+impl Scheme for Scheme03 {
+    fn hash(&self, to_hash: &ContentToHash) -> Result<String> {
+        let salt = to_hash.salt.simple().to_string();
+        let salt = &salt[..SALT_LEN];
+        let params =
+            Sha512Params::new(ROUNDS_DEFAULT).map_err(|_| Error::Hash)?;
+
+        let hashed = sha512_crypt_b64(
+            to_hash.content.as_bytes(),
+            salt.as_bytes(),
+            &params,
+        )
+        .map_err(|_| Error::Hash)?;
+
+        Ok(format!("$6${salt}${hashed}"))
+    }
+
+    fn validate(&self, to_hash: &ContentToHash, pwd_ref: &str) -> Result<()> {
+        sha512_check(&to_hash.content, pwd_ref).map_err(|_| Error::PwdValidate)
+    }
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_scheme_03_hash_crypt_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_to_hash = ContentToHash {
+            content: "hello world".to_string(),
+            salt: Uuid::parse_str("f05e8961-d6ad-4086-9e78-a6de065e5453")?,
+        };
+        // From `openssl passwd -6 -salt f05e8961d6ad4086 "hello world"`.
+        let fx_res = "$6$f05e8961d6ad4086$k7E1qWqhlTCl8ZvGbqBeTczVCbjYmV0aLpeg7MGpPJrdF1l54M9wO0t.BYH2xb6ENYditDKrW0zlVa.nyyNtZ.";
+
+        // -- Exec
+        let scheme = Scheme03;
+        let res = scheme.hash(&fx_to_hash)?;
+
+        // -- Check
+        assert_eq!(res, fx_res);
+        scheme.validate(&fx_to_hash, &res)?;
+
+        Ok(())
+    }
+}
+// endregion: --- Tests
@@ -0,0 +1,647 @@
+//! Minimal ACME server for the tests, in the spirit of Pebble.
+//!
+//! Listens on `TEST_ACME_URI` and serves the directory at its path.
+//! Requests are checked like a real server does (nonce, url, signature),
+//! http-01 challenges are validated with a GET on `127.0.0.1:http01_port`,
+//! dns-01 ones in the records of the dns stand-in, and certificates are
+//! signed by a throwaway CA.
+
+use crate::_dev_utils::TestDnsRecords;
+use crate::config::core_config;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use lib_utils::b64::{b64u_decode, b64u_decode_to_string, b64u_encode};
+use p256::ecdsa::signature::Verifier;
+use p256::ecdsa::{DerSignature, Signature, SigningKey, VerifyingKey};
+use p256::pkcs8::LineEnding;
+use rand_core::OsRng;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use x509_cert::builder::{Builder, CertificateBuilder, Profile};
+use x509_cert::der::asn1::Ia5String;
+use x509_cert::der::{Decode, EncodePem};
+use x509_cert::ext::pkix::SubjectAltName;
+use x509_cert::ext::pkix::name::GeneralName;
+use x509_cert::name::Name;
+use x509_cert::request::CertReq;
+use x509_cert::serial_number::SerialNumber;
+use x509_cert::spki::SubjectPublicKeyInfoOwned;
+use x509_cert::time::Validity;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// Start the stand-in, returns the directory url.
+pub async fn start_acme_stand_in(
+    http01_port: u16,
+    dns_records: TestDnsRecords,
+) -> Result<String> {
+    let directory_url = core_config().TEST_ACME_URI.clone();
+    let addr = directory_url
+        .socket_addrs(|| None)?
+        .into_iter()
+        .next()
+        .ok_or("TEST_ACME_URI has no address")?;
+    let base = directory_url.origin().ascii_serialization();
+
+    let ca_key = SigningKey::random(&mut OsRng);
+    let ca_name = Name::from_str("CN=Stand-in Test CA")?;
+    let ca_cert = CertificateBuilder::new(
+        Profile::Root,
+        SerialNumber::from(1u32),
+        Validity::from_now(Duration::from_secs(3600))?,
+        ca_name.clone(),
+        SubjectPublicKeyInfoOwned::from_key(*ca_key.verifying_key())?,
+        &ca_key,
+    )?
+    .build::<DerSignature>()?
+    .to_pem(LineEnding::LF)?;
+
+    let state = Arc::new(StandIn {
+        base,
+        http01_port,
+        dns_records,
+        ca_key,
+        ca_name,
+        ca_cert,
+        inner: Mutex::new(Inner::default()),
+    });
+
+    let router = Router::new()
+        .route(directory_url.path(), get(directory))
+        .route("/nonce", get(new_nonce).head(new_nonce))
+        .route("/account", post(new_account))
+        .route("/order", post(new_order))
+        .route("/order/{id}", post(get_order))
+        .route("/authz/{id}/{idx}", post(get_authz))
+        .route("/chall/{id}/{idx}/{kind}", post(respond_challenge))
+        .route("/finalize/{id}", post(finalize))
+        .route("/cert/{id}", post(get_certificate))
+        .with_state(state);
+
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    tokio::spawn(async move { axum::serve(listener, router).await });
+
+    Ok(directory_url.to_string())
+}
+
+/// Serve `challenges` like the web-server does, returns the port.
+pub async fn serve_http01(
+    challenges: crate::acme::Http01Challenges,
+) -> Result<u16> {
+    let router = Router::new().route(
+        "/.well-known/acme-challenge/{token}",
+        get(|Path(token): Path<String>| async move {
+            challenges
+                .key_authorization(&token)
+                .ok_or(StatusCode::NOT_FOUND)
+        }),
+    );
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let port = listener.local_addr()?.port();
+    tokio::spawn(async move { axum::serve(listener, router).await });
+
+    Ok(port)
+}
+
+// region:    --- State
+
+struct StandIn {
+    /// e.g. `http://127.0.0.1:14000`
+    base: String,
+    http01_port: u16,
+    dns_records: TestDnsRecords,
+    ca_key: SigningKey,
+    ca_name: Name,
+    ca_cert: String,
+    inner: Mutex<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    next_id: u32,
+    nonces: HashSet<String>,
+    /// Account url to key and thumbprint.
+    accounts: HashMap<String, (VerifyingKey, String)>,
+    orders: HashMap<u32, StandInOrder>,
+}
+
+struct StandInOrder {
+    account: String,
+    domains: Vec<String>,
+    tokens: Vec<String>,
+    authz_status: Vec<&'static str>,
+    certificate: Option<String>,
+}
+
+impl StandInOrder {
+    fn status(&self) -> &'static str {
+        if self.certificate.is_some() {
+            "valid"
+        } else if self.authz_status.contains(&"invalid") {
+            "invalid"
+        } else if self.authz_status.iter().all(|status| *status == "valid") {
+            "ready"
+        } else {
+            "pending"
+        }
+    }
+}
+
+// endregion: --- State
+
+// region:    --- Handlers
+
+#[derive(Deserialize)]
+struct Jws {
+    protected: String,
+    payload: String,
+    signature: String,
+}
+
+/// A verified request: the account url (empty for `newAccount`) and the
+/// payload (`None` for POST-as-GET).
+struct Verified {
+    account: String,
+    payload: Option<Value>,
+}
+
+async fn directory(State(st): State<Arc<StandIn>>) -> Response {
+    Json(json!({
+        "newNonce": format!("{}/nonce", st.base),
+        "newAccount": format!("{}/account", st.base),
+        "newOrder": format!("{}/order", st.base),
+    }))
+    .into_response()
+}
+
+async fn new_nonce(State(st): State<Arc<StandIn>>) -> Response {
+    (StatusCode::OK, st.nonce_headers()).into_response()
+}
+
+async fn new_account(
+    State(st): State<Arc<StandIn>>,
+    Json(jws): Json<Jws>,
+) -> Response {
+    st.handle(&jws, "/account", |st, inner, _| {
+        let protected: Value =
+            serde_json::from_str(&b64u_decode_to_string(&jws.protected)?)?;
+        let jwk = &protected["jwk"];
+        let key = verifying_key(jwk)?;
+        let thumbprint = thumbprint(jwk)?;
+
+        inner.next_id += 1;
+        let account = format!("{}/account/{}", st.base, inner.next_id);
+        inner.accounts.insert(account.clone(), (key, thumbprint));
+
+        Ok((
+            StatusCode::CREATED,
+            Some(account),
+            json!({ "status": "valid" }).to_string(),
+        ))
+    })
+}
+
+async fn new_order(
+    State(st): State<Arc<StandIn>>,
+    Json(jws): Json<Jws>,
+) -> Response {
+    st.handle(&jws, "/order", |st, inner, verified| {
+        let payload = verified.payload.ok_or("payload required")?;
+        let domains: Vec<String> = payload["identifiers"]
+            .as_array()
+            .ok_or("identifiers required")?
+            .iter()
+            .filter_map(|id| id["value"].as_str().map(str::to_string))
+            .collect();
+
+        inner.next_id += 1;
+        let id = inner.next_id;
+        let order = StandInOrder {
+            account: verified.account,
+            tokens: domains
+                .iter()
+                .map(|_| uuid::Uuid::new_v4().simple().to_string())
+                .collect(),
+            authz_status: domains.iter().map(|_| "pending").collect(),
+            domains,
+            certificate: None,
+        };
+        let body = st.order_json(id, &order);
+        inner.orders.insert(id, order);
+
+        Ok((
+            StatusCode::CREATED,
+            Some(format!("{}/order/{id}", st.base)),
+            body,
+        ))
+    })
+}
+
+async fn get_order(
+    State(st): State<Arc<StandIn>>,
+    Path(id): Path<u32>,
+    Json(jws): Json<Jws>,
+) -> Response {
+    st.handle(&jws, &format!("/order/{id}"), |st, inner, verified| {
+        let order = inner.order(id, &verified.account)?;
+        Ok((StatusCode::OK, None, st.order_json(id, order)))
+    })
+}
+
+async fn get_authz(
+    State(st): State<Arc<StandIn>>,
+    Path((id, idx)): Path<(u32, usize)>,
+    Json(jws): Json<Jws>,
+) -> Response {
+    st.handle(
+        &jws,
+        &format!("/authz/{id}/{idx}"),
+        |st, inner, verified| {
+            let order = inner.order(id, &verified.account)?;
+            Ok((StatusCode::OK, None, st.authz_json(id, idx, order)?))
+        },
+    )
+}
+
+async fn respond_challenge(
+    State(st): State<Arc<StandIn>>,
+    Path((id, idx, kind)): Path<(u32, usize, String)>,
+    Json(jws): Json<Jws>,
+) -> Response {
+    // -- Validate, outside of the state lock.
+    let path = format!("/chall/{id}/{idx}/{kind}");
+    let target = {
+        let Ok(mut inner) = st.inner.lock() else {
+            return problem(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "serverInternal",
+            );
+        };
+        match st.verify(&mut inner, &jws, &path).and_then(|verified| {
+            let order = inner.order(id, &verified.account)?;
+            let (_, thumbprint) = &inner.accounts[&order.account];
+            let domain = order.domains.get(idx).ok_or("no such authz")?;
+            let token = &order.tokens[idx];
+            Ok((
+                domain.clone(),
+                token.clone(),
+                format!("{token}.{thumbprint}"),
+            ))
+        }) {
+            Ok(target) => target,
+            Err(ex) => return problem_with(&st, &mut inner, &ex.to_string()),
+        }
+    };
+    let (domain, token, expected) = target;
+    let valid = match kind.as_str() {
+        "http-01" => st.validate_http01(&domain, &token, &expected).await,
+        "dns-01" => st.validate_dns01(&domain, &expected),
+        _ => false,
+    };
+
+    let Ok(mut inner) = st.inner.lock() else {
+        return problem(StatusCode::INTERNAL_SERVER_ERROR, "serverInternal");
+    };
+    let Some(order) = inner.orders.get_mut(&id) else {
+        return problem(StatusCode::NOT_FOUND, "malformed");
+    };
+    order.authz_status[idx] = if valid { "valid" } else { "invalid" };
+    let body = json!({
+        "type": kind,
+        "url": format!("{}{path}", st.base),
+        "token": token,
+        "status": order.authz_status[idx],
+    })
+    .to_string();
+
+    let mut headers = st.nonce_headers_locked(&mut inner);
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json"),
+    );
+    (StatusCode::OK, headers, body).into_response()
+}
+
+async fn finalize(
+    State(st): State<Arc<StandIn>>,
+    Path(id): Path<u32>,
+    Json(jws): Json<Jws>,
+) -> Response {
+    st.handle(&jws, &format!("/finalize/{id}"), |st, inner, verified| {
+        let payload = verified.payload.ok_or("payload required")?;
+        let order = inner.order(id, &verified.account)?;
+        if order.status() != "ready" {
+            return Err("orderNotReady".into());
+        }
+
+        let csr = b64u_decode(payload["csr"].as_str().ok_or("csr required")?)?;
+        let csr = CertReq::from_der(&csr)?;
+        let certificate = st.sign_leaf(id, &order.domains, &csr)?;
+
+        let order = inner.orders.get_mut(&id).ok_or("no such order")?;
+        order.certificate = Some(certificate);
+        Ok((StatusCode::OK, None, st.order_json(id, order)))
+    })
+}
+
+async fn get_certificate(
+    State(st): State<Arc<StandIn>>,
+    Path(id): Path<u32>,
+    Json(jws): Json<Jws>,
+) -> Response {
+    st.handle(&jws, &format!("/cert/{id}"), |_, inner, verified| {
+        let order = inner.order(id, &verified.account)?;
+        let certificate = order.certificate.clone().ok_or("not issued")?;
+        Ok((StatusCode::OK, None, certificate))
+    })
+}
+
+// endregion: --- Handlers
+
+// region:    --- Support
+
+type Handled = (StatusCode, Option<String>, String);
+
+impl StandIn {
+    /// Verify `jws` for `path` and run `f`, errors become `malformed`
+    /// problems (or `badNonce`).
+    fn handle(
+        &self,
+        jws: &Jws,
+        path: &str,
+        f: impl FnOnce(&StandIn, &mut Inner, Verified) -> Result<Handled>,
+    ) -> Response {
+        let Ok(mut inner) = self.inner.lock() else {
+            return problem(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "serverInternal",
+            );
+        };
+
+        let res = self
+            .verify(&mut inner, jws, path)
+            .and_then(|verified| f(self, &mut inner, verified));
+        match res {
+            Ok((status, location, body)) => {
+                let mut headers = self.nonce_headers_locked(&mut inner);
+                let content_type = if path.starts_with("/cert/") {
+                    "application/pem-certificate-chain"
+                } else {
+                    "application/json"
+                };
+                headers.insert(
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_static(content_type),
+                );
+                if let Some(location) = location
+                    && let Ok(location) = HeaderValue::from_str(&location)
+                {
+                    headers.insert(header::LOCATION, location);
+                }
+                (status, headers, body).into_response()
+            }
+            Err(ex) => problem_with(self, &mut inner, &ex.to_string()),
+        }
+    }
+
+    async fn validate_http01(
+        &self,
+        domain: &str,
+        token: &str,
+        expected: &str,
+    ) -> bool {
+        let url = format!(
+            "http://127.0.0.1:{}/.well-known/acme-challenge/{token}",
+            self.http01_port
+        );
+        match reqwest::Client::new()
+            .get(url)
+            .header(reqwest::header::HOST, domain)
+            .send()
+            .await
+        {
+            Ok(res) if res.status().is_success() => {
+                res.text().await.is_ok_and(|body| body.trim() == expected)
+            }
+            _ => false,
+        }
+    }
+
+    fn validate_dns01(&self, domain: &str, expected: &str) -> bool {
+        let domain = domain.strip_prefix("*.").unwrap_or(domain);
+        let digest = b64u_encode(Sha256::digest(expected));
+
+        self.dns_records
+            .txt(&format!("_acme-challenge.{domain}"))
+            .contains(&digest)
+    }
+
+    fn verify(
+        &self,
+        inner: &mut Inner,
+        jws: &Jws,
+        path: &str,
+    ) -> Result<Verified> {
+        let protected: Value =
+            serde_json::from_str(&b64u_decode_to_string(&jws.protected)?)?;
+
+        let nonce = protected["nonce"].as_str().unwrap_or_default();
+        if !inner.nonces.remove(nonce) {
+            return Err("badNonce".into());
+        }
+        if protected["url"].as_str() != Some(&format!("{}{path}", self.base)) {
+            return Err("url mismatch".into());
+        }
+
+        let (account, key) = match protected["kid"].as_str() {
+            Some(kid) => {
+                let (key, _) =
+                    inner.accounts.get(kid).ok_or("accountDoesNotExist")?;
+                (kid.to_string(), *key)
+            }
+            None => (String::new(), verifying_key(&protected["jwk"])?),
+        };
+        let signature = Signature::from_slice(&b64u_decode(&jws.signature)?)?;
+        key.verify(
+            format!("{}.{}", jws.protected, jws.payload).as_bytes(),
+            &signature,
+        )?;
+
+        let payload = if jws.payload.is_empty() {
+            None
+        } else {
+            Some(serde_json::from_str(&b64u_decode_to_string(&jws.payload)?)?)
+        };
+
+        Ok(Verified { account, payload })
+    }
+
+    fn nonce_headers(&self) -> HeaderMap {
+        match self.inner.lock() {
+            Ok(mut inner) => self.nonce_headers_locked(&mut inner),
+            Err(_) => HeaderMap::new(),
+        }
+    }
+
+    fn nonce_headers_locked(&self, inner: &mut Inner) -> HeaderMap {
+        let nonce = uuid::Uuid::new_v4().simple().to_string();
+        let mut headers = HeaderMap::new();
+        if let Ok(value) = HeaderValue::from_str(&nonce) {
+            headers.insert("replay-nonce", value);
+        }
+        inner.nonces.insert(nonce);
+        headers
+    }
+
+    fn order_json(&self, id: u32, order: &StandInOrder) -> String {
+        let mut json = json!({
+            "status": order.status(),
+            "identifiers": order
+                .domains
+                .iter()
+                .map(|domain| json!({ "type": "dns", "value": domain }))
+                .collect::<Vec<_>>(),
+            "authorizations": (0..order.domains.len())
+                .map(|idx| format!("{}/authz/{id}/{idx}", self.base))
+                .collect::<Vec<_>>(),
+            "finalize": format!("{}/finalize/{id}", self.base),
+        });
+        if order.certificate.is_some() {
+            json["certificate"] = json!(format!("{}/cert/{id}", self.base));
+        }
+        json.to_string()
+    }
+
+    fn authz_json(
+        &self,
+        id: u32,
+        idx: usize,
+        order: &StandInOrder,
+    ) -> Result<String> {
+        let domain = order.domains.get(idx).ok_or("no such authz")?;
+        let status = order.authz_status[idx];
+        // As Let's Encrypt: no http-01 for a wildcard.
+        let (value, kinds) = match domain.strip_prefix("*.") {
+            Some(value) => (value, vec!["dns-01"]),
+            None => (domain.as_str(), vec!["http-01", "dns-01"]),
+        };
+        let challenges: Vec<Value> = kinds
+            .into_iter()
+            .map(|kind| {
+                let mut challenge = json!({
+                    "type": kind,
+                    "url": format!("{}/chall/{id}/{idx}/{kind}", self.base),
+                    "token": order.tokens[idx],
+                    "status": status,
+                });
+                if status == "invalid" {
+                    challenge["error"] = json!({
+                        "type": "urn:ietf:params:acme:error:unauthorized",
+                        "detail": "key authorization mismatch",
+                    });
+                }
+                challenge
+            })
+            .collect();
+
+        Ok(json!({
+            "status": status,
+            "identifier": { "type": "dns", "value": value },
+            "wildcard": domain.starts_with("*."),
+            "challenges": challenges,
+        })
+        .to_string())
+    }
+
+    /// The leaf for the key of `csr`, followed by the CA.
+    fn sign_leaf(
+        &self,
+        id: u32,
+        domains: &[String],
+        csr: &CertReq,
+    ) -> Result<String> {
+        let alt_names = domains
+            .iter()
+            .map(|domain| Ia5String::new(domain).map(GeneralName::DnsName))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        let mut builder = CertificateBuilder::new(
+            Profile::Leaf {
+                issuer: self.ca_name.clone(),
+                enable_key_agreement: false,
+                enable_key_encipherment: false,
+            },
+            SerialNumber::from(id + 1),
+            Validity::from_now(Duration::from_secs(90 * 24 * 3600))?,
+            csr.info.subject.clone(),
+            csr.info.public_key.clone(),
+            &self.ca_key,
+        )?;
+        builder.add_extension(&SubjectAltName(alt_names))?;
+        let leaf = builder.build::<DerSignature>()?.to_pem(LineEnding::LF)?;
+
+        Ok(format!("{leaf}{}", self.ca_cert))
+    }
+}
+
+impl Inner {
+    fn order(&self, id: u32, account: &str) -> Result<&StandInOrder> {
+        self.orders
+            .get(&id)
+            .filter(|order| order.account == account)
+            .ok_or_else(|| "no such order".into())
+    }
+}
+
+fn problem(status: StatusCode, kind: &str) -> Response {
+    let body = json!({
+        "type": format!("urn:ietf:params:acme:error:{kind}"),
+        "detail": kind,
+    });
+    (status, Json(body)).into_response()
+}
+
+/// A problem response, with a fresh nonce like every other response.
+fn problem_with(st: &StandIn, inner: &mut Inner, detail: &str) -> Response {
+    let kind = if detail == "badNonce" {
+        "badNonce"
+    } else {
+        "malformed"
+    };
+    let body = json!({
+        "type": format!("urn:ietf:params:acme:error:{kind}"),
+        "detail": detail,
+    });
+    let headers = st.nonce_headers_locked(inner);
+    (StatusCode::BAD_REQUEST, headers, Json(body)).into_response()
+}
+
+fn verifying_key(jwk: &Value) -> Result<VerifyingKey> {
+    let x = b64u_decode(jwk["x"].as_str().ok_or("jwk x required")?)?;
+    let y = b64u_decode(jwk["y"].as_str().ok_or("jwk y required")?)?;
+    let point = p256::EncodedPoint::from_affine_coordinates(
+        x.as_slice().into(),
+        y.as_slice().into(),
+        false,
+    );
+    Ok(VerifyingKey::from_encoded_point(&point)?)
+}
+
+/// RFC 7638, computed independently of the client.
+fn thumbprint(jwk: &Value) -> Result<String> {
+    let x = jwk["x"].as_str().ok_or("jwk x required")?;
+    let y = jwk["y"].as_str().ok_or("jwk y required")?;
+    let jwk = format!(r#"{{"crv":"P-256","kty":"EC","x":"{x}","y":"{y}"}}"#);
+    Ok(b64u_encode(Sha256::digest(jwk.as_bytes())))
+}
+
+// endregion: --- Support
@@ -4,8 +4,8 @@ use sqlx::{Pool, Sqlite};
 use std::path::{Path, PathBuf};
 use tracing::info;
 
-const MIGRATION_DIR: &str = "db/migrations";
-const DEV_INITIAL_SEED_USER: &str = "db/fixtures/dev-seed-user.sql";
+const MIGRATION_DIR: &str = "db-sqlite/migrations";
+const DEV_INITIAL_SEED_USER: &str = "db-sqlite/fixtures/dev-seed-user.sql";
 
 pub async fn init_test_db(
     pool: Pool<Sqlite>,
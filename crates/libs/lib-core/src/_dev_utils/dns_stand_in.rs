@@ -0,0 +1,276 @@
+//! Minimal authoritative server for the tests: RFC 2136 updates of the
+//! TXT records of one zone, signed with one TSIG key (`hmac-sha256`), like
+//! BIND with an `update-policy`.
+//!
+//! Messages are parsed and TSIG verified independently of the client.
+//! Queries are not answered, the records are read with `TestDnsRecords`.
+
+use hmac::{Hmac, Mac};
+use lib_utils::b64::b64_decode;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::net::UdpSocket;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+const NOERROR: u8 = 0;
+const FORMERR: u8 = 1;
+const NOTIMP: u8 = 4;
+const NOTAUTH: u8 = 9;
+const NOTZONE: u8 = 10;
+
+/// TXT values by name, shared with the stand-in.
+#[derive(Debug, Clone, Default)]
+pub struct TestDnsRecords {
+    inner: Arc<Mutex<HashMap<String, Vec<String>>>>,
+}
+
+impl TestDnsRecords {
+    pub fn txt(&self, name: &str) -> Vec<String> {
+        self.inner
+            .lock()
+            .ok()
+            .and_then(|inner| inner.get(name).cloned())
+            .unwrap_or_default()
+    }
+}
+
+/// Start the stand-in on a free UDP port of the loopback, `secret` base64
+/// encoded.
+pub async fn start_dns_stand_in(
+    zone: &str,
+    key_name: &str,
+    secret: &str,
+) -> Result<(SocketAddr, TestDnsRecords)> {
+    let socket = UdpSocket::bind("127.0.0.1:0").await?;
+    let addr = socket.local_addr()?;
+    let records = TestDnsRecords::default();
+    let stand_in = DnsStandIn {
+        zone: zone.to_string(),
+        key_name: key_name.to_string(),
+        secret: b64_decode(secret)?,
+        records: records.clone(),
+    };
+
+    tokio::spawn(async move {
+        let mut buf = [0u8; 1024];
+        while let Ok((len, from)) = socket.recv_from(&mut buf).await {
+            let message = &buf[..len];
+            let rcode = stand_in.handle(message).unwrap_or(FORMERR);
+            if message.len() >= 4 {
+                let reply = [
+                    message[0],
+                    message[1],
+                    // Response, same opcode.
+                    0x80 | (message[2] & 0x78),
+                    rcode,
+                    0,
+                    0,
+                    0,
+                    0,
+                    0,
+                    0,
+                    0,
+                    0,
+                ];
+                let _ = socket.send_to(&reply, from).await;
+            }
+        }
+    });
+
+    Ok((addr, records))
+}
+
+struct DnsStandIn {
+    zone: String,
+    key_name: String,
+    secret: Vec<u8>,
+    records: TestDnsRecords,
+}
+
+/// A resource record of the update section.
+struct Update {
+    name: String,
+    kind: u16,
+    class: u16,
+    rdata: Vec<u8>,
+}
+
+impl DnsStandIn {
+    /// The RCODE of the response to `message`.
+    fn handle(&self, message: &[u8]) -> Result<u8> {
+        let mut reader = Reader {
+            buf: message,
+            pos: 0,
+        };
+        let _id = reader.u16()?;
+        let flags = reader.u16()?;
+        let counts = [reader.u16()?, reader.u16()?, reader.u16()?];
+        let additional = reader.u16()?;
+        if (flags >> 11) & 0xf != 5 {
+            return Ok(NOTIMP);
+        }
+
+        // -- Zone
+        let [1, prerequisites, updates] = counts else {
+            return Ok(FORMERR);
+        };
+        let zone = reader.name()?;
+        reader.u16()?;
+        reader.u16()?;
+        if zone != self.zone {
+            return Ok(NOTAUTH);
+        }
+
+        // -- Prerequisites and updates
+        for _ in 0..prerequisites {
+            reader.record()?;
+        }
+        let mut changes = Vec::new();
+        for _ in 0..updates {
+            changes.push(reader.record()?);
+        }
+
+        // -- TSIG
+        let tsig_start = reader.pos;
+        if additional != 1 || !self.verify_tsig(message, tsig_start)? {
+            return Ok(NOTAUTH);
+        }
+
+        // -- Apply
+        let Ok(mut records) = self.records.inner.lock() else {
+            return Ok(FORMERR);
+        };
+        for change in changes {
+            if !change.name.ends_with(&self.zone) || change.kind != 16 {
+                return Ok(NOTZONE);
+            }
+            let value = String::from_utf8(
+                change.rdata.get(1..).unwrap_or_default().to_vec(),
+            )?;
+            let values = records.entry(change.name).or_default();
+            match change.class {
+                1 => values.push(value),
+                254 => values.retain(|v| *v != value),
+                _ => return Ok(FORMERR),
+            }
+        }
+
+        Ok(NOERROR)
+    }
+
+    /// Check the TSIG record at `tsig_start`, the last one of `message`.
+    fn verify_tsig(&self, message: &[u8], tsig_start: usize) -> Result<bool> {
+        let mut reader = Reader {
+            buf: message,
+            pos: tsig_start,
+        };
+        let key_name = reader.name()?;
+        let (kind, class, ttl) = (reader.u16()?, reader.u16()?, reader.u32()?);
+        reader.u16()?;
+        let algorithm = reader.name()?;
+        let time = reader.bytes(6)?.to_vec();
+        let fudge = reader.u16()?;
+        let mac_len = reader.u16()? as usize;
+        let mac = reader.bytes(mac_len)?.to_vec();
+        if kind != 250
+            || class != 255
+            || ttl != 0
+            || key_name != self.key_name
+            || algorithm != "hmac-sha256"
+        {
+            return Ok(false);
+        }
+
+        let mut time_secs = [0u8; 8];
+        time_secs[2..].copy_from_slice(&time);
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        if u64::from_be_bytes(time_secs).abs_diff(now) > fudge as u64 {
+            return Ok(false);
+        }
+
+        // The message as signed: without the TSIG record.
+        let mut signed = message[..tsig_start].to_vec();
+        signed[11] -= 1;
+        let wire = |name: &str| {
+            let mut wire = Vec::new();
+            for label in name.split('.') {
+                wire.push(label.len() as u8);
+                wire.extend(label.as_bytes());
+            }
+            wire.push(0);
+            wire
+        };
+        let mut expected = Hmac::<Sha256>::new_from_slice(&self.secret)?;
+        expected.update(&signed);
+        expected.update(&wire(&key_name));
+        expected.update(&[0, 255, 0, 0, 0, 0]);
+        expected.update(&wire(&algorithm));
+        expected.update(&time);
+        expected.update(&fudge.to_be_bytes());
+        expected.update(&[0, 0, 0, 0]);
+
+        Ok(expected.verify_slice(&mac).is_ok())
+    }
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn bytes(&mut self, len: usize) -> Result<&[u8]> {
+        let bytes = self
+            .buf
+            .get(self.pos..self.pos + len)
+            .ok_or("message too short")?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        let bytes = self.bytes(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let bytes = self.bytes(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /// Uncompressed names only, lowercased.
+    fn name(&mut self) -> Result<String> {
+        let mut labels = Vec::new();
+        loop {
+            let len = self.bytes(1)?[0] as usize;
+            if len == 0 {
+                break;
+            }
+            if len > 63 {
+                return Err("compressed name".into());
+            }
+            labels.push(String::from_utf8(self.bytes(len)?.to_vec())?);
+        }
+        Ok(labels.join(".").to_lowercase())
+    }
+
+    fn record(&mut self) -> Result<Update> {
+        let name = self.name()?;
+        let kind = self.u16()?;
+        let class = self.u16()?;
+        self.u32()?;
+        let len = self.u16()? as usize;
+        let rdata = self.bytes(len)?.to_vec();
+
+        Ok(Update {
+            name,
+            kind,
+            class,
+            rdata,
+        })
+    }
+}
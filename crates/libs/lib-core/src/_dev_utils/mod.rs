@@ -1,15 +1,24 @@
 // region:    --- Modules
 
+mod acme_stand_in;
 mod dev_db;
+mod dns_stand_in;
+mod test_certificate;
 
 use crate::ctx::Ctx;
+use crate::model::proxy_host::{ForwardScheme, ProxyHostForCreate};
 use crate::model::user::UserForCreate;
 use crate::model::{self, ModelManager};
 use sqlx::{Pool, Sqlite};
 // use tokio::sync::OnceCell;
 use tracing::info;
 
+pub use acme_stand_in::{serve_http01, start_acme_stand_in};
 pub use dev_db::{init_test_db, pexec};
+pub use dns_stand_in::{TestDnsRecords, start_dns_stand_in};
+pub use test_certificate::{
+    new_test_certificate, new_test_certificate_between,
+};
 
 // endregion: --- Modules
 
@@ -50,3 +59,36 @@ pub async fn seed_user(
 }
 
 // endregion: --- User seed/clean
+
+// region:    --- Fixtures
+
+/// A proxy host of `domain` forwarding to `127.0.0.1:3000`, with every
+/// option off. Tests override the fields they need.
+pub fn fx_proxy_host_c(domain: &str) -> ProxyHostForCreate {
+    ProxyHostForCreate {
+        domain_names: vec![domain.to_string()],
+        forward_scheme: ForwardScheme::Http,
+        forward_host: "127.0.0.1".to_string(),
+        forward_port: 3000,
+        cache_assets: false,
+        block_exploits: false,
+        allow_websocket_upgrade: false,
+        ssl_forced: false,
+        http2_support: false,
+        hsts_enabled: false,
+        hsts_subdomains: false,
+        certificate_id: None,
+        access_list_id: None,
+        upstream_group_id: None,
+        locations: Vec::new(),
+        advanced: String::new(),
+        rate_limit: None,
+        rate_limit_burst: 0,
+        rate_limit_nodelay: false,
+        connection_limit: None,
+        health_check_path: None,
+        health_check_interval: 30,
+    }
+}
+
+// endregion: --- Fixtures
@@ -0,0 +1,79 @@
+//! Certificates signed by a throwaway CA, for the certificate store tests.
+
+use p256::ecdsa::{DerSignature, SigningKey};
+use p256::pkcs8::{EncodePrivateKey, LineEnding};
+use rand_core::OsRng;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+use x509_cert::builder::{Builder, CertificateBuilder, Profile};
+use x509_cert::der::EncodePem;
+use x509_cert::der::asn1::Ia5String;
+use x509_cert::ext::pkix::SubjectAltName;
+use x509_cert::ext::pkix::name::GeneralName;
+use x509_cert::name::Name;
+use x509_cert::serial_number::SerialNumber;
+use x509_cert::spki::SubjectPublicKeyInfoOwned;
+use x509_cert::time::{Time, Validity};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// A 90 days leaf for `domains` issued by `CN=Test CA`. Returns the chain
+/// (leaf then CA) and the leaf key, both PEM.
+pub fn new_test_certificate(domains: &[String]) -> Result<(String, String)> {
+    let now = SystemTime::now();
+    new_test_certificate_between(
+        domains,
+        now,
+        now + Duration::from_secs(90 * 24 * 3600),
+    )
+}
+
+/// Same as `new_test_certificate`, valid from `not_before` to `not_after`.
+pub fn new_test_certificate_between(
+    domains: &[String],
+    not_before: SystemTime,
+    not_after: SystemTime,
+) -> Result<(String, String)> {
+    let validity = Validity {
+        not_before: Time::try_from(not_before)?,
+        not_after: Time::try_from(not_after)?,
+    };
+
+    let ca_key = SigningKey::random(&mut OsRng);
+    let ca_name = Name::from_str("CN=Test CA")?;
+    let ca_cert = CertificateBuilder::new(
+        Profile::Root,
+        SerialNumber::from(1u32),
+        validity,
+        ca_name.clone(),
+        SubjectPublicKeyInfoOwned::from_key(*ca_key.verifying_key())?,
+        &ca_key,
+    )?
+    .build::<DerSignature>()?
+    .to_pem(LineEnding::LF)?;
+
+    let leaf_key = SigningKey::random(&mut OsRng);
+    let common_name = domains.first().ok_or("no domain")?;
+    let alt_names = domains
+        .iter()
+        .map(|domain| Ia5String::new(domain).map(GeneralName::DnsName))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    let mut builder = CertificateBuilder::new(
+        Profile::Leaf {
+            issuer: ca_name,
+            enable_key_agreement: false,
+            enable_key_encipherment: false,
+        },
+        SerialNumber::from(2u32),
+        validity,
+        Name::from_str(&format!("CN={common_name}"))?,
+        SubjectPublicKeyInfoOwned::from_key(*leaf_key.verifying_key())?,
+        &ca_key,
+    )?;
+    builder.add_extension(&SubjectAltName(alt_names))?;
+    let leaf = builder.build::<DerSignature>()?.to_pem(LineEnding::LF)?;
+
+    let leaf_key_pem = leaf_key.to_pkcs8_pem(LineEnding::LF)?.to_string();
+
+    Ok((format!("{leaf}{ca_cert}"), leaf_key_pem))
+}
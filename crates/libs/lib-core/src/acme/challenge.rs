@@ -0,0 +1,30 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// Pending http-01 challenges, `token` to key authorization.
+///
+/// The client adds the challenges of an order while it is validated, the
+/// web-server answers `/.well-known/acme-challenge/{token}` from the same
+/// (cloned) instance.
+#[derive(Debug, Clone, Default)]
+pub struct Http01Challenges {
+    inner: Arc<RwLock<HashMap<String, String>>>,
+}
+
+impl Http01Challenges {
+    pub fn key_authorization(&self, token: &str) -> Option<String> {
+        self.inner.read().ok()?.get(token).cloned()
+    }
+
+    pub(super) fn insert(&self, token: &str, key_authorization: String) {
+        if let Ok(mut inner) = self.inner.write() {
+            inner.insert(token.to_string(), key_authorization);
+        }
+    }
+
+    pub(super) fn remove(&self, token: &str) {
+        if let Ok(mut inner) = self.inner.write() {
+            inner.remove(token);
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use crate::acme::dns::{DnsProvider, TxtRecord};
+use crate::acme::{Error, Result};
+use async_trait::async_trait;
+use serde::Serialize;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::time::Instant;
+
+/// How long the user has to publish the records of an order.
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(3600);
+
+/// Records created by hand: the pending ones are listed in the UI, with
+/// the value to publish, until the user confirms them.
+///
+/// Like `Http01Challenges`, the renewal task and the web-server share
+/// (cloned) instances.
+#[derive(Debug, Clone, Default)]
+pub struct ManualDnsProvider {
+    inner: Arc<RwLock<Inner>>,
+    confirmed: Arc<Notify>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    next_id: u64,
+    records: Vec<ManualTxtRecord>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManualTxtRecord {
+    pub id: u64,
+    pub name: String,
+    pub value: String,
+    pub confirmed: bool,
+}
+
+impl ManualDnsProvider {
+    /// The records to publish, oldest first.
+    pub fn records(&self) -> Vec<ManualTxtRecord> {
+        self.inner
+            .read()
+            .map(|inner| inner.records.clone())
+            .unwrap_or_default()
+    }
+
+    /// Mark the record `id` as published, `false` if there is none.
+    pub fn confirm(&self, id: u64) -> bool {
+        let Ok(mut inner) = self.inner.write() else {
+            return false;
+        };
+        let Some(record) = inner.records.iter_mut().find(|r| r.id == id) else {
+            return false;
+        };
+        record.confirmed = true;
+        self.confirmed.notify_waiters();
+
+        true
+    }
+
+    /// The first of `records` not confirmed yet.
+    fn unconfirmed(&self, records: &[TxtRecord]) -> Option<TxtRecord> {
+        let inner = self.inner.read().ok()?;
+        records
+            .iter()
+            .find(|record| {
+                !inner.records.iter().any(|r| {
+                    r.confirmed
+                        && r.name == record.name
+                        && r.value == record.value
+                })
+            })
+            .cloned()
+    }
+}
+
+#[async_trait]
+impl DnsProvider for ManualDnsProvider {
+    async fn present(&self, record: &TxtRecord) -> Result<()> {
+        if let Ok(mut inner) = self.inner.write() {
+            inner.next_id += 1;
+            let id = inner.next_id;
+            inner.records.push(ManualTxtRecord {
+                id,
+                name: record.name.clone(),
+                value: record.value.clone(),
+                confirmed: false,
+            });
+        }
+
+        Ok(())
+    }
+
+    async fn cleanup(&self, record: &TxtRecord) -> Result<()> {
+        if let Ok(mut inner) = self.inner.write() {
+            inner
+                .records
+                .retain(|r| r.name != record.name || r.value != record.value);
+        }
+
+        Ok(())
+    }
+
+    /// Wait for the user to confirm every record, up to `CONFIRM_TIMEOUT`.
+    async fn wait_ready(&self, records: &[TxtRecord]) -> Result<()> {
+        let deadline = Instant::now() + CONFIRM_TIMEOUT;
+        loop {
+            // Created before the check, so no confirmation is missed.
+            let confirmed = self.confirmed.notified();
+            let Some(record) = self.unconfirmed(records) else {
+                return Ok(());
+            };
+            if tokio::time::timeout_at(deadline, confirmed).await.is_err() {
+                return Err(Error::DnsConfirmTimeout { name: record.name });
+            }
+        }
+    }
+}
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_manual_wait_ready_confirmed_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let provider = ManualDnsProvider::default();
+        let fx_records = [
+            TxtRecord::dns01("example.com", "token-1.thumbprint"),
+            TxtRecord::dns01("*.example.com", "token-2.thumbprint"),
+        ];
+        for record in &fx_records {
+            provider.present(record).await?;
+        }
+
+        // -- Exec
+        let waiting = tokio::spawn({
+            let provider = provider.clone();
+            let records = fx_records.to_vec();
+            async move { provider.wait_ready(&records).await }
+        });
+        let ids: Vec<u64> = provider.records().iter().map(|r| r.id).collect();
+        for id in ids {
+            assert!(provider.confirm(id));
+        }
+
+        // -- Check
+        tokio::time::timeout(Duration::from_secs(5), waiting).await???;
+        for record in &fx_records {
+            provider.cleanup(record).await?;
+        }
+        assert!(provider.records().is_empty());
+        assert!(!provider.confirm(1));
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -0,0 +1,95 @@
+//! dns-01 challenges (RFC 8555 section 8.4): a TXT record at
+//! `_acme-challenge.<domain>` holding the digest of the key authorization.
+//! The only challenge accepted for wildcard domains.
+//!
+//! Records are published by a `DnsProvider`:
+//!
+//! - `Rfc2136Provider`, dynamic updates sent to the authoritative server.
+//! - `ManualDnsProvider`, records shown in the UI and created by the user,
+//!   who confirms them once published.
+
+// region:    --- Modules
+
+mod manual;
+mod rfc2136;
+
+pub use self::manual::{ManualDnsProvider, ManualTxtRecord};
+pub use self::rfc2136::{Rfc2136Provider, TsigKey};
+
+use crate::acme::Result;
+use async_trait::async_trait;
+use lib_utils::b64::b64u_encode;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+// endregion: --- Modules
+
+/// Publishes the TXT records of the dns-01 challenges.
+#[async_trait]
+pub trait DnsProvider: Send + Sync {
+    /// Add `record`, next to the other values of its name.
+    async fn present(&self, record: &TxtRecord) -> Result<()>;
+
+    /// Remove `record`, once its challenge is done.
+    async fn cleanup(&self, record: &TxtRecord) -> Result<()>;
+
+    /// Wait until the presented `records` can be seen by the ACME server.
+    async fn wait_ready(&self, _records: &[TxtRecord]) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A TXT record of a dns-01 challenge.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TxtRecord {
+    /// Fully qualified, without the trailing dot.
+    pub name: String,
+    pub value: String,
+}
+
+impl TxtRecord {
+    /// The record validating `domain` (wildcard or not) for
+    /// `key_authorization`.
+    pub fn dns01(domain: &str, key_authorization: &str) -> Self {
+        let domain = domain.strip_prefix("*.").unwrap_or(domain);
+
+        TxtRecord {
+            name: format!("_acme-challenge.{domain}"),
+            value: b64u_encode(Sha256::digest(key_authorization)),
+        }
+    }
+}
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+
+    #[test]
+    fn test_txt_record_dns01_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        // Digest from `openssl dgst -sha256 -binary`, base64url encoded.
+        let fx_key_authorization = "evaGxfADs6pSRb2LAv9IZf17Dt3juxGJ-PCt92wr-oA.\
+            nysa0jUHR5tK5pW_-f7wxgS3jmY6S8VQJ4DwHwl6TmM";
+
+        // -- Exec
+        let wildcard = TxtRecord::dns01("*.example.org", fx_key_authorization);
+        let apex = TxtRecord::dns01("example.org", fx_key_authorization);
+
+        // -- Check
+        assert_eq!(wildcard.name, "_acme-challenge.example.org");
+        assert_eq!(wildcard, apex);
+        assert_eq!(
+            wildcard.value,
+            "i93oPrAHD1LjyTLEzgZr8oMQ9EAYmQMfGNtU2aLtj1k"
+        );
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -0,0 +1,369 @@
+//! RFC 2136 dynamic updates, e.g. to BIND or Knot, signed with a TSIG key
+//! (RFC 8945, `hmac-sha256` only).
+//!
+//! One UPDATE message over UDP per record, without prerequisites: the
+//! record is added next to the other values of its name, and only this
+//! value is deleted on cleanup.
+
+use crate::acme::dns::{DnsProvider, TxtRecord};
+use crate::acme::{Error, Result};
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use lib_utils::b64::b64_decode;
+use sha2::Sha256;
+use std::net::SocketAddr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::net::UdpSocket;
+
+const TYPE_SOA: u16 = 6;
+const TYPE_TXT: u16 = 16;
+const TYPE_TSIG: u16 = 250;
+const CLASS_IN: u16 = 1;
+/// In the update section, delete this value only.
+const CLASS_NONE: u16 = 254;
+const CLASS_ANY: u16 = 255;
+/// Opcode UPDATE, as set in the header flags.
+const OPCODE_UPDATE: u16 = 5 << 11;
+const FLAG_RESPONSE: u16 = 1 << 15;
+
+const TSIG_ALGORITHM: &str = "hmac-sha256";
+/// Clock skew allowed by the server, in seconds.
+const TSIG_FUDGE: u16 = 300;
+
+const RECORD_TTL: u32 = 60;
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A TSIG key, as in the `key` statement of BIND.
+#[derive(Debug, Clone)]
+pub struct TsigKey {
+    name: String,
+    secret: Vec<u8>,
+}
+
+impl TsigKey {
+    /// `secret` is base64 encoded, e.g. from `tsig-keygen`.
+    pub fn new(name: &str, secret: &str) -> Result<Self> {
+        let secret = b64_decode(secret)
+            .map_err(|_| Error::TsigKeyInvalid(name.to_string()))?;
+
+        Ok(TsigKey {
+            name: name.trim_end_matches('.').to_lowercase(),
+            secret,
+        })
+    }
+}
+
+pub struct Rfc2136Provider {
+    server: SocketAddr,
+    /// The zone updated, the records must be in it.
+    zone: String,
+    key: Option<TsigKey>,
+    /// Time for the secondaries to pick the records up.
+    propagation_delay: Duration,
+}
+
+impl Rfc2136Provider {
+    pub fn new(
+        server: SocketAddr,
+        zone: &str,
+        key: Option<TsigKey>,
+        propagation_delay: Duration,
+    ) -> Self {
+        Rfc2136Provider {
+            server,
+            zone: zone.trim_end_matches('.').to_lowercase(),
+            key,
+            propagation_delay,
+        }
+    }
+
+    async fn update(
+        &self,
+        record: &TxtRecord,
+        class: u16,
+        ttl: u32,
+    ) -> Result<()> {
+        let name = record.name.trim_end_matches('.').to_lowercase();
+        if name != self.zone && !name.ends_with(&format!(".{}", self.zone)) {
+            return Err(Error::DnsNotInZone {
+                name,
+                zone: self.zone.clone(),
+            });
+        }
+
+        let id: u16 = rand::random();
+        let mut message = update_message(id, &self.zone, record, class, ttl)?;
+        if let Some(key) = &self.key {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|time| time.as_secs())
+                .unwrap_or_default();
+            sign(&mut message, key, now)?;
+        }
+
+        let local = if self.server.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+        let socket = UdpSocket::bind(local).await?;
+        socket.connect(self.server).await?;
+        socket.send(&message).await?;
+
+        let mut response = [0u8; 512];
+        let len = tokio::time::timeout(TIMEOUT, socket.recv(&mut response))
+            .await
+            .map_err(|_| Error::DnsTimeout {
+                server: self.server.to_string(),
+            })??;
+
+        check_response(id, &response[..len], &self.server)
+    }
+}
+
+#[async_trait]
+impl DnsProvider for Rfc2136Provider {
+    async fn present(&self, record: &TxtRecord) -> Result<()> {
+        self.update(record, CLASS_IN, RECORD_TTL).await
+    }
+
+    async fn cleanup(&self, record: &TxtRecord) -> Result<()> {
+        self.update(record, CLASS_NONE, 0).await
+    }
+
+    async fn wait_ready(&self, _records: &[TxtRecord]) -> Result<()> {
+        tokio::time::sleep(self.propagation_delay).await;
+
+        Ok(())
+    }
+}
+
+// region:    --- Wire
+
+/// UPDATE of the TXT `record` in `zone`, added with `CLASS_IN` or deleted
+/// with `CLASS_NONE`.
+fn update_message(
+    id: u16,
+    zone: &str,
+    record: &TxtRecord,
+    class: u16,
+    ttl: u32,
+) -> Result<Vec<u8>> {
+    let mut message = Vec::with_capacity(512);
+
+    // -- Header: one zone, no prerequisite, one update.
+    for field in [id, OPCODE_UPDATE, 1, 0, 1, 0] {
+        message.extend(field.to_be_bytes());
+    }
+
+    // -- Zone
+    message.extend(encode_name(zone)?);
+    message.extend(TYPE_SOA.to_be_bytes());
+    message.extend(CLASS_IN.to_be_bytes());
+
+    // -- Update
+    let value = record.value.as_bytes();
+    let Ok(value_len) = u8::try_from(value.len()) else {
+        return Err(Error::DnsRecordInvalid(record.name.clone()));
+    };
+    message.extend(encode_name(&record.name)?);
+    message.extend(TYPE_TXT.to_be_bytes());
+    message.extend(class.to_be_bytes());
+    message.extend(ttl.to_be_bytes());
+    message.extend((value.len() as u16 + 1).to_be_bytes());
+    message.push(value_len);
+    message.extend(value);
+
+    Ok(message)
+}
+
+/// Append the TSIG record of `message`, signed at `time` (RFC 8945
+/// section 4.3).
+fn sign(message: &mut Vec<u8>, key: &TsigKey, time: u64) -> Result<()> {
+    let key_name = encode_name(&key.name)?;
+    let algorithm = encode_name(TSIG_ALGORITHM)?;
+    let time = &time.to_be_bytes()[2..];
+    let original_id = [message[0], message[1]];
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(&key.secret)
+        .map_err(|_| Error::TsigKeyInvalid(key.name.clone()))?;
+    mac.update(message);
+    // TSIG variables: no error and no other data.
+    mac.update(&key_name);
+    mac.update(&CLASS_ANY.to_be_bytes());
+    mac.update(&0u32.to_be_bytes());
+    mac.update(&algorithm);
+    mac.update(time);
+    mac.update(&TSIG_FUDGE.to_be_bytes());
+    mac.update(&[0; 4]);
+    let mac = mac.finalize().into_bytes();
+
+    let mut rdata = algorithm;
+    rdata.extend(time);
+    rdata.extend(TSIG_FUDGE.to_be_bytes());
+    rdata.extend((mac.len() as u16).to_be_bytes());
+    rdata.extend(mac);
+    rdata.extend(original_id);
+    rdata.extend([0; 4]);
+
+    message.extend(key_name);
+    message.extend(TYPE_TSIG.to_be_bytes());
+    message.extend(CLASS_ANY.to_be_bytes());
+    message.extend(0u32.to_be_bytes());
+    message.extend((rdata.len() as u16).to_be_bytes());
+    message.extend(rdata);
+
+    // One additional record.
+    message[11] += 1;
+
+    Ok(())
+}
+
+/// Uncompressed wire format of `name`.
+fn encode_name(name: &str) -> Result<Vec<u8>> {
+    let name = name.trim_end_matches('.');
+    let mut encoded = Vec::with_capacity(name.len() + 2);
+    for label in name.split('.') {
+        match u8::try_from(label.len()) {
+            Ok(len @ 1..=63) => {
+                encoded.push(len);
+                encoded.extend(label.as_bytes());
+            }
+            _ => return Err(Error::DnsRecordInvalid(name.to_string())),
+        }
+    }
+    encoded.push(0);
+    if encoded.len() > 255 {
+        return Err(Error::DnsRecordInvalid(name.to_string()));
+    }
+
+    Ok(encoded)
+}
+
+fn check_response(id: u16, response: &[u8], server: &SocketAddr) -> Result<()> {
+    let server = server.to_string();
+    if response.len() < 12 {
+        return Err(Error::DnsResponseInvalid { server });
+    }
+    let flags = u16::from_be_bytes([response[2], response[3]]);
+    if u16::from_be_bytes([response[0], response[1]]) != id
+        || flags & FLAG_RESPONSE == 0
+    {
+        return Err(Error::DnsResponseInvalid { server });
+    }
+
+    let rcode = match flags & 0x000f {
+        0 => return Ok(()),
+        1 => "FORMERR",
+        2 => "SERVFAIL",
+        3 => "NXDOMAIN",
+        4 => "NOTIMP",
+        5 => "REFUSED",
+        6 => "YXDOMAIN",
+        7 => "YXRRSET",
+        8 => "NXRRSET",
+        9 => "NOTAUTH",
+        10 => "NOTZONE",
+        _ => "UNKNOWN",
+    };
+
+    Err(Error::DnsUpdateRejected { server, rcode })
+}
+
+// endregion: --- Wire
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+    use crate::_dev_utils;
+
+    const FX_ZONE: &str = "example.com";
+    const FX_KEY_NAME: &str = "acme-update";
+    // "test-secret-for-the-dns-stand-in"
+    const FX_SECRET: &str = "dGVzdC1zZWNyZXQtZm9yLXRoZS1kbnMtc3RhbmQtaW4=";
+
+    #[tokio::test]
+    async fn test_rfc2136_present_cleanup_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let (server, records) =
+            _dev_utils::start_dns_stand_in(FX_ZONE, FX_KEY_NAME, FX_SECRET)
+                .await?;
+        let provider = Rfc2136Provider::new(
+            server,
+            FX_ZONE,
+            Some(TsigKey::new(FX_KEY_NAME, FX_SECRET)?),
+            Duration::ZERO,
+        );
+        let fx_record = TxtRecord::dns01("*.app.example.com", "token.thumb");
+
+        // -- Exec & Check
+        provider.present(&fx_record).await?;
+        assert_eq!(
+            records.txt("_acme-challenge.app.example.com"),
+            [fx_record.value.as_str()]
+        );
+
+        provider.cleanup(&fx_record).await?;
+        assert!(records.txt("_acme-challenge.app.example.com").is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rfc2136_present_bad_key_err() -> Result<()> {
+        // -- Setup & Fixtures
+        let (server, records) =
+            _dev_utils::start_dns_stand_in(FX_ZONE, FX_KEY_NAME, FX_SECRET)
+                .await?;
+        let provider = Rfc2136Provider::new(
+            server,
+            FX_ZONE,
+            // "another-secret"
+            Some(TsigKey::new(FX_KEY_NAME, "YW5vdGhlci1zZWNyZXQ=")?),
+            Duration::ZERO,
+        );
+        let fx_record = TxtRecord::dns01("app.example.com", "token.thumb");
+
+        // -- Exec
+        let res = provider.present(&fx_record).await;
+
+        // -- Check
+        assert!(matches!(
+            res,
+            Err(super::Error::DnsUpdateRejected {
+                rcode: "NOTAUTH",
+                ..
+            })
+        ));
+        assert!(records.txt("_acme-challenge.app.example.com").is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rfc2136_present_not_in_zone_err() -> Result<()> {
+        // -- Setup & Fixtures
+        let provider = Rfc2136Provider::new(
+            "127.0.0.1:53".parse()?,
+            FX_ZONE,
+            None,
+            Duration::ZERO,
+        );
+        let fx_record = TxtRecord::dns01("example.org", "token.thumb");
+
+        // -- Exec
+        let res = provider.present(&fx_record).await;
+
+        // -- Check
+        assert!(matches!(res, Err(super::Error::DnsNotInZone { .. })));
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -0,0 +1,91 @@
+use crate::acme::Problem;
+use crate::model;
+use serde::Serialize;
+use serde_with::{DisplayFromStr, serde_as};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[serde_as]
+#[derive(thiserror::Error, Debug, Serialize, strum_macros::Display)]
+pub enum Error {
+    // -- Protocol
+    /// Error document returned by the server.
+    Problem {
+        url: String,
+        status: u16,
+        problem: Problem,
+    },
+    MissingHeader {
+        url: String,
+        header: &'static str,
+    },
+    /// The server offers no challenge of the kind of the solver.
+    NoChallenge {
+        domain: String,
+        kind: &'static str,
+    },
+    AuthorizationInvalid {
+        domain: String,
+        problem: Option<Problem>,
+    },
+    OrderInvalid {
+        url: String,
+        problem: Option<Problem>,
+    },
+    PollTimeout {
+        url: String,
+    },
+
+    // -- Dns
+    DnsNotInZone {
+        name: String,
+        zone: String,
+    },
+    DnsRecordInvalid(String),
+    DnsTimeout {
+        server: String,
+    },
+    DnsResponseInvalid {
+        server: String,
+    },
+    /// RCODE of the UPDATE response, e.g. `NOTAUTH` for a bad TSIG.
+    DnsUpdateRejected {
+        server: String,
+        rcode: &'static str,
+    },
+    /// A manual record not confirmed in time.
+    DnsConfirmTimeout {
+        name: String,
+    },
+    TsigKeyInvalid(String),
+
+    // -- Keys
+    KeyInvalid(String),
+    CsrCantBuild(String),
+
+    // -- Modules
+    #[error(transparent)]
+    AcmeAccount(#[from] model::acme_account::Error),
+
+    // -- Externals
+    #[error(transparent)]
+    Http(
+        #[from]
+        #[serde_as(as = "DisplayFromStr")]
+        reqwest::Error,
+    ),
+
+    #[error(transparent)]
+    Io(
+        #[from]
+        #[serde_as(as = "DisplayFromStr")]
+        std::io::Error,
+    ),
+
+    #[error(transparent)]
+    SerdeJson(
+        #[from]
+        #[serde_as(as = "DisplayFromStr")]
+        serde_json::Error,
+    ),
+}
@@ -0,0 +1,195 @@
+//! Account key and JWS signing (RFC 8555 section 6.2).
+//!
+//! Only ES256 (ECDSA P-256 with SHA-256) is used, it is supported by
+//! every ACME server.
+
+use crate::acme::{Error, Result};
+use lib_utils::b64::b64u_encode;
+use p256::ecdsa::signature::Signer;
+use p256::ecdsa::{Signature, SigningKey};
+use p256::pkcs8::LineEnding;
+use p256::pkcs8::{DecodePrivateKey, EncodePrivateKey, EncodePublicKey};
+use rand_core::OsRng;
+use serde::Serialize;
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+
+/// `key_type` of the `acme_account` rows.
+pub const KEY_TYPE: &str = "ES256";
+
+pub struct AccountKey {
+    signing_key: SigningKey,
+}
+
+/// Flattened JWS JSON serialization.
+#[derive(Debug, Serialize)]
+pub struct Jws {
+    pub protected: String,
+    pub payload: String,
+    pub signature: String,
+}
+
+/// Who signs a request: the `jwk` for `newAccount`, the account url
+/// (`kid`) for everything else.
+pub enum KeyId<'a> {
+    Jwk,
+    Kid(&'a str),
+}
+
+impl AccountKey {
+    pub fn generate() -> Self {
+        AccountKey {
+            signing_key: SigningKey::random(&mut OsRng),
+        }
+    }
+
+    pub fn from_pkcs8_pem(pem: &str) -> Result<Self> {
+        let signing_key = SigningKey::from_pkcs8_pem(pem)
+            .map_err(|ex| Error::KeyInvalid(ex.to_string()))?;
+
+        Ok(AccountKey { signing_key })
+    }
+
+    pub fn private_key_pem(&self) -> Result<String> {
+        let pem = self
+            .signing_key
+            .to_pkcs8_pem(LineEnding::LF)
+            .map_err(|ex| Error::KeyInvalid(ex.to_string()))?;
+
+        Ok(pem.to_string())
+    }
+
+    pub fn public_key_pem(&self) -> Result<String> {
+        self.signing_key
+            .verifying_key()
+            .to_public_key_pem(LineEnding::LF)
+            .map_err(|ex| Error::KeyInvalid(ex.to_string()))
+    }
+
+    /// Public key as a JWK (RFC 7517).
+    pub fn jwk(&self) -> Value {
+        let (x, y) = self.coordinates();
+        json!({ "crv": "P-256", "kty": "EC", "x": x, "y": y })
+    }
+
+    /// JWK thumbprint (RFC 7638), used in key authorizations.
+    pub fn thumbprint(&self) -> String {
+        // Required members only, in lexicographic order, no whitespace.
+        let (x, y) = self.coordinates();
+        let jwk =
+            format!(r#"{{"crv":"P-256","kty":"EC","x":"{x}","y":"{y}"}}"#);
+        b64u_encode(Sha256::digest(jwk.as_bytes()))
+    }
+
+    /// `token.thumbprint`, the content expected by the server for an
+    /// http-01 challenge.
+    pub fn key_authorization(&self, token: &str) -> String {
+        format!("{token}.{}", self.thumbprint())
+    }
+
+    /// Sign `payload` for `url`. A `None` payload is a POST-as-GET.
+    pub fn sign(
+        &self,
+        key_id: KeyId,
+        nonce: &str,
+        url: &str,
+        payload: Option<&Value>,
+    ) -> Result<Jws> {
+        let mut protected = json!({
+            "alg": KEY_TYPE,
+            "nonce": nonce,
+            "url": url,
+        });
+        match key_id {
+            KeyId::Jwk => protected["jwk"] = self.jwk(),
+            KeyId::Kid(kid) => protected["kid"] = json!(kid),
+        }
+
+        let protected = b64u_encode(serde_json::to_vec(&protected)?);
+        let payload = match payload {
+            Some(payload) => b64u_encode(serde_json::to_vec(payload)?),
+            None => String::new(),
+        };
+        let signature: Signature = self
+            .signing_key
+            .sign(format!("{protected}.{payload}").as_bytes());
+
+        Ok(Jws {
+            protected,
+            payload,
+            signature: b64u_encode(signature.to_bytes()),
+        })
+    }
+
+    /// Base64url `x` and `y` of the public point.
+    fn coordinates(&self) -> (String, String) {
+        let point = self.signing_key.verifying_key().to_encoded_point(false);
+        // Uncompressed points always have both coordinates.
+        let x = point.x().map(b64u_encode).unwrap_or_default();
+        let y = point.y().map(b64u_encode).unwrap_or_default();
+        (x, y)
+    }
+}
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+    use lib_utils::b64::{b64u_decode, b64u_decode_to_string};
+    use p256::ecdsa::VerifyingKey;
+    use p256::ecdsa::signature::Verifier;
+
+    #[test]
+    fn test_account_key_pem_roundtrip_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_key = AccountKey::generate();
+
+        // -- Exec
+        let key = AccountKey::from_pkcs8_pem(&fx_key.private_key_pem()?)?;
+
+        // -- Check
+        assert_eq!(key.thumbprint(), fx_key.thumbprint());
+        assert!(
+            key.public_key_pem()?
+                .starts_with("-----BEGIN PUBLIC KEY-----")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sign_kid_verify_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_key = AccountKey::generate();
+        let fx_payload = json!({ "identifiers": [] });
+
+        // -- Exec
+        let jws = fx_key.sign(
+            KeyId::Kid("https://acme.test/acct/1"),
+            "nonce-1",
+            "https://acme.test/new-order",
+            Some(&fx_payload),
+        )?;
+
+        // -- Check
+        let protected: Value =
+            serde_json::from_str(&b64u_decode_to_string(&jws.protected)?)?;
+        assert_eq!(protected["kid"], "https://acme.test/acct/1");
+        assert_eq!(protected["nonce"], "nonce-1");
+        assert!(protected.get("jwk").is_none());
+
+        let signature = Signature::from_slice(&b64u_decode(&jws.signature)?)?;
+        VerifyingKey::from(&fx_key.signing_key).verify(
+            format!("{}.{}", jws.protected, jws.payload).as_bytes(),
+            &signature,
+        )?;
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -0,0 +1,81 @@
+//! ACME resources (RFC 8555 section 7.1), only the members used here.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Directory {
+    pub new_nonce: String,
+    pub new_account: String,
+    pub new_order: String,
+}
+
+/// Error document (RFC 7807), e.g.
+/// `urn:ietf:params:acme:error:unauthorized`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Problem {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub detail: String,
+}
+
+impl Problem {
+    pub fn is_bad_nonce(&self) -> bool {
+        self.kind == "urn:ietf:params:acme:error:badNonce"
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Pending,
+    Ready,
+    Processing,
+    Valid,
+    Invalid,
+    Expired,
+    Deactivated,
+    Revoked,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Identifier {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub value: String,
+}
+
+impl Identifier {
+    pub fn dns(domain: &str) -> Self {
+        Identifier {
+            kind: "dns".to_string(),
+            value: domain.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Order {
+    pub status: Status,
+    pub authorizations: Vec<String>,
+    pub finalize: String,
+    pub certificate: Option<String>,
+    pub error: Option<Problem>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Authorization {
+    pub status: Status,
+    pub identifier: Identifier,
+    pub challenges: Vec<Challenge>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Challenge {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub url: String,
+    pub token: String,
+    pub error: Option<Problem>,
+}
@@ -1,7 +1,7 @@
 use lib_utils::envs::{IfMissing, get_env, get_env_parse};
 use std::{sync::OnceLock, time::Duration};
 
-#[cfg(test)]
+#[cfg(any(test, feature = "dev-utils"))]
 use url::Url;
 
 pub fn core_config() -> &'static CoreConfig {
@@ -20,7 +20,7 @@ pub struct CoreConfig {
     pub DB_URL: String,
     pub DB_MAX_CONNECTIONS: u32,
     pub DB_CONNECTION_TIMEOUT: Duration,
-    #[cfg(test)]
+    #[cfg(any(test, feature = "dev-utils"))]
     pub TEST_ACME_URI: Url,
 }
 
@@ -33,16 +33,17 @@ impl CoreConfig {
                 .if_missing(500)
                 .map(Duration::from_millis)?;
 
-        #[cfg(test)]
+        // Default address of the stand-in in `_dev_utils` (and of Pebble).
+        #[cfg(any(test, feature = "dev-utils"))]
         let test_acme_url = get_env_parse::<Url>("TEST_ACME_URI")
-            .if_missing(Url::parse("http://example.com").unwrap())?;
+            .if_missing(Url::parse("http://127.0.0.1:14000/dir").unwrap())?;
 
         Ok(CoreConfig {
             DB_URL: get_env("SERVICE_DB_URL")?,
             DB_MAX_CONNECTIONS: db_max_connections,
             DB_CONNECTION_TIMEOUT: db_connections_timeout,
 
-            #[cfg(test)]
+            #[cfg(any(test, feature = "dev-utils"))]
             TEST_ACME_URI: test_acme_url,
         })
     }
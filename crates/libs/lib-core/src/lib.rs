@@ -1,8 +1,9 @@
+pub mod acme;
 pub mod config;
 pub mod ctx;
 pub mod model;
 
-#[cfg(test)]
+#[cfg(any(test, feature = "dev-utils"))]
 pub mod _dev_utils;
 
 use config::core_config;
@@ -0,0 +1,29 @@
+use crate::model::store::dbx;
+use lib_auth::pwd;
+use serde::Serialize;
+use serde_with::serde_as;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[serde_as]
+#[derive(thiserror::Error, Debug, Serialize, strum_macros::Display)]
+pub enum Error {
+    AccessListNotFound {
+        id: i64,
+    },
+    /// Still referenced by proxy hosts.
+    AccessListInUse {
+        id: i64,
+    },
+    /// A new user without a password.
+    AccessListUserNoPassword {
+        username: String,
+    },
+
+    // -- Modules
+    #[error(transparent)]
+    Pwd(#[from] pwd::Error),
+
+    #[error(transparent)]
+    Dbx(#[from] dbx::Error),
+}
@@ -0,0 +1,606 @@
+use crate::{
+    ctx::Ctx,
+    model::{ModelEvent, ModelManager},
+};
+use lib_auth::pwd::{self, ContentToHash};
+use lib_utils::time::TimeRfc3339;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use sqlx::types::Json;
+use std::collections::HashMap;
+
+mod error;
+
+pub use error::{Error, Result};
+
+// region:    --- AccessList Types
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Deserialize,
+    Serialize,
+    sqlx::Type,
+    strum_macros::Display,
+)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum AccessListSatisfy {
+    /// The ip rules and the basic auth must both pass.
+    #[default]
+    All,
+    /// Either one is enough.
+    Any,
+}
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    Deserialize,
+    Serialize,
+    strum_macros::Display,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum AccessRuleAction {
+    Allow,
+    Deny,
+}
+
+/// An ip rule, checked in the order of the list.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct AccessRule {
+    pub action: AccessRuleAction,
+    /// An IPv4/IPv6 address, a CIDR or `all`.
+    pub address: String,
+}
+
+#[derive(Clone, FromRow, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessList {
+    pub id: i64,
+    /// `user_id` of the owner.
+    pub owner_id: String,
+
+    pub name: String,
+    pub satisfy: AccessListSatisfy,
+    #[sqlx(json)]
+    pub rules: Vec<AccessRule>,
+    /// The basic auth users, their passwords are never read back.
+    #[sqlx(json)]
+    pub usernames: Vec<String>,
+
+    pub ctime: String,
+    pub mtime: String,
+}
+
+/// A basic auth user with its password hash, for the nginx user file.
+#[derive(Clone, FromRow, Debug)]
+pub struct AccessListUser {
+    pub access_list_id: i64,
+    pub username: String,
+    /// Hashed with `pwd::hash_htpasswd`.
+    pub pwd: String,
+}
+
+/// A basic auth user as sent by the UI.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessListUserForSave {
+    pub username: String,
+    /// Clear password, `None` keeps the current one of `username`.
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+/// Fields required for creating new access list
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessListForCreate {
+    pub name: String,
+    #[serde(default)]
+    pub satisfy: AccessListSatisfy,
+    #[serde(default)]
+    pub users: Vec<AccessListUserForSave>,
+    #[serde(default)]
+    pub rules: Vec<AccessRule>,
+}
+
+/// Fields left to `None` are not updated, `users` replaces all the users.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessListForUpdate {
+    pub name: Option<String>,
+    pub satisfy: Option<AccessListSatisfy>,
+    pub users: Option<Vec<AccessListUserForSave>>,
+    pub rules: Option<Vec<AccessRule>>,
+}
+
+// endregion: --- AccessList Types
+
+// region:    --- AccessListBmc
+
+const SELECT_ACCESS_LIST: &str =
+    "SELECT al.serial_id AS id, u.user_id AS owner_id,
+        al.name, al.satisfy, al.rules,
+        (SELECT json_group_array(alu.username) FROM access_list_user alu
+            WHERE alu.access_list_serial_id = al.serial_id) AS usernames,
+        al.ctime, al.mtime
+    FROM access_list al
+    INNER JOIN users u ON al.owner_serial_id = u.serial_id";
+
+/// Access lists are owned by the user who created them, like the hosts
+/// referencing them. The root ctx sees all of them.
+pub struct AccessListBmc;
+
+impl AccessListBmc {
+    pub async fn create(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        access_list_c: AccessListForCreate,
+    ) -> Result<i64> {
+        let AccessListForCreate {
+            name,
+            satisfy,
+            users,
+            rules,
+        } = access_list_c;
+
+        let users = hash_users(users, &HashMap::new()).await?;
+        let now = TimeRfc3339::now_utc().format_time();
+
+        let mm = mm.new_with_txn();
+        mm.dbx().begin_txn().await?;
+
+        let sqlx_query = sqlx::query_as::<_, (i64,)>(
+            "INSERT INTO access_list (owner_serial_id, name, satisfy, rules,
+                ctime, mtime)
+            VALUES ((SELECT serial_id FROM users WHERE user_id = ?),
+                ?, ?, ?, ?, ?)
+            RETURNING serial_id;",
+        )
+        .bind(ctx.user_id())
+        .bind(name)
+        .bind(satisfy)
+        .bind(Json(rules))
+        .bind(&now)
+        .bind(&now);
+        let (id,) = mm.dbx().fetch_one(sqlx_query).await?;
+        insert_users(&mm, id, users, &now).await?;
+
+        mm.dbx().commit_txn().await?;
+        mm.notify(ModelEvent::AccessListChanged { id });
+
+        Ok(id)
+    }
+
+    pub async fn get(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i64,
+    ) -> Result<AccessList> {
+        let sql = format!(
+            "{SELECT_ACCESS_LIST}
+            WHERE al.serial_id = ? AND (? = 'root' OR u.user_id = ?)
+            LIMIT 1;"
+        );
+        let sqlx_query = sqlx::query_as::<_, AccessList>(&sql)
+            .bind(id)
+            .bind(ctx.user_id())
+            .bind(ctx.user_id());
+
+        let access_list = mm
+            .dbx()
+            .fetch_optional(sqlx_query)
+            .await?
+            .ok_or(Error::AccessListNotFound { id })?;
+
+        Ok(access_list)
+    }
+
+    pub async fn list(ctx: &Ctx, mm: &ModelManager) -> Result<Vec<AccessList>> {
+        let sql = format!(
+            "{SELECT_ACCESS_LIST}
+            WHERE ? = 'root' OR u.user_id = ?
+            ORDER BY al.serial_id;"
+        );
+        let sqlx_query = sqlx::query_as::<_, AccessList>(&sql)
+            .bind(ctx.user_id())
+            .bind(ctx.user_id());
+
+        let access_lists = mm.dbx().fetch_all(sqlx_query).await?;
+
+        Ok(access_lists)
+    }
+
+    /// The users of every access list of the ctx, with their hashes.
+    pub async fn list_users(
+        ctx: &Ctx,
+        mm: &ModelManager,
+    ) -> Result<Vec<AccessListUser>> {
+        let sqlx_query = sqlx::query_as::<_, AccessListUser>(
+            "SELECT alu.access_list_serial_id AS access_list_id,
+                alu.username, alu.pwd
+            FROM access_list_user alu
+            INNER JOIN access_list al
+                ON alu.access_list_serial_id = al.serial_id
+            INNER JOIN users u ON al.owner_serial_id = u.serial_id
+            WHERE ? = 'root' OR u.user_id = ?
+            ORDER BY alu.access_list_serial_id, alu.username;",
+        )
+        .bind(ctx.user_id())
+        .bind(ctx.user_id());
+
+        let users = mm.dbx().fetch_all(sqlx_query).await?;
+
+        Ok(users)
+    }
+
+    pub async fn update(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i64,
+        access_list_u: AccessListForUpdate,
+    ) -> Result<()> {
+        let AccessListForUpdate {
+            name,
+            satisfy,
+            users,
+            rules,
+        } = access_list_u;
+
+        // Checks the ownership, and keeps the passwords left unchanged.
+        Self::get(ctx, mm, id).await?;
+        let users = match users {
+            Some(users) => {
+                let current: HashMap<String, String> =
+                    Self::list_users(ctx, mm)
+                        .await?
+                        .into_iter()
+                        .filter(|user| user.access_list_id == id)
+                        .map(|user| (user.username, user.pwd))
+                        .collect();
+                Some(hash_users(users, &current).await?)
+            }
+            None => None,
+        };
+        let now = TimeRfc3339::now_utc().format_time();
+
+        let mm = mm.new_with_txn();
+        mm.dbx().begin_txn().await?;
+
+        let sqlx_query = sqlx::query(
+            "UPDATE access_list SET
+                name = COALESCE(?, name),
+                satisfy = COALESCE(?, satisfy),
+                rules = COALESCE(?, rules),
+                mtime = ?
+            WHERE serial_id = ? AND (? = 'root' OR owner_serial_id = (
+                SELECT serial_id FROM users WHERE user_id = ?));",
+        )
+        .bind(name)
+        .bind(satisfy)
+        .bind(rules.map(Json))
+        .bind(&now)
+        .bind(id)
+        .bind(ctx.user_id())
+        .bind(ctx.user_id());
+
+        let count = mm.dbx().execute(sqlx_query).await?;
+        if count == 0 {
+            return Err(Error::AccessListNotFound { id });
+        }
+        if let Some(users) = users {
+            let sqlx_query = sqlx::query(
+                "DELETE FROM access_list_user WHERE access_list_serial_id = ?;",
+            )
+            .bind(id);
+            mm.dbx().execute(sqlx_query).await?;
+            insert_users(&mm, id, users, &now).await?;
+        }
+
+        mm.dbx().commit_txn().await?;
+        mm.notify(ModelEvent::AccessListChanged { id });
+
+        Ok(())
+    }
+
+    /// Fails with `AccessListInUse` while proxy hosts reference the list.
+    pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+        Self::get(ctx, mm, id).await?;
+
+        let sqlx_query = sqlx::query_as::<_, (i64,)>(
+            "SELECT COUNT(*) FROM proxy_host WHERE access_list_serial_id = ?;",
+        )
+        .bind(id);
+        let (hosts,) = mm.dbx().fetch_one(sqlx_query).await?;
+        if hosts > 0 {
+            return Err(Error::AccessListInUse { id });
+        }
+
+        let sqlx_query = sqlx::query(
+            "DELETE FROM access_list
+            WHERE serial_id = ? AND (? = 'root' OR owner_serial_id = (
+                SELECT serial_id FROM users WHERE user_id = ?));",
+        )
+        .bind(id)
+        .bind(ctx.user_id())
+        .bind(ctx.user_id());
+
+        let count = mm.dbx().execute(sqlx_query).await?;
+        if count == 0 {
+            return Err(Error::AccessListNotFound { id });
+        }
+        mm.notify(ModelEvent::AccessListChanged { id });
+
+        Ok(())
+    }
+}
+
+// endregion: --- AccessListBmc
+
+// region:    --- Support
+
+/// `(username, pwd)` of `users`, hashing the new passwords and taking the
+/// others from `current`.
+async fn hash_users(
+    users: Vec<AccessListUserForSave>,
+    current: &HashMap<String, String>,
+) -> Result<Vec<(String, String)>> {
+    let mut hashed = Vec::with_capacity(users.len());
+    for AccessListUserForSave { username, password } in users {
+        let pwd = match (password, current.get(&username)) {
+            (Some(password), _) if !password.is_empty() => {
+                pwd::hash_htpasswd(ContentToHash {
+                    content: password,
+                    salt: pwd::generate_random_uuid_v4().await?,
+                })
+                .await?
+            }
+            (_, Some(pwd)) => pwd.clone(),
+            (_, None) => {
+                return Err(Error::AccessListUserNoPassword { username });
+            }
+        };
+        hashed.push((username, pwd));
+    }
+
+    Ok(hashed)
+}
+
+async fn insert_users(
+    mm: &ModelManager,
+    access_list_id: i64,
+    users: Vec<(String, String)>,
+    now: &str,
+) -> Result<()> {
+    for (username, pwd) in users {
+        let sqlx_query = sqlx::query(
+            "INSERT INTO access_list_user (access_list_serial_id, username,
+                pwd, ctime, mtime)
+            VALUES (?, ?, ?, ?, ?);",
+        )
+        .bind(access_list_id)
+        .bind(username)
+        .bind(pwd)
+        .bind(now)
+        .bind(now);
+        mm.dbx().execute(sqlx_query).await?;
+    }
+
+    Ok(())
+}
+
+// endregion: --- Support
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+    use crate::_dev_utils;
+    use crate::model::proxy_host::{
+        ForwardScheme, ProxyHostBmc, ProxyHostForCreate,
+    };
+    use sqlx::{Pool, Sqlite};
+
+    fn fx_access_list_c(name: &str) -> AccessListForCreate {
+        AccessListForCreate {
+            name: name.to_string(),
+            satisfy: AccessListSatisfy::Any,
+            users: vec![AccessListUserForSave {
+                username: "alice".to_string(),
+                password: Some("alice pwd".to_string()),
+            }],
+            rules: vec![AccessRule {
+                action: AccessRuleAction::Allow,
+                address: "10.0.0.0/8".to_string(),
+            }],
+        }
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_create_ok(pool: Pool<Sqlite>) -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test_db(pool).await?;
+        let ctx = Ctx::new("demo1")?;
+
+        // -- Exec
+        let id =
+            AccessListBmc::create(&ctx, &mm, fx_access_list_c("staff")).await?;
+
+        // -- Check
+        let access_list = AccessListBmc::get(&ctx, &mm, id).await?;
+        assert_eq!(access_list.owner_id, "demo1");
+        assert_eq!(access_list.satisfy, AccessListSatisfy::Any);
+        assert_eq!(access_list.usernames, ["alice"]);
+        assert_eq!(access_list.rules[0].address, "10.0.0.0/8");
+        let users = AccessListBmc::list_users(&ctx, &mm).await?;
+        assert_eq!(users.len(), 1);
+        assert!(pwd::htpasswd_hash(&users[0].pwd)?.starts_with("$6$"));
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_update_users_keep_pwd_ok(pool: Pool<Sqlite>) -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test_db(pool).await?;
+        let ctx = Ctx::new("demo1")?;
+        let id =
+            AccessListBmc::create(&ctx, &mm, fx_access_list_c("staff")).await?;
+        let alice_pwd =
+            AccessListBmc::list_users(&ctx, &mm).await?[0].pwd.clone();
+
+        // -- Exec
+        AccessListBmc::update(
+            &ctx,
+            &mm,
+            id,
+            AccessListForUpdate {
+                users: Some(vec![
+                    AccessListUserForSave {
+                        username: "alice".to_string(),
+                        password: None,
+                    },
+                    AccessListUserForSave {
+                        username: "bob".to_string(),
+                        password: Some("bob pwd".to_string()),
+                    },
+                ]),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        // -- Check
+        let users = AccessListBmc::list_users(&ctx, &mm).await?;
+        assert_eq!(users.len(), 2);
+        assert_eq!(users[0].username, "alice");
+        assert_eq!(users[0].pwd, alice_pwd);
+        assert_eq!(users[1].username, "bob");
+        let access_list = AccessListBmc::get(&ctx, &mm, id).await?;
+        assert_eq!(access_list.name, "staff");
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_update_new_user_no_pwd_err(pool: Pool<Sqlite>) -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test_db(pool).await?;
+        let ctx = Ctx::new("demo1")?;
+        let id =
+            AccessListBmc::create(&ctx, &mm, fx_access_list_c("staff")).await?;
+
+        // -- Exec
+        let res = AccessListBmc::update(
+            &ctx,
+            &mm,
+            id,
+            AccessListForUpdate {
+                users: Some(vec![AccessListUserForSave {
+                    username: "carol".to_string(),
+                    password: None,
+                }]),
+                ..Default::default()
+            },
+        )
+        .await;
+
+        // -- Check
+        assert!(matches!(
+            res,
+            Err(super::Error::AccessListUserNoPassword { username })
+                if username == "carol"
+        ));
+        assert_eq!(AccessListBmc::list_users(&ctx, &mm).await?.len(), 1);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_delete_not_owned_err(pool: Pool<Sqlite>) -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test_db(pool).await?;
+        let ctx_demo1 = Ctx::new("demo1")?;
+        let ctx_other = Ctx::new("other")?;
+        let id =
+            AccessListBmc::create(&ctx_demo1, &mm, fx_access_list_c("staff"))
+                .await?;
+
+        // -- Exec
+        let res = AccessListBmc::delete(&ctx_other, &mm, id).await;
+
+        // -- Check
+        assert!(
+            matches!(res, Err(super::Error::AccessListNotFound { id: res_id }) if res_id == id)
+        );
+        AccessListBmc::delete(&ctx_demo1, &mm, id).await?;
+        assert!(AccessListBmc::list_users(&ctx_demo1, &mm).await?.is_empty());
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_delete_in_use_err(pool: Pool<Sqlite>) -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test_db(pool).await?;
+        let ctx = Ctx::new("demo1")?;
+        let id =
+            AccessListBmc::create(&ctx, &mm, fx_access_list_c("staff")).await?;
+        let proxy_host_id = ProxyHostBmc::create(
+            &ctx,
+            &mm,
+            ProxyHostForCreate {
+                domain_names: vec!["test-in-use.example.com".to_string()],
+                forward_scheme: ForwardScheme::Http,
+                forward_host: "127.0.0.1".to_string(),
+                forward_port: 3000,
+                cache_assets: false,
+                block_exploits: false,
+                allow_websocket_upgrade: false,
+                ssl_forced: false,
+                http2_support: false,
+                hsts_enabled: false,
+                hsts_subdomains: false,
+                certificate_id: None,
+                access_list_id: Some(id),
+                upstream_group_id: None,
+                locations: Vec::new(),
+                advanced: String::new(),
+                rate_limit: None,
+                rate_limit_burst: 0,
+                rate_limit_nodelay: false,
+                connection_limit: None,
+                health_check_path: None,
+                health_check_interval: 30,
+            },
+        )
+        .await?;
+
+        // -- Exec
+        let res = AccessListBmc::delete(&ctx, &mm, id).await;
+
+        // -- Check
+        assert!(matches!(res, Err(super::Error::AccessListInUse { .. })));
+        let proxy_host = ProxyHostBmc::get(&ctx, &mm, proxy_host_id).await?;
+        assert_eq!(proxy_host.access_list_id, Some(id));
+        assert_eq!(proxy_host.access_list_name.as_deref(), Some("staff"));
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
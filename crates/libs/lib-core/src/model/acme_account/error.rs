@@ -0,0 +1,17 @@
+use crate::model::store::dbx;
+use serde::Serialize;
+use serde_with::serde_as;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[serde_as]
+#[derive(thiserror::Error, Debug, Serialize, strum_macros::Display)]
+pub enum Error {
+    AcmeAccountPemNotUtf8 {
+        id: i64,
+    },
+
+    // -- Modules
+    #[error(transparent)]
+    Dbx(#[from] dbx::Error),
+}
@@ -0,0 +1,111 @@
+use crate::{ctx::Ctx, model::ModelManager};
+use lib_utils::time::TimeRfc3339;
+use sqlx::FromRow;
+
+mod error;
+
+pub use error::{Error, Result};
+
+// region:    --- AcmeAccount Types
+
+/// An account registered with an ACME directory. Accounts are shared by
+/// all users, there is one per directory.
+#[derive(Clone, FromRow, Debug)]
+pub struct AcmeAccount {
+    pub id: i64,
+    /// Account url returned by the server, the `kid` of the requests.
+    pub account_id: String,
+    pub directory_url: String,
+    pub private_key_pem: Vec<u8>,
+    pub public_key_pem: Vec<u8>,
+    /// JWS algorithm of the key, e.g. `ES256`.
+    pub key_type: String,
+
+    pub ctime: String,
+    pub mtime: String,
+}
+
+impl AcmeAccount {
+    pub fn private_key_pem(&self) -> Result<&str> {
+        std::str::from_utf8(&self.private_key_pem)
+            .map_err(|_| Error::AcmeAccountPemNotUtf8 { id: self.id })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AcmeAccountForCreate {
+    pub account_id: String,
+    pub directory_url: String,
+    pub private_key_pem: String,
+    pub public_key_pem: String,
+    pub key_type: String,
+}
+
+// endregion: --- AcmeAccount Types
+
+// region:    --- AcmeAccountBmc
+
+const SELECT_ACME_ACCOUNT: &str = "SELECT aa.serial_id AS id, aa.account_id,
+        aa.directory_url, aa.private_key_pem, aa.public_key_pem, aa.key_type,
+        aa.ctime, aa.mtime
+    FROM acme_account aa";
+
+pub struct AcmeAccountBmc;
+
+impl AcmeAccountBmc {
+    pub async fn create(
+        _ctx: &Ctx,
+        mm: &ModelManager,
+        acme_account_c: AcmeAccountForCreate,
+    ) -> Result<i64> {
+        let AcmeAccountForCreate {
+            account_id,
+            directory_url,
+            private_key_pem,
+            public_key_pem,
+            key_type,
+        } = acme_account_c;
+
+        let now = TimeRfc3339::now_utc().format_time();
+
+        let sqlx_query = sqlx::query_as::<_, (i64,)>(
+            "INSERT INTO acme_account (account_id, directory_url,
+                private_key_pem, public_key_pem, key_type, ctime, mtime)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            RETURNING serial_id;",
+        )
+        .bind(account_id)
+        .bind(directory_url)
+        .bind(private_key_pem.into_bytes())
+        .bind(public_key_pem.into_bytes())
+        .bind(key_type)
+        .bind(&now)
+        .bind(&now);
+
+        let (id,) = mm.dbx().fetch_one(sqlx_query).await?;
+
+        Ok(id)
+    }
+
+    /// The most recent account of `directory_url`, if any.
+    pub async fn first_by_directory_url(
+        _ctx: &Ctx,
+        mm: &ModelManager,
+        directory_url: &str,
+    ) -> Result<Option<AcmeAccount>> {
+        let sql = format!(
+            "{SELECT_ACME_ACCOUNT}
+            WHERE aa.directory_url = ?
+            ORDER BY aa.serial_id DESC
+            LIMIT 1;"
+        );
+        let sqlx_query =
+            sqlx::query_as::<_, AcmeAccount>(&sql).bind(directory_url);
+
+        let acme_account = mm.dbx().fetch_optional(sqlx_query).await?;
+
+        Ok(acme_account)
+    }
+}
+
+// endregion: --- AcmeAccountBmc
@@ -0,0 +1,40 @@
+use crate::model::store::dbx;
+use serde::Serialize;
+use serde_with::serde_as;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[serde_as]
+#[derive(thiserror::Error, Debug, Serialize, strum_macros::Display)]
+pub enum Error {
+    CertificateNotFound {
+        id: i64,
+    },
+    /// Still used by proxy hosts.
+    CertificateInUse {
+        id: i64,
+    },
+
+    // -- Pem
+    CertificatePemEmpty,
+    CertificatePemInvalid(String),
+
+    // -- Bundle
+    PrivateKeyInvalid(String),
+    /// No certificate of the bundle is for the private key.
+    PrivateKeyMismatch,
+    CertificateExpired {
+        not_after: String,
+    },
+    CertificateNotYetValid {
+        not_before: String,
+    },
+    /// A certificate which does not issue the leaf or its issuers.
+    CertificateNotInChain {
+        subject: String,
+    },
+
+    // -- Modules
+    #[error(transparent)]
+    Dbx(#[from] dbx::Error),
+}
@@ -0,0 +1,581 @@
+use crate::{
+    ctx::Ctx,
+    model::{ModelEvent, ModelManager},
+};
+use lib_utils::time::TimeRfc3339;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use sqlx::types::Json;
+
+mod bundle;
+mod error;
+mod pem;
+
+pub use bundle::CertificateBundle;
+pub use error::{Error, Result};
+pub use pem::CertificateInfo;
+
+// region:    --- Certificate Types
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Deserialize,
+    Serialize,
+    sqlx::Type,
+    strum_macros::Display,
+)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum CertificateProvider {
+    /// Issued, and renewed, by the server with ACME.
+    #[default]
+    Acme,
+    /// Uploaded by the user.
+    Custom,
+}
+
+/// How the ACME authorizations are validated.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Deserialize,
+    Serialize,
+    sqlx::Type,
+    strum_macros::Display,
+)]
+pub enum CertificateChallenge {
+    /// Served by this server on port 80.
+    #[default]
+    #[serde(rename = "http-01")]
+    #[sqlx(rename = "http-01")]
+    #[strum(serialize = "http-01")]
+    Http01,
+    /// TXT records published by the configured DNS provider, required
+    /// for wildcard domains.
+    #[serde(rename = "dns-01")]
+    #[sqlx(rename = "dns-01")]
+    #[strum(serialize = "dns-01")]
+    Dns01,
+}
+
+#[derive(Clone, FromRow, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Certificate {
+    pub id: i64,
+    /// `user_id` of the owner.
+    pub owner_id: String,
+
+    pub nice_name: String,
+    pub provider: CertificateProvider,
+    pub challenge: CertificateChallenge,
+    /// The requested domains, the SAN list once issued.
+    #[sqlx(json)]
+    pub domain_names: Vec<String>,
+
+    /// Leaf first, then the intermediates. Empty until issued.
+    pub certificate_pem: String,
+    #[serde(skip)]
+    pub private_key_pem: String,
+    pub issuer: String,
+    pub not_before: Option<String>,
+    pub not_after: Option<String>,
+
+    /// Last issuance attempt, and its error if it failed.
+    pub renew_time: Option<String>,
+    pub renew_error: Option<String>,
+
+    pub ctime: String,
+    pub mtime: String,
+}
+
+impl Certificate {
+    pub fn is_issued(&self) -> bool {
+        !self.certificate_pem.is_empty()
+    }
+}
+
+/// An ACME certificate to issue for `domain_names`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CertificateForCreate {
+    #[serde(default)]
+    pub nice_name: String,
+    pub domain_names: Vec<String>,
+    #[serde(default)]
+    pub challenge: CertificateChallenge,
+}
+
+/// A custom certificate, checked with `CertificateBundle::parse`.
+#[derive(Clone, Debug)]
+pub struct CertificateForUpload {
+    pub nice_name: String,
+    pub bundle: CertificateBundle,
+}
+
+/// The PEM material of an issued certificate.
+#[derive(Clone, Debug)]
+pub struct CertificateForIssue {
+    /// Leaf first, then the intermediates.
+    pub certificate_pem: String,
+    pub private_key_pem: String,
+}
+
+// endregion: --- Certificate Types
+
+// region:    --- CertificateBmc
+
+const SELECT_CERTIFICATE: &str =
+    "SELECT c.serial_id AS id, u.user_id AS owner_id,
+        c.nice_name, c.provider, c.challenge, c.domain_names,
+        c.certificate_pem, c.private_key_pem, c.issuer,
+        c.not_before, c.not_after, c.renew_time, c.renew_error,
+        c.ctime, c.mtime
+    FROM certificate c
+    INNER JOIN users u ON c.owner_serial_id = u.serial_id";
+
+/// Same ownership rules as `ProxyHostBmc`: scoped to the `Ctx` user,
+/// the root ctx sees all certificates.
+pub struct CertificateBmc;
+
+impl CertificateBmc {
+    /// Add an ACME certificate, issued later by the renewal task.
+    pub async fn create(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        certificate_c: CertificateForCreate,
+    ) -> Result<i64> {
+        let CertificateForCreate {
+            nice_name,
+            domain_names,
+            challenge,
+        } = certificate_c;
+
+        let now = TimeRfc3339::now_utc().format_time();
+
+        let sqlx_query = sqlx::query_as::<_, (i64,)>(
+            "INSERT INTO certificate (owner_serial_id, nice_name, provider,
+                challenge, domain_names, ctime, mtime)
+            VALUES ((SELECT serial_id FROM users WHERE user_id = ?),
+                ?, ?, ?, ?, ?, ?)
+            RETURNING serial_id;",
+        )
+        .bind(ctx.user_id())
+        .bind(nice_name)
+        .bind(CertificateProvider::Acme)
+        .bind(challenge)
+        .bind(Json(domain_names))
+        .bind(&now)
+        .bind(&now);
+
+        let (id,) = mm.dbx().fetch_one(sqlx_query).await?;
+        mm.notify(ModelEvent::CertificateChanged { id });
+
+        Ok(id)
+    }
+
+    /// Add an uploaded certificate, never renewed by the server.
+    pub async fn create_custom(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        certificate_u: CertificateForUpload,
+    ) -> Result<i64> {
+        let CertificateForUpload { nice_name, bundle } = certificate_u;
+        let CertificateBundle {
+            certificate_pem,
+            private_key_pem,
+            info,
+        } = bundle;
+
+        let now = TimeRfc3339::now_utc().format_time();
+
+        let sqlx_query = sqlx::query_as::<_, (i64,)>(
+            "INSERT INTO certificate (owner_serial_id, nice_name, provider,
+                domain_names, certificate_pem, private_key_pem, issuer,
+                not_before, not_after, ctime, mtime)
+            VALUES ((SELECT serial_id FROM users WHERE user_id = ?),
+                ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            RETURNING serial_id;",
+        )
+        .bind(ctx.user_id())
+        .bind(nice_name)
+        .bind(CertificateProvider::Custom)
+        .bind(Json(info.domain_names))
+        .bind(certificate_pem)
+        .bind(private_key_pem)
+        .bind(info.issuer)
+        .bind(info.not_before)
+        .bind(info.not_after)
+        .bind(&now)
+        .bind(&now);
+
+        let (id,) = mm.dbx().fetch_one(sqlx_query).await?;
+        mm.notify(ModelEvent::CertificateChanged { id });
+
+        Ok(id)
+    }
+
+    pub async fn get(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i64,
+    ) -> Result<Certificate> {
+        let sql = format!(
+            "{SELECT_CERTIFICATE}
+            WHERE c.serial_id = ? AND (? = 'root' OR u.user_id = ?)
+            LIMIT 1;"
+        );
+        let sqlx_query = sqlx::query_as::<_, Certificate>(&sql)
+            .bind(id)
+            .bind(ctx.user_id())
+            .bind(ctx.user_id());
+
+        let certificate = mm
+            .dbx()
+            .fetch_optional(sqlx_query)
+            .await?
+            .ok_or(Error::CertificateNotFound { id })?;
+
+        Ok(certificate)
+    }
+
+    pub async fn list(
+        ctx: &Ctx,
+        mm: &ModelManager,
+    ) -> Result<Vec<Certificate>> {
+        let sql = format!(
+            "{SELECT_CERTIFICATE}
+            WHERE ? = 'root' OR u.user_id = ?
+            ORDER BY c.serial_id;"
+        );
+        let sqlx_query = sqlx::query_as::<_, Certificate>(&sql)
+            .bind(ctx.user_id())
+            .bind(ctx.user_id());
+
+        let certificates = mm.dbx().fetch_all(sqlx_query).await?;
+
+        Ok(certificates)
+    }
+
+    /// Store a (re)issued certificate. The SAN list, issuer and validity
+    /// are read from the leaf.
+    pub async fn set_issued(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i64,
+        certificate_i: CertificateForIssue,
+    ) -> Result<()> {
+        let CertificateForIssue {
+            certificate_pem,
+            private_key_pem,
+        } = certificate_i;
+        let info = CertificateInfo::from_pem_chain(&certificate_pem)?;
+
+        let now = TimeRfc3339::now_utc().format_time();
+
+        let sqlx_query = sqlx::query(
+            "UPDATE certificate SET
+                domain_names = ?, certificate_pem = ?, private_key_pem = ?,
+                issuer = ?, not_before = ?, not_after = ?,
+                renew_time = ?, renew_error = NULL,
+                mtime = ?
+            WHERE serial_id = ? AND (? = 'root' OR owner_serial_id = (
+                SELECT serial_id FROM users WHERE user_id = ?));",
+        )
+        .bind(Json(info.domain_names))
+        .bind(certificate_pem)
+        .bind(private_key_pem)
+        .bind(info.issuer)
+        .bind(info.not_before)
+        .bind(info.not_after)
+        .bind(&now)
+        .bind(&now)
+        .bind(id)
+        .bind(ctx.user_id())
+        .bind(ctx.user_id());
+
+        let count = mm.dbx().execute(sqlx_query).await?;
+        if count == 0 {
+            return Err(Error::CertificateNotFound { id });
+        }
+        mm.notify(ModelEvent::CertificateChanged { id });
+
+        Ok(())
+    }
+
+    /// Record a failed issuance, the current certificate (if any) is kept.
+    pub async fn set_renew_error(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i64,
+        renew_error: String,
+    ) -> Result<()> {
+        let now = TimeRfc3339::now_utc().format_time();
+
+        let sqlx_query = sqlx::query(
+            "UPDATE certificate SET renew_time = ?, renew_error = ?, mtime = ?
+            WHERE serial_id = ? AND (? = 'root' OR owner_serial_id = (
+                SELECT serial_id FROM users WHERE user_id = ?));",
+        )
+        .bind(&now)
+        .bind(renew_error)
+        .bind(&now)
+        .bind(id)
+        .bind(ctx.user_id())
+        .bind(ctx.user_id());
+
+        let count = mm.dbx().execute(sqlx_query).await?;
+        if count == 0 {
+            return Err(Error::CertificateNotFound { id });
+        }
+        mm.notify(ModelEvent::CertificateChanged { id });
+
+        Ok(())
+    }
+
+    /// Fails with `CertificateInUse` while proxy hosts are served with
+    /// the certificate.
+    pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+        Self::get(ctx, mm, id).await?;
+
+        let sqlx_query = sqlx::query_as::<_, (i64,)>(
+            "SELECT COUNT(*) FROM proxy_host
+            WHERE certificate_serial_id = ?;",
+        )
+        .bind(id);
+        let (hosts,) = mm.dbx().fetch_one(sqlx_query).await?;
+        if hosts > 0 {
+            return Err(Error::CertificateInUse { id });
+        }
+
+        let sqlx_query = sqlx::query(
+            "DELETE FROM certificate
+            WHERE serial_id = ? AND (? = 'root' OR owner_serial_id = (
+                SELECT serial_id FROM users WHERE user_id = ?));",
+        )
+        .bind(id)
+        .bind(ctx.user_id())
+        .bind(ctx.user_id());
+
+        let count = mm.dbx().execute(sqlx_query).await?;
+        if count == 0 {
+            return Err(Error::CertificateNotFound { id });
+        }
+        mm.notify(ModelEvent::CertificateChanged { id });
+
+        Ok(())
+    }
+}
+
+// endregion: --- CertificateBmc
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+    use crate::_dev_utils;
+    use crate::model::proxy_host::{
+        ForwardScheme, ProxyHostBmc, ProxyHostForCreate,
+    };
+    use sqlx::{Pool, Sqlite};
+
+    #[sqlx::test(migrations = false)]
+    async fn test_create_set_issued_ok(pool: Pool<Sqlite>) -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test_db(pool).await?;
+        let ctx = Ctx::new("demo1")?;
+        let fx_domains =
+            vec!["app.example.com".to_string(), "www.example.com".to_string()];
+        let (fx_certificate_pem, fx_private_key_pem) =
+            _dev_utils::new_test_certificate(&fx_domains)?;
+        let id = CertificateBmc::create(
+            &ctx,
+            &mm,
+            CertificateForCreate {
+                nice_name: "app".to_string(),
+                domain_names: fx_domains.clone(),
+                challenge: CertificateChallenge::Dns01,
+            },
+        )
+        .await?;
+        let pending = CertificateBmc::get(&ctx, &mm, id).await?;
+        let mut rx = mm.subscribe();
+
+        // -- Exec
+        CertificateBmc::set_issued(
+            &ctx,
+            &mm,
+            id,
+            CertificateForIssue {
+                certificate_pem: fx_certificate_pem.clone(),
+                private_key_pem: fx_private_key_pem,
+            },
+        )
+        .await?;
+
+        // -- Check
+        assert!(!pending.is_issued());
+        assert_eq!(pending.provider, CertificateProvider::Acme);
+        assert_eq!(pending.challenge, CertificateChallenge::Dns01);
+        let certificate = CertificateBmc::get(&ctx, &mm, id).await?;
+        assert!(certificate.is_issued());
+        assert_eq!(certificate.certificate_pem, fx_certificate_pem);
+        assert_eq!(certificate.domain_names, fx_domains);
+        assert_eq!(certificate.issuer, "CN=Test CA");
+        let not_after = TimeRfc3339::parse_utc(
+            certificate
+                .not_after
+                .as_deref()
+                .ok_or("Should have not_after")?,
+        )?;
+        assert!(*not_after > *TimeRfc3339::now_utc());
+        assert!(certificate.renew_time.is_some());
+        assert_eq!(certificate.renew_error, None);
+        assert_eq!(rx.try_recv()?, ModelEvent::CertificateChanged { id });
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_create_custom_ok(pool: Pool<Sqlite>) -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test_db(pool).await?;
+        let ctx = Ctx::new("demo1")?;
+        let (fx_certificate_pem, fx_private_key_pem) =
+            _dev_utils::new_test_certificate(&["app.example.com".into()])?;
+        let fx_bundle =
+            CertificateBundle::parse(&fx_certificate_pem, &fx_private_key_pem)?;
+
+        // -- Exec
+        let id = CertificateBmc::create_custom(
+            &ctx,
+            &mm,
+            CertificateForUpload {
+                nice_name: "own".to_string(),
+                bundle: fx_bundle,
+            },
+        )
+        .await?;
+
+        // -- Check
+        let certificate = CertificateBmc::get(&ctx, &mm, id).await?;
+        assert_eq!(certificate.provider, CertificateProvider::Custom);
+        assert!(certificate.is_issued());
+        assert_eq!(certificate.domain_names, ["app.example.com"]);
+        assert!(certificate.not_after.is_some());
+        assert_eq!(certificate.renew_time, None);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_set_issued_invalid_pem_err(pool: Pool<Sqlite>) -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test_db(pool).await?;
+        let ctx = Ctx::new("demo1")?;
+        let id = CertificateBmc::create(
+            &ctx,
+            &mm,
+            CertificateForCreate {
+                nice_name: String::new(),
+                domain_names: vec!["app.example.com".to_string()],
+                challenge: CertificateChallenge::Http01,
+            },
+        )
+        .await?;
+
+        // -- Exec
+        let res = CertificateBmc::set_issued(
+            &ctx,
+            &mm,
+            id,
+            CertificateForIssue {
+                certificate_pem: "not a pem".to_string(),
+                private_key_pem: String::new(),
+            },
+        )
+        .await;
+
+        // -- Check
+        assert!(matches!(res, Err(super::Error::CertificatePemInvalid(_))));
+        CertificateBmc::set_renew_error(&ctx, &mm, id, "boom".to_string())
+            .await?;
+        let certificate = CertificateBmc::get(&ctx, &mm, id).await?;
+        assert!(!certificate.is_issued());
+        assert_eq!(certificate.renew_error.as_deref(), Some("boom"));
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_delete_in_use_err(pool: Pool<Sqlite>) -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test_db(pool).await?;
+        let ctx = Ctx::new("demo1")?;
+        let id = CertificateBmc::create(
+            &ctx,
+            &mm,
+            CertificateForCreate {
+                nice_name: "app".to_string(),
+                domain_names: vec!["test-in-use.example.com".to_string()],
+                challenge: CertificateChallenge::Http01,
+            },
+        )
+        .await?;
+        let proxy_host_id = ProxyHostBmc::create(
+            &ctx,
+            &mm,
+            ProxyHostForCreate {
+                domain_names: vec!["test-in-use.example.com".to_string()],
+                forward_scheme: ForwardScheme::Http,
+                forward_host: "127.0.0.1".to_string(),
+                forward_port: 3000,
+                cache_assets: false,
+                block_exploits: false,
+                allow_websocket_upgrade: false,
+                ssl_forced: true,
+                http2_support: false,
+                hsts_enabled: false,
+                hsts_subdomains: false,
+                certificate_id: Some(id),
+                access_list_id: None,
+                upstream_group_id: None,
+                locations: Vec::new(),
+                advanced: String::new(),
+                rate_limit: None,
+                rate_limit_burst: 0,
+                rate_limit_nodelay: false,
+                connection_limit: None,
+                health_check_path: None,
+                health_check_interval: 30,
+            },
+        )
+        .await?;
+
+        // -- Exec
+        let res = CertificateBmc::delete(&ctx, &mm, id).await;
+
+        // -- Check
+        assert!(matches!(res, Err(super::Error::CertificateInUse { .. })));
+        let proxy_host = ProxyHostBmc::get(&ctx, &mm, proxy_host_id).await?;
+        assert_eq!(proxy_host.certificate_id, Some(id));
+        assert_eq!(proxy_host.certificate_name.as_deref(), Some("app"));
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -0,0 +1,64 @@
+//! What the certificate store keeps from the X.509 data of a PEM chain.
+
+use crate::model::certificate::{Error, Result};
+use chrono::{DateTime, Utc};
+use lib_utils::time::TimeRfc3339;
+use x509_cert::Certificate;
+use x509_cert::ext::pkix::SubjectAltName;
+use x509_cert::ext::pkix::name::GeneralName;
+use x509_cert::time::Time;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CertificateInfo {
+    /// dNSName entries of the subjectAltName extension.
+    pub domain_names: Vec<String>,
+    /// RFC 4514 string, e.g. `CN=R11,O=Let's Encrypt,C=US`.
+    pub issuer: String,
+    /// RFC 3339.
+    pub not_before: String,
+    /// RFC 3339.
+    pub not_after: String,
+}
+
+impl CertificateInfo {
+    /// Info of the leaf, the first certificate of `pem`.
+    pub fn from_pem_chain(pem: &str) -> Result<Self> {
+        let chain = Certificate::load_pem_chain(pem.as_bytes())
+            .map_err(|ex| Error::CertificatePemInvalid(ex.to_string()))?;
+        let leaf = chain.first().ok_or(Error::CertificatePemEmpty)?;
+
+        Self::from_certificate(leaf)
+    }
+
+    pub fn from_certificate(certificate: &Certificate) -> Result<Self> {
+        let tbs = &certificate.tbs_certificate;
+
+        let domain_names = tbs
+            .get::<SubjectAltName>()
+            .map_err(|ex| Error::CertificatePemInvalid(ex.to_string()))?
+            .map(|(_, SubjectAltName(names))| {
+                names
+                    .into_iter()
+                    .filter_map(|name| match name {
+                        GeneralName::DnsName(domain) => {
+                            Some(domain.to_string())
+                        }
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(CertificateInfo {
+            domain_names,
+            issuer: tbs.issuer.to_string(),
+            not_before: format_time(tbs.validity.not_before),
+            not_after: format_time(tbs.validity.not_after),
+        })
+    }
+}
+
+fn format_time(time: Time) -> String {
+    TimeRfc3339::from(DateTime::<Utc>::from(time.to_system_time()))
+        .format_time()
+}
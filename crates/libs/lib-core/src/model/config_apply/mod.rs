@@ -0,0 +1,156 @@
+use crate::{ctx::Ctx, model::ModelManager};
+use lib_utils::time::TimeRfc3339;
+use serde::Serialize;
+use sqlx::FromRow;
+
+mod error;
+
+pub use error::{Error, Result};
+
+// region:    --- ConfigApply Types
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    Serialize,
+    sqlx::Type,
+    strum_macros::Display,
+)]
+pub enum ConfigApplyStatus {
+    /// Validated, swapped in and reloaded.
+    Applied,
+    /// `nginx -t` rejected the new generation, the previous one is kept.
+    Invalid,
+    /// The reload failed, the previous generation was restored.
+    ReloadFailed,
+    /// The pipeline itself failed (e.g. io error, nginx not found), or
+    /// some hosts could not be rendered and were applied without.
+    Failed,
+}
+
+#[derive(Clone, FromRow, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigApply {
+    pub id: i64,
+    pub generation: Option<String>,
+    pub status: ConfigApplyStatus,
+    pub output: String,
+    pub ctime: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct ConfigApplyForCreate {
+    pub generation: Option<String>,
+    pub status: ConfigApplyStatus,
+    pub output: String,
+}
+
+// endregion: --- ConfigApply Types
+
+// region:    --- ConfigApplyBmc
+
+pub struct ConfigApplyBmc;
+
+impl ConfigApplyBmc {
+    pub async fn create(
+        _ctx: &Ctx,
+        mm: &ModelManager,
+        config_apply_c: ConfigApplyForCreate,
+    ) -> Result<i64> {
+        let ConfigApplyForCreate {
+            generation,
+            status,
+            output,
+        } = config_apply_c;
+
+        let now = TimeRfc3339::now_utc().format_time();
+
+        let sqlx_query = sqlx::query_as::<_, (i64,)>(
+            "INSERT INTO config_apply (generation, status, output, ctime)
+                VALUES (?, ?, ?, ?)
+            RETURNING serial_id;",
+        )
+        .bind(generation)
+        .bind(status)
+        .bind(output)
+        .bind(now);
+
+        let (id,) = mm.dbx().fetch_one(sqlx_query).await?;
+
+        Ok(id)
+    }
+
+    /// The most recent run, if any.
+    pub async fn last(
+        _ctx: &Ctx,
+        mm: &ModelManager,
+    ) -> Result<Option<ConfigApply>> {
+        let sqlx_query = sqlx::query_as::<_, ConfigApply>(
+            "SELECT serial_id AS id, generation, status, output, ctime
+            FROM config_apply
+            ORDER BY serial_id DESC
+            LIMIT 1;",
+        );
+
+        let config_apply = mm.dbx().fetch_optional(sqlx_query).await?;
+
+        Ok(config_apply)
+    }
+}
+
+// endregion: --- ConfigApplyBmc
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+    use crate::_dev_utils;
+    use sqlx::{Pool, Sqlite};
+
+    #[sqlx::test(migrations = false)]
+    async fn test_last_ok(pool: Pool<Sqlite>) -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test_db(pool).await?;
+        let ctx = Ctx::root_ctx();
+        let fx_runs = [
+            (ConfigApplyStatus::Applied, ""),
+            (
+                ConfigApplyStatus::Invalid,
+                "unknown directive \"proxy_pas\"",
+            ),
+        ];
+
+        // -- Exec
+        assert!(ConfigApplyBmc::last(&ctx, &mm).await?.is_none());
+        for (status, output) in fx_runs {
+            ConfigApplyBmc::create(
+                &ctx,
+                &mm,
+                ConfigApplyForCreate {
+                    generation: Some("gen-1".to_string()),
+                    status,
+                    output: output.to_string(),
+                },
+            )
+            .await?;
+        }
+
+        // -- Check
+        let last = ConfigApplyBmc::last(&ctx, &mm)
+            .await?
+            .ok_or("Should have a run")?;
+        assert_eq!(last.status, ConfigApplyStatus::Invalid);
+        assert_eq!(last.output, "unknown directive \"proxy_pas\"");
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -0,0 +1,299 @@
+use crate::{
+    ctx::Ctx,
+    model::{ModelEvent, ModelManager},
+};
+use lib_utils::time::TimeRfc3339;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use sqlx::types::Json;
+
+mod error;
+
+pub use error::{Error, Result};
+
+// region:    --- DeadHost Types
+
+/// A "404 host": every request to its domains is answered with a 404.
+#[derive(Clone, FromRow, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadHost {
+    pub id: i64,
+    /// `user_id` of the owner.
+    pub owner_id: String,
+
+    #[sqlx(json)]
+    pub domain_names: Vec<String>,
+    /// Custom error page, path relative to the static folder
+    /// (e.g. `html/error_pages/dead_host_1.html`).
+    pub error_page: Option<String>,
+
+    pub enabled: bool,
+
+    pub ctime: String,
+    pub mtime: String,
+}
+
+/// Fields required for creating new 404 host
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadHostForCreate {
+    pub domain_names: Vec<String>,
+}
+
+/// Fields left to `None` are not updated
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadHostForUpdate {
+    pub domain_names: Option<Vec<String>>,
+
+    pub enabled: Option<bool>,
+}
+
+// endregion: --- DeadHost Types
+
+// region:    --- DeadHostBmc
+
+const SELECT_DEAD_HOST: &str =
+    "SELECT dh.serial_id AS id, u.user_id AS owner_id,
+        dh.domain_names, dh.error_page,
+        dh.enabled, dh.ctime, dh.mtime
+    FROM dead_host dh
+    INNER JOIN users u ON dh.owner_serial_id = u.serial_id";
+
+/// Same ownership rules as `ProxyHostBmc`: scoped to the `Ctx` user,
+/// the root ctx sees all hosts.
+pub struct DeadHostBmc;
+
+impl DeadHostBmc {
+    pub async fn create(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        dead_host_c: DeadHostForCreate,
+    ) -> Result<i64> {
+        let DeadHostForCreate { domain_names } = dead_host_c;
+
+        let now = TimeRfc3339::now_utc().format_time();
+
+        let sqlx_query = sqlx::query_as::<_, (i64,)>(
+            "INSERT INTO dead_host (owner_serial_id, domain_names,
+                ctime, mtime)
+            VALUES ((SELECT serial_id FROM users WHERE user_id = ?),
+                ?, ?, ?)
+            RETURNING serial_id;",
+        )
+        .bind(ctx.user_id())
+        .bind(Json(domain_names))
+        .bind(&now)
+        .bind(&now);
+
+        let (id,) = mm.dbx().fetch_one(sqlx_query).await?;
+        mm.notify(ModelEvent::DeadHostChanged { id });
+
+        Ok(id)
+    }
+
+    pub async fn get(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i64,
+    ) -> Result<DeadHost> {
+        let sql = format!(
+            "{SELECT_DEAD_HOST}
+            WHERE dh.serial_id = ? AND (? = 'root' OR u.user_id = ?)
+            LIMIT 1;"
+        );
+        let sqlx_query = sqlx::query_as::<_, DeadHost>(&sql)
+            .bind(id)
+            .bind(ctx.user_id())
+            .bind(ctx.user_id());
+
+        let dead_host = mm
+            .dbx()
+            .fetch_optional(sqlx_query)
+            .await?
+            .ok_or(Error::DeadHostNotFound { id })?;
+
+        Ok(dead_host)
+    }
+
+    pub async fn list(ctx: &Ctx, mm: &ModelManager) -> Result<Vec<DeadHost>> {
+        let sql = format!(
+            "{SELECT_DEAD_HOST}
+            WHERE ? = 'root' OR u.user_id = ?
+            ORDER BY dh.serial_id;"
+        );
+        let sqlx_query = sqlx::query_as::<_, DeadHost>(&sql)
+            .bind(ctx.user_id())
+            .bind(ctx.user_id());
+
+        let dead_hosts = mm.dbx().fetch_all(sqlx_query).await?;
+
+        Ok(dead_hosts)
+    }
+
+    pub async fn update(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i64,
+        dead_host_u: DeadHostForUpdate,
+    ) -> Result<()> {
+        let DeadHostForUpdate {
+            domain_names,
+            enabled,
+        } = dead_host_u;
+
+        let now = TimeRfc3339::now_utc().format_time();
+
+        let sqlx_query = sqlx::query(
+            "UPDATE dead_host SET
+                domain_names = COALESCE(?, domain_names),
+                enabled = COALESCE(?, enabled),
+                mtime = ?
+            WHERE serial_id = ? AND (? = 'root' OR owner_serial_id = (
+                SELECT serial_id FROM users WHERE user_id = ?));",
+        )
+        .bind(domain_names.map(Json))
+        .bind(enabled)
+        .bind(now)
+        .bind(id)
+        .bind(ctx.user_id())
+        .bind(ctx.user_id());
+
+        let count = mm.dbx().execute(sqlx_query).await?;
+        if count == 0 {
+            return Err(Error::DeadHostNotFound { id });
+        }
+        mm.notify(ModelEvent::DeadHostChanged { id });
+
+        Ok(())
+    }
+
+    /// Set, or clear with `None`, the custom error page. The file itself
+    /// is written by the caller.
+    pub async fn set_error_page(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i64,
+        error_page: Option<String>,
+    ) -> Result<()> {
+        let now = TimeRfc3339::now_utc().format_time();
+
+        let sqlx_query = sqlx::query(
+            "UPDATE dead_host SET error_page = ?, mtime = ?
+            WHERE serial_id = ? AND (? = 'root' OR owner_serial_id = (
+                SELECT serial_id FROM users WHERE user_id = ?));",
+        )
+        .bind(error_page)
+        .bind(now)
+        .bind(id)
+        .bind(ctx.user_id())
+        .bind(ctx.user_id());
+
+        let count = mm.dbx().execute(sqlx_query).await?;
+        if count == 0 {
+            return Err(Error::DeadHostNotFound { id });
+        }
+        mm.notify(ModelEvent::DeadHostChanged { id });
+
+        Ok(())
+    }
+
+    pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+        let sqlx_query = sqlx::query(
+            "DELETE FROM dead_host
+            WHERE serial_id = ? AND (? = 'root' OR owner_serial_id = (
+                SELECT serial_id FROM users WHERE user_id = ?));",
+        )
+        .bind(id)
+        .bind(ctx.user_id())
+        .bind(ctx.user_id());
+
+        let count = mm.dbx().execute(sqlx_query).await?;
+        if count == 0 {
+            return Err(Error::DeadHostNotFound { id });
+        }
+        mm.notify(ModelEvent::DeadHostChanged { id });
+
+        Ok(())
+    }
+}
+
+// endregion: --- DeadHostBmc
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+    use crate::_dev_utils;
+    use sqlx::{Pool, Sqlite};
+
+    fn fx_dead_host_c(domain: &str) -> DeadHostForCreate {
+        DeadHostForCreate {
+            domain_names: vec![domain.to_string()],
+        }
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_create_set_error_page_ok(pool: Pool<Sqlite>) -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test_db(pool).await?;
+        let ctx = Ctx::new("demo1")?;
+        let mut rx = mm.subscribe();
+        let id =
+            DeadHostBmc::create(&ctx, &mm, fx_dead_host_c("gone.example.com"))
+                .await?;
+
+        // -- Exec
+        DeadHostBmc::set_error_page(
+            &ctx,
+            &mm,
+            id,
+            Some("html/error_pages/dead_host_1.html".to_string()),
+        )
+        .await?;
+
+        // -- Check
+        let dead_host = DeadHostBmc::get(&ctx, &mm, id).await?;
+        assert_eq!(dead_host.owner_id, "demo1");
+        assert_eq!(dead_host.domain_names, ["gone.example.com"]);
+        assert_eq!(
+            dead_host.error_page.as_deref(),
+            Some("html/error_pages/dead_host_1.html")
+        );
+        assert!(dead_host.enabled);
+        assert_eq!(rx.try_recv()?, ModelEvent::DeadHostChanged { id });
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_delete_not_owned_err(pool: Pool<Sqlite>) -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test_db(pool).await?;
+        let ctx_demo1 = Ctx::new("demo1")?;
+        let ctx_other = Ctx::new("other")?;
+        let id = DeadHostBmc::create(
+            &ctx_demo1,
+            &mm,
+            fx_dead_host_c("test-delete.example.com"),
+        )
+        .await?;
+
+        // -- Exec
+        let res = DeadHostBmc::delete(&ctx_other, &mm, id).await;
+
+        // -- Check
+        assert!(
+            matches!(res, Err(super::Error::DeadHostNotFound { id: res_id }) if res_id == id)
+        );
+        assert_eq!(DeadHostBmc::list(&ctx_demo1, &mm).await?.len(), 1);
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
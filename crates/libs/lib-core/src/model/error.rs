@@ -13,4 +13,34 @@ pub enum Error {
     // -- Modules
     #[error(transparent)]
     User(#[from] model::user::Error),
+
+    #[error(transparent)]
+    ProxyHost(#[from] model::proxy_host::Error),
+
+    #[error(transparent)]
+    RedirectionHost(#[from] model::redirection_host::Error),
+
+    #[error(transparent)]
+    StreamHost(#[from] model::stream_host::Error),
+
+    #[error(transparent)]
+    DeadHost(#[from] model::dead_host::Error),
+
+    #[error(transparent)]
+    AccessList(#[from] model::access_list::Error),
+
+    #[error(transparent)]
+    UpstreamGroup(#[from] model::upstream_group::Error),
+
+    #[error(transparent)]
+    AcmeAccount(#[from] model::acme_account::Error),
+
+    #[error(transparent)]
+    Certificate(#[from] model::certificate::Error),
+
+    #[error(transparent)]
+    HealthCheck(#[from] model::health_check::Error),
+
+    #[error(transparent)]
+    ConfigApply(#[from] model::config_apply::Error),
 }
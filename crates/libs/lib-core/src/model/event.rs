@@ -0,0 +1,19 @@
+//! Change notifications of the model layer.
+//!
+//! Bmcs publish a `ModelEvent` after each successful write. Subsystems
+//! which derive state from the database (e.g. the nginx config apply
+//! pipeline) subscribe with `ModelManager::subscribe`.
+
+/// Number of events a slow subscriber can lag behind before missing some.
+pub(in crate::model) const EVENTS_CAPACITY: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModelEvent {
+    ProxyHostChanged { id: i64 },
+    RedirectionHostChanged { id: i64 },
+    StreamHostChanged { id: i64 },
+    DeadHostChanged { id: i64 },
+    CertificateChanged { id: i64 },
+    AccessListChanged { id: i64 },
+    UpstreamGroupChanged { id: i64 },
+}
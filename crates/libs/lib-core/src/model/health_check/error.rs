@@ -0,0 +1,13 @@
+use crate::model::store::dbx;
+use serde::Serialize;
+use serde_with::serde_as;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[serde_as]
+#[derive(thiserror::Error, Debug, Serialize)]
+pub enum Error {
+    // -- Modules
+    #[error(transparent)]
+    Dbx(#[from] dbx::Error),
+}
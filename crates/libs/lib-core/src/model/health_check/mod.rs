@@ -0,0 +1,233 @@
+use crate::{ctx::Ctx, model::ModelManager};
+use lib_utils::time::TimeRfc3339;
+use serde::Serialize;
+use sqlx::FromRow;
+
+mod error;
+
+pub use error::{Error, Result};
+
+// region:    --- HealthCheck Types
+
+/// Status of a proxy host from its last health check.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    sqlx::Type,
+    strum_macros::Display,
+)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum HealthStatus {
+    /// Not checked yet.
+    #[default]
+    Unknown,
+    Online,
+    Offline,
+}
+
+/// The outcome of one probe of a proxy host.
+#[derive(Clone, FromRow, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthCheck {
+    pub id: i64,
+    pub proxy_host_id: i64,
+    pub healthy: bool,
+    /// `None` when the target did not answer.
+    pub latency_ms: Option<u32>,
+    pub error: Option<String>,
+    pub ctime: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct HealthCheckForCreate {
+    pub proxy_host_id: i64,
+    pub healthy: bool,
+    pub latency_ms: Option<u32>,
+    pub error: Option<String>,
+}
+
+// endregion: --- HealthCheck Types
+
+// region:    --- HealthCheckBmc
+
+/// Written by the health checker only, so no `ModelEvent` is published:
+/// the results change nothing in the nginx configuration.
+pub struct HealthCheckBmc;
+
+impl HealthCheckBmc {
+    /// Record a probe and drop the results of the host older than the
+    /// `keep` most recent ones.
+    pub async fn create(
+        _ctx: &Ctx,
+        mm: &ModelManager,
+        health_check_c: HealthCheckForCreate,
+        keep: usize,
+    ) -> Result<i64> {
+        let HealthCheckForCreate {
+            proxy_host_id,
+            healthy,
+            latency_ms,
+            error,
+        } = health_check_c;
+
+        let now = TimeRfc3339::now_utc().format_time();
+
+        let mm = mm.new_with_txn();
+        mm.dbx().begin_txn().await?;
+
+        let sqlx_query = sqlx::query_as::<_, (i64,)>(
+            "INSERT INTO health_check (proxy_host_serial_id, healthy,
+                latency_ms, error, ctime)
+            VALUES (?, ?, ?, ?, ?)
+            RETURNING serial_id;",
+        )
+        .bind(proxy_host_id)
+        .bind(healthy)
+        .bind(latency_ms)
+        .bind(error)
+        .bind(now);
+        let (id,) = mm.dbx().fetch_one(sqlx_query).await?;
+
+        let sqlx_query = sqlx::query(
+            "DELETE FROM health_check
+            WHERE proxy_host_serial_id = ? AND serial_id NOT IN (
+                SELECT serial_id FROM health_check
+                WHERE proxy_host_serial_id = ?
+                ORDER BY serial_id DESC
+                LIMIT ?);",
+        )
+        .bind(proxy_host_id)
+        .bind(proxy_host_id)
+        .bind(keep as i64);
+        mm.dbx().execute(sqlx_query).await?;
+
+        mm.dbx().commit_txn().await?;
+
+        Ok(id)
+    }
+
+    /// The kept results of a proxy host of the ctx, the most recent first.
+    pub async fn list(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        proxy_host_id: i64,
+    ) -> Result<Vec<HealthCheck>> {
+        let sqlx_query = sqlx::query_as::<_, HealthCheck>(
+            "SELECT hc.serial_id AS id,
+                hc.proxy_host_serial_id AS proxy_host_id,
+                hc.healthy, hc.latency_ms, hc.error, hc.ctime
+            FROM health_check hc
+            INNER JOIN proxy_host ph
+                ON hc.proxy_host_serial_id = ph.serial_id
+            INNER JOIN users u ON ph.owner_serial_id = u.serial_id
+            WHERE hc.proxy_host_serial_id = ? AND (? = 'root' OR u.user_id = ?)
+            ORDER BY hc.serial_id DESC;",
+        )
+        .bind(proxy_host_id)
+        .bind(ctx.user_id())
+        .bind(ctx.user_id());
+
+        let health_checks = mm.dbx().fetch_all(sqlx_query).await?;
+
+        Ok(health_checks)
+    }
+}
+
+// endregion: --- HealthCheckBmc
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+    use crate::_dev_utils;
+    use crate::model::proxy_host::{
+        ForwardScheme, ProxyHostBmc, ProxyHostForCreate,
+    };
+    use sqlx::{Pool, Sqlite};
+
+    #[sqlx::test(migrations = false)]
+    async fn test_create_keep_last_ok(pool: Pool<Sqlite>) -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test_db(pool).await?;
+        let ctx = Ctx::new("demo1")?;
+        let proxy_host_id = ProxyHostBmc::create(
+            &ctx,
+            &mm,
+            ProxyHostForCreate {
+                domain_names: vec!["test-health.example.com".to_string()],
+                forward_scheme: ForwardScheme::Http,
+                forward_host: "127.0.0.1".to_string(),
+                forward_port: 3000,
+                cache_assets: false,
+                block_exploits: false,
+                allow_websocket_upgrade: false,
+                ssl_forced: false,
+                http2_support: false,
+                hsts_enabled: false,
+                hsts_subdomains: false,
+                certificate_id: None,
+                access_list_id: None,
+                upstream_group_id: None,
+                locations: Vec::new(),
+                advanced: String::new(),
+                rate_limit: None,
+                rate_limit_burst: 0,
+                rate_limit_nodelay: false,
+                connection_limit: None,
+                health_check_path: None,
+                health_check_interval: 30,
+            },
+        )
+        .await?;
+        let proxy_host = ProxyHostBmc::get(&ctx, &mm, proxy_host_id).await?;
+        assert_eq!(proxy_host.health_status, HealthStatus::Unknown);
+
+        // -- Exec
+        for latency_ms in [Some(3), Some(4), None] {
+            HealthCheckBmc::create(
+                &Ctx::root_ctx(),
+                &mm,
+                HealthCheckForCreate {
+                    proxy_host_id,
+                    healthy: latency_ms.is_some(),
+                    latency_ms,
+                    error: latency_ms
+                        .is_none()
+                        .then(|| "connection refused".to_string()),
+                },
+                2,
+            )
+            .await?;
+        }
+
+        // -- Check
+        let health_checks =
+            HealthCheckBmc::list(&ctx, &mm, proxy_host_id).await?;
+        assert_eq!(health_checks.len(), 2);
+        assert!(!health_checks[0].healthy);
+        assert_eq!(health_checks[1].latency_ms, Some(4));
+        let proxy_host = ProxyHostBmc::get(&ctx, &mm, proxy_host_id).await?;
+        assert_eq!(proxy_host.health_status, HealthStatus::Offline);
+        assert_eq!(proxy_host.health_latency_ms, None);
+        assert!(
+            HealthCheckBmc::list(&Ctx::new("other")?, &mm, proxy_host_id)
+                .await?
+                .is_empty()
+        );
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -18,16 +18,30 @@
 
 mod acs;
 mod error;
+mod event;
 mod store;
 
+pub mod access_list;
+pub mod acme_account;
+pub mod certificate;
+pub mod config_apply;
+pub mod dead_host;
+pub mod health_check;
+pub mod proxy_host;
+pub mod redirection_host;
+pub mod stream_host;
+pub mod upstream_group;
 pub mod user;
 
 pub use self::error::{Error, Result};
+pub use self::event::ModelEvent;
 
+use crate::model::event::EVENTS_CAPACITY;
 use crate::model::store::dbx::Dbx;
 use crate::model::store::new_db_pool;
+use tokio::sync::broadcast;
 
-#[cfg(test)]
+#[cfg(any(test, feature = "dev-utils"))]
 use sqlx::{Pool, Sqlite};
 
 // endregion: --- Modules
@@ -37,6 +51,7 @@ use sqlx::{Pool, Sqlite};
 #[derive(Clone)]
 pub struct ModelManager {
     dbx: Dbx,
+    events: broadcast::Sender<ModelEvent>,
 }
 
 impl ModelManager {
@@ -45,23 +60,38 @@ impl ModelManager {
             Error::CantCreateModelManagerProvider(ex.to_string())
         })?;
         let dbx = Dbx::new(db_pool, false);
-        Ok(ModelManager { dbx })
+        let (events, _) = broadcast::channel(EVENTS_CAPACITY);
+        Ok(ModelManager { dbx, events })
     }
 
-    #[cfg(test)]
+    #[cfg(any(test, feature = "dev-utils"))]
     pub async fn new_with_pool(db_pool: Pool<Sqlite>) -> Result<Self> {
         let dbx = Dbx::new(db_pool, false);
-        Ok(ModelManager { dbx })
+        let (events, _) = broadcast::channel(EVENTS_CAPACITY);
+        Ok(ModelManager { dbx, events })
     }
 
     pub fn new_with_txn(&self) -> ModelManager {
         let dbx = Dbx::new(self.dbx.db().clone(), true);
-        ModelManager { dbx }
+        ModelManager {
+            dbx,
+            events: self.events.clone(),
+        }
     }
 
     pub fn dbx(&self) -> &Dbx {
         &self.dbx
     }
+
+    /// Receive the `ModelEvent`s published from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<ModelEvent> {
+        self.events.subscribe()
+    }
+
+    pub(in crate::model) fn notify(&self, event: ModelEvent) {
+        // No subscriber is not an error.
+        let _ = self.events.send(event);
+    }
 }
 
 // endregion: --- ModelManager
@@ -0,0 +1,29 @@
+use crate::model::access_list;
+use crate::model::certificate;
+use crate::model::store::dbx;
+use crate::model::upstream_group;
+use serde::Serialize;
+use serde_with::serde_as;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[serde_as]
+#[derive(thiserror::Error, Debug, Serialize, strum_macros::Display)]
+pub enum Error {
+    ProxyHostNotFound {
+        id: i64,
+    },
+
+    // -- Modules
+    #[error(transparent)]
+    Certificate(#[from] certificate::Error),
+
+    #[error(transparent)]
+    AccessList(#[from] access_list::Error),
+
+    #[error(transparent)]
+    UpstreamGroup(#[from] upstream_group::Error),
+
+    #[error(transparent)]
+    Dbx(#[from] dbx::Error),
+}
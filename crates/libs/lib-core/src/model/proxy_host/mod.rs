@@ -0,0 +1,926 @@
+use crate::{
+    ctx::Ctx,
+    model::{
+        ModelEvent, ModelManager, access_list::AccessListBmc,
+        certificate::CertificateBmc, health_check::HealthStatus, store::dbx,
+        upstream_group::UpstreamGroupBmc,
+    },
+};
+use lib_utils::time::TimeRfc3339;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use sqlx::types::Json;
+
+mod error;
+
+pub use error::{Error, Result};
+
+// region:    --- ProxyHost Types
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Deserialize,
+    Serialize,
+    sqlx::Type,
+    strum_macros::Display,
+)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum ForwardScheme {
+    #[default]
+    Http,
+    Https,
+}
+
+/// A custom location, proxied to its own upstream.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProxyLocation {
+    /// Uri prefix, e.g. `/api/`.
+    pub path: String,
+    #[serde(default)]
+    pub forward_scheme: ForwardScheme,
+    pub forward_host: String,
+    pub forward_port: u16,
+    /// Replace the default proxy headers of the same name.
+    #[serde(default)]
+    pub headers: Vec<ProxyHeader>,
+    /// Raw nginx directives appended to the `location` block.
+    #[serde(default)]
+    pub advanced: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ProxyHeader {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Clone, FromRow, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProxyHost {
+    pub id: i64,
+    /// `user_id` of the owner.
+    pub owner_id: String,
+
+    #[sqlx(json)]
+    pub domain_names: Vec<String>,
+    pub forward_scheme: ForwardScheme,
+    pub forward_host: String,
+    pub forward_port: u16,
+
+    pub cache_assets: bool,
+    pub block_exploits: bool,
+    pub allow_websocket_upgrade: bool,
+
+    /// The ssl flags only apply with a certificate.
+    pub ssl_forced: bool,
+    pub http2_support: bool,
+    pub hsts_enabled: bool,
+    pub hsts_subdomains: bool,
+
+    /// Served over https with this certificate, http only when `None`.
+    pub certificate_id: Option<i64>,
+    pub certificate_name: Option<String>,
+
+    /// Public when `None`.
+    pub access_list_id: Option<i64>,
+    pub access_list_name: Option<String>,
+
+    /// Forwards to the servers of the group instead of
+    /// `forward_host:forward_port` when set.
+    pub upstream_group_id: Option<i64>,
+    pub upstream_group_name: Option<String>,
+
+    /// In order, before `location /`.
+    #[sqlx(json)]
+    pub locations: Vec<ProxyLocation>,
+    /// Raw nginx directives of the `server` block.
+    pub advanced: String,
+
+    /// Requests per second of a client, unlimited when `None`.
+    pub rate_limit: Option<u32>,
+    /// Requests over the rate delayed, the next ones refused.
+    pub rate_limit_burst: u32,
+    /// Serves the burst without delay.
+    pub rate_limit_nodelay: bool,
+    /// Concurrent connections of a client, unlimited when `None`.
+    pub connection_limit: Option<u32>,
+
+    /// Probed with a HTTP GET of the path, a TCP connect when `None`.
+    pub health_check_path: Option<String>,
+    /// Seconds between two probes.
+    pub health_check_interval: u32,
+    /// From the last probe.
+    pub health_status: HealthStatus,
+    pub health_latency_ms: Option<u32>,
+    pub health_check_time: Option<String>,
+
+    pub enabled: bool,
+
+    pub ctime: String,
+    pub mtime: String,
+}
+
+/// Fields required for creating new proxy host
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProxyHostForCreate {
+    pub domain_names: Vec<String>,
+    #[serde(default)]
+    pub forward_scheme: ForwardScheme,
+    pub forward_host: String,
+    pub forward_port: u16,
+
+    #[serde(default)]
+    pub cache_assets: bool,
+    #[serde(default)]
+    pub block_exploits: bool,
+    #[serde(default)]
+    pub allow_websocket_upgrade: bool,
+
+    #[serde(default)]
+    pub ssl_forced: bool,
+    #[serde(default)]
+    pub http2_support: bool,
+    #[serde(default)]
+    pub hsts_enabled: bool,
+    #[serde(default)]
+    pub hsts_subdomains: bool,
+    #[serde(default)]
+    pub certificate_id: Option<i64>,
+
+    #[serde(default)]
+    pub access_list_id: Option<i64>,
+    #[serde(default)]
+    pub upstream_group_id: Option<i64>,
+
+    #[serde(default)]
+    pub locations: Vec<ProxyLocation>,
+    #[serde(default)]
+    pub advanced: String,
+
+    #[serde(default)]
+    pub rate_limit: Option<u32>,
+    #[serde(default)]
+    pub rate_limit_burst: u32,
+    #[serde(default)]
+    pub rate_limit_nodelay: bool,
+    #[serde(default)]
+    pub connection_limit: Option<u32>,
+
+    #[serde(default)]
+    pub health_check_path: Option<String>,
+    #[serde(default = "default_health_check_interval")]
+    pub health_check_interval: u32,
+}
+
+fn default_health_check_interval() -> u32 {
+    30
+}
+
+/// Fields left to `None` are not updated
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProxyHostForUpdate {
+    pub domain_names: Option<Vec<String>>,
+    pub forward_scheme: Option<ForwardScheme>,
+    pub forward_host: Option<String>,
+    pub forward_port: Option<u16>,
+
+    pub cache_assets: Option<bool>,
+    pub block_exploits: Option<bool>,
+    pub allow_websocket_upgrade: Option<bool>,
+
+    pub ssl_forced: Option<bool>,
+    pub http2_support: Option<bool>,
+    pub hsts_enabled: Option<bool>,
+    pub hsts_subdomains: Option<bool>,
+    /// `Some(None)` (`null`) serves the host over http only.
+    #[serde(default, with = "::serde_with::rust::double_option")]
+    pub certificate_id: Option<Option<i64>>,
+
+    /// `Some(None)` (`null`) makes the host public.
+    #[serde(default, with = "::serde_with::rust::double_option")]
+    pub access_list_id: Option<Option<i64>>,
+    /// `Some(None)` (`null`) forwards to `forward_host:forward_port` again.
+    #[serde(default, with = "::serde_with::rust::double_option")]
+    pub upstream_group_id: Option<Option<i64>>,
+
+    /// Replaces all the locations.
+    pub locations: Option<Vec<ProxyLocation>>,
+    pub advanced: Option<String>,
+
+    /// `Some(None)` (`null`) removes the rate limit.
+    #[serde(default, with = "::serde_with::rust::double_option")]
+    pub rate_limit: Option<Option<u32>>,
+    pub rate_limit_burst: Option<u32>,
+    pub rate_limit_nodelay: Option<bool>,
+    /// `Some(None)` (`null`) removes the connection limit.
+    #[serde(default, with = "::serde_with::rust::double_option")]
+    pub connection_limit: Option<Option<u32>>,
+
+    /// `Some(None)` (`null`) probes with a TCP connect.
+    #[serde(default, with = "::serde_with::rust::double_option")]
+    pub health_check_path: Option<Option<String>>,
+    pub health_check_interval: Option<u32>,
+
+    pub enabled: Option<bool>,
+}
+
+// endregion: --- ProxyHost Types
+
+// region:    --- ProxyHostBmc
+
+/// Proxy hosts are owned by the user who created them. Every query is
+/// scoped to the `Ctx` user, except for the root ctx which sees all hosts
+/// (e.g. when generating the nginx configuration).
+pub struct ProxyHostBmc;
+
+impl ProxyHostBmc {
+    pub async fn create(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        proxy_host_c: ProxyHostForCreate,
+    ) -> Result<i64> {
+        let ProxyHostForCreate {
+            domain_names,
+            forward_scheme,
+            forward_host,
+            forward_port,
+            cache_assets,
+            block_exploits,
+            allow_websocket_upgrade,
+            ssl_forced,
+            http2_support,
+            hsts_enabled,
+            hsts_subdomains,
+            certificate_id,
+            access_list_id,
+            upstream_group_id,
+            locations,
+            advanced,
+            rate_limit,
+            rate_limit_burst,
+            rate_limit_nodelay,
+            connection_limit,
+            health_check_path,
+            health_check_interval,
+        } = proxy_host_c;
+
+        if let Some(certificate_id) = certificate_id {
+            CertificateBmc::get(ctx, mm, certificate_id).await?;
+        }
+        if let Some(access_list_id) = access_list_id {
+            AccessListBmc::get(ctx, mm, access_list_id).await?;
+        }
+        if let Some(upstream_group_id) = upstream_group_id {
+            UpstreamGroupBmc::get(ctx, mm, upstream_group_id).await?;
+        }
+        let now = TimeRfc3339::now_utc().format_time();
+
+        let domain_names = Json(domain_names);
+        let locations = Json(locations);
+        let user_id = ctx.user_id();
+
+        let sqlx_query = sqlx::query!(
+            r#"INSERT INTO proxy_host (owner_serial_id, domain_names,
+                forward_scheme, forward_host, forward_port,
+                cache_assets, block_exploits, allow_websocket_upgrade,
+                ssl_forced, http2_support, hsts_enabled, hsts_subdomains,
+                certificate_serial_id, access_list_serial_id,
+                upstream_group_serial_id, locations, advanced,
+                rate_limit, rate_limit_burst, rate_limit_nodelay,
+                connection_limit,
+                health_check_path, health_check_interval, ctime, mtime)
+            VALUES ((SELECT serial_id FROM users WHERE user_id = ?),
+                ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?,
+                ?, ?, ?, ?)
+            RETURNING serial_id AS "id!";"#,
+            user_id,
+            domain_names,
+            forward_scheme,
+            forward_host,
+            forward_port,
+            cache_assets,
+            block_exploits,
+            allow_websocket_upgrade,
+            ssl_forced,
+            http2_support,
+            hsts_enabled,
+            hsts_subdomains,
+            certificate_id,
+            access_list_id,
+            upstream_group_id,
+            locations,
+            advanced,
+            rate_limit,
+            rate_limit_burst,
+            rate_limit_nodelay,
+            connection_limit,
+            health_check_path,
+            health_check_interval,
+            now,
+            now,
+        )
+        .fetch_one(mm.dbx().db())
+        .await
+        .map_err(dbx::Error::from)?;
+
+        let id = sqlx_query.id;
+        mm.notify(ModelEvent::ProxyHostChanged { id });
+
+        Ok(id)
+    }
+
+    pub async fn get(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i64,
+    ) -> Result<ProxyHost> {
+        let proxy_host = Self::select(ctx, mm, Some(id))
+            .await?
+            .pop()
+            .ok_or(Error::ProxyHostNotFound { id })?;
+
+        Ok(proxy_host)
+    }
+
+    pub async fn list(ctx: &Ctx, mm: &ModelManager) -> Result<Vec<ProxyHost>> {
+        Self::select(ctx, mm, None).await
+    }
+
+    /// The hosts seen by the ctx, only the `id` one when set.
+    async fn select(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: Option<i64>,
+    ) -> Result<Vec<ProxyHost>> {
+        let user_id = ctx.user_id();
+
+        let proxy_hosts = sqlx::query!(
+            r#"SELECT ph.serial_id AS "id!", u.user_id AS owner_id,
+                ph.domain_names AS "domain_names: Json<Vec<String>>",
+                ph.forward_scheme AS "forward_scheme: ForwardScheme",
+                ph.forward_host, ph.forward_port AS "forward_port: u16",
+                ph.cache_assets AS "cache_assets: bool",
+                ph.block_exploits AS "block_exploits: bool",
+                ph.allow_websocket_upgrade AS "allow_websocket_upgrade: bool",
+                ph.ssl_forced AS "ssl_forced: bool",
+                ph.http2_support AS "http2_support: bool",
+                ph.hsts_enabled AS "hsts_enabled: bool",
+                ph.hsts_subdomains AS "hsts_subdomains: bool",
+                ph.certificate_serial_id AS certificate_id,
+                c.nice_name AS "certificate_name?",
+                ph.access_list_serial_id AS access_list_id,
+                al.name AS "access_list_name?",
+                ph.upstream_group_serial_id AS upstream_group_id,
+                ug.name AS "upstream_group_name?",
+                ph.locations AS "locations: Json<Vec<ProxyLocation>>",
+                ph.advanced,
+                ph.rate_limit AS "rate_limit: u32",
+                ph.rate_limit_burst AS "rate_limit_burst: u32",
+                ph.rate_limit_nodelay AS "rate_limit_nodelay: bool",
+                ph.connection_limit AS "connection_limit: u32",
+                ph.health_check_path,
+                ph.health_check_interval AS "health_check_interval: u32",
+                CASE WHEN hc.serial_id IS NULL THEN 'unknown'
+                    WHEN hc.healthy THEN 'online' ELSE 'offline'
+                    END AS "health_status!: HealthStatus",
+                hc.latency_ms AS "health_latency_ms?: u32",
+                hc.ctime AS "health_check_time?",
+                ph.enabled AS "enabled: bool", ph.ctime, ph.mtime
+            FROM proxy_host ph
+            INNER JOIN users u ON ph.owner_serial_id = u.serial_id
+            LEFT JOIN certificate c ON ph.certificate_serial_id = c.serial_id
+            LEFT JOIN access_list al ON ph.access_list_serial_id = al.serial_id
+            LEFT JOIN upstream_group ug
+                ON ph.upstream_group_serial_id = ug.serial_id
+            LEFT JOIN health_check hc ON hc.serial_id = (
+                SELECT MAX(serial_id) FROM health_check
+                WHERE proxy_host_serial_id = ph.serial_id)
+            WHERE (? IS NULL OR ph.serial_id = ?)
+                AND (? = 'root' OR u.user_id = ?)
+            ORDER BY ph.serial_id;"#,
+            id,
+            id,
+            user_id,
+            user_id,
+        )
+        .map(|row| ProxyHost {
+            id: row.id,
+            owner_id: row.owner_id,
+            domain_names: row.domain_names.0,
+            forward_scheme: row.forward_scheme,
+            forward_host: row.forward_host,
+            forward_port: row.forward_port,
+            cache_assets: row.cache_assets,
+            block_exploits: row.block_exploits,
+            allow_websocket_upgrade: row.allow_websocket_upgrade,
+            ssl_forced: row.ssl_forced,
+            http2_support: row.http2_support,
+            hsts_enabled: row.hsts_enabled,
+            hsts_subdomains: row.hsts_subdomains,
+            certificate_id: row.certificate_id,
+            certificate_name: row.certificate_name,
+            access_list_id: row.access_list_id,
+            access_list_name: row.access_list_name,
+            upstream_group_id: row.upstream_group_id,
+            upstream_group_name: row.upstream_group_name,
+            locations: row.locations.0,
+            advanced: row.advanced,
+            rate_limit: row.rate_limit,
+            rate_limit_burst: row.rate_limit_burst,
+            rate_limit_nodelay: row.rate_limit_nodelay,
+            connection_limit: row.connection_limit,
+            health_check_path: row.health_check_path,
+            health_check_interval: row.health_check_interval,
+            health_status: row.health_status,
+            health_latency_ms: row.health_latency_ms,
+            health_check_time: row.health_check_time,
+            enabled: row.enabled,
+            ctime: row.ctime,
+            mtime: row.mtime,
+        })
+        .fetch_all(mm.dbx().db())
+        .await
+        .map_err(dbx::Error::from)?;
+
+        Ok(proxy_hosts)
+    }
+
+    pub async fn update(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i64,
+        proxy_host_u: ProxyHostForUpdate,
+    ) -> Result<()> {
+        let ProxyHostForUpdate {
+            domain_names,
+            forward_scheme,
+            forward_host,
+            forward_port,
+            cache_assets,
+            block_exploits,
+            allow_websocket_upgrade,
+            ssl_forced,
+            http2_support,
+            hsts_enabled,
+            hsts_subdomains,
+            certificate_id,
+            access_list_id,
+            upstream_group_id,
+            locations,
+            advanced,
+            rate_limit,
+            rate_limit_burst,
+            rate_limit_nodelay,
+            connection_limit,
+            health_check_path,
+            health_check_interval,
+            enabled,
+        } = proxy_host_u;
+
+        if let Some(Some(certificate_id)) = certificate_id {
+            CertificateBmc::get(ctx, mm, certificate_id).await?;
+        }
+        if let Some(Some(access_list_id)) = access_list_id {
+            AccessListBmc::get(ctx, mm, access_list_id).await?;
+        }
+        if let Some(Some(upstream_group_id)) = upstream_group_id {
+            UpstreamGroupBmc::get(ctx, mm, upstream_group_id).await?;
+        }
+        let now = TimeRfc3339::now_utc().format_time();
+
+        let domain_names = domain_names.map(Json);
+        let locations = locations.map(Json);
+        let certificate_id_set = certificate_id.is_some();
+        let certificate_id = certificate_id.flatten();
+        let access_list_id_set = access_list_id.is_some();
+        let access_list_id = access_list_id.flatten();
+        let upstream_group_id_set = upstream_group_id.is_some();
+        let upstream_group_id = upstream_group_id.flatten();
+        let rate_limit_set = rate_limit.is_some();
+        let rate_limit = rate_limit.flatten();
+        let connection_limit_set = connection_limit.is_some();
+        let connection_limit = connection_limit.flatten();
+        let health_check_path_set = health_check_path.is_some();
+        let health_check_path = health_check_path.flatten();
+        let user_id = ctx.user_id();
+
+        let count = sqlx::query!(
+            "UPDATE proxy_host SET
+                domain_names = COALESCE(?, domain_names),
+                forward_scheme = COALESCE(?, forward_scheme),
+                forward_host = COALESCE(?, forward_host),
+                forward_port = COALESCE(?, forward_port),
+                cache_assets = COALESCE(?, cache_assets),
+                block_exploits = COALESCE(?, block_exploits),
+                allow_websocket_upgrade = COALESCE(?, allow_websocket_upgrade),
+                ssl_forced = COALESCE(?, ssl_forced),
+                http2_support = COALESCE(?, http2_support),
+                hsts_enabled = COALESCE(?, hsts_enabled),
+                hsts_subdomains = COALESCE(?, hsts_subdomains),
+                certificate_serial_id = CASE WHEN ?
+                    THEN ? ELSE certificate_serial_id END,
+                access_list_serial_id = CASE WHEN ?
+                    THEN ? ELSE access_list_serial_id END,
+                upstream_group_serial_id = CASE WHEN ?
+                    THEN ? ELSE upstream_group_serial_id END,
+                locations = COALESCE(?, locations),
+                advanced = COALESCE(?, advanced),
+                rate_limit = CASE WHEN ? THEN ? ELSE rate_limit END,
+                rate_limit_burst = COALESCE(?, rate_limit_burst),
+                rate_limit_nodelay = COALESCE(?, rate_limit_nodelay),
+                connection_limit = CASE WHEN ?
+                    THEN ? ELSE connection_limit END,
+                health_check_path = CASE WHEN ?
+                    THEN ? ELSE health_check_path END,
+                health_check_interval = COALESCE(?, health_check_interval),
+                enabled = COALESCE(?, enabled),
+                mtime = ?
+            WHERE serial_id = ? AND (? = 'root' OR owner_serial_id = (
+                SELECT serial_id FROM users WHERE user_id = ?));",
+            domain_names,
+            forward_scheme,
+            forward_host,
+            forward_port,
+            cache_assets,
+            block_exploits,
+            allow_websocket_upgrade,
+            ssl_forced,
+            http2_support,
+            hsts_enabled,
+            hsts_subdomains,
+            certificate_id_set,
+            certificate_id,
+            access_list_id_set,
+            access_list_id,
+            upstream_group_id_set,
+            upstream_group_id,
+            locations,
+            advanced,
+            rate_limit_set,
+            rate_limit,
+            rate_limit_burst,
+            rate_limit_nodelay,
+            connection_limit_set,
+            connection_limit,
+            health_check_path_set,
+            health_check_path,
+            health_check_interval,
+            enabled,
+            now,
+            id,
+            user_id,
+            user_id,
+        )
+        .execute(mm.dbx().db())
+        .await
+        .map_err(dbx::Error::from)?
+        .rows_affected();
+        if count == 0 {
+            return Err(Error::ProxyHostNotFound { id });
+        }
+        mm.notify(ModelEvent::ProxyHostChanged { id });
+
+        Ok(())
+    }
+
+    pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+        let user_id = ctx.user_id();
+
+        let count = sqlx::query!(
+            "DELETE FROM proxy_host
+            WHERE serial_id = ? AND (? = 'root' OR owner_serial_id = (
+                SELECT serial_id FROM users WHERE user_id = ?));",
+            id,
+            user_id,
+            user_id,
+        )
+        .execute(mm.dbx().db())
+        .await
+        .map_err(dbx::Error::from)?
+        .rows_affected();
+        if count == 0 {
+            return Err(Error::ProxyHostNotFound { id });
+        }
+        mm.notify(ModelEvent::ProxyHostChanged { id });
+
+        Ok(())
+    }
+}
+
+// endregion: --- ProxyHostBmc
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+    use crate::_dev_utils;
+    use crate::model::certificate::{
+        self, CertificateChallenge, CertificateForCreate,
+    };
+    use sqlx::{Pool, Sqlite};
+
+    #[sqlx::test(migrations = false)]
+    async fn test_create_ok(pool: Pool<Sqlite>) -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test_db(pool).await?;
+        let ctx = Ctx::new("demo1")?;
+        let fx_domain = "test-create-ok.example.com";
+
+        // -- Exec
+        let id = ProxyHostBmc::create(
+            &ctx,
+            &mm,
+            ProxyHostForCreate {
+                block_exploits: true,
+                .._dev_utils::fx_proxy_host_c(fx_domain)
+            },
+        )
+        .await?;
+
+        // -- Check
+        let proxy_host = ProxyHostBmc::get(&ctx, &mm, id).await?;
+        assert_eq!(proxy_host.owner_id, "demo1");
+        assert_eq!(proxy_host.domain_names, [fx_domain]);
+        assert_eq!(proxy_host.forward_port, 3000);
+        assert!(proxy_host.block_exploits);
+        assert!(proxy_host.enabled);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_update_ok(pool: Pool<Sqlite>) -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test_db(pool).await?;
+        let ctx = Ctx::new("demo1")?;
+        let id = ProxyHostBmc::create(
+            &ctx,
+            &mm,
+            _dev_utils::fx_proxy_host_c("test-update-ok.example.com"),
+        )
+        .await?;
+
+        // -- Exec
+        ProxyHostBmc::update(
+            &ctx,
+            &mm,
+            id,
+            ProxyHostForUpdate {
+                forward_scheme: Some(ForwardScheme::Https),
+                forward_port: Some(8443),
+                advanced: Some("client_max_body_size 50m;".to_string()),
+                rate_limit: Some(Some(10)),
+                connection_limit: Some(Some(50)),
+                enabled: Some(false),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        // -- Check
+        let proxy_host = ProxyHostBmc::get(&ctx, &mm, id).await?;
+        assert_eq!(proxy_host.forward_scheme, ForwardScheme::Https);
+        assert_eq!(proxy_host.forward_port, 8443);
+        assert_eq!(proxy_host.forward_host, "127.0.0.1");
+        assert_eq!(proxy_host.advanced, "client_max_body_size 50m;");
+        assert_eq!(proxy_host.rate_limit, Some(10));
+        assert_eq!(proxy_host.connection_limit, Some(50));
+        assert!(!proxy_host.enabled);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_update_certificate_ok(pool: Pool<Sqlite>) -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test_db(pool).await?;
+        let ctx = Ctx::new("demo1")?;
+        let fx_domain = "test-update-certificate.example.com";
+        let certificate_id = CertificateBmc::create(
+            &ctx,
+            &mm,
+            CertificateForCreate {
+                nice_name: "app".to_string(),
+                domain_names: vec![fx_domain.to_string()],
+                challenge: CertificateChallenge::Http01,
+            },
+        )
+        .await?;
+        let id = ProxyHostBmc::create(
+            &ctx,
+            &mm,
+            _dev_utils::fx_proxy_host_c(fx_domain),
+        )
+        .await?;
+
+        // -- Exec
+        ProxyHostBmc::update(
+            &ctx,
+            &mm,
+            id,
+            ProxyHostForUpdate {
+                certificate_id: Some(Some(certificate_id)),
+                ssl_forced: Some(true),
+                hsts_enabled: Some(true),
+                ..Default::default()
+            },
+        )
+        .await?;
+        let linked = ProxyHostBmc::get(&ctx, &mm, id).await?;
+        ProxyHostBmc::update(
+            &ctx,
+            &mm,
+            id,
+            ProxyHostForUpdate {
+                certificate_id: Some(None),
+                ..Default::default()
+            },
+        )
+        .await?;
+        let res_missing = ProxyHostBmc::update(
+            &ctx,
+            &mm,
+            id,
+            ProxyHostForUpdate {
+                certificate_id: Some(Some(certificate_id + 100)),
+                ..Default::default()
+            },
+        )
+        .await;
+
+        // -- Check
+        assert_eq!(linked.certificate_id, Some(certificate_id));
+        assert_eq!(linked.certificate_name.as_deref(), Some("app"));
+        assert!(linked.ssl_forced && linked.hsts_enabled);
+        let proxy_host = ProxyHostBmc::get(&ctx, &mm, id).await?;
+        assert_eq!(proxy_host.certificate_id, None);
+        assert!(proxy_host.ssl_forced);
+        assert!(matches!(
+            res_missing,
+            Err(super::Error::Certificate(
+                certificate::Error::CertificateNotFound { .. }
+            ))
+        ));
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_update_locations_ok(pool: Pool<Sqlite>) -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test_db(pool).await?;
+        let ctx = Ctx::new("demo1")?;
+        let fx_location = |path: &str| ProxyLocation {
+            path: path.to_string(),
+            forward_scheme: ForwardScheme::Http,
+            forward_host: "10.0.0.6".to_string(),
+            forward_port: 8080,
+            headers: vec![ProxyHeader {
+                name: "X-Api-Key".to_string(),
+                value: "s3cret".to_string(),
+            }],
+            advanced: "proxy_read_timeout 300s;".to_string(),
+        };
+        let id = ProxyHostBmc::create(
+            &ctx,
+            &mm,
+            ProxyHostForCreate {
+                locations: vec![fx_location("/api/"), fx_location("/ws/")],
+                .._dev_utils::fx_proxy_host_c(
+                    "test-update-locations.example.com",
+                )
+            },
+        )
+        .await?;
+
+        // -- Exec
+        ProxyHostBmc::update(
+            &ctx,
+            &mm,
+            id,
+            ProxyHostForUpdate {
+                forward_port: Some(3001),
+                ..Default::default()
+            },
+        )
+        .await?;
+        let kept = ProxyHostBmc::get(&ctx, &mm, id).await?.locations;
+        ProxyHostBmc::update(
+            &ctx,
+            &mm,
+            id,
+            ProxyHostForUpdate {
+                locations: Some(vec![fx_location("/ws/")]),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        // -- Check
+        assert_eq!(kept, [fx_location("/api/"), fx_location("/ws/")]);
+        let proxy_host = ProxyHostBmc::get(&ctx, &mm, id).await?;
+        assert_eq!(proxy_host.locations, [fx_location("/ws/")]);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_list_owned_ok(pool: Pool<Sqlite>) -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test_db(pool).await?;
+        let ctx_demo1 = Ctx::new("demo1")?;
+        let ctx_root = Ctx::root_ctx();
+        ProxyHostBmc::create(
+            &ctx_demo1,
+            &mm,
+            _dev_utils::fx_proxy_host_c("test-list-demo1.example.com"),
+        )
+        .await?;
+        ProxyHostBmc::create(
+            &ctx_root,
+            &mm,
+            _dev_utils::fx_proxy_host_c("test-list-root.example.com"),
+        )
+        .await?;
+
+        // -- Exec
+        let demo1_hosts = ProxyHostBmc::list(&ctx_demo1, &mm).await?;
+        let all_hosts = ProxyHostBmc::list(&ctx_root, &mm).await?;
+
+        // -- Check
+        assert_eq!(demo1_hosts.len(), 1);
+        assert_eq!(
+            demo1_hosts[0].domain_names,
+            ["test-list-demo1.example.com"]
+        );
+        assert_eq!(all_hosts.len(), 2);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_notify_ok(pool: Pool<Sqlite>) -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test_db(pool).await?;
+        let ctx = Ctx::new("demo1")?;
+        let mut rx = mm.subscribe();
+
+        // -- Exec
+        let id = ProxyHostBmc::create(
+            &ctx,
+            &mm,
+            _dev_utils::fx_proxy_host_c("test-notify.example.com"),
+        )
+        .await?;
+        ProxyHostBmc::delete(&ctx, &mm, id).await?;
+
+        // -- Check
+        assert_eq!(rx.try_recv()?, ModelEvent::ProxyHostChanged { id });
+        assert_eq!(rx.try_recv()?, ModelEvent::ProxyHostChanged { id });
+        assert!(rx.try_recv().is_err());
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_delete_not_owned_err(pool: Pool<Sqlite>) -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test_db(pool).await?;
+        let ctx_demo1 = Ctx::new("demo1")?;
+        let ctx_other = Ctx::new("other")?;
+        let id = ProxyHostBmc::create(
+            &ctx_demo1,
+            &mm,
+            _dev_utils::fx_proxy_host_c("test-delete.example.com"),
+        )
+        .await?;
+
+        // -- Exec
+        let res = ProxyHostBmc::delete(&ctx_other, &mm, id).await;
+
+        // -- Check
+        assert!(
+            matches!(res, Err(super::Error::ProxyHostNotFound { id: res_id }) if res_id == id)
+        );
+        ProxyHostBmc::delete(&ctx_demo1, &mm, id).await?;
+        assert!(ProxyHostBmc::get(&ctx_demo1, &mm, id).await.is_err());
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
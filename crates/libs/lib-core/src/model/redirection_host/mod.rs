@@ -0,0 +1,382 @@
+use crate::{
+    ctx::Ctx,
+    model::{ModelEvent, ModelManager},
+};
+use lib_utils::time::TimeRfc3339;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use sqlx::types::Json;
+
+mod error;
+
+pub use error::{Error, Result};
+
+// region:    --- RedirectionHost Types
+
+#[derive(Clone, FromRow, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectionHost {
+    pub id: i64,
+    /// `user_id` of the owner.
+    pub owner_id: String,
+
+    #[sqlx(json)]
+    pub domain_names: Vec<String>,
+    pub forward_url: String,
+    /// One of 301, 302, 307 or 308.
+    pub redirect_code: u16,
+    pub preserve_path: bool,
+
+    pub ssl_forced: bool,
+    pub http2_support: bool,
+    pub hsts_enabled: bool,
+    pub hsts_subdomains: bool,
+
+    pub enabled: bool,
+
+    pub ctime: String,
+    pub mtime: String,
+}
+
+/// Fields required for creating new redirection host
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectionHostForCreate {
+    pub domain_names: Vec<String>,
+    pub forward_url: String,
+    #[serde(default = "default_redirect_code")]
+    pub redirect_code: u16,
+    #[serde(default = "default_preserve_path")]
+    pub preserve_path: bool,
+
+    #[serde(default)]
+    pub ssl_forced: bool,
+    #[serde(default)]
+    pub http2_support: bool,
+    #[serde(default)]
+    pub hsts_enabled: bool,
+    #[serde(default)]
+    pub hsts_subdomains: bool,
+}
+
+fn default_redirect_code() -> u16 {
+    301
+}
+
+fn default_preserve_path() -> bool {
+    true
+}
+
+/// Fields left to `None` are not updated
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectionHostForUpdate {
+    pub domain_names: Option<Vec<String>>,
+    pub forward_url: Option<String>,
+    pub redirect_code: Option<u16>,
+    pub preserve_path: Option<bool>,
+
+    pub ssl_forced: Option<bool>,
+    pub http2_support: Option<bool>,
+    pub hsts_enabled: Option<bool>,
+    pub hsts_subdomains: Option<bool>,
+
+    pub enabled: Option<bool>,
+}
+
+// endregion: --- RedirectionHost Types
+
+// region:    --- RedirectionHostBmc
+
+const SELECT_REDIRECTION_HOST: &str =
+    "SELECT rh.serial_id AS id, u.user_id AS owner_id,
+        rh.domain_names, rh.forward_url, rh.redirect_code, rh.preserve_path,
+        rh.ssl_forced, rh.http2_support, rh.hsts_enabled, rh.hsts_subdomains,
+        rh.enabled, rh.ctime, rh.mtime
+    FROM redirection_host rh
+    INNER JOIN users u ON rh.owner_serial_id = u.serial_id";
+
+/// Same ownership rules as `ProxyHostBmc`: scoped to the `Ctx` user,
+/// the root ctx sees all hosts.
+pub struct RedirectionHostBmc;
+
+impl RedirectionHostBmc {
+    pub async fn create(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        redirection_host_c: RedirectionHostForCreate,
+    ) -> Result<i64> {
+        let RedirectionHostForCreate {
+            domain_names,
+            forward_url,
+            redirect_code,
+            preserve_path,
+            ssl_forced,
+            http2_support,
+            hsts_enabled,
+            hsts_subdomains,
+        } = redirection_host_c;
+
+        let now = TimeRfc3339::now_utc().format_time();
+
+        let sqlx_query = sqlx::query_as::<_, (i64,)>(
+            "INSERT INTO redirection_host (owner_serial_id, domain_names,
+                forward_url, redirect_code, preserve_path,
+                ssl_forced, http2_support, hsts_enabled, hsts_subdomains,
+                ctime, mtime)
+            VALUES ((SELECT serial_id FROM users WHERE user_id = ?),
+                ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            RETURNING serial_id;",
+        )
+        .bind(ctx.user_id())
+        .bind(Json(domain_names))
+        .bind(forward_url)
+        .bind(redirect_code)
+        .bind(preserve_path)
+        .bind(ssl_forced)
+        .bind(http2_support)
+        .bind(hsts_enabled)
+        .bind(hsts_subdomains)
+        .bind(&now)
+        .bind(&now);
+
+        let (id,) = mm.dbx().fetch_one(sqlx_query).await?;
+        mm.notify(ModelEvent::RedirectionHostChanged { id });
+
+        Ok(id)
+    }
+
+    pub async fn get(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i64,
+    ) -> Result<RedirectionHost> {
+        let sql = format!(
+            "{SELECT_REDIRECTION_HOST}
+            WHERE rh.serial_id = ? AND (? = 'root' OR u.user_id = ?)
+            LIMIT 1;"
+        );
+        let sqlx_query = sqlx::query_as::<_, RedirectionHost>(&sql)
+            .bind(id)
+            .bind(ctx.user_id())
+            .bind(ctx.user_id());
+
+        let redirection_host = mm
+            .dbx()
+            .fetch_optional(sqlx_query)
+            .await?
+            .ok_or(Error::RedirectionHostNotFound { id })?;
+
+        Ok(redirection_host)
+    }
+
+    pub async fn list(
+        ctx: &Ctx,
+        mm: &ModelManager,
+    ) -> Result<Vec<RedirectionHost>> {
+        let sql = format!(
+            "{SELECT_REDIRECTION_HOST}
+            WHERE ? = 'root' OR u.user_id = ?
+            ORDER BY rh.serial_id;"
+        );
+        let sqlx_query = sqlx::query_as::<_, RedirectionHost>(&sql)
+            .bind(ctx.user_id())
+            .bind(ctx.user_id());
+
+        let redirection_hosts = mm.dbx().fetch_all(sqlx_query).await?;
+
+        Ok(redirection_hosts)
+    }
+
+    pub async fn update(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i64,
+        redirection_host_u: RedirectionHostForUpdate,
+    ) -> Result<()> {
+        let RedirectionHostForUpdate {
+            domain_names,
+            forward_url,
+            redirect_code,
+            preserve_path,
+            ssl_forced,
+            http2_support,
+            hsts_enabled,
+            hsts_subdomains,
+            enabled,
+        } = redirection_host_u;
+
+        let now = TimeRfc3339::now_utc().format_time();
+
+        let sqlx_query = sqlx::query(
+            "UPDATE redirection_host SET
+                domain_names = COALESCE(?, domain_names),
+                forward_url = COALESCE(?, forward_url),
+                redirect_code = COALESCE(?, redirect_code),
+                preserve_path = COALESCE(?, preserve_path),
+                ssl_forced = COALESCE(?, ssl_forced),
+                http2_support = COALESCE(?, http2_support),
+                hsts_enabled = COALESCE(?, hsts_enabled),
+                hsts_subdomains = COALESCE(?, hsts_subdomains),
+                enabled = COALESCE(?, enabled),
+                mtime = ?
+            WHERE serial_id = ? AND (? = 'root' OR owner_serial_id = (
+                SELECT serial_id FROM users WHERE user_id = ?));",
+        )
+        .bind(domain_names.map(Json))
+        .bind(forward_url)
+        .bind(redirect_code)
+        .bind(preserve_path)
+        .bind(ssl_forced)
+        .bind(http2_support)
+        .bind(hsts_enabled)
+        .bind(hsts_subdomains)
+        .bind(enabled)
+        .bind(now)
+        .bind(id)
+        .bind(ctx.user_id())
+        .bind(ctx.user_id());
+
+        let count = mm.dbx().execute(sqlx_query).await?;
+        if count == 0 {
+            return Err(Error::RedirectionHostNotFound { id });
+        }
+        mm.notify(ModelEvent::RedirectionHostChanged { id });
+
+        Ok(())
+    }
+
+    pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+        let sqlx_query = sqlx::query(
+            "DELETE FROM redirection_host
+            WHERE serial_id = ? AND (? = 'root' OR owner_serial_id = (
+                SELECT serial_id FROM users WHERE user_id = ?));",
+        )
+        .bind(id)
+        .bind(ctx.user_id())
+        .bind(ctx.user_id());
+
+        let count = mm.dbx().execute(sqlx_query).await?;
+        if count == 0 {
+            return Err(Error::RedirectionHostNotFound { id });
+        }
+        mm.notify(ModelEvent::RedirectionHostChanged { id });
+
+        Ok(())
+    }
+}
+
+// endregion: --- RedirectionHostBmc
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+    use crate::_dev_utils;
+    use sqlx::{Pool, Sqlite};
+
+    fn fx_redirection_host_c(domain: &str) -> RedirectionHostForCreate {
+        RedirectionHostForCreate {
+            domain_names: vec![domain.to_string()],
+            forward_url: "https://www.example.com".to_string(),
+            redirect_code: 301,
+            preserve_path: true,
+            ssl_forced: false,
+            http2_support: false,
+            hsts_enabled: false,
+            hsts_subdomains: false,
+        }
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_create_update_ok(pool: Pool<Sqlite>) -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test_db(pool).await?;
+        let ctx = Ctx::new("demo1")?;
+        let mut rx = mm.subscribe();
+        let id = RedirectionHostBmc::create(
+            &ctx,
+            &mm,
+            fx_redirection_host_c("test-create.example.com"),
+        )
+        .await?;
+
+        // -- Exec
+        RedirectionHostBmc::update(
+            &ctx,
+            &mm,
+            id,
+            RedirectionHostForUpdate {
+                redirect_code: Some(308),
+                preserve_path: Some(false),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        // -- Check
+        let redirection_host = RedirectionHostBmc::get(&ctx, &mm, id).await?;
+        assert_eq!(redirection_host.owner_id, "demo1");
+        assert_eq!(redirection_host.domain_names, ["test-create.example.com"]);
+        assert_eq!(redirection_host.forward_url, "https://www.example.com");
+        assert_eq!(redirection_host.redirect_code, 308);
+        assert!(!redirection_host.preserve_path);
+        assert_eq!(rx.try_recv()?, ModelEvent::RedirectionHostChanged { id });
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_create_invalid_code_err(pool: Pool<Sqlite>) -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test_db(pool).await?;
+        let ctx = Ctx::new("demo1")?;
+
+        // -- Exec
+        let res = RedirectionHostBmc::create(
+            &ctx,
+            &mm,
+            RedirectionHostForCreate {
+                redirect_code: 303,
+                ..fx_redirection_host_c("test-invalid.example.com")
+            },
+        )
+        .await;
+
+        // -- Check
+        assert!(matches!(res, Err(super::Error::Dbx(_))));
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_delete_not_owned_err(pool: Pool<Sqlite>) -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test_db(pool).await?;
+        let ctx_demo1 = Ctx::new("demo1")?;
+        let ctx_other = Ctx::new("other")?;
+        let id = RedirectionHostBmc::create(
+            &ctx_demo1,
+            &mm,
+            fx_redirection_host_c("test-delete.example.com"),
+        )
+        .await?;
+
+        // -- Exec
+        let res = RedirectionHostBmc::delete(&ctx_other, &mm, id).await;
+
+        // -- Check
+        assert!(
+            matches!(res, Err(super::Error::RedirectionHostNotFound { id: res_id }) if res_id == id)
+        );
+        assert_eq!(RedirectionHostBmc::list(&ctx_demo1, &mm).await?.len(), 1);
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -0,0 +1,24 @@
+use crate::model::store::dbx;
+use crate::model::stream_host::StreamProtocol;
+use serde::Serialize;
+use serde_with::serde_as;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[serde_as]
+#[derive(thiserror::Error, Debug, Serialize, strum_macros::Display)]
+pub enum Error {
+    StreamHostNotFound {
+        id: i64,
+    },
+    /// Another stream host already listens on the port with an
+    /// overlapping protocol, `both` overlaps `tcp` and `udp`.
+    StreamHostPortInUse {
+        incoming_port: u16,
+        protocol: StreamProtocol,
+    },
+
+    // -- Modules
+    #[error(transparent)]
+    Dbx(#[from] dbx::Error),
+}
@@ -0,0 +1,435 @@
+use crate::{
+    ctx::Ctx,
+    model::{ModelEvent, ModelManager},
+};
+use lib_utils::time::TimeRfc3339;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+mod error;
+
+pub use error::{Error, Result};
+
+// region:    --- StreamHost Types
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Deserialize,
+    Serialize,
+    sqlx::Type,
+    strum_macros::Display,
+)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum StreamProtocol {
+    #[default]
+    Tcp,
+    Udp,
+    Both,
+}
+
+#[derive(Clone, FromRow, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamHost {
+    pub id: i64,
+    /// `user_id` of the owner.
+    pub owner_id: String,
+
+    pub incoming_port: u16,
+    pub protocol: StreamProtocol,
+    pub forward_host: String,
+    pub forward_port: u16,
+
+    pub enabled: bool,
+
+    pub ctime: String,
+    pub mtime: String,
+}
+
+/// Fields required for creating new stream host
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamHostForCreate {
+    pub incoming_port: u16,
+    #[serde(default)]
+    pub protocol: StreamProtocol,
+    pub forward_host: String,
+    pub forward_port: u16,
+}
+
+/// Fields left to `None` are not updated
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamHostForUpdate {
+    pub incoming_port: Option<u16>,
+    pub protocol: Option<StreamProtocol>,
+    pub forward_host: Option<String>,
+    pub forward_port: Option<u16>,
+
+    pub enabled: Option<bool>,
+}
+
+// endregion: --- StreamHost Types
+
+// region:    --- StreamHostBmc
+
+const SELECT_STREAM_HOST: &str =
+    "SELECT sh.serial_id AS id, u.user_id AS owner_id,
+        sh.incoming_port, sh.protocol, sh.forward_host, sh.forward_port,
+        sh.enabled, sh.ctime, sh.mtime
+    FROM stream_host sh
+    INNER JOIN users u ON sh.owner_serial_id = u.serial_id";
+
+/// Same ownership rules as `ProxyHostBmc`: scoped to the `Ctx` user,
+/// the root ctx sees all hosts.
+pub struct StreamHostBmc;
+
+impl StreamHostBmc {
+    /// Fails with `StreamHostPortInUse` when another stream host listens on
+    /// the port with an overlapping protocol.
+    pub async fn create(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        stream_host_c: StreamHostForCreate,
+    ) -> Result<i64> {
+        let StreamHostForCreate {
+            incoming_port,
+            protocol,
+            forward_host,
+            forward_port,
+        } = stream_host_c;
+
+        let now = TimeRfc3339::now_utc().format_time();
+
+        let mm = mm.new_with_txn();
+        mm.dbx().begin_txn().await?;
+
+        check_port_free(&mm, incoming_port, protocol, None).await?;
+
+        let sqlx_query = sqlx::query_as::<_, (i64,)>(
+            "INSERT INTO stream_host (owner_serial_id,
+                incoming_port, protocol, forward_host, forward_port,
+                ctime, mtime)
+            VALUES ((SELECT serial_id FROM users WHERE user_id = ?),
+                ?, ?, ?, ?, ?, ?)
+            RETURNING serial_id;",
+        )
+        .bind(ctx.user_id())
+        .bind(incoming_port)
+        .bind(protocol)
+        .bind(forward_host)
+        .bind(forward_port)
+        .bind(&now)
+        .bind(&now);
+
+        let (id,) = mm.dbx().fetch_one(sqlx_query).await?;
+
+        mm.dbx().commit_txn().await?;
+        mm.notify(ModelEvent::StreamHostChanged { id });
+
+        Ok(id)
+    }
+
+    pub async fn get(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i64,
+    ) -> Result<StreamHost> {
+        let sql = format!(
+            "{SELECT_STREAM_HOST}
+            WHERE sh.serial_id = ? AND (? = 'root' OR u.user_id = ?)
+            LIMIT 1;"
+        );
+        let sqlx_query = sqlx::query_as::<_, StreamHost>(&sql)
+            .bind(id)
+            .bind(ctx.user_id())
+            .bind(ctx.user_id());
+
+        let stream_host = mm
+            .dbx()
+            .fetch_optional(sqlx_query)
+            .await?
+            .ok_or(Error::StreamHostNotFound { id })?;
+
+        Ok(stream_host)
+    }
+
+    pub async fn list(ctx: &Ctx, mm: &ModelManager) -> Result<Vec<StreamHost>> {
+        let sql = format!(
+            "{SELECT_STREAM_HOST}
+            WHERE ? = 'root' OR u.user_id = ?
+            ORDER BY sh.serial_id;"
+        );
+        let sqlx_query = sqlx::query_as::<_, StreamHost>(&sql)
+            .bind(ctx.user_id())
+            .bind(ctx.user_id());
+
+        let stream_hosts = mm.dbx().fetch_all(sqlx_query).await?;
+
+        Ok(stream_hosts)
+    }
+
+    /// Fails with `StreamHostPortInUse` when the port or protocol change
+    /// overlaps another stream host.
+    pub async fn update(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i64,
+        stream_host_u: StreamHostForUpdate,
+    ) -> Result<()> {
+        let StreamHostForUpdate {
+            incoming_port,
+            protocol,
+            forward_host,
+            forward_port,
+            enabled,
+        } = stream_host_u;
+
+        let now = TimeRfc3339::now_utc().format_time();
+
+        let mm = mm.new_with_txn();
+        mm.dbx().begin_txn().await?;
+
+        if incoming_port.is_some() || protocol.is_some() {
+            let stream_host = Self::get(ctx, &mm, id).await?;
+            check_port_free(
+                &mm,
+                incoming_port.unwrap_or(stream_host.incoming_port),
+                protocol.unwrap_or(stream_host.protocol),
+                Some(id),
+            )
+            .await?;
+        }
+
+        let sqlx_query = sqlx::query(
+            "UPDATE stream_host SET
+                incoming_port = COALESCE(?, incoming_port),
+                protocol = COALESCE(?, protocol),
+                forward_host = COALESCE(?, forward_host),
+                forward_port = COALESCE(?, forward_port),
+                enabled = COALESCE(?, enabled),
+                mtime = ?
+            WHERE serial_id = ? AND (? = 'root' OR owner_serial_id = (
+                SELECT serial_id FROM users WHERE user_id = ?));",
+        )
+        .bind(incoming_port)
+        .bind(protocol)
+        .bind(forward_host)
+        .bind(forward_port)
+        .bind(enabled)
+        .bind(now)
+        .bind(id)
+        .bind(ctx.user_id())
+        .bind(ctx.user_id());
+
+        let count = mm.dbx().execute(sqlx_query).await?;
+        if count == 0 {
+            return Err(Error::StreamHostNotFound { id });
+        }
+
+        mm.dbx().commit_txn().await?;
+        mm.notify(ModelEvent::StreamHostChanged { id });
+
+        Ok(())
+    }
+
+    pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+        let sqlx_query = sqlx::query(
+            "DELETE FROM stream_host
+            WHERE serial_id = ? AND (? = 'root' OR owner_serial_id = (
+                SELECT serial_id FROM users WHERE user_id = ?));",
+        )
+        .bind(id)
+        .bind(ctx.user_id())
+        .bind(ctx.user_id());
+
+        let count = mm.dbx().execute(sqlx_query).await?;
+        if count == 0 {
+            return Err(Error::StreamHostNotFound { id });
+        }
+        mm.notify(ModelEvent::StreamHostChanged { id });
+
+        Ok(())
+    }
+}
+
+/// Only one `listen` per port and protocol is accepted by nginx, of any
+/// owner. `exclude_id` is the host being updated.
+async fn check_port_free(
+    mm: &ModelManager,
+    incoming_port: u16,
+    protocol: StreamProtocol,
+    exclude_id: Option<i64>,
+) -> Result<()> {
+    let sqlx_query = sqlx::query_as::<_, (i64,)>(
+        "SELECT COUNT(*) FROM stream_host
+        WHERE incoming_port = ? AND serial_id IS NOT ?
+            AND (protocol = ? OR protocol = 'both' OR ? = 'both');",
+    )
+    .bind(incoming_port)
+    .bind(exclude_id)
+    .bind(protocol)
+    .bind(protocol);
+    let (hosts,) = mm.dbx().fetch_one(sqlx_query).await?;
+    if hosts > 0 {
+        return Err(Error::StreamHostPortInUse {
+            incoming_port,
+            protocol,
+        });
+    }
+
+    Ok(())
+}
+
+// endregion: --- StreamHostBmc
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+    use crate::_dev_utils;
+    use sqlx::{Pool, Sqlite};
+
+    fn fx_stream_host_c(incoming_port: u16) -> StreamHostForCreate {
+        StreamHostForCreate {
+            incoming_port,
+            protocol: StreamProtocol::Tcp,
+            forward_host: "10.0.0.5".to_string(),
+            forward_port: 5432,
+        }
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_create_update_ok(pool: Pool<Sqlite>) -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test_db(pool).await?;
+        let ctx = Ctx::new("demo1")?;
+        let mut rx = mm.subscribe();
+        let id =
+            StreamHostBmc::create(&ctx, &mm, fx_stream_host_c(15432)).await?;
+
+        // -- Exec
+        StreamHostBmc::update(
+            &ctx,
+            &mm,
+            id,
+            StreamHostForUpdate {
+                protocol: Some(StreamProtocol::Both),
+                enabled: Some(false),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        // -- Check
+        let stream_host = StreamHostBmc::get(&ctx, &mm, id).await?;
+        assert_eq!(stream_host.owner_id, "demo1");
+        assert_eq!(stream_host.incoming_port, 15432);
+        assert_eq!(stream_host.protocol, StreamProtocol::Both);
+        assert_eq!(stream_host.forward_port, 5432);
+        assert!(!stream_host.enabled);
+        assert_eq!(rx.try_recv()?, ModelEvent::StreamHostChanged { id });
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_create_update_port_in_use_err(
+        pool: Pool<Sqlite>,
+    ) -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test_db(pool).await?;
+        let ctx_demo1 = Ctx::new("demo1")?;
+        let ctx_root = Ctx::root_ctx();
+        StreamHostBmc::create(&ctx_demo1, &mm, fx_stream_host_c(15434)).await?;
+        let udp_id = StreamHostBmc::create(
+            &ctx_root,
+            &mm,
+            StreamHostForCreate {
+                protocol: StreamProtocol::Udp,
+                ..fx_stream_host_c(15434)
+            },
+        )
+        .await?;
+
+        // -- Exec
+        let res_tcp =
+            StreamHostBmc::create(&ctx_root, &mm, fx_stream_host_c(15434))
+                .await;
+        let res_both = StreamHostBmc::update(
+            &ctx_root,
+            &mm,
+            udp_id,
+            StreamHostForUpdate {
+                protocol: Some(StreamProtocol::Both),
+                ..Default::default()
+            },
+        )
+        .await;
+
+        // -- Check
+        assert!(matches!(
+            res_tcp,
+            Err(super::Error::StreamHostPortInUse {
+                incoming_port: 15434,
+                protocol: StreamProtocol::Tcp,
+            })
+        ));
+        assert!(matches!(
+            res_both,
+            Err(super::Error::StreamHostPortInUse {
+                incoming_port: 15434,
+                protocol: StreamProtocol::Both,
+            })
+        ));
+        // The host keeps its own port on updates of other fields.
+        StreamHostBmc::update(
+            &ctx_root,
+            &mm,
+            udp_id,
+            StreamHostForUpdate {
+                incoming_port: Some(15434),
+                forward_port: Some(5433),
+                ..Default::default()
+            },
+        )
+        .await?;
+        assert_eq!(StreamHostBmc::list(&ctx_root, &mm).await?.len(), 2);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_delete_not_owned_err(pool: Pool<Sqlite>) -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test_db(pool).await?;
+        let ctx_demo1 = Ctx::new("demo1")?;
+        let ctx_other = Ctx::new("other")?;
+        let id =
+            StreamHostBmc::create(&ctx_demo1, &mm, fx_stream_host_c(15433))
+                .await?;
+
+        // -- Exec
+        let res = StreamHostBmc::delete(&ctx_other, &mm, id).await;
+
+        // -- Check
+        assert!(
+            matches!(res, Err(super::Error::StreamHostNotFound { id: res_id }) if res_id == id)
+        );
+        assert_eq!(StreamHostBmc::list(&ctx_demo1, &mm).await?.len(), 1);
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -0,0 +1,501 @@
+use crate::{
+    ctx::Ctx,
+    model::{ModelEvent, ModelManager},
+};
+use lib_utils::time::TimeRfc3339;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+mod error;
+
+pub use error::{Error, Result};
+
+// region:    --- UpstreamGroup Types
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Deserialize,
+    Serialize,
+    sqlx::Type,
+    strum_macros::Display,
+)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum UpstreamBalance {
+    /// Weighted round-robin, the nginx default.
+    #[default]
+    RoundRobin,
+    LeastConn,
+    /// Sticky by client address, not compatible with backup servers.
+    IpHash,
+}
+
+/// A backend of the group. The parameters left to `None` keep the nginx
+/// defaults.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpstreamGroupServer {
+    pub host: String,
+    pub port: u16,
+    #[serde(default)]
+    pub weight: Option<u32>,
+    #[serde(default)]
+    pub max_fails: Option<u32>,
+    /// In seconds.
+    #[serde(default)]
+    pub fail_timeout: Option<u32>,
+    /// Only used when the other servers are down.
+    #[serde(default)]
+    pub backup: bool,
+}
+
+#[derive(Clone, FromRow, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpstreamGroup {
+    pub id: i64,
+    /// `user_id` of the owner.
+    pub owner_id: String,
+
+    pub name: String,
+    pub balance: UpstreamBalance,
+    /// In order.
+    #[sqlx(json)]
+    pub servers: Vec<UpstreamGroupServer>,
+
+    pub ctime: String,
+    pub mtime: String,
+}
+
+/// Fields required for creating new upstream group
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpstreamGroupForCreate {
+    pub name: String,
+    #[serde(default)]
+    pub balance: UpstreamBalance,
+    pub servers: Vec<UpstreamGroupServer>,
+}
+
+/// Fields left to `None` are not updated, `servers` replaces all the
+/// servers.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpstreamGroupForUpdate {
+    pub name: Option<String>,
+    pub balance: Option<UpstreamBalance>,
+    pub servers: Option<Vec<UpstreamGroupServer>>,
+}
+
+// endregion: --- UpstreamGroup Types
+
+// region:    --- UpstreamGroupBmc
+
+const SELECT_UPSTREAM_GROUP: &str =
+    "SELECT ug.serial_id AS id, u.user_id AS owner_id,
+        ug.name, ug.balance,
+        (SELECT json_group_array(json_object(
+                'host', ugs.host, 'port', ugs.port, 'weight', ugs.weight,
+                'maxFails', ugs.max_fails, 'failTimeout', ugs.fail_timeout,
+                'backup', json(CASE WHEN ugs.backup THEN 'true'
+                    ELSE 'false' END))
+                ORDER BY ugs.position)
+            FROM upstream_group_server ugs
+            WHERE ugs.upstream_group_serial_id = ug.serial_id) AS servers,
+        ug.ctime, ug.mtime
+    FROM upstream_group ug
+    INNER JOIN users u ON ug.owner_serial_id = u.serial_id";
+
+/// Upstream groups are owned by the user who created them, like the hosts
+/// forwarding to them. The root ctx sees all of them.
+pub struct UpstreamGroupBmc;
+
+impl UpstreamGroupBmc {
+    pub async fn create(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        upstream_group_c: UpstreamGroupForCreate,
+    ) -> Result<i64> {
+        let UpstreamGroupForCreate {
+            name,
+            balance,
+            servers,
+        } = upstream_group_c;
+
+        let now = TimeRfc3339::now_utc().format_time();
+
+        let mm = mm.new_with_txn();
+        mm.dbx().begin_txn().await?;
+
+        let sqlx_query = sqlx::query_as::<_, (i64,)>(
+            "INSERT INTO upstream_group (owner_serial_id, name, balance,
+                ctime, mtime)
+            VALUES ((SELECT serial_id FROM users WHERE user_id = ?),
+                ?, ?, ?, ?)
+            RETURNING serial_id;",
+        )
+        .bind(ctx.user_id())
+        .bind(name)
+        .bind(balance)
+        .bind(&now)
+        .bind(&now);
+        let (id,) = mm.dbx().fetch_one(sqlx_query).await?;
+        insert_servers(&mm, id, servers, &now).await?;
+
+        mm.dbx().commit_txn().await?;
+        mm.notify(ModelEvent::UpstreamGroupChanged { id });
+
+        Ok(id)
+    }
+
+    pub async fn get(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i64,
+    ) -> Result<UpstreamGroup> {
+        let sql = format!(
+            "{SELECT_UPSTREAM_GROUP}
+            WHERE ug.serial_id = ? AND (? = 'root' OR u.user_id = ?)
+            LIMIT 1;"
+        );
+        let sqlx_query = sqlx::query_as::<_, UpstreamGroup>(&sql)
+            .bind(id)
+            .bind(ctx.user_id())
+            .bind(ctx.user_id());
+
+        let upstream_group = mm
+            .dbx()
+            .fetch_optional(sqlx_query)
+            .await?
+            .ok_or(Error::UpstreamGroupNotFound { id })?;
+
+        Ok(upstream_group)
+    }
+
+    pub async fn list(
+        ctx: &Ctx,
+        mm: &ModelManager,
+    ) -> Result<Vec<UpstreamGroup>> {
+        let sql = format!(
+            "{SELECT_UPSTREAM_GROUP}
+            WHERE ? = 'root' OR u.user_id = ?
+            ORDER BY ug.serial_id;"
+        );
+        let sqlx_query = sqlx::query_as::<_, UpstreamGroup>(&sql)
+            .bind(ctx.user_id())
+            .bind(ctx.user_id());
+
+        let upstream_groups = mm.dbx().fetch_all(sqlx_query).await?;
+
+        Ok(upstream_groups)
+    }
+
+    pub async fn update(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i64,
+        upstream_group_u: UpstreamGroupForUpdate,
+    ) -> Result<()> {
+        let UpstreamGroupForUpdate {
+            name,
+            balance,
+            servers,
+        } = upstream_group_u;
+
+        let now = TimeRfc3339::now_utc().format_time();
+
+        let mm = mm.new_with_txn();
+        mm.dbx().begin_txn().await?;
+
+        let sqlx_query = sqlx::query(
+            "UPDATE upstream_group SET
+                name = COALESCE(?, name),
+                balance = COALESCE(?, balance),
+                mtime = ?
+            WHERE serial_id = ? AND (? = 'root' OR owner_serial_id = (
+                SELECT serial_id FROM users WHERE user_id = ?));",
+        )
+        .bind(name)
+        .bind(balance)
+        .bind(&now)
+        .bind(id)
+        .bind(ctx.user_id())
+        .bind(ctx.user_id());
+
+        let count = mm.dbx().execute(sqlx_query).await?;
+        if count == 0 {
+            return Err(Error::UpstreamGroupNotFound { id });
+        }
+        if let Some(servers) = servers {
+            let sqlx_query = sqlx::query(
+                "DELETE FROM upstream_group_server
+                WHERE upstream_group_serial_id = ?;",
+            )
+            .bind(id);
+            mm.dbx().execute(sqlx_query).await?;
+            insert_servers(&mm, id, servers, &now).await?;
+        }
+
+        mm.dbx().commit_txn().await?;
+        mm.notify(ModelEvent::UpstreamGroupChanged { id });
+
+        Ok(())
+    }
+
+    /// Fails with `UpstreamGroupInUse` while proxy hosts reference the
+    /// group.
+    pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+        Self::get(ctx, mm, id).await?;
+
+        let sqlx_query = sqlx::query_as::<_, (i64,)>(
+            "SELECT COUNT(*) FROM proxy_host
+            WHERE upstream_group_serial_id = ?;",
+        )
+        .bind(id);
+        let (hosts,) = mm.dbx().fetch_one(sqlx_query).await?;
+        if hosts > 0 {
+            return Err(Error::UpstreamGroupInUse { id });
+        }
+
+        let sqlx_query = sqlx::query(
+            "DELETE FROM upstream_group
+            WHERE serial_id = ? AND (? = 'root' OR owner_serial_id = (
+                SELECT serial_id FROM users WHERE user_id = ?));",
+        )
+        .bind(id)
+        .bind(ctx.user_id())
+        .bind(ctx.user_id());
+
+        let count = mm.dbx().execute(sqlx_query).await?;
+        if count == 0 {
+            return Err(Error::UpstreamGroupNotFound { id });
+        }
+        mm.notify(ModelEvent::UpstreamGroupChanged { id });
+
+        Ok(())
+    }
+}
+
+// endregion: --- UpstreamGroupBmc
+
+// region:    --- Support
+
+async fn insert_servers(
+    mm: &ModelManager,
+    upstream_group_id: i64,
+    servers: Vec<UpstreamGroupServer>,
+    now: &str,
+) -> Result<()> {
+    for (position, server) in servers.into_iter().enumerate() {
+        let sqlx_query = sqlx::query(
+            "INSERT INTO upstream_group_server (upstream_group_serial_id,
+                position, host, port, weight, max_fails, fail_timeout, backup,
+                ctime, mtime)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?);",
+        )
+        .bind(upstream_group_id)
+        .bind(position as i64)
+        .bind(server.host)
+        .bind(server.port)
+        .bind(server.weight)
+        .bind(server.max_fails)
+        .bind(server.fail_timeout)
+        .bind(server.backup)
+        .bind(now)
+        .bind(now);
+        mm.dbx().execute(sqlx_query).await?;
+    }
+
+    Ok(())
+}
+
+// endregion: --- Support
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+    use crate::_dev_utils;
+    use crate::model::proxy_host::{
+        ForwardScheme, ProxyHostBmc, ProxyHostForCreate,
+    };
+    use sqlx::{Pool, Sqlite};
+
+    fn fx_server(host: &str) -> UpstreamGroupServer {
+        UpstreamGroupServer {
+            host: host.to_string(),
+            port: 3000,
+            weight: None,
+            max_fails: None,
+            fail_timeout: None,
+            backup: false,
+        }
+    }
+
+    fn fx_upstream_group_c(name: &str) -> UpstreamGroupForCreate {
+        UpstreamGroupForCreate {
+            name: name.to_string(),
+            balance: UpstreamBalance::LeastConn,
+            servers: vec![
+                UpstreamGroupServer {
+                    weight: Some(3),
+                    ..fx_server("10.0.0.5")
+                },
+                UpstreamGroupServer {
+                    max_fails: Some(2),
+                    fail_timeout: Some(30),
+                    ..fx_server("10.0.0.6")
+                },
+                UpstreamGroupServer {
+                    backup: true,
+                    ..fx_server("10.0.0.7")
+                },
+            ],
+        }
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_create_ok(pool: Pool<Sqlite>) -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test_db(pool).await?;
+        let ctx = Ctx::new("demo1")?;
+        let fx_upstream_group_c = fx_upstream_group_c("app");
+
+        // -- Exec
+        let id =
+            UpstreamGroupBmc::create(&ctx, &mm, fx_upstream_group_c.clone())
+                .await?;
+
+        // -- Check
+        let upstream_group = UpstreamGroupBmc::get(&ctx, &mm, id).await?;
+        assert_eq!(upstream_group.owner_id, "demo1");
+        assert_eq!(upstream_group.balance, UpstreamBalance::LeastConn);
+        assert_eq!(upstream_group.servers, fx_upstream_group_c.servers);
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_update_servers_ok(pool: Pool<Sqlite>) -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test_db(pool).await?;
+        let ctx = Ctx::new("demo1")?;
+        let id =
+            UpstreamGroupBmc::create(&ctx, &mm, fx_upstream_group_c("app"))
+                .await?;
+
+        // -- Exec
+        UpstreamGroupBmc::update(
+            &ctx,
+            &mm,
+            id,
+            UpstreamGroupForUpdate {
+                balance: Some(UpstreamBalance::RoundRobin),
+                servers: Some(vec![
+                    fx_server("10.0.0.9"),
+                    fx_server("10.0.0.8"),
+                ]),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        // -- Check
+        let upstream_group = UpstreamGroupBmc::get(&ctx, &mm, id).await?;
+        assert_eq!(upstream_group.name, "app");
+        assert_eq!(upstream_group.balance, UpstreamBalance::RoundRobin);
+        assert_eq!(
+            upstream_group.servers,
+            [fx_server("10.0.0.9"), fx_server("10.0.0.8")]
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_delete_not_owned_err(pool: Pool<Sqlite>) -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test_db(pool).await?;
+        let ctx_demo1 = Ctx::new("demo1")?;
+        let ctx_other = Ctx::new("other")?;
+        let id = UpstreamGroupBmc::create(
+            &ctx_demo1,
+            &mm,
+            fx_upstream_group_c("app"),
+        )
+        .await?;
+
+        // -- Exec
+        let res = UpstreamGroupBmc::delete(&ctx_other, &mm, id).await;
+
+        // -- Check
+        assert!(
+            matches!(res, Err(super::Error::UpstreamGroupNotFound { id: res_id }) if res_id == id)
+        );
+        UpstreamGroupBmc::delete(&ctx_demo1, &mm, id).await?;
+        assert!(UpstreamGroupBmc::list(&ctx_demo1, &mm).await?.is_empty());
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_delete_in_use_err(pool: Pool<Sqlite>) -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test_db(pool).await?;
+        let ctx = Ctx::new("demo1")?;
+        let id =
+            UpstreamGroupBmc::create(&ctx, &mm, fx_upstream_group_c("app"))
+                .await?;
+        let proxy_host_id = ProxyHostBmc::create(
+            &ctx,
+            &mm,
+            ProxyHostForCreate {
+                domain_names: vec!["test-in-use.example.com".to_string()],
+                forward_scheme: ForwardScheme::Http,
+                forward_host: String::new(),
+                forward_port: 3000,
+                cache_assets: false,
+                block_exploits: false,
+                allow_websocket_upgrade: false,
+                ssl_forced: false,
+                http2_support: false,
+                hsts_enabled: false,
+                hsts_subdomains: false,
+                certificate_id: None,
+                access_list_id: None,
+                upstream_group_id: Some(id),
+                locations: Vec::new(),
+                advanced: String::new(),
+                rate_limit: None,
+                rate_limit_burst: 0,
+                rate_limit_nodelay: false,
+                connection_limit: None,
+                health_check_path: None,
+                health_check_interval: 30,
+            },
+        )
+        .await?;
+
+        // -- Exec
+        let res = UpstreamGroupBmc::delete(&ctx, &mm, id).await;
+
+        // -- Check
+        assert!(matches!(res, Err(super::Error::UpstreamGroupInUse { .. })));
+        let proxy_host = ProxyHostBmc::get(&ctx, &mm, proxy_host_id).await?;
+        assert_eq!(proxy_host.upstream_group_id, Some(id));
+        assert_eq!(proxy_host.upstream_group_name.as_deref(), Some("app"));
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -0,0 +1,114 @@
+use crate::context::Layout;
+use crate::error::{Error, Result};
+use crate::node::{Directive, Node};
+use crate::render::Render;
+use crate::value::OnOff;
+use serde::Serialize;
+
+/// https://nginx.org/en/docs/ngx_core_module.html#events
+///
+/// The fields left to `None` are not rendered, nginx uses its defaults.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Events {
+    /// https://nginx.org/en/docs/ngx_core_module.html#worker_connections
+    pub worker_connections: Option<u32>,
+    /// https://nginx.org/en/docs/ngx_core_module.html#multi_accept
+    pub multi_accept: Option<OnOff>,
+
+    /// Directives and comments not covered by the fields above.
+    pub extra: Vec<Node>,
+
+    #[serde(skip)]
+    pub layout: Layout,
+}
+
+/// The values the crate recommends.
+impl Default for Events {
+    fn default() -> Self {
+        Events {
+            worker_connections: Some(1024),
+            multi_accept: Some(OnOff::Off),
+            extra: Vec::new(),
+            layout: Layout::default(),
+        }
+    }
+}
+
+impl Events {
+    /// The nodes of each field, grouped as rendered when built in code.
+    fn sections(&self) -> Vec<Vec<(&'static str, Vec<Node>)>> {
+        vec![
+            vec![
+                (
+                    "worker_connections",
+                    self.worker_connections
+                        .iter()
+                        .map(|count| {
+                            Directive::new("worker_connections")
+                                .arg(count.to_string())
+                                .into()
+                        })
+                        .collect(),
+                ),
+                (
+                    "multi_accept",
+                    self.multi_accept
+                        .iter()
+                        .map(|multi_accept| {
+                            Directive::new("multi_accept")
+                                .arg(multi_accept.to_string())
+                                .into()
+                        })
+                        .collect(),
+                ),
+            ],
+            vec![("extra", self.extra.clone())],
+        ]
+    }
+}
+
+impl Render for Events {
+    fn to_nodes(&self) -> Vec<Node> {
+        let children = self.layout.lay_out(self.sections());
+
+        vec![Directive::new_block("events", children).into()]
+    }
+}
+
+/// Only the directives of the block are set.
+impl TryFrom<&Directive> for Events {
+    type Error = Error;
+
+    fn try_from(directive: &Directive) -> Result<Self> {
+        let mut events = Events {
+            worker_connections: None,
+            multi_accept: None,
+            extra: Vec::new(),
+            layout: Layout::default(),
+        };
+
+        for node in directive.block_children()? {
+            let field = match node {
+                Node::Directive(d) if d.name == "worker_connections" => {
+                    events.worker_connections = Some(d.parse_single()?);
+                    "worker_connections"
+                }
+                Node::Directive(d) if d.name == "multi_accept" => {
+                    events.multi_accept = Some(d.parse_single()?);
+                    "multi_accept"
+                }
+                Node::Blank => {
+                    events.layout.push_blank();
+                    continue;
+                }
+                node => {
+                    events.extra.push(node.clone());
+                    "extra"
+                }
+            };
+            events.layout.push(field, node);
+        }
+
+        Ok(events)
+    }
+}
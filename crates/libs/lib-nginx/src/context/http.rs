@@ -0,0 +1,324 @@
+use crate::context::Layout;
+use crate::error::{Error, Result};
+use crate::node::{Directive, Node};
+use crate::render::Render;
+use crate::value::{ErrorLog, KeepaliveTimeout, OnOff, Time};
+use serde::Serialize;
+
+/// https://nginx.org/en/docs/http/ngx_http_core_module.html#http
+///
+/// The fields left to `None` are not rendered, nginx uses its defaults.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Http {
+    // -- Connection handling
+    pub sendfile: Option<OnOff>,
+    pub tcp_nopush: Option<OnOff>,
+    pub tcp_nodelay: Option<OnOff>,
+    pub keepalive_timeout: Option<KeepaliveTimeout>,
+    pub types_hash_max_size: Option<u32>,
+    pub server_tokens: Option<OnOff>,
+
+    // -- Mime types
+    /// Path of the `types` file, rendered as `include <path>;`.
+    pub mime_types: Option<String>,
+    pub default_type: Option<String>,
+
+    // -- Logs
+    /// Path or `off`, one directive each.
+    pub access_log: Vec<String>,
+    pub error_log: Vec<ErrorLog>,
+
+    // -- Compression
+    pub gzip: Option<OnOff>,
+
+    /// `include` directives rendered at the end of the block
+    /// (e.g. `/etc/nginx/conf.d/*.conf`).
+    pub include: Vec<String>,
+
+    /// Directives and comments not covered by the fields above.
+    pub extra: Vec<Node>,
+
+    #[serde(skip)]
+    pub layout: Layout,
+}
+
+/// The values the crate recommends.
+impl Default for Http {
+    fn default() -> Self {
+        Http {
+            sendfile: Some(OnOff::On),
+            tcp_nopush: Some(OnOff::On),
+            tcp_nodelay: Some(OnOff::On),
+            keepalive_timeout: Some(KeepaliveTimeout {
+                timeout: Time::seconds(65),
+                header_timeout: None,
+            }),
+            types_hash_max_size: Some(2048),
+            server_tokens: Some(OnOff::Off),
+
+            mime_types: Some("mime.types".to_string()),
+            default_type: Some("application/octet-stream".to_string()),
+
+            access_log: Vec::new(),
+            error_log: Vec::new(),
+
+            gzip: Some(OnOff::On),
+
+            include: Vec::new(),
+
+            extra: Vec::new(),
+
+            layout: Layout::default(),
+        }
+    }
+}
+
+impl Http {
+    /// The nodes of each field, grouped as rendered when built in code.
+    fn sections(&self) -> Vec<Vec<(&'static str, Vec<Node>)>> {
+        let on_off = |name: &'static str, value: Option<OnOff>| {
+            let nodes = value
+                .iter()
+                .map(|value| Directive::new(name).arg(value.to_string()).into())
+                .collect();
+            (name, nodes)
+        };
+
+        vec![
+            vec![
+                on_off("sendfile", self.sendfile),
+                on_off("tcp_nopush", self.tcp_nopush),
+                on_off("tcp_nodelay", self.tcp_nodelay),
+                (
+                    "keepalive_timeout",
+                    self.keepalive_timeout
+                        .iter()
+                        .map(|keepalive| {
+                            let directive = Directive::new("keepalive_timeout")
+                                .arg(keepalive.timeout.to_string());
+                            match &keepalive.header_timeout {
+                                Some(header) => {
+                                    directive.arg(header.to_string())
+                                }
+                                None => directive,
+                            }
+                            .into()
+                        })
+                        .collect(),
+                ),
+                (
+                    "types_hash_max_size",
+                    self.types_hash_max_size
+                        .iter()
+                        .map(|size| {
+                            Directive::new("types_hash_max_size")
+                                .arg(size.to_string())
+                                .into()
+                        })
+                        .collect(),
+                ),
+                on_off("server_tokens", self.server_tokens),
+            ],
+            vec![
+                (
+                    "mime_types",
+                    self.mime_types
+                        .iter()
+                        .map(|path| Directive::new("include").arg(path).into())
+                        .collect(),
+                ),
+                (
+                    "default_type",
+                    self.default_type
+                        .iter()
+                        .map(|default_type| {
+                            Directive::new("default_type")
+                                .arg(default_type)
+                                .into()
+                        })
+                        .collect(),
+                ),
+            ],
+            vec![
+                (
+                    "access_log",
+                    self.access_log
+                        .iter()
+                        .map(|path| {
+                            Directive::new("access_log").arg(path).into()
+                        })
+                        .collect(),
+                ),
+                (
+                    "error_log",
+                    self.error_log
+                        .iter()
+                        .map(|error_log| error_log_directive(error_log).into())
+                        .collect(),
+                ),
+            ],
+            vec![on_off("gzip", self.gzip)],
+            vec![(
+                "include",
+                self.include
+                    .iter()
+                    .map(|path| Directive::new("include").arg(path).into())
+                    .collect(),
+            )],
+            vec![("extra", self.extra.clone())],
+        ]
+    }
+}
+
+impl Render for Http {
+    fn to_nodes(&self) -> Vec<Node> {
+        let children = self.layout.lay_out(self.sections());
+
+        vec![Directive::new_block("http", children).into()]
+    }
+}
+
+/// Only the directives of the block are set.
+impl TryFrom<&Directive> for Http {
+    type Error = Error;
+
+    fn try_from(directive: &Directive) -> Result<Self> {
+        let mut http = Http {
+            sendfile: None,
+            tcp_nopush: None,
+            tcp_nodelay: None,
+            keepalive_timeout: None,
+            types_hash_max_size: None,
+            server_tokens: None,
+            mime_types: None,
+            default_type: None,
+            access_log: Vec::new(),
+            error_log: Vec::new(),
+            gzip: None,
+            include: Vec::new(),
+            extra: Vec::new(),
+            layout: Layout::default(),
+        };
+
+        for node in directive.block_children()? {
+            let d = match node {
+                Node::Directive(d) => d,
+                Node::Comment(_) => {
+                    http.extra.push(node.clone());
+                    http.layout.push("extra", node);
+                    continue;
+                }
+                Node::Blank => {
+                    http.layout.push_blank();
+                    continue;
+                }
+            };
+
+            let field = match d.name.as_str() {
+                "sendfile" => {
+                    http.sendfile = Some(d.parse_single()?);
+                    "sendfile"
+                }
+                "tcp_nopush" => {
+                    http.tcp_nopush = Some(d.parse_single()?);
+                    "tcp_nopush"
+                }
+                "tcp_nodelay" => {
+                    http.tcp_nodelay = Some(d.parse_single()?);
+                    "tcp_nodelay"
+                }
+                "keepalive_timeout" => {
+                    http.keepalive_timeout = Some(keepalive_timeout(d)?);
+                    "keepalive_timeout"
+                }
+                "types_hash_max_size" => {
+                    http.types_hash_max_size = Some(d.parse_single()?);
+                    "types_hash_max_size"
+                }
+                "server_tokens" => {
+                    http.server_tokens = Some(d.parse_single()?);
+                    "server_tokens"
+                }
+                "include" => {
+                    let path = d.single_value()?;
+                    if http.mime_types.is_none() && path.ends_with("mime.types")
+                    {
+                        http.mime_types = Some(path);
+                        "mime_types"
+                    } else {
+                        http.include.push(path);
+                        "include"
+                    }
+                }
+                "default_type" => {
+                    http.default_type = Some(d.single_value()?);
+                    "default_type"
+                }
+                "access_log" if d.args.len() == 1 => {
+                    http.access_log.push(d.single_value()?);
+                    "access_log"
+                }
+                "error_log" => {
+                    http.error_log.push(ErrorLog::try_from(d)?);
+                    "error_log"
+                }
+                "gzip" => {
+                    http.gzip = Some(d.parse_single()?);
+                    "gzip"
+                }
+                _ => {
+                    http.extra.push(node.clone());
+                    "extra"
+                }
+            };
+            http.layout.push(field, node);
+        }
+
+        Ok(http)
+    }
+}
+
+/// `keepalive_timeout timeout [header_timeout];`
+fn keepalive_timeout(directive: &Directive) -> Result<KeepaliveTimeout> {
+    let mut values = directive.values();
+    let (Some(timeout), header_timeout, None) =
+        (values.next(), values.next(), values.next())
+    else {
+        return Err(directive.invalid_args());
+    };
+    let parse = |value: String| -> Result<Time> {
+        value.parse().map_err(|_| directive.invalid_args())
+    };
+
+    Ok(KeepaliveTimeout {
+        timeout: parse(timeout)?,
+        header_timeout: header_timeout.map(parse).transpose()?,
+    })
+}
+
+impl TryFrom<&Directive> for ErrorLog {
+    type Error = Error;
+
+    fn try_from(directive: &Directive) -> Result<Self> {
+        let mut values = directive.values();
+        let (Some(path), level, None) =
+            (values.next(), values.next(), values.next())
+        else {
+            return Err(directive.invalid_args());
+        };
+        let level = level
+            .map(|level| level.parse())
+            .transpose()
+            .map_err(|_| directive.invalid_args())?;
+
+        Ok(ErrorLog { path, level })
+    }
+}
+
+pub(crate) fn error_log_directive(error_log: &ErrorLog) -> Directive {
+    let directive = Directive::new("error_log").arg(&error_log.path);
+    match error_log.level {
+        Some(level) => directive.arg(level.to_string()),
+        None => directive,
+    }
+}
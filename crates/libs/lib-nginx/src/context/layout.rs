@@ -0,0 +1,195 @@
+use crate::node::{Node, push_section};
+use std::collections::{HashMap, VecDeque};
+
+/// Source order of the directives of a parsed context.
+///
+/// The typed fields of a context lose where their directives were in the
+/// file, while the order matters to nginx (e.g. `if` and `return` in the
+/// rewrite phase). A parsed context records here the field of each of its
+/// directives, with their comments on the same line, and the blank
+/// lines, so rendering it writes them back in place.
+///
+/// Empty for a context built in code, which is rendered in the order of
+/// its fields.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Layout(Vec<Slot>);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Slot {
+    /// The next item of the named field.
+    Field(&'static str, Comments),
+    Blank,
+}
+
+/// The comments on the lines of a directive, which its typed field does
+/// not keep.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Comments {
+    /// After the `;` or `}`.
+    line: Option<String>,
+    /// After the `{`.
+    open: Option<String>,
+}
+
+impl Comments {
+    /// Set the comments on `node`, unless it has its own.
+    fn apply(&self, mut node: Node) -> Node {
+        if let Node::Directive(directive) = &mut node
+            && directive.comment.is_none()
+            && directive.open_comment.is_none()
+        {
+            directive.comment.clone_from(&self.line);
+            directive.open_comment.clone_from(&self.open);
+        }
+        node
+    }
+}
+
+impl Layout {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Record that `node` was read into `field`.
+    pub(crate) fn push(&mut self, field: &'static str, node: &Node) {
+        let comments = match node {
+            Node::Directive(directive) => Comments {
+                line: directive.comment.clone(),
+                open: directive.open_comment.clone(),
+            },
+            Node::Comment(_) | Node::Blank => Comments::default(),
+        };
+        self.0.push(Slot::Field(field, comments));
+    }
+
+    pub(crate) fn push_blank(&mut self) {
+        self.0.push(Slot::Blank);
+    }
+
+    /// Write the fields of a context, given as sections of
+    /// `(field, nodes)`.
+    ///
+    /// A parsed context is written in the recorded order, see `arrange`.
+    /// One built in code is written field by field, with a blank line
+    /// between the sections.
+    pub(crate) fn lay_out(
+        &self,
+        sections: Vec<Vec<(&'static str, Vec<Node>)>>,
+    ) -> Vec<Node> {
+        if !self.is_empty() {
+            return self.arrange(sections.concat());
+        }
+
+        let mut nodes = Vec::new();
+        for section in sections {
+            push_section(
+                &mut nodes,
+                section.into_iter().flat_map(|(_, items)| items).collect(),
+            );
+        }
+        nodes
+    }
+
+    /// Write the items of `fields` in the recorded order.
+    ///
+    /// The n-th item of a field takes the n-th slot of that field, and
+    /// its comments. Items added after the parse follow the last slot of
+    /// their field, fields without any slot go at the end.
+    fn arrange(&self, fields: Vec<(&'static str, Vec<Node>)>) -> Vec<Node> {
+        let last_slots: HashMap<&str, usize> = self
+            .0
+            .iter()
+            .enumerate()
+            .filter_map(|(i, slot)| match slot {
+                Slot::Field(field, _) => Some((*field, i)),
+                Slot::Blank => None,
+            })
+            .collect();
+        let mut order: Vec<&str> = Vec::new();
+        let mut items: HashMap<&str, VecDeque<Node>> = HashMap::new();
+        for (field, nodes) in fields {
+            if !order.contains(&field) {
+                order.push(field);
+            }
+            items.entry(field).or_default().extend(nodes);
+        }
+
+        let mut nodes = Vec::new();
+        for (i, slot) in self.0.iter().enumerate() {
+            let (field, comments) = match slot {
+                Slot::Field(field, comments) => (field, comments),
+                Slot::Blank => {
+                    nodes.push(Node::Blank);
+                    continue;
+                }
+            };
+            let Some(queue) = items.get_mut(field) else {
+                continue;
+            };
+            nodes.extend(queue.pop_front().map(|node| comments.apply(node)));
+            if last_slots.get(field) == Some(&i) {
+                nodes.extend(queue.drain(..));
+            }
+        }
+        for field in order {
+            if !last_slots.contains_key(field)
+                && let Some(queue) = items.remove(field)
+            {
+                nodes.extend(queue);
+            }
+        }
+
+        nodes
+    }
+}
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+    use crate::node::Directive;
+
+    fn fx_nodes(name: &str, count: usize) -> Vec<Node> {
+        (0..count)
+            .map(|i| Directive::new(name).arg(i.to_string()).into())
+            .collect()
+    }
+
+    #[test]
+    fn test_layout_arrange_changed_fields_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mut fx_layout = Layout::default();
+        fx_layout.push("b", &Node::Blank);
+        fx_layout.push("a", &Node::Blank);
+        fx_layout.push_blank();
+        fx_layout.push("b", &Node::Blank);
+        fx_layout.push("a", &Node::Blank);
+
+        // -- Exec
+        let res = fx_layout.arrange(vec![
+            ("a", fx_nodes("a", 1)),
+            ("b", fx_nodes("b", 3)),
+            ("c", fx_nodes("c", 1)),
+        ]);
+
+        // -- Check
+        // One `a` was removed, one `b` and the `c` were added.
+        let expected: Vec<Node> = [
+            fx_nodes("b", 1),
+            fx_nodes("a", 1),
+            vec![Node::Blank],
+            fx_nodes("b", 3)[1..].to_vec(),
+            fx_nodes("c", 1),
+        ]
+        .concat();
+        assert_eq!(res, expected);
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
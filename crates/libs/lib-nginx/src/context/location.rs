@@ -0,0 +1,181 @@
+use crate::context::Layout;
+use crate::context::server::{
+    client_max_body_size_nodes, header_nodes, return_nodes, rewrite_nodes,
+};
+use crate::error::{Error, Result};
+use crate::node::{Directive, Node};
+use crate::render::Render;
+use crate::value::{FailToParse, Header, Return, Rewrite, Size};
+use serde::Serialize;
+use std::str::FromStr;
+
+/// https://nginx.org/en/docs/http/ngx_http_core_module.html#location
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Location {
+    pub modifier: Option<LocationModifier>,
+    pub path: String,
+
+    pub client_max_body_size: Option<Size>,
+    pub rewrite: Vec<Rewrite>,
+    pub r#return: Option<Return>,
+
+    /// https://nginx.org/en/docs/http/ngx_http_proxy_module.html#proxy_set_header
+    pub proxy_set_header: Vec<Header>,
+    /// https://nginx.org/en/docs/http/ngx_http_proxy_module.html#proxy_pass
+    pub proxy_pass: Option<String>,
+
+    /// Directives and comments not covered by the fields above, rendered
+    /// at the end of the block.
+    pub extra: Vec<Node>,
+
+    #[serde(skip)]
+    pub layout: Layout,
+}
+
+impl Location {
+    pub fn new(path: impl Into<String>) -> Self {
+        Location {
+            path: path.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// The optional modifier between `location` and its uri.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum LocationModifier {
+    /// `=`
+    Exact,
+    /// `^~`
+    PreferPrefix,
+    /// `~`
+    Regex,
+    /// `~*`
+    RegexCaseInsensitive,
+}
+
+impl LocationModifier {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LocationModifier::Exact => "=",
+            LocationModifier::PreferPrefix => "^~",
+            LocationModifier::Regex => "~",
+            LocationModifier::RegexCaseInsensitive => "~*",
+        }
+    }
+}
+
+impl FromStr for LocationModifier {
+    type Err = FailToParse;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        match value {
+            "=" => Ok(LocationModifier::Exact),
+            "^~" => Ok(LocationModifier::PreferPrefix),
+            "~" => Ok(LocationModifier::Regex),
+            "~*" => Ok(LocationModifier::RegexCaseInsensitive),
+            other => Err(FailToParse(other.to_string())),
+        }
+    }
+}
+
+impl Render for Location {
+    fn to_nodes(&self) -> Vec<Node> {
+        let children = self.layout.lay_out(vec![vec![
+            (
+                "client_max_body_size",
+                client_max_body_size_nodes(&self.client_max_body_size),
+            ),
+            ("rewrite", rewrite_nodes(&self.rewrite)),
+            ("return", return_nodes(&self.r#return)),
+            ("proxy_set_header", header_nodes(&self.proxy_set_header)),
+            (
+                "proxy_pass",
+                self.proxy_pass
+                    .iter()
+                    .map(|url| Directive::new("proxy_pass").arg(url).into())
+                    .collect(),
+            ),
+            ("extra", self.extra.clone()),
+        ]]);
+
+        let mut location = Directive::new_block("location", children);
+        if let Some(modifier) = self.modifier {
+            location = location.raw_arg(modifier.as_str());
+        }
+
+        vec![location.arg(&self.path).into()]
+    }
+}
+
+impl TryFrom<&Directive> for Location {
+    type Error = Error;
+
+    fn try_from(directive: &Directive) -> Result<Self> {
+        let mut values = directive.values();
+        let (modifier, path) =
+            match (values.next(), values.next(), values.next()) {
+                (Some(path), None, None) => (None, path),
+                (Some(modifier), Some(path), None) => (
+                    Some(
+                        modifier
+                            .parse()
+                            .map_err(|_| directive.invalid_args())?,
+                    ),
+                    path,
+                ),
+                _ => return Err(directive.invalid_args()),
+            };
+
+        let mut location = Location {
+            modifier,
+            ..Location::new(path)
+        };
+
+        for node in directive.block_children()? {
+            let d = match node {
+                Node::Directive(d) => d,
+                Node::Comment(_) => {
+                    location.extra.push(node.clone());
+                    location.layout.push("extra", node);
+                    continue;
+                }
+                Node::Blank => {
+                    location.layout.push_blank();
+                    continue;
+                }
+            };
+
+            let field = match d.name.as_str() {
+                "client_max_body_size" => {
+                    location.client_max_body_size = Some(d.parse_single()?);
+                    "client_max_body_size"
+                }
+                "rewrite" => {
+                    location.rewrite.push(Rewrite::try_from(d)?);
+                    "rewrite"
+                }
+                // A later `return` (e.g. after an `if`) stays opaque.
+                "return" if location.r#return.is_none() => {
+                    location.r#return = Some(Return::try_from(d)?);
+                    "return"
+                }
+                "proxy_set_header" => {
+                    location.proxy_set_header.push(Header::try_from(d)?);
+                    "proxy_set_header"
+                }
+                "proxy_pass" => {
+                    location.proxy_pass = Some(d.single_value()?);
+                    "proxy_pass"
+                }
+                _ => {
+                    location.extra.push(node.clone());
+                    "extra"
+                }
+            };
+            location.layout.push(field, node);
+        }
+
+        Ok(location)
+    }
+}
@@ -0,0 +1,312 @@
+//! Typed nginx contexts.
+//!
+//! `NginxConfig` is the main context, i.e. the whole `nginx.conf` file.
+//! Nested contexts (`events`, `http`, `stream`, `server`, `location`,
+//! `upstream`) live in their own modules.
+
+// region:    --- Modules
+
+mod events;
+mod http;
+mod layout;
+mod location;
+mod server;
+mod stream;
+mod upstream;
+
+pub use events::Events;
+pub use http::Http;
+pub use layout::Layout;
+pub use location::{Location, LocationModifier};
+pub use server::{Listen, Server};
+pub use stream::{Stream, StreamServer};
+pub use upstream::{Upstream, UpstreamBalance, UpstreamServer};
+
+use crate::error::{Error, Result};
+use crate::node::{Directive, Node};
+use crate::parser::parse;
+use crate::render::Render;
+use crate::value::{ErrorLog, OnOff, WorkerProcesses};
+use http::error_log_directive;
+use serde::Serialize;
+use std::str::FromStr;
+
+// endregion: --- Modules
+
+/// A complete `nginx.conf`.
+///
+/// Rendering is deterministic. A parsed config is written back in the
+/// order of its file, with only the directives it had. One built in code
+/// is written in the order of the fields below, the ones left to `None`
+/// being skipped, and `include` lists keep their order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NginxConfig {
+    /// https://nginx.org/en/docs/ngx_core_module.html#user
+    pub user: Option<String>,
+    pub worker_processes: Option<WorkerProcesses>,
+    /// https://nginx.org/en/docs/ngx_core_module.html#pid
+    pub pid: Option<String>,
+    pub pcre_jit: Option<OnOff>,
+    /// https://nginx.org/en/docs/ngx_core_module.html#worker_rlimit_nofile
+    pub worker_rlimit_nofile: Option<u32>,
+    /// One directive each, nginx logs to all of them.
+    pub error_log: Vec<ErrorLog>,
+    /// Main context `include` directives (e.g. dynamic modules).
+    pub include: Vec<String>,
+    /// Main context directives and comments not covered by the fields
+    /// above, rendered before the `events` block.
+    pub extra: Vec<Node>,
+
+    pub events: Option<Events>,
+    pub http: Option<Http>,
+    /// nginx fails on `stream` without the module.
+    pub stream: Option<Stream>,
+
+    #[serde(skip)]
+    pub layout: Layout,
+}
+
+/// The configuration the crate recommends.
+impl Default for NginxConfig {
+    fn default() -> Self {
+        NginxConfig {
+            user: None,
+            worker_processes: Some(WorkerProcesses::Auto),
+            pid: None,
+            pcre_jit: Some(OnOff::Off),
+            worker_rlimit_nofile: None,
+            error_log: Vec::new(),
+            include: Vec::new(),
+            extra: Vec::new(),
+            events: Some(Events::default()),
+            http: Some(Http::default()),
+            stream: None,
+            layout: Layout::default(),
+        }
+    }
+}
+
+impl NginxConfig {
+    /// The nodes of each field, grouped as rendered when built in code.
+    fn sections(&self) -> Vec<Vec<(&'static str, Vec<Node>)>> {
+        fn single(
+            name: &'static str,
+            value: Option<impl ToString>,
+        ) -> (&'static str, Vec<Node>) {
+            let nodes = value
+                .iter()
+                .map(|value| Directive::new(name).arg(value.to_string()).into())
+                .collect();
+            (name, nodes)
+        }
+
+        vec![
+            vec![
+                single("user", self.user.as_ref()),
+                single("worker_processes", self.worker_processes),
+                single("pid", self.pid.as_ref()),
+                single("pcre_jit", self.pcre_jit),
+                single("worker_rlimit_nofile", self.worker_rlimit_nofile),
+                (
+                    "error_log",
+                    self.error_log
+                        .iter()
+                        .map(|error_log| error_log_directive(error_log).into())
+                        .collect(),
+                ),
+                (
+                    "include",
+                    self.include
+                        .iter()
+                        .map(|path| Directive::new("include").arg(path).into())
+                        .collect(),
+                ),
+            ],
+            vec![("extra", self.extra.clone())],
+            vec![(
+                "events",
+                self.events.iter().flat_map(Events::to_nodes).collect(),
+            )],
+            vec![("http", self.http.iter().flat_map(Http::to_nodes).collect())],
+            vec![(
+                "stream",
+                self.stream.iter().flat_map(Stream::to_nodes).collect(),
+            )],
+        ]
+    }
+}
+
+impl Render for NginxConfig {
+    fn to_nodes(&self) -> Vec<Node> {
+        self.layout.lay_out(self.sections())
+    }
+}
+
+/// Only the directives of the file are set.
+impl TryFrom<&[Node]> for NginxConfig {
+    type Error = Error;
+
+    fn try_from(nodes: &[Node]) -> Result<Self> {
+        let mut config = NginxConfig {
+            user: None,
+            worker_processes: None,
+            pid: None,
+            pcre_jit: None,
+            worker_rlimit_nofile: None,
+            error_log: Vec::new(),
+            include: Vec::new(),
+            extra: Vec::new(),
+            events: None,
+            http: None,
+            stream: None,
+            layout: Layout::default(),
+        };
+
+        for node in nodes {
+            let d = match node {
+                Node::Directive(d) => d,
+                Node::Comment(_) => {
+                    config.extra.push(node.clone());
+                    config.layout.push("extra", node);
+                    continue;
+                }
+                Node::Blank => {
+                    config.layout.push_blank();
+                    continue;
+                }
+            };
+
+            let field = match d.name.as_str() {
+                "user" if d.args.len() == 1 => {
+                    config.user = Some(d.single_value()?);
+                    "user"
+                }
+                "worker_processes" => {
+                    config.worker_processes = Some(d.parse_single()?);
+                    "worker_processes"
+                }
+                "pid" => {
+                    config.pid = Some(d.single_value()?);
+                    "pid"
+                }
+                "pcre_jit" => {
+                    config.pcre_jit = Some(d.parse_single()?);
+                    "pcre_jit"
+                }
+                "worker_rlimit_nofile" => {
+                    config.worker_rlimit_nofile = Some(d.parse_single()?);
+                    "worker_rlimit_nofile"
+                }
+                "error_log" => {
+                    config.error_log.push(ErrorLog::try_from(d)?);
+                    "error_log"
+                }
+                "include" => {
+                    config.include.push(d.single_value()?);
+                    "include"
+                }
+                "events" => {
+                    config.events = Some(Events::try_from(d)?);
+                    "events"
+                }
+                "http" => {
+                    config.http = Some(Http::try_from(d)?);
+                    "http"
+                }
+                "stream" => {
+                    config.stream = Some(Stream::try_from(d)?);
+                    "stream"
+                }
+                _ => {
+                    config.extra.push(node.clone());
+                    "extra"
+                }
+            };
+            config.layout.push(field, node);
+        }
+
+        Ok(config)
+    }
+}
+
+impl FromStr for NginxConfig {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        NginxConfig::try_from(parse(input)?.as_slice())
+    }
+}
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+    use crate::value::LogLevel;
+
+    #[test]
+    fn test_render_default_golden_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_golden = include_str!("../../golden/default.conf");
+
+        // -- Exec
+        let res = NginxConfig::default().render();
+
+        // -- Check
+        assert_eq!(res, fx_golden);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_full_golden_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_golden = include_str!("../../golden/full.conf");
+        let fx_config = NginxConfig {
+            user: Some("www-data".to_string()),
+            worker_processes: Some(WorkerProcesses::U8(4)),
+            pid: Some("/run/nginx.pid".to_string()),
+            pcre_jit: Some(OnOff::On),
+            worker_rlimit_nofile: Some(8192),
+            error_log: vec![ErrorLog {
+                path: "/var/log/nginx/error.log".to_string(),
+                level: Some(LogLevel::Warn),
+            }],
+            include: vec!["/etc/nginx/modules-enabled/*.conf".to_string()],
+            events: Some(Events {
+                worker_connections: Some(768),
+                multi_accept: Some(OnOff::On),
+                ..Default::default()
+            }),
+            http: Some(Http {
+                server_tokens: Some(OnOff::On),
+                mime_types: Some("/etc/nginx/mime.types".to_string()),
+                access_log: vec!["/var/log/nginx/access.log".to_string()],
+                error_log: vec![ErrorLog {
+                    path: "/var/log/nginx/http_error.log".to_string(),
+                    level: None,
+                }],
+                gzip: Some(OnOff::Off),
+                include: vec![
+                    "/etc/nginx/conf.d/*.conf".to_string(),
+                    "/etc/nginx/sites-enabled/*".to_string(),
+                ],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        // -- Exec
+        let res = fx_config.render();
+
+        // -- Check
+        assert_eq!(res, fx_golden);
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -0,0 +1,489 @@
+use crate::context::{Layout, Location};
+use crate::error::{Error, Result};
+use crate::node::{Directive, Node};
+use crate::render::Render;
+use crate::value::{Header, Return, Rewrite, Size};
+use serde::Serialize;
+
+/// https://nginx.org/en/docs/http/ngx_http_core_module.html#server
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Server {
+    pub listen: Vec<Listen>,
+    pub server_name: Vec<String>,
+
+    pub client_max_body_size: Option<Size>,
+    pub rewrite: Vec<Rewrite>,
+    pub r#return: Option<Return>,
+
+    pub proxy_set_header: Vec<Header>,
+    pub locations: Vec<Location>,
+
+    /// Directives and comments not covered by the fields above,
+    /// rendered before the locations.
+    pub extra: Vec<Node>,
+
+    #[serde(skip)]
+    pub layout: Layout,
+}
+
+/// https://nginx.org/en/docs/http/ngx_http_core_module.html#listen
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Listen {
+    /// Address part, e.g. `127.0.0.1` or `[::]`. Port only when `None`.
+    pub address: Option<String>,
+    pub port: u16,
+    pub default_server: bool,
+    pub ssl: bool,
+    pub http2: bool,
+    /// Stream servers only.
+    pub udp: bool,
+    pub proxy_protocol: bool,
+}
+
+// Constructors.
+impl Listen {
+    pub fn port(port: u16) -> Self {
+        Listen {
+            address: None,
+            port,
+            default_server: false,
+            ssl: false,
+            http2: false,
+            udp: false,
+            proxy_protocol: false,
+        }
+    }
+
+    /// `[::]:port`
+    pub fn ipv6(port: u16) -> Self {
+        Listen {
+            address: Some("[::]".to_string()),
+            ..Self::port(port)
+        }
+    }
+}
+
+impl From<&Listen> for Directive {
+    fn from(listen: &Listen) -> Self {
+        let socket = match &listen.address {
+            Some(address) => format!("{address}:{}", listen.port),
+            None => listen.port.to_string(),
+        };
+
+        let flags = [
+            (listen.default_server, "default_server"),
+            (listen.ssl, "ssl"),
+            (listen.http2, "http2"),
+            (listen.udp, "udp"),
+            (listen.proxy_protocol, "proxy_protocol"),
+        ];
+
+        Directive::new("listen").arg(socket).args(
+            flags
+                .into_iter()
+                .filter_map(|(enabled, flag)| enabled.then_some(flag)),
+        )
+    }
+}
+
+impl Server {
+    /// The nodes of each field, grouped as rendered when built in code.
+    fn sections(&self) -> Vec<Vec<(&'static str, Vec<Node>)>> {
+        let mut sections = vec![
+            vec![
+                (
+                    "listen",
+                    self.listen
+                        .iter()
+                        .map(|listen| Directive::from(listen).into())
+                        .collect(),
+                ),
+                (
+                    "server_name",
+                    (!self.server_name.is_empty())
+                        .then(|| {
+                            Directive::new("server_name")
+                                .args(&self.server_name)
+                                .into()
+                        })
+                        .into_iter()
+                        .collect(),
+                ),
+                (
+                    "client_max_body_size",
+                    client_max_body_size_nodes(&self.client_max_body_size),
+                ),
+            ],
+            vec![
+                ("rewrite", rewrite_nodes(&self.rewrite)),
+                ("return", return_nodes(&self.r#return)),
+                ("proxy_set_header", header_nodes(&self.proxy_set_header)),
+            ],
+            vec![("extra", self.extra.clone())],
+        ];
+        // One section each, separated by a blank line.
+        sections.extend(
+            self.locations
+                .iter()
+                .map(|location| vec![("location", location.to_nodes())]),
+        );
+        sections
+    }
+}
+
+impl Render for Server {
+    fn to_nodes(&self) -> Vec<Node> {
+        let children = self.layout.lay_out(self.sections());
+
+        vec![Directive::new_block("server", children).into()]
+    }
+}
+
+impl TryFrom<&Directive> for Server {
+    type Error = Error;
+
+    fn try_from(directive: &Directive) -> Result<Self> {
+        let mut server = Server::default();
+
+        for node in directive.block_children()? {
+            let d = match node {
+                Node::Directive(d) => d,
+                Node::Comment(_) => {
+                    server.extra.push(node.clone());
+                    server.layout.push("extra", node);
+                    continue;
+                }
+                Node::Blank => {
+                    server.layout.push_blank();
+                    continue;
+                }
+            };
+
+            let field = match d.name.as_str() {
+                // Listen options without a typed field stay opaque.
+                "listen" => match Listen::try_from(d) {
+                    Ok(listen) => {
+                        server.listen.push(listen);
+                        "listen"
+                    }
+                    Err(_) => {
+                        server.extra.push(node.clone());
+                        "extra"
+                    }
+                },
+                "server_name" => {
+                    server.server_name.extend(d.values());
+                    "server_name"
+                }
+                "client_max_body_size" => {
+                    server.client_max_body_size = Some(d.parse_single()?);
+                    "client_max_body_size"
+                }
+                "rewrite" => {
+                    server.rewrite.push(Rewrite::try_from(d)?);
+                    "rewrite"
+                }
+                // A later `return` (e.g. after an `if`) stays opaque.
+                "return" if server.r#return.is_none() => {
+                    server.r#return = Some(Return::try_from(d)?);
+                    "return"
+                }
+                "proxy_set_header" => {
+                    server.proxy_set_header.push(Header::try_from(d)?);
+                    "proxy_set_header"
+                }
+                "location" => {
+                    server.locations.push(Location::try_from(d)?);
+                    "location"
+                }
+                _ => {
+                    server.extra.push(node.clone());
+                    "extra"
+                }
+            };
+            server.layout.push(field, node);
+        }
+
+        Ok(server)
+    }
+}
+
+impl TryFrom<&Directive> for Listen {
+    type Error = Error;
+
+    fn try_from(directive: &Directive) -> Result<Self> {
+        let mut values = directive.values();
+        let socket = values.next().ok_or_else(|| directive.invalid_args())?;
+
+        // `80`, `127.0.0.1:80`, `[::]:80`. Unix sockets are not typed.
+        let (address, port) = match socket.rsplit_once(':') {
+            Some((address, port)) if !port.ends_with(']') => {
+                (Some(address.to_string()), port)
+            }
+            _ => (None, socket.as_str()),
+        };
+        let port = port.parse().map_err(|_| directive.invalid_args())?;
+
+        let mut listen = Listen {
+            address,
+            ..Listen::port(port)
+        };
+        for flag in values {
+            match flag.as_str() {
+                "default_server" => listen.default_server = true,
+                "ssl" => listen.ssl = true,
+                "http2" => listen.http2 = true,
+                "udp" => listen.udp = true,
+                "proxy_protocol" => listen.proxy_protocol = true,
+                _ => return Err(directive.invalid_args()),
+            }
+        }
+
+        Ok(listen)
+    }
+}
+
+// region:    --- Shared Directives
+
+pub(crate) fn client_max_body_size_nodes(size: &Option<Size>) -> Vec<Node> {
+    size.iter()
+        .map(|size| {
+            Directive::new("client_max_body_size")
+                .arg(size.to_string())
+                .into()
+        })
+        .collect()
+}
+
+pub(crate) fn rewrite_nodes(rewrites: &[Rewrite]) -> Vec<Node> {
+    rewrites
+        .iter()
+        .map(|rewrite| {
+            let directive = Directive::new("rewrite")
+                .arg(&rewrite.regex)
+                .arg(&rewrite.replacement);
+            match rewrite.flag {
+                Some(flag) => directive.arg(flag.to_string()),
+                None => directive,
+            }
+            .into()
+        })
+        .collect()
+}
+
+pub(crate) fn return_nodes(r#return: &Option<Return>) -> Vec<Node> {
+    r#return
+        .iter()
+        .map(|Return { code, text }| {
+            let directive = Directive::new("return").arg(code.to_string());
+            match text {
+                Some(text) => directive.arg(text),
+                None => directive,
+            }
+            .into()
+        })
+        .collect()
+}
+
+pub(crate) fn header_nodes(headers: &[Header]) -> Vec<Node> {
+    headers
+        .iter()
+        .map(|header| {
+            Directive::new("proxy_set_header")
+                .arg(&header.name)
+                .arg(&header.value)
+                .into()
+        })
+        .collect()
+}
+
+impl TryFrom<&Directive> for Rewrite {
+    type Error = Error;
+
+    fn try_from(directive: &Directive) -> Result<Self> {
+        let mut values = directive.values();
+        let (Some(regex), Some(replacement), flag, None) =
+            (values.next(), values.next(), values.next(), values.next())
+        else {
+            return Err(directive.invalid_args());
+        };
+        let flag = flag
+            .map(|flag| flag.parse())
+            .transpose()
+            .map_err(|_| directive.invalid_args())?;
+
+        Ok(Rewrite {
+            regex,
+            replacement,
+            flag,
+        })
+    }
+}
+
+impl TryFrom<&Directive> for Return {
+    type Error = Error;
+
+    fn try_from(directive: &Directive) -> Result<Self> {
+        let mut values = directive.values();
+        let (Some(first), second, None) =
+            (values.next(), values.next(), values.next())
+        else {
+            return Err(directive.invalid_args());
+        };
+
+        // `return URL;` is a shortcut for `return 302 URL;`.
+        match (first.parse(), second) {
+            (Ok(code), text) => Ok(Return { code, text }),
+            (Err(_), None) => Ok(Return {
+                code: 302,
+                text: Some(first),
+            }),
+            (Err(_), Some(_)) => Err(directive.invalid_args()),
+        }
+    }
+}
+
+impl TryFrom<&Directive> for Header {
+    type Error = Error;
+
+    fn try_from(directive: &Directive) -> Result<Self> {
+        let mut values = directive.values();
+        let (Some(name), Some(value), None) =
+            (values.next(), values.next(), values.next())
+        else {
+            return Err(directive.invalid_args());
+        };
+
+        Ok(Header { name, value })
+    }
+}
+
+// endregion: --- Shared Directives
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+    use crate::context::LocationModifier;
+    use crate::value::RewriteFlag;
+
+    #[test]
+    fn test_render_server_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_server = Server {
+            listen: vec![
+                Listen {
+                    default_server: true,
+                    ..Listen::port(80)
+                },
+                Listen {
+                    ssl: true,
+                    http2: true,
+                    proxy_protocol: true,
+                    ..Listen::ipv6(443)
+                },
+            ],
+            server_name: vec!["example.com".into(), "www.example.com".into()],
+            client_max_body_size: Some(Size::Megabytes(20)),
+            rewrite: vec![Rewrite {
+                regex: "^/old/(.*)$".into(),
+                replacement: "/new/$1".into(),
+                flag: Some(RewriteFlag::Permanent),
+            }],
+            r#return: None,
+            proxy_set_header: vec![Header::new("Host", "$host")],
+            locations: vec![
+                Location {
+                    proxy_pass: Some("http://127.0.0.1:3000".into()),
+                    ..Location::new("/")
+                },
+                Location {
+                    modifier: Some(LocationModifier::Exact),
+                    r#return: Some(Return {
+                        code: 404,
+                        text: Some("not here".into()),
+                    }),
+                    ..Location::new("/hidden")
+                },
+            ],
+            extra: vec![],
+            layout: Layout::default(),
+        };
+        let fx_expected = r#"server {
+    listen 80 default_server;
+    listen [::]:443 ssl http2 proxy_protocol;
+    server_name example.com www.example.com;
+    client_max_body_size 20m;
+
+    rewrite ^/old/(.*)$ /new/$1 permanent;
+    proxy_set_header Host $host;
+
+    location / {
+        proxy_pass http://127.0.0.1:3000;
+    }
+
+    location = /hidden {
+        return 404 "not here";
+    }
+}
+"#;
+
+        // -- Exec
+        let res = fx_server.render();
+
+        // -- Check
+        assert_eq!(res, fx_expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_server_parse_render_order_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_input = r#"server {
+    listen 80;
+    set $maintenance 0;
+    # Flip to 1 during upgrades.
+    if ($maintenance) {
+        return 503;
+    }
+    return 301 https://$host$request_uri;
+    return 200;
+
+    location / {
+        if ($request_method = POST) {
+            return 405;
+        }
+        return 200 "all ok";
+    }
+    server_name example.com;
+}
+"#;
+
+        // -- Exec
+        let nodes = crate::parse(fx_input)?;
+        let Some(Node::Directive(directive)) = nodes.first() else {
+            return Err("Should have a server directive".into());
+        };
+        let server = Server::try_from(directive)?;
+
+        // -- Check
+        assert_eq!(
+            server.r#return,
+            Some(Return {
+                code: 301,
+                text: Some("https://$host$request_uri".into()),
+            })
+        );
+        assert_eq!(server.server_name, ["example.com"]);
+        assert_eq!(server.render(), fx_input);
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -0,0 +1,200 @@
+use crate::context::{Layout, Listen};
+use crate::error::{Error, Result};
+use crate::node::{Directive, Node};
+use crate::render::Render;
+use serde::Serialize;
+
+/// https://nginx.org/en/docs/stream/ngx_stream_core_module.html#stream
+///
+/// Rendered in the main context, next to `http`. Requires nginx built
+/// with (or loading) the stream module.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Stream {
+    pub servers: Vec<StreamServer>,
+
+    /// `include` directives rendered after the servers
+    /// (e.g. `/etc/nginx/streams/*.conf`).
+    pub include: Vec<String>,
+
+    /// Directives and comments not covered by the fields above.
+    pub extra: Vec<Node>,
+
+    #[serde(skip)]
+    pub layout: Layout,
+}
+
+/// https://nginx.org/en/docs/stream/ngx_stream_core_module.html#server
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct StreamServer {
+    pub listen: Vec<Listen>,
+    /// https://nginx.org/en/docs/stream/ngx_stream_proxy_module.html#proxy_pass
+    /// `host:port` or an upstream name, without scheme.
+    pub proxy_pass: Option<String>,
+
+    /// Directives and comments not covered by the fields above.
+    pub extra: Vec<Node>,
+
+    #[serde(skip)]
+    pub layout: Layout,
+}
+
+impl Render for Stream {
+    fn to_nodes(&self) -> Vec<Node> {
+        let mut sections = vec![vec![("extra", self.extra.clone())]];
+        // One section each, separated by a blank line.
+        sections.extend(
+            self.servers
+                .iter()
+                .map(|server| vec![("server", server.to_nodes())]),
+        );
+        sections.push(vec![(
+            "include",
+            self.include
+                .iter()
+                .map(|path| Directive::new("include").arg(path).into())
+                .collect(),
+        )]);
+        let children = self.layout.lay_out(sections);
+
+        vec![Directive::new_block("stream", children).into()]
+    }
+}
+
+impl Render for StreamServer {
+    fn to_nodes(&self) -> Vec<Node> {
+        let children = self.layout.lay_out(vec![
+            vec![
+                (
+                    "listen",
+                    self.listen
+                        .iter()
+                        .map(|listen| Directive::from(listen).into())
+                        .collect(),
+                ),
+                (
+                    "proxy_pass",
+                    self.proxy_pass
+                        .iter()
+                        .map(|proxy_pass| {
+                            Directive::new("proxy_pass").arg(proxy_pass).into()
+                        })
+                        .collect(),
+                ),
+            ],
+            vec![("extra", self.extra.clone())],
+        ]);
+
+        vec![Directive::new_block("server", children).into()]
+    }
+}
+
+impl TryFrom<&Directive> for Stream {
+    type Error = Error;
+
+    fn try_from(directive: &Directive) -> Result<Self> {
+        let mut stream = Stream::default();
+
+        for node in directive.block_children()? {
+            let field = match node {
+                Node::Directive(d) if d.name == "server" => {
+                    stream.servers.push(StreamServer::try_from(d)?);
+                    "server"
+                }
+                Node::Directive(d) if d.name == "include" => {
+                    stream.include.push(d.single_value()?);
+                    "include"
+                }
+                Node::Blank => {
+                    stream.layout.push_blank();
+                    continue;
+                }
+                node => {
+                    stream.extra.push(node.clone());
+                    "extra"
+                }
+            };
+            stream.layout.push(field, node);
+        }
+
+        Ok(stream)
+    }
+}
+
+impl TryFrom<&Directive> for StreamServer {
+    type Error = Error;
+
+    fn try_from(directive: &Directive) -> Result<Self> {
+        let mut server = StreamServer::default();
+
+        for node in directive.block_children()? {
+            let field = match node {
+                Node::Directive(d) if d.name == "listen" => {
+                    server.listen.push(Listen::try_from(d)?);
+                    "listen"
+                }
+                Node::Directive(d) if d.name == "proxy_pass" => {
+                    server.proxy_pass = Some(d.single_value()?);
+                    "proxy_pass"
+                }
+                Node::Blank => {
+                    server.layout.push_blank();
+                    continue;
+                }
+                node => {
+                    server.extra.push(node.clone());
+                    "extra"
+                }
+            };
+            server.layout.push(field, node);
+        }
+
+        Ok(server)
+    }
+}
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_stream_roundtrip_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_input = "stream {
+    proxy_timeout 10m;
+
+    server {
+        listen 53 udp;
+        listen [::]:53 udp;
+        proxy_pass 10.0.0.53:53;
+    }
+
+    include /etc/nginx/streams/*.conf;
+}
+";
+
+        // -- Exec
+        let nodes = parse(fx_input)?;
+        let Some(Node::Directive(directive)) = nodes.first() else {
+            return Err("Should have a stream directive".into());
+        };
+        let stream = Stream::try_from(directive)?;
+
+        // -- Check
+        let [server] = stream.servers.as_slice() else {
+            return Err("Should have one server".into());
+        };
+        assert!(server.listen.iter().all(|listen| listen.udp));
+        assert_eq!(server.proxy_pass.as_deref(), Some("10.0.0.53:53"));
+        assert_eq!(stream.render(), fx_input);
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
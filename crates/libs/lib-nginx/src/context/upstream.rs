@@ -0,0 +1,266 @@
+use crate::context::Layout;
+use crate::error::{Error, Result};
+use crate::node::{Directive, Node};
+use crate::render::Render;
+use serde::Serialize;
+
+/// https://nginx.org/en/docs/http/ngx_http_upstream_module.html#upstream
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Upstream {
+    pub name: String,
+    /// Weighted round-robin when `None`.
+    pub balance: Option<UpstreamBalance>,
+    pub servers: Vec<UpstreamServer>,
+
+    /// Directives and comments not covered by the fields above, e.g.
+    /// `keepalive` or servers with other parameters.
+    pub extra: Vec<Node>,
+
+    #[serde(skip)]
+    pub layout: Layout,
+}
+
+impl Upstream {
+    pub fn new(name: impl Into<String>) -> Self {
+        Upstream {
+            name: name.into(),
+            balance: None,
+            servers: Vec::new(),
+            extra: Vec::new(),
+            layout: Layout::default(),
+        }
+    }
+}
+
+/// Load balancing method of an upstream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum UpstreamBalance {
+    /// https://nginx.org/en/docs/http/ngx_http_upstream_module.html#least_conn
+    LeastConn,
+    /// https://nginx.org/en/docs/http/ngx_http_upstream_module.html#ip_hash
+    IpHash,
+}
+
+impl UpstreamBalance {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UpstreamBalance::LeastConn => "least_conn",
+            UpstreamBalance::IpHash => "ip_hash",
+        }
+    }
+}
+
+/// https://nginx.org/en/docs/http/ngx_http_upstream_module.html#server
+///
+/// The parameters left to `None` keep the nginx defaults.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UpstreamServer {
+    /// `host:port` or `unix:/path`.
+    pub address: String,
+    pub weight: Option<u32>,
+    pub max_fails: Option<u32>,
+    /// In seconds.
+    pub fail_timeout: Option<u32>,
+    /// Only used when the other servers are down.
+    pub backup: bool,
+}
+
+impl UpstreamServer {
+    pub fn new(address: impl Into<String>) -> Self {
+        UpstreamServer {
+            address: address.into(),
+            weight: None,
+            max_fails: None,
+            fail_timeout: None,
+            backup: false,
+        }
+    }
+}
+
+impl Render for Upstream {
+    fn to_nodes(&self) -> Vec<Node> {
+        let children = self.layout.lay_out(vec![vec![
+            (
+                "balance",
+                self.balance
+                    .iter()
+                    .map(|balance| Directive::new(balance.as_str()).into())
+                    .collect(),
+            ),
+            (
+                "server",
+                self.servers.iter().map(server_directive).collect(),
+            ),
+            ("extra", self.extra.clone()),
+        ]]);
+
+        vec![
+            Directive::new_block("upstream", children)
+                .arg(&self.name)
+                .into(),
+        ]
+    }
+}
+
+fn server_directive(server: &UpstreamServer) -> Node {
+    let mut directive = Directive::new("server").arg(&server.address);
+    if let Some(weight) = server.weight {
+        directive = directive.arg(format!("weight={weight}"));
+    }
+    if let Some(max_fails) = server.max_fails {
+        directive = directive.arg(format!("max_fails={max_fails}"));
+    }
+    if let Some(fail_timeout) = server.fail_timeout {
+        directive = directive.arg(format!("fail_timeout={fail_timeout}s"));
+    }
+    if server.backup {
+        directive = directive.arg("backup");
+    }
+
+    directive.into()
+}
+
+impl TryFrom<&Directive> for Upstream {
+    type Error = Error;
+
+    fn try_from(directive: &Directive) -> Result<Self> {
+        let mut values = directive.values();
+        let (Some(name), None) = (values.next(), values.next()) else {
+            return Err(directive.invalid_args());
+        };
+
+        let mut upstream = Upstream::new(name);
+
+        for node in directive.block_children()? {
+            let d = match node {
+                Node::Directive(d) => d,
+                Node::Comment(_) => {
+                    upstream.extra.push(node.clone());
+                    upstream.layout.push("extra", node);
+                    continue;
+                }
+                Node::Blank => {
+                    upstream.layout.push_blank();
+                    continue;
+                }
+            };
+
+            let field = match (d.name.as_str(), d.args.is_empty()) {
+                ("least_conn", true) => {
+                    upstream.balance = Some(UpstreamBalance::LeastConn);
+                    "balance"
+                }
+                ("ip_hash", true) => {
+                    upstream.balance = Some(UpstreamBalance::IpHash);
+                    "balance"
+                }
+                ("server", false) => match parse_server(d) {
+                    Some(server) => {
+                        upstream.servers.push(server);
+                        "server"
+                    }
+                    None => {
+                        upstream.extra.push(node.clone());
+                        "extra"
+                    }
+                },
+                _ => {
+                    upstream.extra.push(node.clone());
+                    "extra"
+                }
+            };
+            upstream.layout.push(field, node);
+        }
+
+        Ok(upstream)
+    }
+}
+
+/// `None` for the parameters not modeled (e.g. `slow_start`), the whole
+/// directive is then kept as is.
+fn parse_server(directive: &Directive) -> Option<UpstreamServer> {
+    if directive.is_block() {
+        return None;
+    }
+
+    let mut values = directive.values();
+    let mut server = UpstreamServer::new(values.next()?);
+    for value in values {
+        match value.split_once('=') {
+            Some(("weight", weight)) => {
+                server.weight = Some(weight.parse().ok()?)
+            }
+            Some(("max_fails", max_fails)) => {
+                server.max_fails = Some(max_fails.parse().ok()?)
+            }
+            Some(("fail_timeout", fail_timeout)) => {
+                let seconds =
+                    fail_timeout.strip_suffix('s').unwrap_or(fail_timeout);
+                server.fail_timeout = Some(seconds.parse().ok()?)
+            }
+            None if value == "backup" => server.backup = true,
+            _ => return None,
+        }
+    }
+
+    Some(server)
+}
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn test_upstream_render_parse_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_conf = r#"upstream app {
+    least_conn;
+    server 10.0.0.5:3000 weight=3;
+    server 10.0.0.6:3000 max_fails=2 fail_timeout=30s;
+    server 10.0.0.7:3000 backup;
+    server 10.0.0.8:3000 slow_start=30s;
+    keepalive 16;
+}
+"#;
+
+        // -- Exec
+        let nodes = parse(fx_conf)?;
+        let Some(Node::Directive(directive)) = nodes.first() else {
+            return Err("Should parse the upstream".into());
+        };
+        let upstream = Upstream::try_from(directive)?;
+
+        // -- Check
+        assert_eq!(upstream.balance, Some(UpstreamBalance::LeastConn));
+        assert_eq!(
+            upstream.servers,
+            [
+                UpstreamServer {
+                    weight: Some(3),
+                    ..UpstreamServer::new("10.0.0.5:3000")
+                },
+                UpstreamServer {
+                    max_fails: Some(2),
+                    fail_timeout: Some(30),
+                    ..UpstreamServer::new("10.0.0.6:3000")
+                },
+                UpstreamServer {
+                    backup: true,
+                    ..UpstreamServer::new("10.0.0.7:3000")
+                },
+            ]
+        );
+        assert_eq!(upstream.extra.len(), 2);
+        assert_eq!(upstream.render(), fx_conf);
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -0,0 +1,56 @@
+use crate::parser::SnippetContext;
+use serde::Serialize;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Lines and columns are 1-based, columns count characters.
+#[derive(
+    thiserror::Error,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    Serialize,
+    strum_macros::Display,
+)]
+pub enum Error {
+    // -- Syntax
+    UnexpectedEof {
+        line: usize,
+        col: usize,
+    },
+    UnexpectedToken {
+        token: String,
+        line: usize,
+        col: usize,
+    },
+    UnexpectedClosingBrace {
+        line: usize,
+        col: usize,
+    },
+    UnclosedBlock {
+        directive: String,
+        line: usize,
+        col: usize,
+    },
+    UnterminatedQuote {
+        line: usize,
+        col: usize,
+    },
+
+    // -- Snippets
+    DirectiveNotAllowed {
+        directive: String,
+        context: SnippetContext,
+        line: usize,
+        col: usize,
+    },
+
+    // -- Typed model
+    InvalidArgs {
+        directive: String,
+    },
+    ExpectedBlock {
+        directive: String,
+    },
+}
@@ -0,0 +1,174 @@
+use crate::node::{Directive, Node};
+use serde::Serialize;
+use std::fmt;
+
+/// An access list applied to a host: ordered ip rules and basic auth.
+///
+/// Addresses not matched by a rule are denied, a trailing `deny all` is
+/// added unless the last rule already covers `all`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AccessConf {
+    pub satisfy: Satisfy,
+    pub rules: Vec<AccessRule>,
+    /// Realm and user file, no basic auth when `None`.
+    pub auth_basic: Option<AuthBasic>,
+}
+
+/// https://nginx.org/en/docs/http/ngx_http_core_module.html#satisfy
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Satisfy {
+    /// The ip rules and the basic auth must both pass.
+    #[default]
+    All,
+    /// Either one is enough.
+    Any,
+}
+
+impl fmt::Display for Satisfy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Satisfy::All => f.write_str("all"),
+            Satisfy::Any => f.write_str("any"),
+        }
+    }
+}
+
+/// https://nginx.org/en/docs/http/ngx_http_access_module.html
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "action", content = "address", rename_all = "lowercase")]
+pub enum AccessRule {
+    /// An address, a CIDR or `all`.
+    Allow(String),
+    Deny(String),
+}
+
+impl AccessRule {
+    fn address(&self) -> &str {
+        match self {
+            AccessRule::Allow(address) | AccessRule::Deny(address) => address,
+        }
+    }
+}
+
+/// https://nginx.org/en/docs/http/ngx_http_auth_basic_module.html
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AuthBasic {
+    pub realm: String,
+    /// Absolute path of the htpasswd file.
+    pub user_file: String,
+}
+
+impl AccessConf {
+    /// Directives of the `server` block.
+    pub fn to_directives(&self) -> Vec<Node> {
+        let mut nodes = Vec::new();
+        if !self.rules.is_empty() && self.auth_basic.is_some() {
+            nodes.push(
+                Directive::new("satisfy")
+                    .arg(self.satisfy.to_string())
+                    .into(),
+            );
+        }
+
+        for rule in &self.rules {
+            let name = match rule {
+                AccessRule::Allow(_) => "allow",
+                AccessRule::Deny(_) => "deny",
+            };
+            nodes.push(Directive::new(name).arg(rule.address()).into());
+        }
+        if self
+            .rules
+            .last()
+            .is_some_and(|rule| rule.address() != "all")
+        {
+            nodes.push(Directive::new("deny").arg("all").into());
+        }
+
+        if let Some(AuthBasic { realm, user_file }) = &self.auth_basic {
+            nodes.push(Directive::new("auth_basic").arg(realm).into());
+            nodes.push(
+                Directive::new("auth_basic_user_file").arg(user_file).into(),
+            );
+        }
+
+        nodes
+    }
+
+    /// Directives lifting the restrictions in a `location`, e.g. for the
+    /// acme challenges which must stay reachable.
+    pub(crate) fn public_directives() -> Vec<Node> {
+        vec![
+            Directive::new("auth_basic").arg("off").into(),
+            Directive::new("allow").arg("all").into(),
+        ]
+    }
+}
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+    use crate::render::render_nodes;
+
+    #[test]
+    fn test_access_directives_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_access = AccessConf {
+            satisfy: Satisfy::Any,
+            rules: vec![
+                AccessRule::Deny("10.0.0.13".into()),
+                AccessRule::Allow("10.0.0.0/8".into()),
+                AccessRule::Allow("fd00::/8".into()),
+            ],
+            auth_basic: Some(AuthBasic {
+                realm: "Staff only".into(),
+                user_file: "/etc/nginx/access/access_list_1.htpasswd".into(),
+            }),
+        };
+        let fx_expected = r#"satisfy any;
+deny 10.0.0.13;
+allow 10.0.0.0/8;
+allow fd00::/8;
+deny all;
+auth_basic "Staff only";
+auth_basic_user_file /etc/nginx/access/access_list_1.htpasswd;
+"#;
+
+        // -- Exec
+        let res = render_nodes(&fx_access.to_directives());
+
+        // -- Check
+        assert_eq!(res, fx_expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_access_directives_rules_only_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_access = AccessConf {
+            satisfy: Satisfy::All,
+            rules: vec![
+                AccessRule::Deny("192.0.2.1".into()),
+                AccessRule::Allow("all".into()),
+            ],
+            auth_basic: None,
+        };
+
+        // -- Exec
+        let res = render_nodes(&fx_access.to_directives());
+
+        // -- Check
+        assert_eq!(res, "deny 192.0.2.1;\nallow all;\n");
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
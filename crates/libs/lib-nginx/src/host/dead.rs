@@ -0,0 +1,142 @@
+use crate::context::{Listen, Location, LocationModifier, Server};
+use crate::host::acme_challenge_location;
+use crate::node::{Directive, Node};
+use crate::render::Render;
+use crate::value::Return;
+use serde::Serialize;
+
+/// A 404 host as edited from the `/404-host` page: every request is
+/// answered with a 404.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DeadHostConf {
+    pub domain_names: Vec<String>,
+    /// Page sent with the 404, the nginx built-in page when `None`.
+    pub error_page: Option<ErrorPage>,
+    /// Url the `/.well-known/acme-challenge/` requests are proxied to,
+    /// e.g. the web-server answering the http-01 challenges.
+    pub acme_challenge_pass: Option<String>,
+}
+
+/// A static html file served from the web-server static folder.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ErrorPage {
+    /// Absolute path of the static folder, e.g. `/srv/frontend/static`.
+    pub static_root: String,
+    /// Path of the page in `static_root`, e.g.
+    /// `html/error_pages/dead_host_1.html`.
+    pub file: String,
+}
+
+impl DeadHostConf {
+    pub fn server(&self) -> Server {
+        let mut server = Server {
+            listen: vec![Listen::port(80), Listen::ipv6(80)],
+            server_name: self.domain_names.clone(),
+            locations: vec![Location {
+                r#return: Some(Return {
+                    code: 404,
+                    text: None,
+                }),
+                ..Location::new("/")
+            }],
+            ..Default::default()
+        };
+
+        if let Some(pass) = &self.acme_challenge_pass {
+            server.locations.insert(0, acme_challenge_location(pass));
+        }
+        if let Some(error_page) = &self.error_page {
+            let uri = format!("/{}", error_page.file.trim_start_matches('/'));
+            let static_root = error_page.static_root.trim_end_matches('/');
+
+            server.extra = vec![
+                Directive::new("root").arg(static_root).into(),
+                Directive::new("error_page").arg("404").arg(&uri).into(),
+            ];
+            server.locations.extend([
+                Location {
+                    modifier: Some(LocationModifier::Exact),
+                    extra: vec![Directive::new("internal").into()],
+                    ..Location::new(uri)
+                },
+                // Stylesheets and scripts of the page, e.g. the default
+                // `error404.html`.
+                Location {
+                    extra: vec![
+                        Directive::new("alias")
+                            .arg(format!("{static_root}/"))
+                            .into(),
+                    ],
+                    ..Location::new("/static/")
+                },
+            ]);
+        }
+
+        server
+    }
+}
+
+impl Render for DeadHostConf {
+    fn to_nodes(&self) -> Vec<Node> {
+        let mut nodes = vec![Node::Comment(format!(
+            " 404 host: {}",
+            self.domain_names.join(", ")
+        ))];
+        nodes.extend(self.server().to_nodes());
+        nodes
+    }
+}
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+
+    #[test]
+    fn test_render_dead_host_golden_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_golden = include_str!("../../golden/dead_host.conf");
+        let fx_host = DeadHostConf {
+            domain_names: vec!["gone.example.com".into()],
+            error_page: Some(ErrorPage {
+                static_root: "/srv/frontend/static/".into(),
+                file: "html/error_pages/dead_host_1.html".into(),
+            }),
+            acme_challenge_pass: None,
+        };
+
+        // -- Exec
+        let res = fx_host.render();
+
+        // -- Check
+        assert_eq!(res, fx_golden);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_dead_host_builtin_page_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_host = DeadHostConf {
+            domain_names: vec!["gone.example.com".into()],
+            error_page: None,
+            acme_challenge_pass: None,
+        };
+
+        // -- Exec
+        let server = fx_host.server();
+
+        // -- Check
+        assert!(server.extra.is_empty());
+        assert_eq!(server.locations.len(), 1);
+        assert!(fx_host.render().contains("return 404;"));
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
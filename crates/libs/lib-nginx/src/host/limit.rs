@@ -0,0 +1,260 @@
+use crate::host::ProxyHostConf;
+use crate::node::{Directive, Node};
+use crate::render::Render;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Shared memory of each zone, about 16 000 clients of a host.
+const ZONE_SIZE: &str = "1m";
+/// The client address, the zones being per host.
+const ZONE_KEY: &str = "$binary_remote_addr";
+/// Answered to the limited requests, rather than `503`.
+const LIMITED_STATUS: &str = "429";
+
+/// https://nginx.org/en/docs/http/ngx_http_limit_req_module.html
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RateLimit {
+    /// Names the zone of the host, e.g. `proxy_host_3`, unique per host
+    /// so the hosts do not share the budget of a client.
+    pub zone: String,
+    /// Requests per second of a client.
+    pub rate: u32,
+    /// Requests over the rate delayed, the next ones refused.
+    pub burst: u32,
+    /// Serves the `burst` requests without delay.
+    pub nodelay: bool,
+}
+
+impl RateLimit {
+    fn zone_name(&self) -> String {
+        format!("limit_req_{}", self.zone)
+    }
+
+    /// Directives of the `server` block.
+    pub fn to_directives(&self) -> Vec<Node> {
+        let mut limit_req = Directive::new("limit_req")
+            .arg(format!("zone={}", self.zone_name()));
+        if self.burst > 0 {
+            limit_req = limit_req.arg(format!("burst={}", self.burst));
+        }
+        if self.nodelay {
+            limit_req = limit_req.arg("nodelay");
+        }
+
+        vec![
+            limit_req.into(),
+            Directive::new("limit_req_status")
+                .arg(LIMITED_STATUS)
+                .into(),
+        ]
+    }
+}
+
+/// https://nginx.org/en/docs/http/ngx_http_limit_conn_module.html,
+/// concurrent connections of a client.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConnectionLimit {
+    /// Names the zone of the host, as `RateLimit::zone`.
+    pub zone: String,
+    pub limit: u32,
+}
+
+impl ConnectionLimit {
+    fn zone_name(&self) -> String {
+        format!("limit_conn_{}", self.zone)
+    }
+
+    /// Directives of the `server` block.
+    pub fn to_directives(&self) -> Vec<Node> {
+        vec![
+            Directive::new("limit_conn")
+                .arg(self.zone_name())
+                .arg(self.limit.to_string())
+                .into(),
+            Directive::new("limit_conn_status")
+                .arg(LIMITED_STATUS)
+                .into(),
+        ]
+    }
+}
+
+/// The `limit_req_zone` and `limit_conn_zone` of the hosts, rendered at
+/// the `http` level, once each.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct LimitZones {
+    /// Rate of each `limit_req_zone`, by name.
+    rates: BTreeMap<String, u32>,
+    connections: BTreeSet<String>,
+}
+
+impl LimitZones {
+    pub fn new<'a>(hosts: impl IntoIterator<Item = &'a ProxyHostConf>) -> Self {
+        let mut zones = LimitZones::default();
+        for host in hosts {
+            if let Some(rate_limit) = &host.rate_limit {
+                zones.rates.insert(rate_limit.zone_name(), rate_limit.rate);
+            }
+            if let Some(connection_limit) = &host.connection_limit {
+                zones.connections.insert(connection_limit.zone_name());
+            }
+        }
+        zones
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rates.is_empty() && self.connections.is_empty()
+    }
+}
+
+impl Render for LimitZones {
+    fn to_nodes(&self) -> Vec<Node> {
+        let rates = self.rates.iter().map(|(name, rate)| {
+            Directive::new("limit_req_zone")
+                .raw_arg(ZONE_KEY)
+                .arg(format!("zone={name}:{ZONE_SIZE}"))
+                .arg(format!("rate={rate}r/s"))
+                .into()
+        });
+        let connections = self.connections.iter().map(|name| {
+            Directive::new("limit_conn_zone")
+                .raw_arg(ZONE_KEY)
+                .arg(format!("zone={name}:{ZONE_SIZE}"))
+                .into()
+        });
+        rates.chain(connections).collect()
+    }
+}
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+    use crate::host::ForwardScheme;
+
+    fn fx_host(
+        zone: &str,
+        rate: Option<u32>,
+        connection_limit: Option<u32>,
+    ) -> ProxyHostConf {
+        ProxyHostConf {
+            domain_names: vec![format!("{zone}.example.com")],
+            forward_scheme: ForwardScheme::Http,
+            forward_host: "10.0.0.5".into(),
+            forward_port: 3000,
+            upstream: None,
+            acme_challenge_pass: None,
+            access: None,
+            ssl: None,
+            block_exploits: false,
+            cache_assets: false,
+            allow_websocket_upgrade: false,
+            advanced: Vec::new(),
+            rate_limit: rate.map(|rate| RateLimit {
+                zone: zone.to_string(),
+                rate,
+                burst: 0,
+                nodelay: false,
+            }),
+            connection_limit: connection_limit.map(|limit| ConnectionLimit {
+                zone: zone.to_string(),
+                limit,
+            }),
+            locations: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_render_limit_zones_dedup_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_hosts = [
+            fx_host("api", Some(10), None),
+            fx_host("web", Some(5), Some(20)),
+            fx_host("web", Some(5), Some(20)),
+            fx_host("static", None, None),
+        ];
+
+        // -- Exec
+        let zones = LimitZones::new(&fx_hosts);
+
+        // -- Check
+        assert_eq!(
+            zones.render(),
+            "limit_req_zone $binary_remote_addr zone=limit_req_api:1m rate=10r/s;
+limit_req_zone $binary_remote_addr zone=limit_req_web:1m rate=5r/s;
+limit_conn_zone $binary_remote_addr zone=limit_conn_web:1m;
+"
+        );
+        assert!(LimitZones::new(&fx_hosts[3..]).is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_limit_zones_per_host_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_hosts = [
+            fx_host("proxy_host_1", Some(10), Some(50)),
+            fx_host("proxy_host_2", Some(10), Some(50)),
+        ];
+
+        // -- Exec
+        let zones = LimitZones::new(&fx_hosts).render();
+        let servers: Vec<String> =
+            fx_hosts.iter().map(|host| host.render()).collect();
+
+        // -- Check
+        // Same rate, but a zone each: a client has a budget per host.
+        for (id, server) in [(1, &servers[0]), (2, &servers[1])] {
+            let other = 3 - id;
+            assert!(zones.contains(&format!(
+                "limit_req_zone $binary_remote_addr zone=limit_req_proxy_host_{id}:1m rate=10r/s;"
+            )));
+            assert!(zones.contains(&format!(
+                "limit_conn_zone $binary_remote_addr zone=limit_conn_proxy_host_{id}:1m;"
+            )));
+            assert!(server.contains(&format!(
+                "limit_req zone=limit_req_proxy_host_{id};"
+            )));
+            assert!(server.contains(&format!(
+                "limit_conn limit_conn_proxy_host_{id} 50;"
+            )));
+            assert!(!server.contains(&format!("proxy_host_{other}")));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_proxy_host_limits_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mut fx_host = fx_host("api", Some(10), Some(50));
+        if let Some(rate_limit) = &mut fx_host.rate_limit {
+            rate_limit.burst = 20;
+            rate_limit.nodelay = true;
+        }
+
+        // -- Exec
+        let res = fx_host.render();
+
+        // -- Check
+        assert!(res.contains(
+            "    server_name api.example.com;
+
+    limit_req zone=limit_req_api burst=20 nodelay;
+    limit_req_status 429;
+    limit_conn limit_conn_api 50;
+    limit_conn_status 429;
+
+    location / {
+"
+        ));
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
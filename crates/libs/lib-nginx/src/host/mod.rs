@@ -0,0 +1,37 @@
+//! Per-host configuration files.
+//!
+//! Each host managed from the UI is rendered as its own `.conf` file,
+//! meant to be included from the `http` context of `nginx.conf`, or
+//! from the `stream` context for stream hosts.
+
+// region:    --- Modules
+
+mod access;
+mod dead;
+mod limit;
+mod proxy;
+mod redirection;
+mod ssl;
+mod stream;
+
+pub use access::{AccessConf, AccessRule, AuthBasic, Satisfy};
+pub use dead::{DeadHostConf, ErrorPage};
+pub use limit::{ConnectionLimit, LimitZones, RateLimit};
+pub use proxy::{ForwardScheme, ProxyHostConf, ProxyLocationConf};
+pub use redirection::RedirectionHostConf;
+pub use ssl::{Http2Syntax, SslConf};
+pub use stream::{StreamHostConf, StreamProtocol};
+
+use crate::context::{Location, LocationModifier};
+
+// endregion: --- Modules
+
+/// `location ^~ /.well-known/acme-challenge/`, for the http-01 challenges
+/// of the certificates of the host.
+fn acme_challenge_location(pass: &str) -> Location {
+    Location {
+        modifier: Some(LocationModifier::PreferPrefix),
+        proxy_pass: Some(pass.to_string()),
+        ..Location::new("/.well-known/acme-challenge/")
+    }
+}
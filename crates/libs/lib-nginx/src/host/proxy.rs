@@ -0,0 +1,803 @@
+use crate::context::{Listen, Location, Server};
+use crate::host::acme_challenge_location;
+use crate::host::{AccessConf, ConnectionLimit, RateLimit, SslConf};
+use crate::node::{Node, push_section};
+use crate::render::Render;
+use crate::snippet::{
+    block_exploits, cache_assets_location, websocket_upgrade,
+};
+use crate::value::Header;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A proxy host as edited from the `/proxy` page.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProxyHostConf {
+    pub domain_names: Vec<String>,
+    pub forward_scheme: ForwardScheme,
+    pub forward_host: String,
+    pub forward_port: u16,
+    /// Name of an `upstream` proxied to instead of
+    /// `forward_host:forward_port`.
+    pub upstream: Option<String>,
+    /// Url the `/.well-known/acme-challenge/` requests are proxied to,
+    /// e.g. the web-server answering the http-01 challenges.
+    pub acme_challenge_pass: Option<String>,
+    /// Access list of the host, public when `None`.
+    pub access: Option<AccessConf>,
+    /// Served over https too, or only when forced, http only when `None`.
+    pub ssl: Option<SslConf>,
+    /// Includes the `block_exploits` snippet.
+    pub block_exploits: bool,
+    /// Caches the static assets proxied by `location /`, in the zone of a
+    /// `CacheZone`.
+    pub cache_assets: bool,
+    /// Passes the websocket upgrades through, with the `$connection_upgrade`
+    /// of a `ConnectionUpgradeMap`.
+    pub allow_websocket_upgrade: bool,
+    /// Raw directives appended to the `server` block, e.g. a parsed
+    /// snippet.
+    pub advanced: Vec<Node>,
+    /// Requests of a client, in the zone of the host of `LimitZones`.
+    pub rate_limit: Option<RateLimit>,
+    /// Concurrent connections of a client, in the zone of the host of
+    /// `LimitZones`.
+    pub connection_limit: Option<ConnectionLimit>,
+    /// Custom locations, rendered in order before `location /`.
+    pub locations: Vec<ProxyLocationConf>,
+}
+
+/// A custom `location` of a proxy host, with its own upstream.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProxyLocationConf {
+    /// Uri prefix, e.g. `/api/`.
+    pub path: String,
+    pub forward_scheme: ForwardScheme,
+    pub forward_host: String,
+    pub forward_port: u16,
+    /// Added to the default proxy headers, replacing the ones of the same
+    /// name.
+    pub headers: Vec<Header>,
+    /// Raw directives appended to the block, e.g. a parsed snippet.
+    pub advanced: Vec<Node>,
+}
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum ForwardScheme {
+    #[default]
+    Http,
+    Https,
+}
+
+impl fmt::Display for ForwardScheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ForwardScheme::Http => f.write_str("http"),
+            ForwardScheme::Https => f.write_str("https"),
+        }
+    }
+}
+
+impl ProxyHostConf {
+    /// `scheme://host:port`, with IPv6 hosts in brackets, or
+    /// `scheme://upstream`.
+    pub fn forward_url(&self) -> String {
+        match &self.upstream {
+            Some(upstream) => format!("{}://{upstream}", self.forward_scheme),
+            None => forward_url(
+                self.forward_scheme,
+                &self.forward_host,
+                self.forward_port,
+            ),
+        }
+    }
+
+    /// The `server` proxying the requests.
+    pub fn server(&self) -> Server {
+        let mut locations: Vec<Location> = self
+            .locations
+            .iter()
+            .map(ProxyLocationConf::location)
+            .collect();
+        let mut root = Location {
+            proxy_set_header: default_proxy_headers(),
+            proxy_pass: Some(self.forward_url()),
+            ..Location::new("/")
+        };
+        if self.cache_assets {
+            // Nested, a regex location of the server would also take the
+            // assets of the custom locations.
+            root.extra.extend(
+                cache_assets_location(
+                    &self.forward_url(),
+                    default_proxy_headers(),
+                )
+                .to_nodes(),
+            );
+        }
+        locations.push(root);
+        if self.allow_websocket_upgrade {
+            locations.iter_mut().for_each(websocket_upgrade);
+        }
+
+        let mut server = Server {
+            listen: vec![Listen::port(80), Listen::ipv6(80)],
+            server_name: self.domain_names.clone(),
+            locations,
+            ..Default::default()
+        };
+        if let Some(ssl) = &self.ssl {
+            if ssl.forced {
+                server.listen.clear();
+            }
+            server.listen.extend(ssl.listen());
+            server.extra.extend(ssl.to_directives());
+        }
+        if let Some(access) = &self.access {
+            server.extra.extend(access.to_directives());
+        }
+        if let Some(rate_limit) = &self.rate_limit {
+            server.extra.extend(rate_limit.to_directives());
+        }
+        if let Some(connection_limit) = &self.connection_limit {
+            server.extra.extend(connection_limit.to_directives());
+        }
+        if self.block_exploits {
+            server.extra.extend(block_exploits());
+        }
+        push_section(&mut server.extra, self.advanced.clone());
+        if let Some(pass) = &self.acme_challenge_pass {
+            let mut location = acme_challenge_location(pass);
+            if self.access.is_some() {
+                location.extra = AccessConf::public_directives();
+            }
+            server.locations.insert(0, location);
+        }
+
+        server
+    }
+
+    /// The plain http `server` redirecting to https, when SSL is forced.
+    pub fn redirect_server(&self) -> Option<Server> {
+        self.ssl.as_ref().filter(|ssl| ssl.forced).map(|_| {
+            SslConf::redirect_server(
+                &self.domain_names,
+                self.acme_challenge_pass.as_deref(),
+            )
+        })
+    }
+}
+
+impl ProxyLocationConf {
+    /// `scheme://host:port`, with IPv6 hosts in brackets.
+    pub fn forward_url(&self) -> String {
+        forward_url(self.forward_scheme, &self.forward_host, self.forward_port)
+    }
+
+    pub fn location(&self) -> Location {
+        let mut headers = default_proxy_headers();
+        for header in &self.headers {
+            match headers
+                .iter_mut()
+                .find(|h| h.name.eq_ignore_ascii_case(&header.name))
+            {
+                Some(default) => *default = header.clone(),
+                None => headers.push(header.clone()),
+            }
+        }
+
+        Location {
+            proxy_set_header: headers,
+            proxy_pass: Some(self.forward_url()),
+            extra: self.advanced.clone(),
+            ..Location::new(&self.path)
+        }
+    }
+}
+
+// Import.
+impl ProxyHostConf {
+    /// Read a proxy host back from a `server` block, e.g. one parsed from
+    /// an existing `sites-enabled` file.
+    ///
+    /// Only the upstream of `location /` is kept. Returns `None` when the
+    /// server does not proxy `/` to a plain `scheme://host[:port]` url.
+    pub fn from_server(server: &Server) -> Option<Self> {
+        let location = server.locations.iter().find(|location| {
+            location.modifier.is_none() && location.path == "/"
+        })?;
+        let url = location.proxy_pass.as_deref()?;
+
+        let (forward_scheme, rest) =
+            if let Some(rest) = url.strip_prefix("http://") {
+                (ForwardScheme::Http, rest)
+            } else {
+                (ForwardScheme::Https, url.strip_prefix("https://")?)
+            };
+        let authority = rest.strip_suffix('/').unwrap_or(rest);
+        if authority.contains(['/', '$']) {
+            return None;
+        }
+
+        let (forward_host, port) = match authority.strip_prefix('[') {
+            Some(ipv6) => {
+                let (host, port) = ipv6.split_once(']')?;
+                (host, port.strip_prefix(':'))
+            }
+            None => match authority.split_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (authority, None),
+            },
+        };
+        let forward_port = match (port, forward_scheme) {
+            (Some(port), _) => port.parse().ok()?,
+            (None, ForwardScheme::Http) => 80,
+            (None, ForwardScheme::Https) => 443,
+        };
+
+        Some(ProxyHostConf {
+            domain_names: server.server_name.clone(),
+            forward_scheme,
+            forward_host: forward_host.to_string(),
+            forward_port,
+            upstream: None,
+            acme_challenge_pass: None,
+            access: None,
+            ssl: None,
+            block_exploits: false,
+            cache_assets: false,
+            allow_websocket_upgrade: false,
+            advanced: Vec::new(),
+            rate_limit: None,
+            connection_limit: None,
+            locations: Vec::new(),
+        })
+    }
+}
+
+fn forward_url(scheme: ForwardScheme, host: &str, port: u16) -> String {
+    if host.contains(':') {
+        format!("{scheme}://[{host}]:{port}")
+    } else {
+        format!("{scheme}://{host}:{port}")
+    }
+}
+
+fn default_proxy_headers() -> Vec<Header> {
+    vec![
+        Header::new("Host", "$host"),
+        Header::new("X-Real-IP", "$remote_addr"),
+        Header::new("X-Forwarded-For", "$proxy_add_x_forwarded_for"),
+        Header::new("X-Forwarded-Proto", "$scheme"),
+    ]
+}
+
+impl Render for ProxyHostConf {
+    fn to_nodes(&self) -> Vec<Node> {
+        let mut nodes = vec![Node::Comment(format!(
+            " Proxy host: {}",
+            self.domain_names.join(", ")
+        ))];
+        if let Some(redirect) = self.redirect_server() {
+            nodes.extend(redirect.to_nodes());
+            nodes.push(Node::Blank);
+        }
+        nodes.extend(self.server().to_nodes());
+        nodes
+    }
+}
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+    use crate::host::{AccessRule, AuthBasic, Http2Syntax, Satisfy};
+
+    #[test]
+    fn test_render_proxy_host_golden_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_golden = include_str!("../../golden/proxy_host.conf");
+        let fx_host = ProxyHostConf {
+            domain_names: vec!["example.com".into(), "www.example.com".into()],
+            forward_scheme: ForwardScheme::Http,
+            forward_host: "10.0.0.5".into(),
+            forward_port: 3000,
+            upstream: None,
+            acme_challenge_pass: None,
+            access: None,
+            ssl: None,
+            block_exploits: false,
+            cache_assets: false,
+            allow_websocket_upgrade: false,
+            advanced: Vec::new(),
+            rate_limit: None,
+            connection_limit: None,
+            locations: Vec::new(),
+        };
+
+        // -- Exec
+        let res = fx_host.render();
+
+        // -- Check
+        assert_eq!(res, fx_golden);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_server_golden_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_golden = include_str!("../../golden/proxy_host.conf");
+
+        // -- Exec
+        let servers = crate::parse_servers(fx_golden)?;
+        let res = ProxyHostConf::from_server(&servers[0])
+            .ok_or("Should import the proxy host")?;
+
+        // -- Check
+        assert_eq!(res.domain_names, ["example.com", "www.example.com"]);
+        assert_eq!(res.forward_url(), "http://10.0.0.5:3000");
+        assert_eq!(res.render(), fx_golden);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_proxy_host_access_golden_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_golden = include_str!("../../golden/proxy_host_access.conf");
+        let fx_host = ProxyHostConf {
+            domain_names: vec!["intranet.example.com".into()],
+            forward_scheme: ForwardScheme::Http,
+            forward_host: "10.0.0.7".into(),
+            forward_port: 8080,
+            upstream: None,
+            acme_challenge_pass: Some("http://127.0.0.1:8080".into()),
+            access: Some(AccessConf {
+                satisfy: Satisfy::Any,
+                rules: vec![AccessRule::Allow("10.0.0.0/8".into())],
+                auth_basic: Some(AuthBasic {
+                    realm: "Intranet".into(),
+                    user_file: "/etc/nginx/access/access_list_1.htpasswd"
+                        .into(),
+                }),
+            }),
+            ssl: None,
+            block_exploits: false,
+            cache_assets: false,
+            allow_websocket_upgrade: false,
+            advanced: Vec::new(),
+            rate_limit: None,
+            connection_limit: None,
+            locations: Vec::new(),
+        };
+
+        // -- Exec
+        let res = fx_host.render();
+
+        // -- Check
+        assert_eq!(res, fx_golden);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_proxy_host_locations_golden_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_golden = include_str!("../../golden/proxy_host_locations.conf");
+        let fx_host = ProxyHostConf {
+            domain_names: vec!["app.example.com".into()],
+            forward_scheme: ForwardScheme::Http,
+            forward_host: "10.0.0.5".into(),
+            forward_port: 3000,
+            upstream: None,
+            acme_challenge_pass: None,
+            access: None,
+            ssl: None,
+            block_exploits: false,
+            cache_assets: false,
+            allow_websocket_upgrade: false,
+            advanced: Vec::new(),
+            rate_limit: None,
+            connection_limit: None,
+            locations: vec![
+                ProxyLocationConf {
+                    path: "/api/".into(),
+                    forward_scheme: ForwardScheme::Https,
+                    forward_host: "fd00::6".into(),
+                    forward_port: 8443,
+                    headers: vec![
+                        Header::new("host", "api.internal"),
+                        Header::new("X-Api-Key", "s3cret"),
+                    ],
+                    advanced: crate::parse("proxy_read_timeout 300s;")?,
+                },
+                ProxyLocationConf {
+                    path: "/static/".into(),
+                    forward_scheme: ForwardScheme::Http,
+                    forward_host: "10.0.0.9".into(),
+                    forward_port: 80,
+                    headers: Vec::new(),
+                    advanced: Vec::new(),
+                },
+            ],
+        };
+
+        // -- Exec
+        let res = fx_host.render();
+
+        // -- Check
+        assert_eq!(res, fx_golden);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_proxy_host_ssl_forced_golden_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_golden = include_str!("../../golden/proxy_host_ssl.conf");
+        let fx_host = ProxyHostConf {
+            domain_names: vec!["secure.example.com".into()],
+            forward_scheme: ForwardScheme::Http,
+            forward_host: "10.0.0.5".into(),
+            forward_port: 3000,
+            upstream: None,
+            acme_challenge_pass: Some("http://127.0.0.1:8080".into()),
+            access: None,
+            ssl: Some(SslConf {
+                certificate: "/etc/nginx/certs/certificate_1.pem".into(),
+                certificate_key: "/etc/nginx/certs/certificate_1.key".into(),
+                forced: true,
+                http2: true,
+                http2_syntax: Http2Syntax::ListenParam,
+                hsts: true,
+                hsts_subdomains: true,
+            }),
+            block_exploits: false,
+            cache_assets: false,
+            allow_websocket_upgrade: false,
+            advanced: Vec::new(),
+            rate_limit: None,
+            connection_limit: None,
+            locations: Vec::new(),
+        };
+
+        // -- Exec
+        let res = fx_host.render();
+
+        // -- Check
+        assert_eq!(res, fx_golden);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_proxy_host_ssl_http2_directive_golden_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_golden =
+            include_str!("../../golden/proxy_host_ssl_http2_directive.conf");
+        let fx_host = ProxyHostConf {
+            domain_names: vec!["secure.example.com".into()],
+            forward_scheme: ForwardScheme::Http,
+            forward_host: "10.0.0.5".into(),
+            forward_port: 3000,
+            upstream: None,
+            acme_challenge_pass: Some("http://127.0.0.1:8080".into()),
+            access: None,
+            ssl: Some(SslConf {
+                certificate: "certs/certificate_1.pem".into(),
+                certificate_key: "certs/certificate_1.key".into(),
+                forced: false,
+                http2: true,
+                http2_syntax: Http2Syntax::Directive,
+                hsts: false,
+                hsts_subdomains: false,
+            }),
+            block_exploits: false,
+            cache_assets: false,
+            allow_websocket_upgrade: false,
+            advanced: Vec::new(),
+            rate_limit: None,
+            connection_limit: None,
+            locations: Vec::new(),
+        };
+
+        // -- Exec
+        let res = fx_host.render();
+
+        // -- Check
+        assert_eq!(res, fx_golden);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_proxy_host_ssl_not_forced_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_host = ProxyHostConf {
+            domain_names: vec!["example.com".into()],
+            forward_scheme: ForwardScheme::Http,
+            forward_host: "10.0.0.5".into(),
+            forward_port: 3000,
+            upstream: None,
+            acme_challenge_pass: None,
+            access: None,
+            ssl: Some(SslConf {
+                certificate: "/etc/nginx/certs/certificate_2.pem".into(),
+                certificate_key: "/etc/nginx/certs/certificate_2.key".into(),
+                forced: false,
+                http2: false,
+                http2_syntax: Http2Syntax::ListenParam,
+                hsts: true,
+                hsts_subdomains: false,
+            }),
+            block_exploits: false,
+            cache_assets: false,
+            allow_websocket_upgrade: false,
+            advanced: Vec::new(),
+            rate_limit: None,
+            connection_limit: None,
+            locations: Vec::new(),
+        };
+
+        // -- Exec
+        let res = fx_host.render();
+
+        // -- Check
+        assert!(fx_host.redirect_server().is_none());
+        assert_eq!(res.matches("server {").count(), 1);
+        assert!(res.contains(
+            "    listen 80;
+    listen [::]:80;
+    listen 443 ssl;
+    listen [::]:443 ssl;
+"
+        ));
+        assert!(res.contains(
+            "    ssl_certificate /etc/nginx/certs/certificate_2.pem;
+    ssl_certificate_key /etc/nginx/certs/certificate_2.key;
+    add_header Strict-Transport-Security max-age=63072000 always;
+"
+        ));
+        assert!(!res.contains("return 301"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_proxy_host_snippets_golden_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_golden = include_str!("../../golden/proxy_host_snippets.conf");
+        let fx_host = ProxyHostConf {
+            domain_names: vec!["shop.example.com".into()],
+            forward_scheme: ForwardScheme::Http,
+            forward_host: "10.0.0.8".into(),
+            forward_port: 8000,
+            upstream: None,
+            acme_challenge_pass: None,
+            access: None,
+            ssl: None,
+            block_exploits: true,
+            cache_assets: true,
+            allow_websocket_upgrade: false,
+            advanced: Vec::new(),
+            rate_limit: None,
+            connection_limit: None,
+            locations: Vec::new(),
+        };
+
+        // -- Exec
+        let res = fx_host.render();
+
+        // -- Check
+        assert_eq!(res, fx_golden);
+        let [server] = crate::parse_servers(&res)?
+            .try_into()
+            .map_err(|_| "Should have one server")?;
+        assert_eq!(server.render(), fx_host.server().render());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_proxy_host_cache_locations_golden_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_golden =
+            include_str!("../../golden/proxy_host_cache_locations.conf");
+        let fx_host = ProxyHostConf {
+            domain_names: vec!["shop.example.com".into()],
+            forward_scheme: ForwardScheme::Http,
+            forward_host: "10.0.0.8".into(),
+            forward_port: 8000,
+            upstream: None,
+            acme_challenge_pass: None,
+            access: None,
+            ssl: None,
+            block_exploits: false,
+            cache_assets: true,
+            allow_websocket_upgrade: false,
+            advanced: Vec::new(),
+            rate_limit: None,
+            connection_limit: None,
+            locations: vec![ProxyLocationConf {
+                path: "/api/".into(),
+                forward_scheme: ForwardScheme::Http,
+                forward_host: "10.0.0.9".into(),
+                forward_port: 9000,
+                headers: Vec::new(),
+                advanced: Vec::new(),
+            }],
+        };
+
+        // -- Exec
+        let res = fx_host.render();
+
+        // -- Check
+        assert_eq!(res, fx_golden);
+        // The assets of `/api/` stay on its upstream.
+        let server = &crate::parse_servers(&res)?[0];
+        assert_eq!(server.render(), fx_host.server().render());
+        assert!(
+            server
+                .locations
+                .iter()
+                .all(|location| location.modifier.is_none())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_proxy_host_websocket_golden_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_golden = include_str!("../../golden/proxy_host_websocket.conf");
+        let fx_host = ProxyHostConf {
+            domain_names: vec!["dashboard.example.com".into()],
+            forward_scheme: ForwardScheme::Http,
+            forward_host: "10.0.0.5".into(),
+            forward_port: 3000,
+            upstream: None,
+            acme_challenge_pass: Some("http://127.0.0.1:8080".into()),
+            access: None,
+            ssl: None,
+            block_exploits: false,
+            cache_assets: false,
+            allow_websocket_upgrade: true,
+            advanced: Vec::new(),
+            rate_limit: None,
+            connection_limit: None,
+            locations: vec![ProxyLocationConf {
+                path: "/live/".into(),
+                forward_scheme: ForwardScheme::Http,
+                forward_host: "10.0.0.6".into(),
+                forward_port: 4000,
+                headers: vec![Header::new("connection", "keep-alive")],
+                advanced: crate::parse("proxy_read_timeout 1h;")?,
+            }],
+        };
+
+        // -- Exec
+        let res = fx_host.render();
+
+        // -- Check
+        assert_eq!(res, fx_golden);
+        let [server] = crate::parse_servers(&res)?
+            .try_into()
+            .map_err(|_| "Should have one server")?;
+        assert_eq!(server.render(), fx_host.server().render());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_proxy_host_advanced_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_host = ProxyHostConf {
+            domain_names: vec!["example.com".into()],
+            forward_scheme: ForwardScheme::Http,
+            forward_host: "10.0.0.5".into(),
+            forward_port: 3000,
+            upstream: None,
+            acme_challenge_pass: None,
+            access: None,
+            ssl: None,
+            block_exploits: true,
+            cache_assets: false,
+            allow_websocket_upgrade: false,
+            advanced: crate::parse(
+                "client_max_body_size 50m;\nlocation = /robots.txt {\n    return 200;\n}\n",
+            )?,
+            rate_limit: None,
+            connection_limit: None,
+            locations: Vec::new(),
+        };
+
+        // -- Exec
+        let res = fx_host.render();
+
+        // -- Check
+        assert!(res.contains(
+            "        return 403;
+    }
+
+    client_max_body_size 50m;
+    location = /robots.txt {
+        return 200;
+    }
+
+    location / {
+"
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_forward_url_ipv6_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_host = ProxyHostConf {
+            domain_names: vec!["example.com".into()],
+            forward_scheme: ForwardScheme::Https,
+            forward_host: "fd00::5".into(),
+            forward_port: 8443,
+            upstream: None,
+            acme_challenge_pass: None,
+            access: None,
+            ssl: None,
+            block_exploits: false,
+            cache_assets: false,
+            allow_websocket_upgrade: false,
+            advanced: Vec::new(),
+            rate_limit: None,
+            connection_limit: None,
+            locations: Vec::new(),
+        };
+
+        // -- Exec
+        let res = fx_host.forward_url();
+
+        // -- Check
+        assert_eq!(res, "https://[fd00::5]:8443");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_forward_url_upstream_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_host = ProxyHostConf {
+            domain_names: vec!["example.com".into()],
+            forward_scheme: ForwardScheme::Http,
+            forward_host: String::new(),
+            forward_port: 80,
+            upstream: Some("upstream_group_1".into()),
+            acme_challenge_pass: None,
+            access: None,
+            ssl: None,
+            block_exploits: false,
+            cache_assets: false,
+            allow_websocket_upgrade: false,
+            advanced: Vec::new(),
+            rate_limit: None,
+            connection_limit: None,
+            locations: Vec::new(),
+        };
+
+        // -- Exec
+        let res = fx_host.forward_url();
+
+        // -- Check
+        assert_eq!(res, "http://upstream_group_1");
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -0,0 +1,168 @@
+use crate::context::{Listen, Location, Server};
+use crate::host::acme_challenge_location;
+use crate::node::Node;
+use crate::render::Render;
+use crate::value::Return;
+use serde::Serialize;
+
+/// A redirection host as edited from the `/redirection` page.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RedirectionHostConf {
+    pub domain_names: Vec<String>,
+    /// Target of the redirection, e.g. `https://www.example.com`.
+    pub forward_url: String,
+    /// One of 301, 302, 307 or 308.
+    pub redirect_code: u16,
+    /// Append the request path and query (`$request_uri`) to the target.
+    pub preserve_path: bool,
+    /// Url the `/.well-known/acme-challenge/` requests are proxied to,
+    /// e.g. the web-server answering the http-01 challenges.
+    pub acme_challenge_pass: Option<String>,
+}
+
+impl RedirectionHostConf {
+    /// The url of the `return` directive.
+    pub fn target(&self) -> String {
+        if self.preserve_path {
+            let base = self.forward_url.trim_end_matches('/');
+            format!("{base}$request_uri")
+        } else {
+            self.forward_url.clone()
+        }
+    }
+
+    pub fn server(&self) -> Server {
+        let r#return = Return {
+            code: self.redirect_code,
+            text: Some(self.target()),
+        };
+        let mut server = Server {
+            listen: vec![Listen::port(80), Listen::ipv6(80)],
+            server_name: self.domain_names.clone(),
+            ..Default::default()
+        };
+
+        // A server level `return` would also answer the challenges.
+        match &self.acme_challenge_pass {
+            Some(pass) => {
+                server.locations = vec![
+                    acme_challenge_location(pass),
+                    Location {
+                        r#return: Some(r#return),
+                        ..Location::new("/")
+                    },
+                ];
+            }
+            None => server.r#return = Some(r#return),
+        }
+
+        server
+    }
+}
+
+impl Render for RedirectionHostConf {
+    fn to_nodes(&self) -> Vec<Node> {
+        let mut nodes = vec![Node::Comment(format!(
+            " Redirection host: {}",
+            self.domain_names.join(", ")
+        ))];
+        nodes.extend(self.server().to_nodes());
+        nodes
+    }
+}
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+
+    #[test]
+    fn test_render_redirection_host_golden_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_golden = include_str!("../../golden/redirection_host.conf");
+        let fx_host = RedirectionHostConf {
+            domain_names: vec!["old.example.com".into()],
+            forward_url: "https://www.example.com/".into(),
+            redirect_code: 301,
+            preserve_path: true,
+            acme_challenge_pass: None,
+        };
+
+        // -- Exec
+        let res = fx_host.render();
+
+        // -- Check
+        assert_eq!(res, fx_golden);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_target_without_path_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_host = RedirectionHostConf {
+            domain_names: vec!["old.example.com".into()],
+            forward_url: "https://www.example.com/landing?from=old".into(),
+            redirect_code: 302,
+            preserve_path: false,
+            acme_challenge_pass: None,
+        };
+
+        // -- Exec
+        let server = fx_host.server();
+
+        // -- Check
+        assert_eq!(
+            server.r#return,
+            Some(Return {
+                code: 302,
+                text: Some("https://www.example.com/landing?from=old".into()),
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_acme_challenge_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_host = RedirectionHostConf {
+            domain_names: vec!["old.example.com".into()],
+            forward_url: "https://www.example.com".into(),
+            redirect_code: 301,
+            preserve_path: true,
+            acme_challenge_pass: Some("http://127.0.0.1:8080".into()),
+        };
+
+        // -- Exec
+        let res = fx_host.render();
+
+        // -- Check
+        assert_eq!(
+            res,
+            "# Redirection host: old.example.com
+server {
+    listen 80;
+    listen [::]:80;
+    server_name old.example.com;
+
+    location ^~ /.well-known/acme-challenge/ {
+        proxy_pass http://127.0.0.1:8080;
+    }
+
+    location / {
+        return 301 https://www.example.com$request_uri;
+    }
+}
+"
+        );
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
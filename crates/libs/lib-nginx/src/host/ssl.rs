@@ -0,0 +1,183 @@
+use crate::context::{Listen, Location, Server};
+use crate::host::acme_challenge_location;
+use crate::node::{Directive, Node};
+use crate::value::Return;
+use serde::Serialize;
+
+/// `max-age` of the `Strict-Transport-Security` header, two years.
+const HSTS_MAX_AGE: u32 = 63072000;
+
+/// First nginx version with the `http2` directive.
+const HTTP2_DIRECTIVE_SINCE: (u32, u32, u32) = (1, 25, 1);
+
+/// TLS of a host: the `443` listeners, its certificate and the HSTS
+/// header.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SslConf {
+    /// Path of the certificate, leaf first, then the chain. A relative
+    /// path is resolved by nginx from the directory of `nginx.conf`.
+    pub certificate: String,
+    /// Path of the private key, as `certificate`.
+    pub certificate_key: String,
+    /// Redirect http to https, from a `server` of its own.
+    pub forced: bool,
+    pub http2: bool,
+    pub http2_syntax: Http2Syntax,
+    pub hsts: bool,
+    /// Adds `includeSubDomains` to the HSTS header.
+    pub hsts_subdomains: bool,
+}
+
+/// How http/2 is turned on. nginx 1.25.1 deprecates the `http2`
+/// parameter of `listen` for the `http2` directive, which the older
+/// versions don't know.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub enum Http2Syntax {
+    /// `listen 443 ssl http2;`
+    #[default]
+    ListenParam,
+    /// `listen 443 ssl;` and `http2 on;`
+    Directive,
+}
+
+impl Http2Syntax {
+    /// The syntax of the nginx printing `version` with `nginx -v`, e.g.
+    /// `nginx version: nginx/1.25.3`. `ListenParam` when it can't be read.
+    pub fn for_nginx_version(version: &str) -> Self {
+        let parsed = version
+            .split_once("nginx/")
+            .and_then(|(_, rest)| rest.split_whitespace().next())
+            .map(|number| {
+                number
+                    .split('.')
+                    .map(str::parse::<u32>)
+                    .collect::<std::result::Result<Vec<_>, _>>()
+            });
+
+        match parsed {
+            Some(Ok(parts)) if parts.len() == 3 => {
+                if (parts[0], parts[1], parts[2]) >= HTTP2_DIRECTIVE_SINCE {
+                    Http2Syntax::Directive
+                } else {
+                    Http2Syntax::ListenParam
+                }
+            }
+            _ => Http2Syntax::ListenParam,
+        }
+    }
+}
+
+impl SslConf {
+    /// `listen 443 ssl [http2]`, on IPv4 and IPv6.
+    pub fn listen(&self) -> Vec<Listen> {
+        [Listen::port(443), Listen::ipv6(443)]
+            .into_iter()
+            .map(|listen| Listen {
+                ssl: true,
+                http2: self.http2
+                    && self.http2_syntax == Http2Syntax::ListenParam,
+                ..listen
+            })
+            .collect()
+    }
+
+    /// Directives of the `server` block.
+    pub fn to_directives(&self) -> Vec<Node> {
+        let mut nodes = Vec::new();
+        if self.http2 && self.http2_syntax == Http2Syntax::Directive {
+            nodes.push(Directive::new("http2").arg("on").into());
+        }
+        nodes.extend([
+            Directive::new("ssl_certificate")
+                .arg(&self.certificate)
+                .into(),
+            Directive::new("ssl_certificate_key")
+                .arg(&self.certificate_key)
+                .into(),
+        ]);
+
+        if self.hsts {
+            let mut value = format!("max-age={HSTS_MAX_AGE}");
+            if self.hsts_subdomains {
+                value.push_str("; includeSubDomains");
+            }
+            nodes.push(
+                Directive::new("add_header")
+                    .arg("Strict-Transport-Security")
+                    .arg(value)
+                    .arg("always")
+                    .into(),
+            );
+        }
+
+        nodes
+    }
+
+    /// The plain http `server` of a forced host, redirecting everything
+    /// but the acme challenges.
+    pub(crate) fn redirect_server(
+        domain_names: &[String],
+        acme_challenge_pass: Option<&str>,
+    ) -> Server {
+        let mut locations: Vec<Location> = acme_challenge_pass
+            .map(acme_challenge_location)
+            .into_iter()
+            .collect();
+        locations.push(Location {
+            r#return: Some(Return {
+                code: 301,
+                text: Some("https://$host$request_uri".to_string()),
+            }),
+            ..Location::new("/")
+        });
+
+        Server {
+            listen: vec![Listen::port(80), Listen::ipv6(80)],
+            server_name: domain_names.to_vec(),
+            locations,
+            ..Default::default()
+        }
+    }
+}
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+
+    #[test]
+    fn test_http2_syntax_for_nginx_version_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_cases = [
+            ("nginx version: nginx/1.24.0", Http2Syntax::ListenParam),
+            ("nginx version: nginx/1.25.0", Http2Syntax::ListenParam),
+            ("nginx version: nginx/1.25.1", Http2Syntax::Directive),
+            (
+                "nginx version: nginx/1.27.4 (Ubuntu)",
+                Http2Syntax::Directive,
+            ),
+            (
+                "nginx version: openresty/1.21.4.1",
+                Http2Syntax::ListenParam,
+            ),
+            ("nginx: stub ok -v", Http2Syntax::ListenParam),
+        ];
+
+        // -- Exec & Check
+        for (version, syntax) in fx_cases {
+            assert_eq!(
+                Http2Syntax::for_nginx_version(version),
+                syntax,
+                "{version}"
+            );
+        }
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -0,0 +1,141 @@
+use crate::context::{Listen, StreamServer};
+use crate::node::Node;
+use crate::render::Render;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A stream host as edited from the `/stream` page, rendered as a
+/// `server` of the `stream` context.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StreamHostConf {
+    pub incoming_port: u16,
+    pub protocol: StreamProtocol,
+    pub forward_host: String,
+    pub forward_port: u16,
+}
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum StreamProtocol {
+    #[default]
+    Tcp,
+    Udp,
+    /// TCP and UDP on the same port.
+    Both,
+}
+
+impl fmt::Display for StreamProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StreamProtocol::Tcp => f.write_str("tcp"),
+            StreamProtocol::Udp => f.write_str("udp"),
+            StreamProtocol::Both => f.write_str("both"),
+        }
+    }
+}
+
+impl StreamHostConf {
+    /// `host:port`, with IPv6 hosts in brackets.
+    pub fn forward_address(&self) -> String {
+        if self.forward_host.contains(':') {
+            format!("[{}]:{}", self.forward_host, self.forward_port)
+        } else {
+            format!("{}:{}", self.forward_host, self.forward_port)
+        }
+    }
+
+    pub fn server(&self) -> StreamServer {
+        let udp_flags: &[bool] = match self.protocol {
+            StreamProtocol::Tcp => &[false],
+            StreamProtocol::Udp => &[true],
+            StreamProtocol::Both => &[false, true],
+        };
+
+        let listen = udp_flags
+            .iter()
+            .flat_map(|&udp| {
+                [
+                    Listen {
+                        udp,
+                        ..Listen::port(self.incoming_port)
+                    },
+                    Listen {
+                        udp,
+                        ..Listen::ipv6(self.incoming_port)
+                    },
+                ]
+            })
+            .collect();
+
+        StreamServer {
+            listen,
+            proxy_pass: Some(self.forward_address()),
+            ..Default::default()
+        }
+    }
+}
+
+impl Render for StreamHostConf {
+    fn to_nodes(&self) -> Vec<Node> {
+        let mut nodes = vec![Node::Comment(format!(
+            " Stream host: {} {}",
+            self.protocol, self.incoming_port
+        ))];
+        nodes.extend(self.server().to_nodes());
+        nodes
+    }
+}
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+
+    #[test]
+    fn test_render_stream_host_golden_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_golden = include_str!("../../golden/stream_host.conf");
+        let fx_host = StreamHostConf {
+            incoming_port: 27015,
+            protocol: StreamProtocol::Both,
+            forward_host: "10.0.0.7".into(),
+            forward_port: 27015,
+        };
+
+        // -- Exec
+        let res = fx_host.render();
+
+        // -- Check
+        assert_eq!(res, fx_golden);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_forward_address_ipv6_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_host = StreamHostConf {
+            incoming_port: 5432,
+            protocol: StreamProtocol::Tcp,
+            forward_host: "fd00::5".into(),
+            forward_port: 5432,
+        };
+
+        // -- Exec
+        let server = fx_host.server();
+
+        // -- Check
+        assert_eq!(server.proxy_pass.as_deref(), Some("[fd00::5]:5432"));
+        assert_eq!(server.listen.len(), 2);
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
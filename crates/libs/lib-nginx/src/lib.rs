@@ -1,32 +1,49 @@
-#![allow(unused)]
+//! Typed model and renderer for nginx configuration files.
+//!
+//! - `context` holds the typed contexts (`NginxConfig`, `Http`, `Server`...).
+//! - `host` builds the per-host `.conf` files from what the UI edits.
+//! - `snippet` holds the versioned directives the hosts can include.
+//! - `node` is the untyped tree every typed structure is lowered to.
+//! - `render` writes a tree of nodes as deterministic nginx text.
+//! - `parser` reads existing nginx files back into nodes and typed contexts.
 
-use serde::Serialize;
+// region:    --- Modules
 
-/// https://nginx.org/en/docs/ngx_core_module.html#pcre_jit
-#[derive(Debug, Clone, Default, Serialize)]
-#[serde(rename_all = "lowercase")]
-enum OnOff {
-    #[default]
-    Off,
-    On,
-}
+mod context;
+mod error;
+mod host;
+mod node;
+mod parser;
+mod render;
+mod snippet;
+mod value;
 
-/// https://nginx.org/en/docs/ngx_core_module.html#worker_processes
-#[derive(Debug, Clone, Default, Serialize)]
-enum WorkerProcesses {
-    #[default]
-    #[serde(rename = "auto")]
-    Auto,
-    U8(u8),
-}
+pub use error::{Error, Result};
 
-struct Events {
-    /// https://nginx.org/en/docs/ngx_core_module.html#worker_connections
-    worker_connections: u32,
-}
+pub use context::{
+    Events, Http, Layout, Listen, Location, LocationModifier, NginxConfig,
+    Server, Stream, StreamServer, Upstream, UpstreamBalance, UpstreamServer,
+};
+pub use host::{
+    AccessConf, AccessRule, AuthBasic, ConnectionLimit, DeadHostConf,
+    ErrorPage, ForwardScheme, Http2Syntax, LimitZones, ProxyHostConf,
+    ProxyLocationConf, RateLimit, RedirectionHostConf, Satisfy, SslConf,
+    StreamHostConf, StreamProtocol,
+};
+pub use node::{Directive, Node, quote, unquote};
+pub use parser::{
+    SnippetContext, append_to_block, parse, parse_config, parse_servers,
+    parse_snippet,
+};
+pub use render::{Render, render_nodes};
+pub use snippet::{
+    BLOCK_EXPLOITS_RULES, BLOCK_EXPLOITS_VERSION, CACHE_ASSETS_VERSION,
+    CacheZone, ConnectionUpgradeMap, WEBSOCKET_VERSION, block_exploits,
+    cache_assets_location, websocket_upgrade,
+};
+pub use value::{
+    ErrorLog, FailToParse, Header, KeepaliveTimeout, LogLevel, OnOff, Return,
+    Rewrite, RewriteFlag, Size, Time, WorkerProcesses,
+};
 
-struct NginxConfig {
-    worker_processes: WorkerProcesses,
-    pcre_jit: OnOff,
-    events: Events,
-}
+// endregion: --- Modules
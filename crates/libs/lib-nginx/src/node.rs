@@ -0,0 +1,235 @@
+//! Untyped nginx configuration tree.
+//!
+//! Every typed structure of this crate is lowered to a list of `Node`s
+//! before being written out, so the render step only has to know about
+//! directives, blocks, comments and blank lines.
+
+use crate::error::{Error, Result};
+use serde::Serialize;
+use std::str::FromStr;
+
+// region:    --- Node
+
+/// One line (or block) of an nginx configuration file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum Node {
+    Directive(Directive),
+    /// A `#` comment, stored without the leading `#`.
+    Comment(String),
+    Blank,
+}
+
+impl From<Directive> for Node {
+    fn from(value: Directive) -> Self {
+        Node::Directive(value)
+    }
+}
+
+// endregion: --- Node
+
+// region:    --- Directive
+
+/// A simple (`name args;`) or block (`name args { ... }`) directive.
+///
+/// Arguments are stored as nginx tokens, i.e. already quoted when the
+/// value needs it. Use `Directive::arg` to push a plain value and
+/// `Directive::raw_arg` when the token is already in nginx syntax.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Directive {
+    pub name: String,
+    pub args: Vec<String>,
+    pub block: Option<Vec<Node>>,
+    /// Comment at the end of the line, after `;` or `}`.
+    pub comment: Option<String>,
+    /// Comment on the line of the `{` of a block.
+    pub open_comment: Option<String>,
+}
+
+// Constructors.
+impl Directive {
+    pub fn new(name: impl Into<String>) -> Self {
+        Directive {
+            name: name.into(),
+            args: Vec::new(),
+            block: None,
+            comment: None,
+            open_comment: None,
+        }
+    }
+
+    pub fn new_block(name: impl Into<String>, children: Vec<Node>) -> Self {
+        Directive {
+            name: name.into(),
+            args: Vec::new(),
+            block: Some(children),
+            comment: None,
+            open_comment: None,
+        }
+    }
+
+    /// Push a plain value, quoting it if nginx would not read it as a
+    /// single token.
+    pub fn arg(mut self, value: impl AsRef<str>) -> Self {
+        self.args.push(quote(value.as_ref()));
+        self
+    }
+
+    /// Push a token as is (e.g. `$host`, `~*`, `'$remote_addr - ...'`).
+    pub fn raw_arg(mut self, token: impl Into<String>) -> Self {
+        self.args.push(token.into());
+        self
+    }
+
+    pub fn args<I, S>(mut self, values: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.args
+            .extend(values.into_iter().map(|v| quote(v.as_ref())));
+        self
+    }
+}
+
+// Property Accessors.
+impl Directive {
+    pub fn is_block(&self) -> bool {
+        self.block.is_some()
+    }
+
+    /// The arguments with their quotes removed.
+    pub fn values(&self) -> impl Iterator<Item = String> + '_ {
+        self.args.iter().map(|a| unquote(a))
+    }
+
+    pub fn children(&self) -> &[Node] {
+        self.block.as_deref().unwrap_or_default()
+    }
+}
+
+// Typed Accessors.
+impl Directive {
+    /// The children of a block directive, `ExpectedBlock` otherwise.
+    pub(crate) fn block_children(&self) -> Result<&[Node]> {
+        self.block.as_deref().ok_or_else(|| Error::ExpectedBlock {
+            directive: self.name.clone(),
+        })
+    }
+
+    /// The only argument of the directive, unquoted.
+    pub(crate) fn single_value(&self) -> Result<String> {
+        match self.args.as_slice() {
+            [arg] if self.block.is_none() => Ok(unquote(arg)),
+            _ => Err(self.invalid_args()),
+        }
+    }
+
+    pub(crate) fn parse_single<T: FromStr>(&self) -> Result<T> {
+        self.single_value()?
+            .parse()
+            .map_err(|_| self.invalid_args())
+    }
+
+    pub(crate) fn invalid_args(&self) -> Error {
+        Error::InvalidArgs {
+            directive: self.name.clone(),
+        }
+    }
+}
+
+// endregion: --- Directive
+
+/// Append `section` to `nodes`, separated by a blank line when both are
+/// non empty.
+pub(crate) fn push_section(nodes: &mut Vec<Node>, section: Vec<Node>) {
+    if section.is_empty() {
+        return;
+    }
+    if !nodes.is_empty() {
+        nodes.push(Node::Blank);
+    }
+    nodes.extend(section);
+}
+
+// region:    --- Quoting
+
+/// Returns `value` as a single nginx token, wrapping it in double quotes
+/// when it is empty or contains whitespace, `;`, `{`, `}`, `#` or quotes.
+pub fn quote(value: &str) -> String {
+    let needs_quotes = value.is_empty()
+        || value.chars().any(|c| {
+            c.is_whitespace() || matches!(c, ';' | '{' | '}' | '#' | '"' | '\'')
+        });
+
+    if !needs_quotes {
+        return value.to_string();
+    }
+
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        if matches!(c, '"' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Inverse of `quote`, also accepting single quoted tokens.
+pub fn unquote(token: &str) -> String {
+    let quote_char = match token.chars().next() {
+        Some(c @ ('"' | '\'')) if token.len() >= 2 && token.ends_with(c) => c,
+        _ => return token.to_string(),
+    };
+
+    let inner = &token[1..token.len() - 1];
+    let mut value = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some(next)) if next == quote_char || next == '\\' => {
+                value.push(next);
+                chars.next();
+            }
+            _ => value.push(c),
+        }
+    }
+    value
+}
+
+// endregion: --- Quoting
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+
+    #[test]
+    fn test_quote_roundtrip_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_values = [
+            ("plain", "plain"),
+            ("", r#""""#),
+            ("two words", r#""two words""#),
+            (r#"say "hi""#, r#""say \"hi\"""#),
+            (r"back\slash;", r#""back\\slash;""#),
+        ];
+
+        // -- Exec & Check
+        for (value, token) in fx_values {
+            assert_eq!(quote(value), token);
+            assert_eq!(unquote(token), value);
+        }
+        assert_eq!(unquote("'single quoted'"), "single quoted");
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -0,0 +1,165 @@
+use crate::error::{Error, Result};
+use std::iter::Peekable;
+use std::str::Chars;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum Token {
+    /// A word or a quoted string, quotes included.
+    Word(String),
+    Semicolon,
+    OpenBrace,
+    CloseBrace,
+    /// Comment text without the leading `#`.
+    Comment(String),
+    Newline,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Spanned {
+    pub token: Token,
+    pub line: usize,
+    pub col: usize,
+    /// Byte offset of the token in the input.
+    pub offset: usize,
+}
+
+pub(super) struct Lexer<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+    col: usize,
+    offset: usize,
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Lexer {
+            chars: input.chars().peekable(),
+            line: 1,
+            col: 1,
+            offset: 0,
+        }
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        self.offset += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+            self.col = 1;
+        } else {
+            self.col += 1;
+        }
+        Some(c)
+    }
+
+    pub fn position(&self) -> (usize, usize) {
+        (self.line, self.col)
+    }
+
+    pub fn next_token(&mut self) -> Result<Option<Spanned>> {
+        // -- Skip whitespace, but not newlines.
+        while let Some(c) = self.chars.peek() {
+            if *c != '\n' && c.is_whitespace() {
+                self.bump();
+            } else {
+                break;
+            }
+        }
+
+        let (line, col, offset) = (self.line, self.col, self.offset);
+        let Some(c) = self.bump() else {
+            return Ok(None);
+        };
+
+        let token = match c {
+            '\n' => Token::Newline,
+            ';' => Token::Semicolon,
+            '{' => Token::OpenBrace,
+            '}' => Token::CloseBrace,
+            '#' => {
+                let mut comment = String::new();
+                while let Some(c) = self.chars.peek() {
+                    if *c == '\n' {
+                        break;
+                    }
+                    comment.push(*c);
+                    self.bump();
+                }
+                Token::Comment(comment.trim_end().to_string())
+            }
+            '"' | '\'' => {
+                let mut word = self.quoted(c, line, col)?;
+                // e.g. `("a b")`, the quoted part is only a piece of the word.
+                if let Some(&next) = self.chars.peek()
+                    && !next.is_whitespace()
+                    && !matches!(next, ';' | '{' | '}')
+                {
+                    self.bump();
+                    word.push_str(&self.word(next));
+                }
+                Token::Word(word)
+            }
+            c => Token::Word(self.word(c)),
+        };
+
+        Ok(Some(Spanned {
+            token,
+            line,
+            col,
+            offset,
+        }))
+    }
+
+    fn quoted(
+        &mut self,
+        quote: char,
+        line: usize,
+        col: usize,
+    ) -> Result<String> {
+        let mut word = String::from(quote);
+        loop {
+            match self.bump() {
+                None => return Err(Error::UnterminatedQuote { line, col }),
+                Some('\\') => {
+                    word.push('\\');
+                    if let Some(c) = self.bump() {
+                        word.push(c);
+                    }
+                }
+                Some(c) if c == quote => {
+                    word.push(c);
+                    return Ok(word);
+                }
+                Some(c) => word.push(c),
+            }
+        }
+    }
+
+    /// Unquoted word. `${name}` variables may contain braces.
+    fn word(&mut self, first: char) -> String {
+        let mut word = String::from(first);
+        let mut in_var_braces = false;
+        while let Some(&c) = self.chars.peek() {
+            match c {
+                '{' if word.ends_with('$') => in_var_braces = true,
+                '}' if in_var_braces => in_var_braces = false,
+                ';' | '{' | '}' => break,
+                c if c.is_whitespace() => break,
+                '\\' => {
+                    word.push(c);
+                    self.bump();
+                    let Some(&escaped) = self.chars.peek() else {
+                        break;
+                    };
+                    word.push(escaped);
+                    self.bump();
+                    continue;
+                }
+                _ => {}
+            }
+            word.push(c);
+            self.bump();
+        }
+        word
+    }
+}
@@ -0,0 +1,853 @@
+//! nginx configuration parser.
+//!
+//! `parse` reads any nginx file into the untyped `Node` tree. Comments
+//! and blank lines are kept, so rendering the result of `parse` gives
+//! back the same text for files using the crate layout (one directive
+//! per line, four spaces indentation). Other layouts are normalized.
+//!
+//! The typed model is built on top of that tree with
+//! `NginxConfig::try_from(&[Node])` / `Server::try_from(&Directive)`,
+//! or directly with `parse_config` and `parse_servers`. Directives the
+//! typed model does not know are kept as opaque nodes in `extra`, and
+//! each context records the order of its directives in its `Layout`, so
+//! a parsed config renders back to its file.
+
+// region:    --- Modules
+
+mod lexer;
+mod snippet;
+
+pub use snippet::SnippetContext;
+
+use crate::context::{NginxConfig, Server};
+use crate::error::{Error, Result};
+use crate::node::{Directive, Node};
+use crate::render::{INDENT, render_nodes};
+use lexer::{Lexer, Spanned, Token};
+
+// endregion: --- Modules
+
+// region:    --- Public Functions
+
+/// Parse nginx text into nodes.
+pub fn parse(input: &str) -> Result<Vec<Node>> {
+    Parser::new(input).block(None, None)
+}
+
+/// Parse directives written by a user for a `server` or `location`
+/// block, accepting only the ones allowed in there.
+///
+/// The blocks of `location` and `if` are checked against their own
+/// context. `include` is never allowed.
+pub fn parse_snippet(
+    input: &str,
+    context: SnippetContext,
+) -> Result<Vec<Node>> {
+    Parser::new(input).block(None, Some(context))
+}
+
+/// Parse a whole `nginx.conf` into the typed model.
+pub fn parse_config(input: &str) -> Result<NginxConfig> {
+    input.parse()
+}
+
+/// Parse every `server` block of a file, typically a
+/// `sites-enabled/*.conf` file. Blocks nested in `http` are included.
+pub fn parse_servers(input: &str) -> Result<Vec<Server>> {
+    fn collect(nodes: &[Node], servers: &mut Vec<Server>) -> Result<()> {
+        for node in nodes {
+            let Node::Directive(directive) = node else {
+                continue;
+            };
+            match directive.name.as_str() {
+                "server" => servers.push(Server::try_from(directive)?),
+                "http" => collect(directive.children(), servers)?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    let mut servers = Vec::new();
+    collect(&parse(input)?, &mut servers)?;
+    Ok(servers)
+}
+
+/// Add `directive` at the end of the top level `block` (e.g. `http`) of
+/// `input`, the rest of the text being kept as is. The block is added at
+/// the end when `input` has none.
+pub fn append_to_block(
+    input: &str,
+    block: &str,
+    directive: Directive,
+) -> Result<String> {
+    // Only a valid file is edited.
+    parse(input)?;
+
+    let mut lexer = Lexer::new(input);
+    let mut depth = 0;
+    // The next word is a directive name.
+    let mut at_name = true;
+    // Seen `block` at the top level, until its `{`.
+    let mut in_name = false;
+    let mut in_block = false;
+    let mut close = None;
+    while let Some(Spanned { token, offset, .. }) = lexer.next_token()? {
+        match token {
+            Token::Word(word) => {
+                if at_name && depth == 0 {
+                    in_name = word == block;
+                }
+                at_name = false;
+            }
+            Token::Semicolon => at_name = true,
+            Token::OpenBrace => {
+                in_block |= depth == 0 && in_name;
+                depth += 1;
+                at_name = true;
+            }
+            Token::CloseBrace => {
+                depth -= 1;
+                at_name = true;
+                if depth == 0 && in_block {
+                    close = Some(offset);
+                    break;
+                }
+            }
+            Token::Comment(_) | Token::Newline => {}
+        }
+    }
+
+    let Some(close) = close else {
+        let mut output = input.to_string();
+        if !output.is_empty() && !output.ends_with('\n') {
+            output.push('\n');
+        }
+        let block = Directive::new_block(block, vec![directive.into()]);
+        output.push_str(&render_nodes(&[block.into()]));
+        return Ok(output);
+    };
+
+    // On its own line before the `}`, indented one more level, or on the
+    // line of the `}` when it follows other directives.
+    let line = render_nodes(&[directive.into()]);
+    let line_start = input[..close].rfind('\n').map_or(0, |i| i + 1);
+    let indent = &input[line_start..close];
+    let (at, text) = if indent.trim().is_empty() {
+        (line_start, format!("{indent}{INDENT}{line}"))
+    } else {
+        (close, format!("{} ", line.trim_end()))
+    };
+
+    let mut output = input.to_string();
+    output.insert_str(at, &text);
+    Ok(output)
+}
+
+// endregion: --- Public Functions
+
+// region:    --- Parser
+
+struct Parser<'a> {
+    lexer: Lexer<'a>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        Parser {
+            lexer: Lexer::new(input),
+        }
+    }
+
+    /// Parse nodes until the closing brace of `open`, or until the end
+    /// of input for the top level. A comment on the line of the `{` is
+    /// set as the `open_comment` of `open`.
+    ///
+    /// The directives are checked against `context` when set.
+    fn block(
+        &mut self,
+        mut open: Option<(&mut Directive, usize, usize)>,
+        context: Option<SnippetContext>,
+    ) -> Result<Vec<Node>> {
+        let mut nodes = Vec::new();
+        // Newlines seen since the last node. The start of the file counts
+        // as the end of a previous line, a `{` is followed by one.
+        let mut newlines = if open.is_none() { 1 } else { 0 };
+
+        loop {
+            let Some(Spanned {
+                token, line, col, ..
+            }) = self.lexer.next_token()?
+            else {
+                return match open {
+                    Some((directive, line, col)) => Err(Error::UnclosedBlock {
+                        directive: directive.name.clone(),
+                        line,
+                        col,
+                    }),
+                    None => {
+                        push_blanks(&mut nodes, newlines);
+                        Ok(nodes)
+                    }
+                };
+            };
+
+            match token {
+                Token::Newline => newlines += 1,
+                Token::Comment(text) => {
+                    // Comment on the line of the `{`.
+                    if newlines == 0
+                        && nodes.is_empty()
+                        && let Some((directive, ..)) = &mut open
+                    {
+                        directive.open_comment = Some(text);
+                        continue;
+                    }
+                    // Comment on the same line as the previous directive.
+                    if newlines == 0
+                        && let Some(Node::Directive(directive)) =
+                            nodes.last_mut()
+                        && directive.comment.is_none()
+                    {
+                        directive.comment = Some(text);
+                        continue;
+                    }
+                    push_blanks(&mut nodes, newlines);
+                    newlines = 0;
+                    nodes.push(Node::Comment(text));
+                }
+                Token::Word(name) => {
+                    if let Some(context) = context
+                        && !context.allows(&name)
+                    {
+                        return Err(Error::DirectiveNotAllowed {
+                            directive: name,
+                            context,
+                            line,
+                            col,
+                        });
+                    }
+                    push_blanks(&mut nodes, newlines);
+                    newlines = 0;
+                    let child = context.and_then(|c| c.child(&name));
+                    let mut comments = Vec::new();
+                    let directive =
+                        self.directive(name, line, col, child, &mut comments)?;
+                    nodes.extend(comments.into_iter().map(Node::Comment));
+                    nodes.push(directive.into());
+                }
+                Token::CloseBrace => {
+                    if open.is_none() {
+                        return Err(Error::UnexpectedClosingBrace {
+                            line,
+                            col,
+                        });
+                    }
+                    push_blanks(&mut nodes, newlines);
+                    return Ok(nodes);
+                }
+                Token::Semicolon => {
+                    return Err(Error::UnexpectedToken {
+                        token: ";".to_string(),
+                        line,
+                        col,
+                    });
+                }
+                Token::OpenBrace => {
+                    return Err(Error::UnexpectedToken {
+                        token: "{".to_string(),
+                        line,
+                        col,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Parse the arguments (and block) of a directive whose name was just
+    /// read at `line`, `col`, its block being checked against `context`.
+    ///
+    /// The comments between the arguments are pushed to `comments`, to be
+    /// written before the directive.
+    fn directive(
+        &mut self,
+        name: String,
+        line: usize,
+        col: usize,
+        context: Option<SnippetContext>,
+        comments: &mut Vec<String>,
+    ) -> Result<Directive> {
+        let mut directive = Directive::new(name);
+
+        loop {
+            let Some(spanned) = self.lexer.next_token()? else {
+                let (line, col) = self.lexer.position();
+                return Err(Error::UnexpectedEof { line, col });
+            };
+
+            match spanned.token {
+                Token::Word(arg) => directive.args.push(arg),
+                // Directives can span several lines.
+                Token::Newline => {}
+                Token::Comment(text) => comments.push(text),
+                Token::Semicolon => return Ok(directive),
+                Token::OpenBrace => {
+                    let children =
+                        self.block(Some((&mut directive, line, col)), context)?;
+                    directive.block = Some(children);
+                    return Ok(directive);
+                }
+                Token::CloseBrace => {
+                    return Err(Error::UnexpectedToken {
+                        token: "}".to_string(),
+                        line: spanned.line,
+                        col: spanned.col,
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// One blank node per empty line, `newlines` being the number of line
+/// breaks since the last node.
+fn push_blanks(nodes: &mut Vec<Node>, newlines: usize) {
+    for _ in 1..newlines {
+        nodes.push(Node::Blank);
+    }
+}
+
+// endregion: --- Parser
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+    use crate::error::Error as NginxError;
+    use crate::render::{Render, render_nodes};
+    use crate::value::{KeepaliveTimeout, OnOff, Time};
+
+    #[test]
+    fn test_parse_render_roundtrip_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_golden = include_str!("../../golden/sites_enabled.conf");
+
+        // -- Exec
+        let nodes = parse(fx_golden)?;
+
+        // -- Check
+        assert_eq!(render_nodes(&nodes), fx_golden);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_normalize_layout_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_input = "events{worker_connections 16;}\n\
+            http {\n  map $http_upgrade $connection_upgrade\n  {\n\
+            default upgrade; '' close; }\n}";
+        let fx_expected = "events {
+    worker_connections 16;
+}
+http {
+    map $http_upgrade $connection_upgrade {
+        default upgrade;
+        '' close;
+    }
+}
+";
+
+        // -- Exec
+        let res = render_nodes(&parse(fx_input)?);
+
+        // -- Check
+        assert_eq!(res, fx_expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_servers_typed_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_golden = include_str!("../../golden/sites_enabled.conf");
+        // The leading comment is outside of the server block.
+        let (_, fx_server_text) =
+            fx_golden.split_once('\n').ok_or("Should have lines")?;
+
+        // -- Exec
+        let servers = parse_servers(fx_golden)?;
+
+        // -- Check
+        let [server] = servers.as_slice() else {
+            return Err("Should have one server".into());
+        };
+        assert_eq!(server.server_name, ["app.example.com"]);
+        assert!(
+            server
+                .listen
+                .iter()
+                .all(|listen| listen.ssl && listen.http2)
+        );
+        assert_eq!(server.locations.len(), 2);
+        assert_eq!(
+            server.locations[0].proxy_pass.as_deref(),
+            Some("http://127.0.0.1:8080")
+        );
+        assert_eq!(server.extra.len(), 3);
+        assert_eq!(server.render(), fx_server_text);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_config_golden_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_goldens = [
+            include_str!("../../golden/default.conf"),
+            include_str!("../../golden/full.conf"),
+        ];
+
+        for fx_golden in fx_goldens {
+            // -- Exec
+            let config = parse_config(fx_golden)?;
+
+            // -- Check
+            assert!(config.extra.is_empty());
+            assert_eq!(config.render(), fx_golden);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_config_stream_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_input = "stream {
+    server {
+        listen 3306;
+        proxy_pass db.internal:3306;
+    }
+}
+";
+
+        // -- Exec
+        let config = parse_config(fx_input)?;
+
+        // -- Check
+        let stream = config.stream.as_ref().ok_or("Should have stream")?;
+        assert_eq!(stream.servers.len(), 1);
+        assert!(config.extra.is_empty());
+        assert_eq!(config.render(), fx_input);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_config_handwritten_roundtrip_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_input = r#"# Written by hand, not by lib-nginx.
+user nginx;
+error_log /var/log/nginx/error.log;
+error_log /var/log/nginx/debug.log debug;
+
+events {
+    # Small box.
+    worker_connections 256;
+}
+
+http {
+    include mime.types;
+    keepalive_timeout 65 60;
+    log_format main '$remote_addr - $request';
+    access_log /var/log/nginx/access.log;
+
+    server {
+        listen 80;
+        set $maintenance 0;
+        if ($maintenance) {
+            return 503;
+        }
+        return 200 "up";
+
+        location /old/ {
+            # Moved in 2024.
+            if ($arg_keep) {
+                return 204;
+            }
+            return 301 /new/;
+        }
+        server_name example.com;
+    }
+
+    keepalive_requests 100;
+    include /etc/nginx/conf.d/*.conf;
+}
+worker_processes 2;
+"#;
+
+        // -- Exec
+        let config = parse_config(fx_input)?;
+
+        // -- Check
+        assert_eq!(config.render(), fx_input);
+        assert_eq!(config.error_log.len(), 2);
+        assert_eq!(config.pcre_jit, None);
+        let http = config.http.as_ref().ok_or("Should have http")?;
+        assert_eq!(http.sendfile, None);
+        assert_eq!(http.gzip, None);
+        assert_eq!(
+            http.keepalive_timeout,
+            Some(KeepaliveTimeout {
+                timeout: Time::seconds(65),
+                header_timeout: Some(Time::seconds(60)),
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_config_inline_comments_roundtrip_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_input = r#"worker_processes auto; # four cores
+
+events { # defaults
+    worker_connections 512; # small box
+}
+
+http {
+    gzip on; # text only
+    upstream app { # pool
+        least_conn; # fair
+        server 10.0.0.5:3000; # first
+    }
+
+    server {
+        listen 443 ssl; # tls
+        server_name a.com; # main
+
+        location / { # root
+            proxy_pass http://app; # backend
+        } # end root
+    }
+} # end
+"#;
+
+        // -- Exec
+        let config = parse_config(fx_input)?;
+
+        // -- Check
+        assert_eq!(config.render(), fx_input);
+        let nodes = parse(fx_input)?;
+        assert_eq!(render_nodes(&nodes), fx_input);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_config_edit_keeps_order_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_input = "http {
+    include /etc/nginx/conf.d/*.conf;
+    keepalive_timeout 1m;
+}
+";
+        let fx_expected = "http {
+    include /etc/nginx/conf.d/*.conf;
+    include /etc/nginx/sites-enabled/*;
+    keepalive_timeout 1m;
+    gzip on;
+}
+";
+
+        // -- Exec
+        let mut config = parse_config(fx_input)?;
+        let http = config.http.as_mut().ok_or("Should have http")?;
+        http.include.push("/etc/nginx/sites-enabled/*".to_string());
+        http.gzip = Some(OnOff::On);
+
+        // -- Check
+        assert_eq!(config.render(), fx_expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_comment_in_arguments_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_input = "log_format main
+    '$remote_addr' # client
+    '$request'; # request line
+";
+        let fx_expected = "# client
+log_format main '$remote_addr' '$request'; # request line
+";
+
+        // -- Exec
+        let res = render_nodes(&parse(fx_input)?);
+
+        // -- Check
+        assert_eq!(res, fx_expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_config_unknown_directive_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_input = "load_module modules/ngx_mail_module.so;
+mail {
+    server {
+        listen 25;
+        protocol smtp;
+    }
+}
+";
+
+        // -- Exec
+        let config = parse_config(fx_input)?;
+
+        // -- Check
+        assert_eq!(render_nodes(&config.extra), fx_input);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_append_to_block_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_include = || Directive::new("include").arg("/gen/hosts/*.conf");
+        let fx_cases = [
+            (
+                "events {}\nhttp {\n  keepalive_timeout 1m; # tuned\n  server { listen 80; }\n}\n",
+                "http",
+                "events {}\nhttp {\n  keepalive_timeout 1m; # tuned\n  server { listen 80; }\n    include /gen/hosts/*.conf;\n}\n",
+            ),
+            (
+                "http { gzip on; }",
+                "http",
+                "http { gzip on; include /gen/hosts/*.conf; }",
+            ),
+            (
+                "# no http\nevents {}",
+                "stream",
+                "# no http\nevents {}\nstream {\n    include /gen/hosts/*.conf;\n}\n",
+            ),
+        ];
+
+        for (fx_input, fx_block, fx_expected) in fx_cases {
+            // -- Exec
+            let res = append_to_block(fx_input, fx_block, fx_include())?;
+
+            // -- Check
+            assert_eq!(res, fx_expected);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_err_position() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_cases = [
+            (
+                "http {\n    server {\n}\n",
+                NginxError::UnclosedBlock {
+                    directive: "http".to_string(),
+                    line: 1,
+                    col: 1,
+                },
+            ),
+            (
+                "events {}\n}\n",
+                NginxError::UnexpectedClosingBrace { line: 2, col: 1 },
+            ),
+            (
+                "user www-data",
+                NginxError::UnexpectedEof { line: 1, col: 14 },
+            ),
+            (
+                "return 200 \"ok;\n",
+                NginxError::UnterminatedQuote { line: 1, col: 12 },
+            ),
+            (
+                "http {\n    ;\n}\n",
+                NginxError::UnexpectedToken {
+                    token: ";".to_string(),
+                    line: 2,
+                    col: 5,
+                },
+            ),
+        ];
+
+        for (fx_input, fx_error) in fx_cases {
+            // -- Exec
+            let res = parse(fx_input);
+
+            // -- Check
+            assert_eq!(res, Err(fx_error), "input: {fx_input:?}");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_snippet_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_input = "client_max_body_size 50m;
+location /admin/ {
+    if ($request_method = POST) {
+        return 405;
+    }
+}
+";
+
+        // -- Exec
+        let res = parse_snippet(fx_input, SnippetContext::Server)?;
+
+        // -- Check
+        assert_eq!(res, parse(fx_input)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_snippet_err_position() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_cases = [
+            (
+                "gzip on;
+  listen 8080;
+",
+                SnippetContext::Server,
+                NginxError::DirectiveNotAllowed {
+                    directive: "listen".to_string(),
+                    context: SnippetContext::Server,
+                    line: 2,
+                    col: 3,
+                },
+            ),
+            (
+                "location / {
+    upstream backend {}
+}
+",
+                SnippetContext::Server,
+                NginxError::DirectiveNotAllowed {
+                    directive: "upstream".to_string(),
+                    context: SnippetContext::Location,
+                    line: 2,
+                    col: 5,
+                },
+            ),
+            (
+                "expires 1d;
+ssl_certificate /tmp/cert.pem;
+",
+                SnippetContext::Location,
+                NginxError::DirectiveNotAllowed {
+                    directive: "ssl_certificate".to_string(),
+                    context: SnippetContext::Location,
+                    line: 2,
+                    col: 1,
+                },
+            ),
+            (
+                "gzip on;
+server_side_magic on;
+",
+                SnippetContext::Server,
+                NginxError::DirectiveNotAllowed {
+                    directive: "server_side_magic".to_string(),
+                    context: SnippetContext::Server,
+                    line: 2,
+                    col: 1,
+                },
+            ),
+            (
+                "location /api/ {
+    include /etc/passwd;
+}
+",
+                SnippetContext::Server,
+                NginxError::DirectiveNotAllowed {
+                    directive: "include".to_string(),
+                    context: SnippetContext::Location,
+                    line: 2,
+                    col: 5,
+                },
+            ),
+            (
+                "include snippets/ssl.conf;
+",
+                SnippetContext::Location,
+                NginxError::DirectiveNotAllowed {
+                    directive: "include".to_string(),
+                    context: SnippetContext::Location,
+                    line: 1,
+                    col: 1,
+                },
+            ),
+            (
+                "if ($request_method = POST) {
+    return 405;
+    proxy_read_timeout 300s;
+}
+",
+                SnippetContext::Location,
+                NginxError::DirectiveNotAllowed {
+                    directive: "proxy_read_timeout".to_string(),
+                    context: SnippetContext::If,
+                    line: 3,
+                    col: 5,
+                },
+            ),
+            (
+                "location / {
+",
+                SnippetContext::Server,
+                NginxError::UnclosedBlock {
+                    directive: "location".to_string(),
+                    line: 1,
+                    col: 1,
+                },
+            ),
+        ];
+
+        for (fx_input, fx_context, fx_error) in fx_cases {
+            // -- Exec
+            let res = parse_snippet(fx_input, fx_context);
+
+            // -- Check
+            assert_eq!(res, Err(fx_error), "input: {fx_input:?}");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_config_invalid_args_err() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_input = "worker_processes many;\n";
+
+        // -- Exec
+        let res = parse_config(fx_input);
+
+        // -- Check
+        assert_eq!(
+            res,
+            Err(NginxError::InvalidArgs {
+                directive: "worker_processes".to_string()
+            })
+        );
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
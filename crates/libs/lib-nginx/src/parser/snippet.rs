@@ -0,0 +1,162 @@
+use serde::Serialize;
+use std::fmt;
+
+/// Denied in every context: reads a file of the host.
+const DENIED: &[&str] = &["include"];
+
+/// Directives of the `http`, `server` and `location` contexts.
+const COMMON: &[&str] = &[
+    "absolute_redirect",
+    "access_log",
+    "add_header",
+    "add_trailer",
+    "allow",
+    "auth_basic",
+    "auth_basic_user_file",
+    "auth_request",
+    "auth_request_set",
+    "charset",
+    "chunked_transfer_encoding",
+    "client_body_buffer_size",
+    "client_body_timeout",
+    "client_max_body_size",
+    "default_type",
+    "deny",
+    "error_log",
+    "error_page",
+    "etag",
+    "expires",
+    "gzip",
+    "gzip_comp_level",
+    "gzip_min_length",
+    "gzip_proxied",
+    "gzip_types",
+    "gzip_vary",
+    "if_modified_since",
+    "index",
+    "keepalive_timeout",
+    "limit_conn",
+    "limit_rate",
+    "limit_rate_after",
+    "limit_req",
+    "log_not_found",
+    "port_in_redirect",
+    "proxy_buffer_size",
+    "proxy_buffering",
+    "proxy_buffers",
+    "proxy_busy_buffers_size",
+    "proxy_cache",
+    "proxy_cache_bypass",
+    "proxy_cache_key",
+    "proxy_cache_methods",
+    "proxy_cache_use_stale",
+    "proxy_cache_valid",
+    "proxy_connect_timeout",
+    "proxy_cookie_domain",
+    "proxy_cookie_path",
+    "proxy_hide_header",
+    "proxy_http_version",
+    "proxy_ignore_headers",
+    "proxy_intercept_errors",
+    "proxy_max_temp_file_size",
+    "proxy_next_upstream",
+    "proxy_no_cache",
+    "proxy_pass_header",
+    "proxy_pass_request_body",
+    "proxy_pass_request_headers",
+    "proxy_read_timeout",
+    "proxy_redirect",
+    "proxy_request_buffering",
+    "proxy_send_timeout",
+    "proxy_set_header",
+    "proxy_ssl_name",
+    "proxy_ssl_server_name",
+    "proxy_ssl_verify",
+    "real_ip_header",
+    "real_ip_recursive",
+    "resolver",
+    "resolver_timeout",
+    "root",
+    "sendfile",
+    "server_tokens",
+    "set_real_ip_from",
+    "sub_filter",
+    "sub_filter_once",
+    "sub_filter_types",
+    "tcp_nodelay",
+    "tcp_nopush",
+    "try_files",
+];
+
+/// Directives of the rewrite module, the ones nginx takes in the `if` of
+/// both a `server` and a `location`.
+const REWRITE: &[&str] = &["break", "if", "return", "rewrite", "set"];
+
+/// Directives of the `server` context, not of `location`. `listen` and
+/// `server_name` are written by the host itself.
+const SERVER: &[&str] = &[
+    "location",
+    "ssl_ciphers",
+    "ssl_prefer_server_ciphers",
+    "ssl_protocols",
+    "ssl_session_cache",
+    "ssl_session_timeout",
+];
+
+/// Directives of the `location` context, not of `server`.
+const LOCATION: &[&str] = &["alias", "internal", "location", "proxy_pass"];
+
+/// The block a snippet is included in, see `parse_snippet`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum SnippetContext {
+    Server,
+    Location,
+    If,
+}
+
+impl SnippetContext {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SnippetContext::Server => "server",
+            SnippetContext::Location => "location",
+            SnippetContext::If => "if",
+        }
+    }
+
+    pub(super) fn allows(&self, directive: &str) -> bool {
+        if DENIED.contains(&directive) {
+            return false;
+        }
+        match self {
+            SnippetContext::Server => {
+                COMMON.contains(&directive)
+                    || REWRITE.contains(&directive)
+                    || SERVER.contains(&directive)
+            }
+            SnippetContext::Location => {
+                COMMON.contains(&directive)
+                    || REWRITE.contains(&directive)
+                    || LOCATION.contains(&directive)
+            }
+            // `if` blocks don't nest.
+            SnippetContext::If => {
+                directive != "if" && REWRITE.contains(&directive)
+            }
+        }
+    }
+
+    /// Context of the block of `directive`, not checked when `None`.
+    pub(super) fn child(&self, directive: &str) -> Option<SnippetContext> {
+        match directive {
+            "location" => Some(SnippetContext::Location),
+            "if" => Some(SnippetContext::If),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for SnippetContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
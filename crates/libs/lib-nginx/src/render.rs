@@ -0,0 +1,128 @@
+use crate::node::{Directive, Node};
+
+/// Number of spaces used per nesting level.
+pub(crate) const INDENT: &str = "    ";
+
+/// Typed configuration pieces that can be written as nginx text.
+pub trait Render {
+    fn to_nodes(&self) -> Vec<Node>;
+
+    fn render(&self) -> String {
+        render_nodes(&self.to_nodes())
+    }
+}
+
+/// Write `nodes` as nginx configuration text.
+///
+/// The output is deterministic: one directive per line, four spaces of
+/// indentation per block level and a trailing newline.
+pub fn render_nodes(nodes: &[Node]) -> String {
+    let mut out = String::new();
+    write_nodes(&mut out, nodes, 0);
+    out
+}
+
+fn write_nodes(out: &mut String, nodes: &[Node], depth: usize) {
+    for node in nodes {
+        match node {
+            Node::Directive(directive) => {
+                write_directive(out, directive, depth)
+            }
+            Node::Comment(comment) => {
+                write_indent(out, depth);
+                out.push('#');
+                out.push_str(comment);
+                out.push('\n');
+            }
+            Node::Blank => out.push('\n'),
+        }
+    }
+}
+
+fn write_directive(out: &mut String, directive: &Directive, depth: usize) {
+    write_indent(out, depth);
+    out.push_str(&directive.name);
+    for arg in &directive.args {
+        out.push(' ');
+        out.push_str(arg);
+    }
+
+    match &directive.block {
+        None => out.push(';'),
+        Some(children) => {
+            out.push_str(" {");
+            if let Some(comment) = &directive.open_comment {
+                out.push_str(" #");
+                out.push_str(comment);
+            }
+            out.push('\n');
+            write_nodes(out, children, depth + 1);
+            write_indent(out, depth);
+            out.push('}');
+        }
+    }
+
+    if let Some(comment) = &directive.comment {
+        out.push_str(" #");
+        out.push_str(comment);
+    }
+    out.push('\n');
+}
+
+fn write_indent(out: &mut String, depth: usize) {
+    for _ in 0..depth {
+        out.push_str(INDENT);
+    }
+}
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+
+    #[test]
+    fn test_render_nested_block_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_nodes = vec![
+            Node::Comment(" generated".to_string()),
+            Directive::new("worker_processes").arg("auto").into(),
+            Node::Blank,
+            Directive::new_block(
+                "http",
+                vec![
+                    Directive::new_block(
+                        "map",
+                        vec![Directive::new("default").arg("close").into()],
+                    )
+                    .raw_arg("$http_upgrade")
+                    .raw_arg("$connection_upgrade")
+                    .into(),
+                ],
+            )
+            .into(),
+        ];
+        let fx_expected = "# generated
+worker_processes auto;
+
+http {
+    map $http_upgrade $connection_upgrade {
+        default close;
+    }
+}
+";
+
+        // -- Exec
+        let res = render_nodes(&fx_nodes);
+
+        // -- Check
+        assert_eq!(res, fx_expected);
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -0,0 +1,116 @@
+use crate::context::{Location, LocationModifier};
+use crate::node::{Directive, Node};
+use crate::render::Render;
+use crate::snippet::header;
+use crate::value::{Header, Size};
+use serde::Serialize;
+
+pub const CACHE_ASSETS_VERSION: u32 = 1;
+
+/// Name of the `keys_zone` the asset locations cache into.
+const ZONE_NAME: &str = "cache_assets";
+
+/// Extensions of the static assets, matched case insensitively.
+const ASSET_EXTENSIONS: &[&str] = &[
+    "css", "js", "mjs", "map", "jpe?g", "png", "gif", "webp", "avif", "svg",
+    "ico", "woff2?", "ttf", "otf", "eot", "mp4", "webm",
+];
+
+/// The `proxy_cache_path` of the asset locations, rendered at the `http`
+/// level once for all the hosts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CacheZone {
+    /// Directory of the cached responses, created by nginx.
+    pub path: String,
+    pub max_size: Size,
+    /// Entries not read for that many days are removed.
+    pub inactive_days: u32,
+}
+
+impl CacheZone {
+    pub fn new(path: impl Into<String>) -> Self {
+        CacheZone {
+            path: path.into(),
+            max_size: Size::Gigabytes(1),
+            inactive_days: 7,
+        }
+    }
+}
+
+impl Render for CacheZone {
+    fn to_nodes(&self) -> Vec<Node> {
+        vec![
+            header("cache_assets", CACHE_ASSETS_VERSION),
+            Directive::new("proxy_cache_path")
+                .arg(&self.path)
+                .arg("levels=1:2")
+                .arg(format!("keys_zone={ZONE_NAME}:10m"))
+                .arg(format!("max_size={}", self.max_size))
+                .arg(format!("inactive={}d", self.inactive_days))
+                .arg("use_temp_path=off")
+                .into(),
+        ]
+    }
+}
+
+/// `location ~* \.(css|js|...)$`, proxying the static assets to
+/// `forward_url` through the zone of `CacheZone`.
+///
+/// Meant to be nested in the prefix location proxying to `forward_url`:
+/// at the `server` level it would win over every prefix location.
+///
+/// No `add_header` is set, so the ones of the `server` (e.g. HSTS) are
+/// still inherited.
+pub fn cache_assets_location(
+    forward_url: &str,
+    proxy_set_header: Vec<Header>,
+) -> Location {
+    Location {
+        modifier: Some(LocationModifier::RegexCaseInsensitive),
+        proxy_set_header,
+        proxy_pass: Some(forward_url.to_string()),
+        extra: vec![
+            header("cache_assets", CACHE_ASSETS_VERSION),
+            Directive::new("proxy_cache").arg(ZONE_NAME).into(),
+            Directive::new("proxy_cache_valid")
+                .args(["200", "301", "302", "30m"])
+                .into(),
+            Directive::new("proxy_cache_use_stale")
+                .args(["error", "timeout", "updating"])
+                .into(),
+            Directive::new("expires").arg("30d").into(),
+        ],
+        ..Location::new(format!(r"\.({})$", ASSET_EXTENSIONS.join("|")))
+    }
+}
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+
+    #[test]
+    fn test_render_cache_zone_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_zone = CacheZone::new("/var/cache/nginx/assets");
+
+        // -- Exec
+        let res = fx_zone.render();
+
+        // -- Check
+        assert_eq!(
+            res,
+            "# cache_assets v1
+proxy_cache_path /var/cache/nginx/assets levels=1:2 keys_zone=cache_assets:10m max_size=1g inactive=7d use_temp_path=off;
+"
+        );
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
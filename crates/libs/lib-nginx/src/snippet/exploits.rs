@@ -0,0 +1,95 @@
+use crate::node::{Directive, Node};
+use crate::snippet::header;
+
+pub const BLOCK_EXPLOITS_VERSION: u32 = 1;
+
+/// `(variable, case insensitive regex)`, a request matching any of them
+/// is refused with a `403`. Also checked by the native data plane.
+///
+/// The regexes are written between double quotes as is, so they must
+/// not contain one.
+pub const BLOCK_EXPLOITS_RULES: &[(&str, &str)] = &[
+    // -- SQL injection
+    ("$query_string", r"union.*select.*\("),
+    ("$query_string", r"union.*all.*select"),
+    ("$query_string", r"concat.*\("),
+    (
+        "$query_string",
+        r"(;|<|>|'|%0A|%0D|%22|%27|%3C|%3E|%00).*(/\*|union|select|insert|drop|delete|update|cast|create|char|convert|alter|declare|script|set|md5|benchmark|encode)",
+    ),
+    // -- Cross site scripting
+    ("$query_string", r"(<|%3C).*script.*(>|%3E)"),
+    ("$query_string", r"javascript:"),
+    ("$query_string", r"base64_(en|de)code\(.*\)"),
+    ("$query_string", r"(GLOBALS|_REQUEST)(=|\[|%[0-9A-Z]{0,2})"),
+    // -- Path traversal and file injection
+    ("$request_uri", r"(\.\./|%2e%2e(/|%2f))"),
+    ("$query_string", r"(\.\./|%2e%2e(/|%2f))"),
+    ("$query_string", r"(boot\.ini|etc/passwd|self/environ)"),
+    ("$query_string", r"[a-z0-9_]=https?://"),
+    // -- Scanners
+    (
+        "$http_user_agent",
+        r"(nikto|sqlmap|acunetix|nessus|netsparker|w3af|masscan)",
+    ),
+];
+
+/// `if ($variable ~* "regex") { return 403; }` for each of the rules, to
+/// be added to a `server` block.
+pub fn block_exploits() -> Vec<Node> {
+    let mut nodes = vec![header("block_exploits", BLOCK_EXPLOITS_VERSION)];
+    nodes.extend(BLOCK_EXPLOITS_RULES.iter().map(|(variable, regex)| {
+        let forbidden = Directive::new("return").arg("403");
+        Directive::new_block("if", vec![forbidden.into()])
+            .raw_arg(format!("({variable}"))
+            .raw_arg("~*")
+            .raw_arg(format!("\"{regex}\")"))
+            .into()
+    }));
+    nodes
+}
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+    use crate::render::render_nodes;
+
+    #[test]
+    fn test_block_exploits_rules_ok() -> Result<()> {
+        // -- Exec & Check
+        for (variable, regex) in BLOCK_EXPLOITS_RULES {
+            assert!(variable.starts_with('$'), "{variable}");
+            assert!(!regex.contains('"'), "{regex}");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_block_exploits_parse_back_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_text = render_nodes(&block_exploits());
+
+        // -- Exec
+        let res = crate::parse(&fx_text)?;
+
+        // -- Check
+        assert_eq!(res, block_exploits());
+        assert!(fx_text.starts_with("# block_exploits v1\n"));
+        assert!(fx_text.contains(
+            "if ($query_string ~* \"union.*select.*\\(\") {
+    return 403;
+}
+"
+        ));
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -0,0 +1,29 @@
+//! Directives shipped with the crate and included by the hosts that
+//! enable them.
+//!
+//! Each snippet starts with a `# <name> v<version>` comment, so a
+//! rendered file tells which revision of the rules it was written with.
+//! Bump the version of a snippet whenever its directives change.
+
+// region:    --- Modules
+
+mod cache;
+mod exploits;
+mod websocket;
+
+pub use cache::{CACHE_ASSETS_VERSION, CacheZone, cache_assets_location};
+pub use exploits::{
+    BLOCK_EXPLOITS_RULES, BLOCK_EXPLOITS_VERSION, block_exploits,
+};
+pub use websocket::{
+    ConnectionUpgradeMap, WEBSOCKET_VERSION, websocket_upgrade,
+};
+
+use crate::node::Node;
+
+// endregion: --- Modules
+
+/// The `# <name> v<version>` header of a snippet.
+fn header(name: &str, version: u32) -> Node {
+    Node::Comment(format!(" {name} v{version}"))
+}
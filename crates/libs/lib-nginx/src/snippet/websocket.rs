@@ -0,0 +1,84 @@
+use crate::context::Location;
+use crate::node::{Directive, Node};
+use crate::render::Render;
+use crate::snippet::header;
+use crate::value::Header;
+use serde::Serialize;
+
+pub const WEBSOCKET_VERSION: u32 = 1;
+
+/// `map $http_upgrade $connection_upgrade`, rendered at the `http` level
+/// once for all the hosts: `upgrade` when the client asks for one,
+/// `close` otherwise.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ConnectionUpgradeMap;
+
+impl Render for ConnectionUpgradeMap {
+    fn to_nodes(&self) -> Vec<Node> {
+        vec![
+            header("websocket", WEBSOCKET_VERSION),
+            Directive::new_block(
+                "map",
+                vec![
+                    Directive::new("default").arg("upgrade").into(),
+                    Directive::new("''").arg("close").into(),
+                ],
+            )
+            .raw_arg("$http_upgrade")
+            .raw_arg("$connection_upgrade")
+            .into(),
+        ]
+    }
+}
+
+/// Pass the upgrades of `location` through: `proxy_http_version 1.1`
+/// and the `Upgrade` and `Connection` headers, replacing the ones of the
+/// same name.
+///
+/// `$connection_upgrade` is declared by `ConnectionUpgradeMap`.
+pub fn websocket_upgrade(location: &mut Location) {
+    for header in [
+        Header::new("Upgrade", "$http_upgrade"),
+        Header::new("Connection", "$connection_upgrade"),
+    ] {
+        location
+            .proxy_set_header
+            .retain(|h| !h.name.eq_ignore_ascii_case(&header.name));
+        location.proxy_set_header.push(header);
+    }
+    location
+        .extra
+        .insert(0, Directive::new("proxy_http_version").arg("1.1").into());
+}
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+
+    #[test]
+    fn test_render_connection_upgrade_map_ok() -> Result<()> {
+        // -- Exec
+        let res = ConnectionUpgradeMap.render();
+
+        // -- Check
+        assert_eq!(
+            res,
+            "# websocket v1
+map $http_upgrade $connection_upgrade {
+    default upgrade;
+    '' close;
+}
+"
+        );
+        assert_eq!(crate::parse(&res)?, ConnectionUpgradeMap.to_nodes());
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
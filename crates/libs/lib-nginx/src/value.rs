@@ -0,0 +1,310 @@
+//! Argument types shared by several directives.
+
+use serde::Serialize;
+use std::fmt;
+use std::str::FromStr;
+
+// region:    --- Error
+
+#[derive(thiserror::Error, Debug, Serialize)]
+pub struct FailToParse(pub(crate) String);
+
+impl fmt::Display for FailToParse {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// endregion: --- Error
+
+/// https://nginx.org/en/docs/ngx_core_module.html#pcre_jit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OnOff {
+    #[default]
+    Off,
+    On,
+}
+
+impl From<bool> for OnOff {
+    fn from(value: bool) -> Self {
+        if value { OnOff::On } else { OnOff::Off }
+    }
+}
+
+impl fmt::Display for OnOff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OnOff::Off => f.write_str("off"),
+            OnOff::On => f.write_str("on"),
+        }
+    }
+}
+
+impl FromStr for OnOff {
+    type Err = FailToParse;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "on" => Ok(OnOff::On),
+            "off" => Ok(OnOff::Off),
+            _ => Err(FailToParse(value.to_string())),
+        }
+    }
+}
+
+/// https://nginx.org/en/docs/ngx_core_module.html#worker_processes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub enum WorkerProcesses {
+    #[default]
+    #[serde(rename = "auto")]
+    Auto,
+    U8(u8),
+}
+
+impl fmt::Display for WorkerProcesses {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorkerProcesses::Auto => f.write_str("auto"),
+            WorkerProcesses::U8(count) => write!(f, "{count}"),
+        }
+    }
+}
+
+impl FromStr for WorkerProcesses {
+    type Err = FailToParse;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "auto" => Ok(WorkerProcesses::Auto),
+            count => count
+                .parse()
+                .map(WorkerProcesses::U8)
+                .map_err(|_| FailToParse(value.to_string())),
+        }
+    }
+}
+
+/// https://nginx.org/en/docs/ngx_core_module.html#error_log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Notice,
+    Warn,
+    Error,
+    Crit,
+    Alert,
+    Emerg,
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level = match self {
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Notice => "notice",
+            LogLevel::Warn => "warn",
+            LogLevel::Error => "error",
+            LogLevel::Crit => "crit",
+            LogLevel::Alert => "alert",
+            LogLevel::Emerg => "emerg",
+        };
+        f.write_str(level)
+    }
+}
+
+impl FromStr for LogLevel {
+    type Err = FailToParse;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "debug" => Ok(LogLevel::Debug),
+            "info" => Ok(LogLevel::Info),
+            "notice" => Ok(LogLevel::Notice),
+            "warn" => Ok(LogLevel::Warn),
+            "error" => Ok(LogLevel::Error),
+            "crit" => Ok(LogLevel::Crit),
+            "alert" => Ok(LogLevel::Alert),
+            "emerg" => Ok(LogLevel::Emerg),
+            _ => Err(FailToParse(value.to_string())),
+        }
+    }
+}
+
+/// `error_log file [level];`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ErrorLog {
+    pub path: String,
+    pub level: Option<LogLevel>,
+}
+
+/// A size argument such as `client_max_body_size 10m;`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Size {
+    Bytes(u64),
+    Kilobytes(u64),
+    Megabytes(u64),
+    Gigabytes(u64),
+}
+
+impl fmt::Display for Size {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Size::Bytes(v) => write!(f, "{v}"),
+            Size::Kilobytes(v) => write!(f, "{v}k"),
+            Size::Megabytes(v) => write!(f, "{v}m"),
+            Size::Gigabytes(v) => write!(f, "{v}g"),
+        }
+    }
+}
+
+impl FromStr for Size {
+    type Err = FailToParse;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let fail = || FailToParse(value.to_string());
+        let (number, unit) = match value.char_indices().last() {
+            Some((i, c)) if c.is_ascii_alphabetic() => (&value[..i], Some(c)),
+            _ => (value, None),
+        };
+        let number: u64 = number.parse().map_err(|_| fail())?;
+
+        match unit.map(|c| c.to_ascii_lowercase()) {
+            None => Ok(Size::Bytes(number)),
+            Some('k') => Ok(Size::Kilobytes(number)),
+            Some('m') => Ok(Size::Megabytes(number)),
+            Some('g') => Ok(Size::Gigabytes(number)),
+            Some(_) => Err(fail()),
+        }
+    }
+}
+
+/// A time argument such as `keepalive_timeout 1m30s;`, kept as written.
+///
+/// A number without unit is in seconds.
+/// https://nginx.org/en/docs/syntax.html
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Time(String);
+
+impl Time {
+    pub fn seconds(seconds: u64) -> Self {
+        Time(seconds.to_string())
+    }
+}
+
+impl fmt::Display for Time {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for Time {
+    type Err = FailToParse;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let fail = || FailToParse(value.to_string());
+
+        // One or more `<number><unit>`, e.g. `1h30m`.
+        let mut rest = value;
+        while !rest.is_empty() {
+            let digits = rest.find(|c: char| !c.is_ascii_digit());
+            let (number, tail) = rest.split_at(digits.unwrap_or(rest.len()));
+            if number.is_empty() {
+                return Err(fail());
+            }
+            let unit_len = ["ms", "s", "m", "h", "d", "w", "M", "y"]
+                .into_iter()
+                .find(|unit| tail.starts_with(unit))
+                .map_or(0, str::len);
+            if unit_len == 0 && !tail.is_empty() {
+                return Err(fail());
+            }
+            rest = &tail[unit_len..];
+        }
+        if value.is_empty() {
+            return Err(fail());
+        }
+
+        Ok(Time(value.to_string()))
+    }
+}
+
+/// https://nginx.org/en/docs/http/ngx_http_core_module.html#keepalive_timeout
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct KeepaliveTimeout {
+    pub timeout: Time,
+    /// Value of the `Keep-Alive: timeout=` response header.
+    pub header_timeout: Option<Time>,
+}
+
+/// A `name value` pair, e.g. for `proxy_set_header` or `add_header`.
+///
+/// `value` is only quoted when needed, so variables like `$host` are
+/// written as is.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Header {
+    pub name: String,
+    pub value: String,
+}
+
+impl Header {
+    pub fn new(name: impl Into<String>, value: impl Into<String>) -> Self {
+        Header {
+            name: name.into(),
+            value: value.into(),
+        }
+    }
+}
+
+/// https://nginx.org/en/docs/http/ngx_http_rewrite_module.html#return
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Return {
+    pub code: u16,
+    /// Redirect url for 301, 302, 303, 307 and 308, response text otherwise.
+    pub text: Option<String>,
+}
+
+/// https://nginx.org/en/docs/http/ngx_http_rewrite_module.html#rewrite
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Rewrite {
+    pub regex: String,
+    pub replacement: String,
+    pub flag: Option<RewriteFlag>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RewriteFlag {
+    Last,
+    Break,
+    Redirect,
+    Permanent,
+}
+
+impl fmt::Display for RewriteFlag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let flag = match self {
+            RewriteFlag::Last => "last",
+            RewriteFlag::Break => "break",
+            RewriteFlag::Redirect => "redirect",
+            RewriteFlag::Permanent => "permanent",
+        };
+        f.write_str(flag)
+    }
+}
+
+impl FromStr for RewriteFlag {
+    type Err = FailToParse;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "last" => Ok(RewriteFlag::Last),
+            "break" => Ok(RewriteFlag::Break),
+            "redirect" => Ok(RewriteFlag::Redirect),
+            "permanent" => Ok(RewriteFlag::Permanent),
+            _ => Err(FailToParse(value.to_string())),
+        }
+    }
+}
@@ -7,6 +7,7 @@ pub type Result<T> = std::result::Result<T, Error>;
 #[derive(thiserror::Error, Debug, strum_macros::Display)]
 pub enum Error {
     FailToB64uDecode,
+    FailToB64Decode,
 }
 
 // endregion: --- Error
@@ -27,3 +28,15 @@ pub fn b64u_decode_to_string(b64u: &str) -> Result<String> {
         .and_then(|r| String::from_utf8(r).ok())
         .ok_or(Error::FailToB64uDecode)
 }
+
+/// Standard alphabet with padding, e.g. the `Authorization: Basic` credentials.
+pub fn b64_encode(content: impl AsRef<[u8]>) -> String {
+    general_purpose::STANDARD.encode(content)
+}
+
+/// Standard alphabet with padding, e.g. file contents sent by the browser.
+pub fn b64_decode(b64: &str) -> Result<Vec<u8>> {
+    general_purpose::STANDARD
+        .decode(b64)
+        .map_err(|_| Error::FailToB64Decode)
+}
@@ -40,6 +40,127 @@ pub enum Error {
     #[error("LoginFailPwdNotMatching: user_id: {user_id}")]
     LoginFailPwdNotMatching { user_id: String },
 
+    // -- Validation
+    #[error("NoDomainName")]
+    NoDomainName,
+
+    #[error("InvalidDomainName: {0}")]
+    InvalidDomainName(String),
+
+    #[error("InvalidForwardHost: {0}")]
+    InvalidForwardHost(String),
+
+    #[error("InvalidPort: {0}")]
+    InvalidPort(u16),
+
+    #[error("InvalidIncomingPort: {0}")]
+    InvalidIncomingPort(u16),
+
+    #[error("InvalidForwardUrl: {0}")]
+    InvalidForwardUrl(String),
+
+    #[error("InvalidRedirectCode: {0}")]
+    InvalidRedirectCode(u16),
+
+    #[error("InvalidErrorPage: {0}")]
+    InvalidErrorPage(String),
+
+    #[error("WildcardNeedsDns01: {0}")]
+    WildcardNeedsDns01(String),
+
+    #[error("NoAccessListName")]
+    NoAccessListName,
+
+    #[error("InvalidUsername: {0}")]
+    InvalidUsername(String),
+
+    #[error("DuplicateUsername: {0}")]
+    DuplicateUsername(String),
+
+    #[error("InvalidAccessRule: {0}")]
+    InvalidAccessRule(String),
+
+    #[error("InvalidAccessAddress: {0}")]
+    InvalidAccessAddress(String),
+
+    #[error("NoUpstreamGroupName")]
+    NoUpstreamGroupName,
+
+    #[error("NoUpstreamServer")]
+    NoUpstreamServer,
+
+    #[error("InvalidUpstreamServer: {0}")]
+    InvalidUpstreamServer(String),
+
+    #[error("BackupWithIpHash: {0}")]
+    BackupWithIpHash(String),
+
+    #[error("InvalidHealthCheckPath: {0}")]
+    InvalidHealthCheckPath(String),
+
+    #[error("InvalidHealthCheckInterval: {0}")]
+    InvalidHealthCheckInterval(u32),
+
+    #[error("InvalidRateLimit: {0}")]
+    InvalidRateLimit(u32),
+
+    #[error("InvalidRateLimitBurst: {0}")]
+    InvalidRateLimitBurst(u32),
+
+    #[error("InvalidConnectionLimit: {0}")]
+    InvalidConnectionLimit(u32),
+
+    #[error("InvalidLocationPath: {0}")]
+    InvalidLocationPath(String),
+
+    #[error("ReservedLocationPath: {0}")]
+    ReservedLocationPath(String),
+
+    #[error("ConflictingLocationPath: {path} / {other}")]
+    ConflictingLocationPath { path: String, other: String },
+
+    #[error("InvalidHeader: {0}")]
+    InvalidHeader(String),
+
+    #[error("InvalidLocationSnippet: {path}")]
+    InvalidLocationSnippet {
+        path: String,
+        cause: lib_nginx::Error,
+    },
+
+    #[error("InvalidHostSnippet")]
+    InvalidHostSnippet { cause: lib_nginx::Error },
+
+    // -- Error pages
+    #[error("ErrorPageCantSave: {0}")]
+    ErrorPageCantSave(String),
+
+    // -- Certificates
+    #[error("CertificateInvalid: {0}")]
+    CertificateInvalid(String),
+
+    #[error("PrivateKeyInvalid: {0}")]
+    PrivateKeyInvalid(String),
+
+    #[error("CertificateKeyMismatch")]
+    CertificateKeyMismatch,
+
+    #[error("CertificateExpired: {not_after}")]
+    CertificateExpired { not_after: String },
+
+    #[error("CertificateNotYetValid: {not_before}")]
+    CertificateNotYetValid { not_before: String },
+
+    #[error("CertificateChainInvalid: {0}")]
+    CertificateChainInvalid(String),
+
+    // -- Acme
+    #[error("AcmeChallengeNotFound: {0}")]
+    AcmeChallengeNotFound(String),
+
+    #[error("DnsRecordNotFound: {0}")]
+    DnsRecordNotFound(u64),
+
     // -- CtxExtError
     #[error(transparent)]
     CtxExt(#[from] middleware::mw_auth::CtxExtError),
@@ -190,6 +311,307 @@ impl Error {
                 (StatusCode::FORBIDDEN, ClientError::LOGIN_FAIL)
             }
 
+            // -- Validation
+            NoDomainName => (
+                StatusCode::BAD_REQUEST,
+                ClientError::INVALID_FIELD {
+                    field: "domainNames",
+                    message: "At least one domain name is required".to_string(),
+                },
+            ),
+            InvalidDomainName(domain) => (
+                StatusCode::BAD_REQUEST,
+                ClientError::INVALID_FIELD {
+                    field: "domainNames",
+                    message: format!("'{domain}' is not a valid domain name"),
+                },
+            ),
+            InvalidForwardHost(host) => (
+                StatusCode::BAD_REQUEST,
+                ClientError::INVALID_FIELD {
+                    field: "forwardHost",
+                    message: format!(
+                        "'{host}' is not a valid hostname or ip address"
+                    ),
+                },
+            ),
+            InvalidPort(port) => (
+                StatusCode::BAD_REQUEST,
+                ClientError::INVALID_FIELD {
+                    field: "forwardPort",
+                    message: format!("'{port}' is not a valid port"),
+                },
+            ),
+            InvalidIncomingPort(port) => (
+                StatusCode::BAD_REQUEST,
+                ClientError::INVALID_FIELD {
+                    field: "incomingPort",
+                    message: format!("'{port}' is not a valid port"),
+                },
+            ),
+            InvalidForwardUrl(url) => (
+                StatusCode::BAD_REQUEST,
+                ClientError::INVALID_FIELD {
+                    field: "forwardUrl",
+                    message: format!(
+                        "'{url}' is not a valid http or https url"
+                    ),
+                },
+            ),
+            InvalidRedirectCode(code) => (
+                StatusCode::BAD_REQUEST,
+                ClientError::INVALID_FIELD {
+                    field: "redirectCode",
+                    message: format!(
+                        "'{code}' is not one of 301, 302, 307 or 308"
+                    ),
+                },
+            ),
+            InvalidErrorPage(message) => (
+                StatusCode::BAD_REQUEST,
+                ClientError::INVALID_FIELD {
+                    field: "errorPage",
+                    message: message.to_string(),
+                },
+            ),
+            WildcardNeedsDns01(domain) => (
+                StatusCode::BAD_REQUEST,
+                ClientError::INVALID_FIELD {
+                    field: "challenge",
+                    message: format!(
+                        "'{domain}' can only be validated with dns-01"
+                    ),
+                },
+            ),
+            NoAccessListName => (
+                StatusCode::BAD_REQUEST,
+                ClientError::INVALID_FIELD {
+                    field: "name",
+                    message: "A name is required".to_string(),
+                },
+            ),
+            InvalidUsername(username) => (
+                StatusCode::BAD_REQUEST,
+                ClientError::INVALID_FIELD {
+                    field: "users",
+                    message: format!("'{username}' is not a valid username"),
+                },
+            ),
+            DuplicateUsername(username) => (
+                StatusCode::BAD_REQUEST,
+                ClientError::INVALID_FIELD {
+                    field: "users",
+                    message: format!("'{username}' is listed twice"),
+                },
+            ),
+            InvalidAccessRule(rule) => (
+                StatusCode::BAD_REQUEST,
+                ClientError::INVALID_FIELD {
+                    field: "rules",
+                    message: format!(
+                        "'{rule}' is not 'allow' or 'deny' and an address"
+                    ),
+                },
+            ),
+            InvalidAccessAddress(address) => (
+                StatusCode::BAD_REQUEST,
+                ClientError::INVALID_FIELD {
+                    field: "rules",
+                    message: format!(
+                        "'{address}' is not an ip address, a CIDR or 'all'"
+                    ),
+                },
+            ),
+            NoUpstreamGroupName => (
+                StatusCode::BAD_REQUEST,
+                ClientError::INVALID_FIELD {
+                    field: "name",
+                    message: "A name is required".to_string(),
+                },
+            ),
+            NoUpstreamServer => (
+                StatusCode::BAD_REQUEST,
+                ClientError::INVALID_FIELD {
+                    field: "servers",
+                    message: "At least one server is required".to_string(),
+                },
+            ),
+            InvalidUpstreamServer(server) => (
+                StatusCode::BAD_REQUEST,
+                ClientError::INVALID_FIELD {
+                    field: "servers",
+                    message: format!(
+                        "'{server}' is not 'host:port' and optional parameters"
+                    ),
+                },
+            ),
+            BackupWithIpHash(server) => (
+                StatusCode::BAD_REQUEST,
+                ClientError::INVALID_FIELD {
+                    field: "servers",
+                    message: format!(
+                        "'{server}' can't be a backup with ip_hash balancing"
+                    ),
+                },
+            ),
+            InvalidHealthCheckPath(path) => (
+                StatusCode::BAD_REQUEST,
+                ClientError::INVALID_FIELD {
+                    field: "healthCheckPath",
+                    message: format!(
+                        "'{path}' is not a path starting with '/'"
+                    ),
+                },
+            ),
+            InvalidHealthCheckInterval(interval) => (
+                StatusCode::BAD_REQUEST,
+                ClientError::INVALID_FIELD {
+                    field: "healthCheckInterval",
+                    message: format!(
+                        "'{interval}' is not between 5 and 3600 seconds"
+                    ),
+                },
+            ),
+            InvalidRateLimit(rate) => (
+                StatusCode::BAD_REQUEST,
+                ClientError::INVALID_FIELD {
+                    field: "rateLimit",
+                    message: format!(
+                        "'{rate}' is not between 1 and 10000 requests per second"
+                    ),
+                },
+            ),
+            InvalidRateLimitBurst(burst) => (
+                StatusCode::BAD_REQUEST,
+                ClientError::INVALID_FIELD {
+                    field: "rateLimitBurst",
+                    message: format!("'{burst}' is over 10000 requests"),
+                },
+            ),
+            InvalidConnectionLimit(limit) => (
+                StatusCode::BAD_REQUEST,
+                ClientError::INVALID_FIELD {
+                    field: "connectionLimit",
+                    message: format!(
+                        "'{limit}' is not between 1 and 10000 connections"
+                    ),
+                },
+            ),
+            InvalidLocationPath(path) => (
+                StatusCode::BAD_REQUEST,
+                ClientError::INVALID_FIELD {
+                    field: "locations",
+                    message: format!(
+                        "'{path}' is not a path starting with '/'"
+                    ),
+                },
+            ),
+            ReservedLocationPath(path) => (
+                StatusCode::BAD_REQUEST,
+                ClientError::INVALID_FIELD {
+                    field: "locations",
+                    message: format!(
+                        "'{path}' is already proxied by the host itself"
+                    ),
+                },
+            ),
+            ConflictingLocationPath { path, other } => (
+                StatusCode::BAD_REQUEST,
+                ClientError::INVALID_FIELD {
+                    field: "locations",
+                    message: format!("'{path}' conflicts with '{other}'"),
+                },
+            ),
+            InvalidHeader(name) => (
+                StatusCode::BAD_REQUEST,
+                ClientError::INVALID_FIELD {
+                    field: "locations",
+                    message: format!("'{name}' is not a valid header"),
+                },
+            ),
+            InvalidLocationSnippet { path, cause } => (
+                StatusCode::BAD_REQUEST,
+                ClientError::INVALID_FIELD {
+                    field: "locations",
+                    message: format!(
+                        "Advanced of '{path}': {}",
+                        snippet_error_message(cause)
+                    ),
+                },
+            ),
+            InvalidHostSnippet { cause } => (
+                StatusCode::BAD_REQUEST,
+                ClientError::INVALID_FIELD {
+                    field: "advanced",
+                    message: snippet_error_message(cause),
+                },
+            ),
+
+            // -- Certificates
+            CertificateInvalid(message) => (
+                StatusCode::BAD_REQUEST,
+                ClientError::INVALID_FIELD {
+                    field: "certificate",
+                    message: message.to_string(),
+                },
+            ),
+            PrivateKeyInvalid(message) => (
+                StatusCode::BAD_REQUEST,
+                ClientError::INVALID_FIELD {
+                    field: "privateKey",
+                    message: message.to_string(),
+                },
+            ),
+            CertificateKeyMismatch => (
+                StatusCode::BAD_REQUEST,
+                ClientError::INVALID_FIELD {
+                    field: "privateKey",
+                    message: "The private key does not match any certificate"
+                        .to_string(),
+                },
+            ),
+            CertificateExpired { not_after } => (
+                StatusCode::BAD_REQUEST,
+                ClientError::INVALID_FIELD {
+                    field: "certificate",
+                    message: format!("The certificate expired on {not_after}"),
+                },
+            ),
+            CertificateNotYetValid { not_before } => (
+                StatusCode::BAD_REQUEST,
+                ClientError::INVALID_FIELD {
+                    field: "certificate",
+                    message: format!(
+                        "The certificate is not valid before {not_before}"
+                    ),
+                },
+            ),
+            CertificateChainInvalid(subject) => (
+                StatusCode::BAD_REQUEST,
+                ClientError::INVALID_FIELD {
+                    field: "certificate",
+                    message: format!(
+                        "'{subject}' is not an issuer of the certificate"
+                    ),
+                },
+            ),
+
+            // -- Acme
+            AcmeChallengeNotFound(token) => (
+                StatusCode::NOT_FOUND,
+                ClientError::ENTITY_NOT_FOUND {
+                    entity: "acme_challenge",
+                    id: token.to_string(),
+                },
+            ),
+            DnsRecordNotFound(id) => (
+                StatusCode::NOT_FOUND,
+                ClientError::ENTITY_NOT_FOUND {
+                    entity: "dns_record",
+                    id: id.to_string(),
+                },
+            ),
+
             // -- Auth
             CtxExt(_) => (StatusCode::FORBIDDEN, ClientError::NO_AUTH),
 
@@ -203,6 +625,134 @@ impl Error {
             | Model(model::Error::User(model::user::Error::UserNotFound {
                 ..
             })) => (StatusCode::FORBIDDEN, ClientError::LOGIN_FAIL),
+            Model(model::Error::ProxyHost(
+                model::proxy_host::Error::ProxyHostNotFound { id },
+            )) => (
+                StatusCode::NOT_FOUND,
+                ClientError::ENTITY_NOT_FOUND {
+                    entity: "proxy_host",
+                    id: id.to_string(),
+                },
+            ),
+            Model(model::Error::StreamHost(
+                model::stream_host::Error::StreamHostPortInUse {
+                    incoming_port,
+                    protocol,
+                },
+            )) => (
+                StatusCode::CONFLICT,
+                ClientError::PORT_IN_USE {
+                    port: *incoming_port,
+                    protocol: protocol.to_string(),
+                },
+            ),
+            Model(model::Error::StreamHost(
+                model::stream_host::Error::StreamHostNotFound { id },
+            )) => (
+                StatusCode::NOT_FOUND,
+                ClientError::ENTITY_NOT_FOUND {
+                    entity: "stream_host",
+                    id: id.to_string(),
+                },
+            ),
+            Model(model::Error::RedirectionHost(
+                model::redirection_host::Error::RedirectionHostNotFound { id },
+            )) => (
+                StatusCode::NOT_FOUND,
+                ClientError::ENTITY_NOT_FOUND {
+                    entity: "redirection_host",
+                    id: id.to_string(),
+                },
+            ),
+            Model(model::Error::DeadHost(
+                model::dead_host::Error::DeadHostNotFound { id },
+            )) => (
+                StatusCode::NOT_FOUND,
+                ClientError::ENTITY_NOT_FOUND {
+                    entity: "dead_host",
+                    id: id.to_string(),
+                },
+            ),
+            Model(model::Error::Certificate(
+                model::certificate::Error::CertificateNotFound { id },
+            ))
+            | Model(model::Error::ProxyHost(
+                model::proxy_host::Error::Certificate(
+                    model::certificate::Error::CertificateNotFound { id },
+                ),
+            )) => (
+                StatusCode::NOT_FOUND,
+                ClientError::ENTITY_NOT_FOUND {
+                    entity: "certificate",
+                    id: id.to_string(),
+                },
+            ),
+            Model(model::Error::Certificate(
+                model::certificate::Error::CertificateInUse { id },
+            )) => (
+                StatusCode::CONFLICT,
+                ClientError::ENTITY_IN_USE {
+                    entity: "certificate",
+                    id: id.to_string(),
+                },
+            ),
+            Model(model::Error::AccessList(
+                model::access_list::Error::AccessListNotFound { id },
+            ))
+            | Model(model::Error::ProxyHost(
+                model::proxy_host::Error::AccessList(
+                    model::access_list::Error::AccessListNotFound { id },
+                ),
+            )) => (
+                StatusCode::NOT_FOUND,
+                ClientError::ENTITY_NOT_FOUND {
+                    entity: "access_list",
+                    id: id.to_string(),
+                },
+            ),
+            Model(model::Error::AccessList(
+                model::access_list::Error::AccessListInUse { id },
+            )) => (
+                StatusCode::CONFLICT,
+                ClientError::ENTITY_IN_USE {
+                    entity: "access_list",
+                    id: id.to_string(),
+                },
+            ),
+            Model(model::Error::UpstreamGroup(
+                model::upstream_group::Error::UpstreamGroupNotFound { id },
+            ))
+            | Model(model::Error::ProxyHost(
+                model::proxy_host::Error::UpstreamGroup(
+                    model::upstream_group::Error::UpstreamGroupNotFound { id },
+                ),
+            )) => (
+                StatusCode::NOT_FOUND,
+                ClientError::ENTITY_NOT_FOUND {
+                    entity: "upstream_group",
+                    id: id.to_string(),
+                },
+            ),
+            Model(model::Error::UpstreamGroup(
+                model::upstream_group::Error::UpstreamGroupInUse { id },
+            )) => (
+                StatusCode::CONFLICT,
+                ClientError::ENTITY_IN_USE {
+                    entity: "upstream_group",
+                    id: id.to_string(),
+                },
+            ),
+            Model(model::Error::AccessList(
+                model::access_list::Error::AccessListUserNoPassword {
+                    username,
+                },
+            )) => (
+                StatusCode::BAD_REQUEST,
+                ClientError::INVALID_FIELD {
+                    field: "users",
+                    message: format!("'{username}' needs a password"),
+                },
+            ),
 
             // -- Tera.
             TeraRender(_) => (
@@ -219,18 +769,84 @@ impl Error {
     }
 }
 
+/// Parse error of a raw snippet, with the position for the user.
+fn snippet_error_message(cause: &lib_nginx::Error) -> String {
+    use lib_nginx::Error::*;
+
+    match cause {
+        UnexpectedEof { line, col } => {
+            format!("unexpected end at line {line}, column {col}")
+        }
+        UnexpectedToken { token, line, col } => {
+            format!("unexpected '{token}' at line {line}, column {col}")
+        }
+        UnexpectedClosingBrace { line, col } => {
+            format!("unexpected '}}' at line {line}, column {col}")
+        }
+        UnclosedBlock {
+            directive,
+            line,
+            col,
+        } => {
+            format!("'{directive}' at line {line}, column {col} is not closed")
+        }
+        UnterminatedQuote { line, col } => {
+            format!("unterminated quote at line {line}, column {col}")
+        }
+        DirectiveNotAllowed {
+            directive,
+            context,
+            line,
+            col,
+        } => format!(
+            "'{directive}' at line {line}, column {col} is not allowed in {context} blocks"
+        ),
+        InvalidArgs { directive } => {
+            format!("invalid arguments of '{directive}'")
+        }
+        ExpectedBlock { directive } => format!("'{directive}' needs a block"),
+    }
+}
+
 #[derive(Debug, Serialize, strum_macros::AsRefStr)]
 #[serde(tag = "message", content = "detail")]
 #[allow(non_camel_case_types)]
 pub enum ClientError {
-    ROUTE_NOT_EXIST { uri: String },
-    JSON_DESERIALIZE { message: &'static str },
-    FORM_DESERIALIZE { message: &'static str },
-    QUERY_DESERIALIZE { message: &'static str },
-    DATASTAR_QUERY_DESERIALIZATION { message: &'static str },
+    ROUTE_NOT_EXIST {
+        uri: String,
+    },
+    JSON_DESERIALIZE {
+        message: &'static str,
+    },
+    FORM_DESERIALIZE {
+        message: &'static str,
+    },
+    QUERY_DESERIALIZE {
+        message: &'static str,
+    },
+    INVALID_FIELD {
+        field: &'static str,
+        message: String,
+    },
+    DATASTAR_QUERY_DESERIALIZATION {
+        message: &'static str,
+    },
     LOGIN_FAIL,
     NO_AUTH,
-    ENTITY_NOT_FOUND { entity: &'static str, id: String },
+    ENTITY_NOT_FOUND {
+        entity: &'static str,
+        id: String,
+    },
+    /// Still referenced, e.g. an access list by proxy hosts.
+    ENTITY_IN_USE {
+        entity: &'static str,
+        id: String,
+    },
+    /// A stream host already listens on the port and protocol.
+    PORT_IN_USE {
+        port: u16,
+        protocol: String,
+    },
     USER_ALREADY_EXISTS,
     SERVICE_ERROR,
     UNSUPPORTED_MEDIA,
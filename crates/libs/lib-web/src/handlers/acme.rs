@@ -0,0 +1,17 @@
+use crate::error::{Error, Result};
+use axum::extract::{Path, State};
+use lib_core::acme::Http01Challenges;
+use tracing::debug;
+
+/// http-01 challenges of the running ACME orders, `text/plain` key
+/// authorization.
+pub async fn acme_challenge_handler(
+    State(challenges): State<Http01Challenges>,
+    Path(token): Path<String>,
+) -> Result<String> {
+    debug!("{:<12} - acme_challenge_handler", "HANDLER");
+
+    challenges
+        .key_authorization(&token)
+        .ok_or(Error::AcmeChallengeNotFound(token))
+}
@@ -0,0 +1,165 @@
+use crate::error::{Error, Result};
+use crate::middleware::mw_auth::CtxW;
+use crate::utils::validate::{validate_access_address, validate_username};
+
+use axum::Json;
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{Path, State};
+use lib_core::model::access_list::{
+    AccessListBmc, AccessListForCreate, AccessListForUpdate,
+    AccessListUserForSave, AccessRule,
+};
+use lib_core::model::{self, ModelManager};
+use serde_json::{Value, json};
+use std::collections::HashSet;
+use tracing::debug;
+
+// region:    --- Validation
+
+pub(crate) fn validate_access_list_c(
+    access_list_c: &AccessListForCreate,
+) -> Result<()> {
+    validate_name(&access_list_c.name)?;
+    validate_users(&access_list_c.users)?;
+    validate_rules(&access_list_c.rules)?;
+
+    Ok(())
+}
+
+pub(crate) fn validate_access_list_u(
+    access_list_u: &AccessListForUpdate,
+) -> Result<()> {
+    if let Some(name) = &access_list_u.name {
+        validate_name(name)?;
+    }
+    if let Some(users) = &access_list_u.users {
+        validate_users(users)?;
+    }
+    if let Some(rules) = &access_list_u.rules {
+        validate_rules(rules)?;
+    }
+
+    Ok(())
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    if name.trim().is_empty() {
+        return Err(Error::NoAccessListName);
+    }
+
+    Ok(())
+}
+
+fn validate_users(users: &[AccessListUserForSave]) -> Result<()> {
+    let mut usernames = HashSet::new();
+    for user in users {
+        validate_username(&user.username)?;
+        if !usernames.insert(user.username.as_str()) {
+            return Err(Error::DuplicateUsername(user.username.clone()));
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_rules(rules: &[AccessRule]) -> Result<()> {
+    for rule in rules {
+        validate_access_address(&rule.address)?;
+    }
+
+    Ok(())
+}
+
+// endregion: --- Validation
+
+pub async fn api_list_access_lists_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_list_access_lists_handler", "HANDLER");
+
+    let access_lists = AccessListBmc::list(&ctx, &mm)
+        .await
+        .map_err(model::Error::from)?;
+
+    Ok(Json(json!({ "result": access_lists })))
+}
+
+pub async fn api_get_access_list_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_get_access_list_handler", "HANDLER");
+
+    let access_list = AccessListBmc::get(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    Ok(Json(json!({ "result": access_list })))
+}
+
+pub async fn api_create_access_list_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    payload_or_error: std::result::Result<
+        Json<AccessListForCreate>,
+        JsonRejection,
+    >,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_create_access_list_handler", "HANDLER");
+
+    let payload = payload_or_error?.0;
+    validate_access_list_c(&payload)?;
+
+    let id = AccessListBmc::create(&ctx, &mm, payload)
+        .await
+        .map_err(model::Error::from)?;
+    let access_list = AccessListBmc::get(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    Ok(Json(json!({ "result": access_list })))
+}
+
+pub async fn api_update_access_list_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+    payload_or_error: std::result::Result<
+        Json<AccessListForUpdate>,
+        JsonRejection,
+    >,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_update_access_list_handler", "HANDLER");
+
+    let payload = payload_or_error?.0;
+    validate_access_list_u(&payload)?;
+
+    AccessListBmc::update(&ctx, &mm, id, payload)
+        .await
+        .map_err(model::Error::from)?;
+    let access_list = AccessListBmc::get(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    Ok(Json(json!({ "result": access_list })))
+}
+
+pub async fn api_delete_access_list_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_delete_access_list_handler", "HANDLER");
+
+    AccessListBmc::delete(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    Ok(Json(json!({
+     "result": {
+      "success": true
+     }
+    })))
+}
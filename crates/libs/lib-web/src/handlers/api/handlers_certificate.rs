@@ -0,0 +1,151 @@
+use crate::error::{Error, Result};
+use crate::middleware::mw_auth::CtxW;
+use crate::utils::certificate::parse_certificate_upload;
+use crate::utils::validate::validate_domain_names;
+
+use axum::Json;
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{Path, State};
+use lib_core::model::certificate::{
+    CertificateBmc, CertificateChallenge, CertificateForCreate,
+    CertificateForUpload,
+};
+use lib_core::model::{self, ModelManager};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use tracing::debug;
+
+// region:    --- Validation
+
+pub(crate) fn validate_certificate_c(
+    certificate_c: &CertificateForCreate,
+) -> Result<()> {
+    validate_domain_names(&certificate_c.domain_names)?;
+
+    if certificate_c.challenge == CertificateChallenge::Http01
+        && let Some(wildcard) = certificate_c
+            .domain_names
+            .iter()
+            .find(|domain| domain.starts_with("*."))
+    {
+        return Err(Error::WildcardNeedsDns01(wildcard.to_string()));
+    }
+
+    Ok(())
+}
+
+// endregion: --- Validation
+
+pub async fn api_list_certificates_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_list_certificates_handler", "HANDLER");
+
+    let certificates = CertificateBmc::list(&ctx, &mm)
+        .await
+        .map_err(model::Error::from)?;
+
+    Ok(Json(json!({ "result": certificates })))
+}
+
+pub async fn api_get_certificate_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_get_certificate_handler", "HANDLER");
+
+    let certificate = CertificateBmc::get(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    Ok(Json(json!({ "result": certificate })))
+}
+
+/// Request an ACME certificate, issued in the background: poll it until
+/// `notAfter` (or `renewError`) is set. Wildcard domains need the
+/// `dns-01` challenge.
+pub async fn api_create_certificate_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    payload_or_error: std::result::Result<
+        Json<CertificateForCreate>,
+        JsonRejection,
+    >,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_create_certificate_handler", "HANDLER");
+
+    let payload = payload_or_error?.0;
+    validate_certificate_c(&payload)?;
+
+    let id = CertificateBmc::create(&ctx, &mm, payload)
+        .await
+        .map_err(model::Error::from)?;
+    let certificate = CertificateBmc::get(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    Ok(Json(json!({ "result": certificate })))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomCertificatePayload {
+    #[serde(default)]
+    nice_name: String,
+    /// PEM, the leaf and its chain in any order.
+    certificate: String,
+    /// PEM, PKCS#8, PKCS#1 or SEC1.
+    private_key: String,
+}
+
+/// Upload a certificate, stored as is and never renewed.
+pub async fn api_create_custom_certificate_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    payload_or_error: std::result::Result<
+        Json<CustomCertificatePayload>,
+        JsonRejection,
+    >,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_create_custom_certificate_handler", "HANDLER");
+
+    let payload = payload_or_error?.0;
+    let bundle =
+        parse_certificate_upload(&payload.certificate, &payload.private_key)?;
+
+    let id = CertificateBmc::create_custom(
+        &ctx,
+        &mm,
+        CertificateForUpload {
+            nice_name: payload.nice_name.trim().to_string(),
+            bundle,
+        },
+    )
+    .await
+    .map_err(model::Error::from)?;
+    let certificate = CertificateBmc::get(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    Ok(Json(json!({ "result": certificate })))
+}
+
+pub async fn api_delete_certificate_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_delete_certificate_handler", "HANDLER");
+
+    CertificateBmc::delete(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    Ok(Json(json!({
+     "result": {
+      "success": true
+     }
+    })))
+}
@@ -0,0 +1,191 @@
+use crate::error::Result;
+use crate::middleware::mw_auth::CtxW;
+use crate::utils::error_page::{
+    UploadedFile, decode_error_page, remove_error_page,
+    save_dead_host_error_page,
+};
+use crate::utils::validate::validate_domain_names;
+
+use axum::Json;
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{Path, State};
+use lib_core::model::dead_host::{
+    DeadHostBmc, DeadHostForCreate, DeadHostForUpdate,
+};
+use lib_core::model::{self, ModelManager};
+use serde_json::{Value, json};
+use tracing::debug;
+
+// region:    --- Validation
+
+pub(crate) fn validate_dead_host_c(
+    dead_host_c: &DeadHostForCreate,
+) -> Result<()> {
+    validate_domain_names(&dead_host_c.domain_names)?;
+
+    Ok(())
+}
+
+pub(crate) fn validate_dead_host_u(
+    dead_host_u: &DeadHostForUpdate,
+) -> Result<()> {
+    if let Some(domain_names) = &dead_host_u.domain_names {
+        validate_domain_names(domain_names)?;
+    }
+
+    Ok(())
+}
+
+// endregion: --- Validation
+
+pub async fn api_list_dead_hosts_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_list_dead_hosts_handler", "HANDLER");
+
+    let dead_hosts = DeadHostBmc::list(&ctx, &mm)
+        .await
+        .map_err(model::Error::from)?;
+
+    Ok(Json(json!({ "result": dead_hosts })))
+}
+
+pub async fn api_get_dead_host_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_get_dead_host_handler", "HANDLER");
+
+    let dead_host = DeadHostBmc::get(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    Ok(Json(json!({ "result": dead_host })))
+}
+
+pub async fn api_create_dead_host_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    payload_or_error: std::result::Result<
+        Json<DeadHostForCreate>,
+        JsonRejection,
+    >,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_create_dead_host_handler", "HANDLER");
+
+    let payload = payload_or_error?.0;
+    validate_dead_host_c(&payload)?;
+
+    let id = DeadHostBmc::create(&ctx, &mm, payload)
+        .await
+        .map_err(model::Error::from)?;
+    let dead_host = DeadHostBmc::get(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    Ok(Json(json!({ "result": dead_host })))
+}
+
+pub async fn api_update_dead_host_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+    payload_or_error: std::result::Result<
+        Json<DeadHostForUpdate>,
+        JsonRejection,
+    >,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_update_dead_host_handler", "HANDLER");
+
+    let payload = payload_or_error?.0;
+    validate_dead_host_u(&payload)?;
+
+    DeadHostBmc::update(&ctx, &mm, id, payload)
+        .await
+        .map_err(model::Error::from)?;
+    let dead_host = DeadHostBmc::get(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    Ok(Json(json!({ "result": dead_host })))
+}
+
+pub async fn api_delete_dead_host_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_delete_dead_host_handler", "HANDLER");
+
+    let dead_host = DeadHostBmc::get(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+    DeadHostBmc::delete(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+    if let Some(error_page) = &dead_host.error_page {
+        remove_error_page(error_page).await?;
+    }
+
+    Ok(Json(json!({
+     "result": {
+      "success": true
+     }
+    })))
+}
+
+/// Payload: a file as sent by the UI, `{"name", "contents", "mime"}`
+/// with base64 `contents`.
+pub async fn api_set_dead_host_error_page_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+    payload_or_error: std::result::Result<Json<UploadedFile>, JsonRejection>,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_set_dead_host_error_page_handler", "HANDLER");
+
+    let payload = payload_or_error?.0;
+    let content = decode_error_page(&payload)?;
+
+    // Checks the ownership before writing the file.
+    DeadHostBmc::get(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+    let error_page = save_dead_host_error_page(id, &content).await?;
+    DeadHostBmc::set_error_page(&ctx, &mm, id, Some(error_page))
+        .await
+        .map_err(model::Error::from)?;
+    let dead_host = DeadHostBmc::get(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    Ok(Json(json!({ "result": dead_host })))
+}
+
+pub async fn api_remove_dead_host_error_page_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+) -> Result<Json<Value>> {
+    debug!(
+        "{:<12} - api_remove_dead_host_error_page_handler",
+        "HANDLER"
+    );
+
+    let dead_host = DeadHostBmc::get(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+    if let Some(error_page) = &dead_host.error_page {
+        DeadHostBmc::set_error_page(&ctx, &mm, id, None)
+            .await
+            .map_err(model::Error::from)?;
+        remove_error_page(error_page).await?;
+    }
+    let dead_host = DeadHostBmc::get(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    Ok(Json(json!({ "result": dead_host })))
+}
@@ -0,0 +1,232 @@
+use crate::error::Result;
+use crate::middleware::mw_auth::CtxW;
+use crate::utils::validate::{
+    validate_connection_limit, validate_domain_names, validate_forward_host,
+    validate_header, validate_health_check_interval,
+    validate_health_check_path, validate_host_snippet, validate_location_paths,
+    validate_location_snippet, validate_port, validate_rate_limit,
+    validate_rate_limit_burst,
+};
+
+use axum::Json;
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{Path, State};
+use lib_core::model::health_check::HealthCheckBmc;
+use lib_core::model::proxy_host::{
+    ProxyHostBmc, ProxyHostForCreate, ProxyHostForUpdate, ProxyLocation,
+};
+use lib_core::model::{self, ModelManager};
+use serde_json::{Value, json};
+use tracing::debug;
+
+// region:    --- Validation
+
+pub(crate) fn validate_proxy_host_c(
+    proxy_host_c: &ProxyHostForCreate,
+) -> Result<()> {
+    validate_domain_names(&proxy_host_c.domain_names)?;
+    // The servers of an upstream group replace the forward host.
+    if proxy_host_c.upstream_group_id.is_none() {
+        validate_forward_host(&proxy_host_c.forward_host)?;
+    }
+    validate_port(proxy_host_c.forward_port)?;
+    validate_locations(&proxy_host_c.locations)?;
+    validate_host_snippet(&proxy_host_c.advanced)?;
+    if let Some(rate) = proxy_host_c.rate_limit {
+        validate_rate_limit(rate)?;
+    }
+    validate_rate_limit_burst(proxy_host_c.rate_limit_burst)?;
+    if let Some(limit) = proxy_host_c.connection_limit {
+        validate_connection_limit(limit)?;
+    }
+    if let Some(path) = &proxy_host_c.health_check_path {
+        validate_health_check_path(path)?;
+    }
+    validate_health_check_interval(proxy_host_c.health_check_interval)?;
+
+    Ok(())
+}
+
+pub(crate) fn validate_proxy_host_u(
+    proxy_host_u: &ProxyHostForUpdate,
+) -> Result<()> {
+    if let Some(domain_names) = &proxy_host_u.domain_names {
+        validate_domain_names(domain_names)?;
+    }
+    if let Some(forward_host) = &proxy_host_u.forward_host
+        && !matches!(proxy_host_u.upstream_group_id, Some(Some(_)))
+    {
+        validate_forward_host(forward_host)?;
+    }
+    if let Some(forward_port) = proxy_host_u.forward_port {
+        validate_port(forward_port)?;
+    }
+    if let Some(locations) = &proxy_host_u.locations {
+        validate_locations(locations)?;
+    }
+    if let Some(advanced) = &proxy_host_u.advanced {
+        validate_host_snippet(advanced)?;
+    }
+    if let Some(Some(rate)) = proxy_host_u.rate_limit {
+        validate_rate_limit(rate)?;
+    }
+    if let Some(burst) = proxy_host_u.rate_limit_burst {
+        validate_rate_limit_burst(burst)?;
+    }
+    if let Some(Some(limit)) = proxy_host_u.connection_limit {
+        validate_connection_limit(limit)?;
+    }
+    if let Some(Some(path)) = &proxy_host_u.health_check_path {
+        validate_health_check_path(path)?;
+    }
+    if let Some(interval) = proxy_host_u.health_check_interval {
+        validate_health_check_interval(interval)?;
+    }
+
+    Ok(())
+}
+
+fn validate_locations(locations: &[ProxyLocation]) -> Result<()> {
+    let paths: Vec<&str> = locations
+        .iter()
+        .map(|location| location.path.as_str())
+        .collect();
+    validate_location_paths(&paths)?;
+
+    for location in locations {
+        validate_forward_host(&location.forward_host)?;
+        validate_port(location.forward_port)?;
+        for header in &location.headers {
+            validate_header(&header.name, &header.value)?;
+        }
+        validate_location_snippet(&location.path, &location.advanced)?;
+    }
+
+    Ok(())
+}
+
+// endregion: --- Validation
+
+pub async fn api_list_proxy_hosts_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_list_proxy_hosts_handler", "HANDLER");
+
+    let proxy_hosts = ProxyHostBmc::list(&ctx, &mm)
+        .await
+        .map_err(model::Error::from)?;
+
+    Ok(Json(json!({ "result": proxy_hosts })))
+}
+
+pub async fn api_get_proxy_host_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_get_proxy_host_handler", "HANDLER");
+
+    let proxy_host = ProxyHostBmc::get(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    Ok(Json(json!({ "result": proxy_host })))
+}
+
+/// The kept health check results of the host, the most recent first.
+pub async fn api_list_proxy_host_health_checks_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+) -> Result<Json<Value>> {
+    debug!(
+        "{:<12} - api_list_proxy_host_health_checks_handler",
+        "HANDLER"
+    );
+
+    // Not found rather than an empty list for the hosts of other users.
+    ProxyHostBmc::get(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+    let health_checks = HealthCheckBmc::list(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    Ok(Json(json!({ "result": health_checks })))
+}
+
+pub async fn api_create_proxy_host_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    payload_or_error: std::result::Result<
+        Json<ProxyHostForCreate>,
+        JsonRejection,
+    >,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_create_proxy_host_handler", "HANDLER");
+
+    let payload = payload_or_error?.0;
+    validate_proxy_host_c(&payload)?;
+
+    let id = ProxyHostBmc::create(&ctx, &mm, payload)
+        .await
+        .map_err(model::Error::from)?;
+    let proxy_host = ProxyHostBmc::get(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    Ok(Json(json!({ "result": proxy_host })))
+}
+
+pub async fn api_update_proxy_host_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+    payload_or_error: std::result::Result<
+        Json<ProxyHostForUpdate>,
+        JsonRejection,
+    >,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_update_proxy_host_handler", "HANDLER");
+
+    let payload = payload_or_error?.0;
+    validate_proxy_host_u(&payload)?;
+    // Detached from its group, the host forwards to its stored forward host
+    // again, which was not checked while the group replaced it.
+    if matches!(payload.upstream_group_id, Some(None))
+        && payload.forward_host.is_none()
+    {
+        let proxy_host = ProxyHostBmc::get(&ctx, &mm, id)
+            .await
+            .map_err(model::Error::from)?;
+        validate_forward_host(&proxy_host.forward_host)?;
+    }
+
+    ProxyHostBmc::update(&ctx, &mm, id, payload)
+        .await
+        .map_err(model::Error::from)?;
+    let proxy_host = ProxyHostBmc::get(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    Ok(Json(json!({ "result": proxy_host })))
+}
+
+pub async fn api_delete_proxy_host_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_delete_proxy_host_handler", "HANDLER");
+
+    ProxyHostBmc::delete(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    Ok(Json(json!({
+     "result": {
+      "success": true
+     }
+    })))
+}
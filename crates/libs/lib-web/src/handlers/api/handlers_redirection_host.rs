@@ -0,0 +1,137 @@
+use crate::error::Result;
+use crate::middleware::mw_auth::CtxW;
+use crate::utils::validate::{
+    validate_domain_names, validate_forward_url, validate_redirect_code,
+};
+
+use axum::Json;
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{Path, State};
+use lib_core::model::redirection_host::{
+    RedirectionHostBmc, RedirectionHostForCreate, RedirectionHostForUpdate,
+};
+use lib_core::model::{self, ModelManager};
+use serde_json::{Value, json};
+use tracing::debug;
+
+// region:    --- Validation
+
+pub(crate) fn validate_redirection_host_c(
+    redirection_host_c: &RedirectionHostForCreate,
+) -> Result<()> {
+    validate_domain_names(&redirection_host_c.domain_names)?;
+    validate_forward_url(&redirection_host_c.forward_url)?;
+    validate_redirect_code(redirection_host_c.redirect_code)?;
+
+    Ok(())
+}
+
+pub(crate) fn validate_redirection_host_u(
+    redirection_host_u: &RedirectionHostForUpdate,
+) -> Result<()> {
+    if let Some(domain_names) = &redirection_host_u.domain_names {
+        validate_domain_names(domain_names)?;
+    }
+    if let Some(forward_url) = &redirection_host_u.forward_url {
+        validate_forward_url(forward_url)?;
+    }
+    if let Some(redirect_code) = redirection_host_u.redirect_code {
+        validate_redirect_code(redirect_code)?;
+    }
+
+    Ok(())
+}
+
+// endregion: --- Validation
+
+pub async fn api_list_redirection_hosts_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_list_redirection_hosts_handler", "HANDLER");
+
+    let redirection_hosts = RedirectionHostBmc::list(&ctx, &mm)
+        .await
+        .map_err(model::Error::from)?;
+
+    Ok(Json(json!({ "result": redirection_hosts })))
+}
+
+pub async fn api_get_redirection_host_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_get_redirection_host_handler", "HANDLER");
+
+    let redirection_host = RedirectionHostBmc::get(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    Ok(Json(json!({ "result": redirection_host })))
+}
+
+pub async fn api_create_redirection_host_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    payload_or_error: std::result::Result<
+        Json<RedirectionHostForCreate>,
+        JsonRejection,
+    >,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_create_redirection_host_handler", "HANDLER");
+
+    let payload = payload_or_error?.0;
+    validate_redirection_host_c(&payload)?;
+
+    let id = RedirectionHostBmc::create(&ctx, &mm, payload)
+        .await
+        .map_err(model::Error::from)?;
+    let redirection_host = RedirectionHostBmc::get(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    Ok(Json(json!({ "result": redirection_host })))
+}
+
+pub async fn api_update_redirection_host_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+    payload_or_error: std::result::Result<
+        Json<RedirectionHostForUpdate>,
+        JsonRejection,
+    >,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_update_redirection_host_handler", "HANDLER");
+
+    let payload = payload_or_error?.0;
+    validate_redirection_host_u(&payload)?;
+
+    RedirectionHostBmc::update(&ctx, &mm, id, payload)
+        .await
+        .map_err(model::Error::from)?;
+    let redirection_host = RedirectionHostBmc::get(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    Ok(Json(json!({ "result": redirection_host })))
+}
+
+pub async fn api_delete_redirection_host_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_delete_redirection_host_handler", "HANDLER");
+
+    RedirectionHostBmc::delete(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    Ok(Json(json!({
+     "result": {
+      "success": true
+     }
+    })))
+}
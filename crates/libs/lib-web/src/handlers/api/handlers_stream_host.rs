@@ -0,0 +1,137 @@
+use crate::error::Result;
+use crate::middleware::mw_auth::CtxW;
+use crate::utils::validate::{
+    validate_forward_host, validate_incoming_port, validate_port,
+};
+
+use axum::Json;
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{Path, State};
+use lib_core::model::stream_host::{
+    StreamHostBmc, StreamHostForCreate, StreamHostForUpdate,
+};
+use lib_core::model::{self, ModelManager};
+use serde_json::{Value, json};
+use tracing::debug;
+
+// region:    --- Validation
+
+pub(crate) fn validate_stream_host_c(
+    stream_host_c: &StreamHostForCreate,
+) -> Result<()> {
+    validate_incoming_port(stream_host_c.incoming_port)?;
+    validate_forward_host(&stream_host_c.forward_host)?;
+    validate_port(stream_host_c.forward_port)?;
+
+    Ok(())
+}
+
+pub(crate) fn validate_stream_host_u(
+    stream_host_u: &StreamHostForUpdate,
+) -> Result<()> {
+    if let Some(incoming_port) = stream_host_u.incoming_port {
+        validate_incoming_port(incoming_port)?;
+    }
+    if let Some(forward_host) = &stream_host_u.forward_host {
+        validate_forward_host(forward_host)?;
+    }
+    if let Some(forward_port) = stream_host_u.forward_port {
+        validate_port(forward_port)?;
+    }
+
+    Ok(())
+}
+
+// endregion: --- Validation
+
+pub async fn api_list_stream_hosts_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_list_stream_hosts_handler", "HANDLER");
+
+    let stream_hosts = StreamHostBmc::list(&ctx, &mm)
+        .await
+        .map_err(model::Error::from)?;
+
+    Ok(Json(json!({ "result": stream_hosts })))
+}
+
+pub async fn api_get_stream_host_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_get_stream_host_handler", "HANDLER");
+
+    let stream_host = StreamHostBmc::get(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    Ok(Json(json!({ "result": stream_host })))
+}
+
+pub async fn api_create_stream_host_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    payload_or_error: std::result::Result<
+        Json<StreamHostForCreate>,
+        JsonRejection,
+    >,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_create_stream_host_handler", "HANDLER");
+
+    let payload = payload_or_error?.0;
+    validate_stream_host_c(&payload)?;
+
+    let id = StreamHostBmc::create(&ctx, &mm, payload)
+        .await
+        .map_err(model::Error::from)?;
+    let stream_host = StreamHostBmc::get(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    Ok(Json(json!({ "result": stream_host })))
+}
+
+pub async fn api_update_stream_host_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+    payload_or_error: std::result::Result<
+        Json<StreamHostForUpdate>,
+        JsonRejection,
+    >,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_update_stream_host_handler", "HANDLER");
+
+    let payload = payload_or_error?.0;
+    validate_stream_host_u(&payload)?;
+
+    StreamHostBmc::update(&ctx, &mm, id, payload)
+        .await
+        .map_err(model::Error::from)?;
+    let stream_host = StreamHostBmc::get(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    Ok(Json(json!({ "result": stream_host })))
+}
+
+pub async fn api_delete_stream_host_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_delete_stream_host_handler", "HANDLER");
+
+    StreamHostBmc::delete(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    Ok(Json(json!({
+     "result": {
+      "success": true
+     }
+    })))
+}
@@ -0,0 +1,169 @@
+use crate::error::{Error, Result};
+use crate::middleware::mw_auth::CtxW;
+use crate::utils::validate::{validate_forward_host, validate_port};
+
+use axum::Json;
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{Path, State};
+use lib_core::model::upstream_group::{
+    UpstreamBalance, UpstreamGroupBmc, UpstreamGroupForCreate,
+    UpstreamGroupForUpdate, UpstreamGroupServer,
+};
+use lib_core::model::{self, ModelManager};
+use serde_json::{Value, json};
+use tracing::debug;
+
+// region:    --- Validation
+
+pub(crate) fn validate_upstream_group_c(
+    upstream_group_c: &UpstreamGroupForCreate,
+) -> Result<()> {
+    validate_name(&upstream_group_c.name)?;
+    validate_servers(upstream_group_c.balance, &upstream_group_c.servers)?;
+
+    Ok(())
+}
+
+/// The backup check needs both the balance and the servers, it is left to
+/// the complete form when only one of them is updated.
+pub(crate) fn validate_upstream_group_u(
+    upstream_group_u: &UpstreamGroupForUpdate,
+) -> Result<()> {
+    if let Some(name) = &upstream_group_u.name {
+        validate_name(name)?;
+    }
+    if let Some(servers) = &upstream_group_u.servers {
+        let balance = upstream_group_u.balance.unwrap_or_default();
+        validate_servers(balance, servers)?;
+    }
+
+    Ok(())
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    if name.trim().is_empty() {
+        return Err(Error::NoUpstreamGroupName);
+    }
+
+    Ok(())
+}
+
+fn validate_servers(
+    balance: UpstreamBalance,
+    servers: &[UpstreamGroupServer],
+) -> Result<()> {
+    if servers.is_empty() {
+        return Err(Error::NoUpstreamServer);
+    }
+
+    for server in servers {
+        let address = format!("{}:{}", server.host, server.port);
+        validate_forward_host(&server.host)
+            .and_then(|_| validate_port(server.port))
+            .map_err(|_| Error::InvalidUpstreamServer(address.clone()))?;
+        // nginx rejects `weight=0`.
+        if server.weight == Some(0) {
+            return Err(Error::InvalidUpstreamServer(address));
+        }
+        // nginx rejects the `backup` parameter with `ip_hash`.
+        if server.backup && balance == UpstreamBalance::IpHash {
+            return Err(Error::BackupWithIpHash(address));
+        }
+    }
+
+    Ok(())
+}
+
+// endregion: --- Validation
+
+pub async fn api_list_upstream_groups_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_list_upstream_groups_handler", "HANDLER");
+
+    let upstream_groups = UpstreamGroupBmc::list(&ctx, &mm)
+        .await
+        .map_err(model::Error::from)?;
+
+    Ok(Json(json!({ "result": upstream_groups })))
+}
+
+pub async fn api_get_upstream_group_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_get_upstream_group_handler", "HANDLER");
+
+    let upstream_group = UpstreamGroupBmc::get(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    Ok(Json(json!({ "result": upstream_group })))
+}
+
+pub async fn api_create_upstream_group_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    payload_or_error: std::result::Result<
+        Json<UpstreamGroupForCreate>,
+        JsonRejection,
+    >,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_create_upstream_group_handler", "HANDLER");
+
+    let payload = payload_or_error?.0;
+    validate_upstream_group_c(&payload)?;
+
+    let id = UpstreamGroupBmc::create(&ctx, &mm, payload)
+        .await
+        .map_err(model::Error::from)?;
+    let upstream_group = UpstreamGroupBmc::get(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    Ok(Json(json!({ "result": upstream_group })))
+}
+
+pub async fn api_update_upstream_group_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+    payload_or_error: std::result::Result<
+        Json<UpstreamGroupForUpdate>,
+        JsonRejection,
+    >,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_update_upstream_group_handler", "HANDLER");
+
+    let payload = payload_or_error?.0;
+    validate_upstream_group_u(&payload)?;
+
+    UpstreamGroupBmc::update(&ctx, &mm, id, payload)
+        .await
+        .map_err(model::Error::from)?;
+    let upstream_group = UpstreamGroupBmc::get(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    Ok(Json(json!({ "result": upstream_group })))
+}
+
+pub async fn api_delete_upstream_group_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_delete_upstream_group_handler", "HANDLER");
+
+    UpstreamGroupBmc::delete(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    Ok(Json(json!({
+     "result": {
+      "success": true
+     }
+    })))
+}
@@ -1,7 +1,14 @@
 use crate::error::{Error, Result};
 use axum::http::Uri;
 
+pub mod handlers_access_list;
+pub mod handlers_certificate;
+pub mod handlers_dead_host;
 pub mod handlers_login;
+pub mod handlers_proxy_host;
+pub mod handlers_redirection_host;
+pub mod handlers_stream_host;
+pub mod handlers_upstream_group;
 
 pub async fn fallback(uri: Uri) -> Result<()> {
     Err(Error::RouteNotExist(uri.to_string()))
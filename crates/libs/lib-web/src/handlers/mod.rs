@@ -1,2 +1,3 @@
+pub mod acme;
 pub mod api;
 pub mod web;
@@ -0,0 +1,12 @@
+use crate::{error::Result, tera::render};
+use axum::response::IntoResponse;
+use tera::Context;
+use tracing::debug;
+
+pub async fn render_certificates() -> Result<impl IntoResponse> {
+    debug!("{:<12} - web_certificates_handler", "HANDLER");
+
+    let context = Context::new();
+    render("routes/certificates.html", &context)
+        .map(IntoResponse::into_response)
+}
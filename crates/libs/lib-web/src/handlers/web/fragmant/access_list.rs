@@ -0,0 +1,336 @@
+//! Server rendered fragments of the `/access-lists` page.
+//!
+//! Same signals as the `/proxy` page (`search` and `form`), patched into
+//! `#access-list-rows` and `#access-list-form`. The users and the ip rules
+//! are edited as text, one per line:
+//! - users: `username:password`, a bare `username` keeps its password.
+//! - rules: `allow 10.0.0.0/8`, `deny all`.
+
+use crate::error::{ClientError, Error, Result};
+use crate::extractors::{DatastarQuery, DatastarQueryError};
+use crate::handlers::api::handlers_access_list::{
+    validate_access_list_c, validate_access_list_u,
+};
+use crate::middleware::mw_auth::CtxW;
+use crate::tera::render_fragmant;
+
+use axum::Json;
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{Path, State};
+use axum::response::Html;
+use lib_core::ctx::Ctx;
+use lib_core::model::access_list::{
+    AccessList, AccessListBmc, AccessListForCreate, AccessListForUpdate,
+    AccessListSatisfy, AccessListUserForSave, AccessRule, AccessRuleAction,
+};
+use lib_core::model::{self, ModelManager};
+use serde::{Deserialize, Serialize};
+use tera::Context;
+use tracing::debug;
+
+// region:    --- Signals
+
+#[derive(Debug, Default, Deserialize)]
+pub struct AccessListListSignals {
+    #[serde(default)]
+    search: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AccessListFormSignals {
+    #[serde(default)]
+    search: String,
+    form: AccessListForm,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AccessListForm {
+    name: String,
+    satisfy: AccessListSatisfy,
+    /// One `username[:password]` per line.
+    users: String,
+    /// One `allow|deny address` per line.
+    rules: String,
+}
+
+impl AccessListForm {
+    fn users(&self) -> Vec<AccessListUserForSave> {
+        lines(&self.users)
+            .map(|line| match line.split_once(':') {
+                Some((username, password)) => AccessListUserForSave {
+                    username: username.to_string(),
+                    password: Some(password.to_string()),
+                },
+                None => AccessListUserForSave {
+                    username: line.to_string(),
+                    password: None,
+                },
+            })
+            .collect()
+    }
+
+    fn rules(&self) -> Result<Vec<AccessRule>> {
+        lines(&self.rules)
+            .map(|line| {
+                let invalid = || Error::InvalidAccessRule(line.to_string());
+                let (action, address) =
+                    line.split_once(char::is_whitespace).ok_or_else(invalid)?;
+                let action = match action.to_lowercase().as_str() {
+                    "allow" => AccessRuleAction::Allow,
+                    "deny" => AccessRuleAction::Deny,
+                    _ => return Err(invalid()),
+                };
+
+                Ok(AccessRule {
+                    action,
+                    address: address.trim().to_string(),
+                })
+            })
+            .collect()
+    }
+}
+
+impl From<&AccessList> for AccessListForm {
+    fn from(access_list: &AccessList) -> Self {
+        let rules: Vec<String> = access_list
+            .rules
+            .iter()
+            .map(|rule| format!("{} {}", rule.action, rule.address))
+            .collect();
+
+        AccessListForm {
+            name: access_list.name.clone(),
+            satisfy: access_list.satisfy,
+            users: access_list.usernames.join("\n"),
+            rules: rules.join("\n"),
+        }
+    }
+}
+
+impl TryFrom<&AccessListForm> for AccessListForCreate {
+    type Error = Error;
+
+    fn try_from(form: &AccessListForm) -> Result<Self> {
+        Ok(AccessListForCreate {
+            name: form.name.trim().to_string(),
+            satisfy: form.satisfy,
+            users: form.users(),
+            rules: form.rules()?,
+        })
+    }
+}
+
+impl TryFrom<&AccessListForm> for AccessListForUpdate {
+    type Error = Error;
+
+    fn try_from(form: &AccessListForm) -> Result<Self> {
+        Ok(AccessListForUpdate {
+            name: Some(form.name.trim().to_string()),
+            satisfy: Some(form.satisfy),
+            users: Some(form.users()),
+            rules: Some(form.rules()?),
+        })
+    }
+}
+
+/// The trimmed non-empty lines of a textarea.
+fn lines(text: &str) -> impl Iterator<Item = &str> {
+    text.lines().map(str::trim).filter(|line| !line.is_empty())
+}
+
+// endregion: --- Signals
+
+// region:    --- Handlers
+
+pub async fn fragmant_access_list_rows(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    signals: std::result::Result<
+        DatastarQuery<AccessListListSignals>,
+        DatastarQueryError,
+    >,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_access_list_rows", "HANDLER");
+
+    let DatastarQuery(signals) = signals?;
+
+    render_rows(&ctx, &mm, &signals.search).await
+}
+
+pub async fn fragmant_access_list_new_form() -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_access_list_new_form", "HANDLER");
+
+    render_form(None, &AccessListForm::default(), None)
+}
+
+pub async fn fragmant_access_list_edit_form(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_access_list_edit_form", "HANDLER");
+
+    let access_list = AccessListBmc::get(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    render_form(Some(id), &AccessListForm::from(&access_list), None)
+}
+
+pub async fn fragmant_access_list_close_form() -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_access_list_close_form", "HANDLER");
+
+    render_fragmant("fragmants/access_list/form.html", &Context::new())
+}
+
+pub async fn fragmant_access_list_create(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    signals: std::result::Result<Json<AccessListFormSignals>, JsonRejection>,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_access_list_create", "HANDLER");
+
+    let AccessListFormSignals { search, form } = signals?.0;
+
+    let access_list_c =
+        match AccessListForCreate::try_from(&form).and_then(|access_list_c| {
+            validate_access_list_c(&access_list_c).map(|_| access_list_c)
+        }) {
+            Ok(access_list_c) => access_list_c,
+            Err(error) => return render_form_error(None, &form, error),
+        };
+
+    // A new user without password is only known by the model.
+    if let Err(error) = AccessListBmc::create(&ctx, &mm, access_list_c).await {
+        return render_form_error(
+            None,
+            &form,
+            model::Error::from(error).into(),
+        );
+    }
+
+    render_saved(&ctx, &mm, &search).await
+}
+
+pub async fn fragmant_access_list_update(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+    signals: std::result::Result<Json<AccessListFormSignals>, JsonRejection>,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_access_list_update", "HANDLER");
+
+    let AccessListFormSignals { search, form } = signals?.0;
+
+    let access_list_u =
+        match AccessListForUpdate::try_from(&form).and_then(|access_list_u| {
+            validate_access_list_u(&access_list_u).map(|_| access_list_u)
+        }) {
+            Ok(access_list_u) => access_list_u,
+            Err(error) => return render_form_error(Some(id), &form, error),
+        };
+
+    if let Err(error) =
+        AccessListBmc::update(&ctx, &mm, id, access_list_u).await
+    {
+        return render_form_error(
+            Some(id),
+            &form,
+            model::Error::from(error).into(),
+        );
+    }
+
+    render_saved(&ctx, &mm, &search).await
+}
+
+pub async fn fragmant_access_list_delete(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+    signals: std::result::Result<Json<AccessListListSignals>, JsonRejection>,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_access_list_delete", "HANDLER");
+
+    let Json(signals) = signals?;
+
+    AccessListBmc::delete(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    render_rows(&ctx, &mm, &signals.search).await
+}
+
+// endregion: --- Handlers
+
+// region:    --- Render
+
+async fn render_rows(
+    ctx: &Ctx,
+    mm: &ModelManager,
+    search: &str,
+) -> Result<Html<String>> {
+    let search = search.trim().to_lowercase();
+    let access_lists: Vec<AccessList> = AccessListBmc::list(ctx, mm)
+        .await
+        .map_err(model::Error::from)?
+        .into_iter()
+        .filter(|access_list| {
+            search.is_empty()
+                || access_list.name.to_lowercase().contains(&search)
+        })
+        .collect();
+
+    let mut context = Context::new();
+    context.insert("access_lists", &access_lists);
+    context.insert("search", &search);
+
+    render_fragmant("fragmants/access_list/rows.html", &context)
+}
+
+fn render_form(
+    id: Option<i64>,
+    form: &AccessListForm,
+    error: Option<String>,
+) -> Result<Html<String>> {
+    let mut context = Context::new();
+    context.insert("id", &id);
+    context.insert(
+        "signals",
+        &serde_json::to_string(&serde_json::json!({
+            "form": form
+        }))?,
+    );
+    context.insert("error", &error);
+
+    render_fragmant("fragmants/access_list/form.html", &context)
+}
+
+/// Render the form again with the validation message of `error`.
+fn render_form_error(
+    id: Option<i64>,
+    form: &AccessListForm,
+    error: Error,
+) -> Result<Html<String>> {
+    let message = match error.client_status_and_error().1 {
+        ClientError::INVALID_FIELD { message, .. } => message,
+        _ => return Err(error),
+    };
+
+    render_form(id, form, Some(message))
+}
+
+/// After a create or an update: the refreshed rows and a closed form.
+async fn render_saved(
+    ctx: &Ctx,
+    mm: &ModelManager,
+    search: &str,
+) -> Result<Html<String>> {
+    let Html(rows) = render_rows(ctx, mm, search).await?;
+    let Html(form) =
+        render_fragmant("fragmants/access_list/form.html", &Context::new())?;
+
+    Ok(Html(format!("{rows}{form}")))
+}
+
+// endregion: --- Render
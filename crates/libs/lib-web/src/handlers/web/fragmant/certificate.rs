@@ -0,0 +1,387 @@
+//! Server rendered fragments of the `/certificates` page.
+//!
+//! Same signals as the `/proxy` page (`search` and `form`), patched into
+//! `#certificate-rows` and `#certificate-form`. Requested certificates
+//! are issued in the background, the rows poll while one is pending.
+//!
+//! Custom certificates are uploaded with the same form in its `custom`
+//! mode, the certificate and key files bound to `form.certificate` and
+//! `form.privateKey`.
+//!
+//! The records of the manual DNS provider are listed in `#dns-records`
+//! until the user confirms them.
+
+use crate::error::{ClientError, Error, Result};
+use crate::extractors::{DatastarQuery, DatastarQueryError};
+use crate::handlers::api::handlers_certificate::validate_certificate_c;
+use crate::middleware::mw_auth::CtxW;
+use crate::tera::render_fragmant;
+use crate::utils::certificate::{
+    decode_certificate_file, decode_private_key_file, parse_certificate_upload,
+};
+use crate::utils::error_page::UploadedFile;
+
+use axum::Json;
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{Path, State};
+use axum::response::Html;
+use lib_core::acme::dns::ManualDnsProvider;
+use lib_core::ctx::Ctx;
+use lib_core::model::certificate::{
+    Certificate, CertificateBmc, CertificateBundle, CertificateChallenge,
+    CertificateForCreate, CertificateForUpload,
+};
+use lib_core::model::{self, ModelManager};
+use lib_utils::time::TimeRfc3339;
+use serde::{Deserialize, Serialize};
+use tera::Context;
+use tracing::debug;
+
+// region:    --- Signals
+
+#[derive(Debug, Default, Deserialize)]
+pub struct CertificateListSignals {
+    #[serde(default)]
+    search: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CertificateFormSignals {
+    #[serde(default)]
+    search: String,
+    form: CertificateForm,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CertificateForm {
+    nice_name: String,
+    /// Comma or whitespace separated.
+    domain_names: String,
+    challenge: CertificateChallenge,
+}
+
+impl CertificateForm {
+    fn domain_names(&self) -> Vec<String> {
+        self.domain_names
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|domain| !domain.is_empty())
+            .map(str::to_lowercase)
+            .collect()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CustomCertificateFormSignals {
+    #[serde(default)]
+    search: String,
+    form: CustomCertificateForm,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CustomCertificateForm {
+    nice_name: String,
+    certificate: Vec<UploadedFile>,
+    private_key: Vec<UploadedFile>,
+}
+
+impl CustomCertificateForm {
+    /// The selected files, checked against each other.
+    fn bundle(&self) -> Result<CertificateBundle> {
+        let certificate_pem = match self.certificate.first() {
+            Some(file) => decode_certificate_file(file)?,
+            None => {
+                return Err(Error::CertificateInvalid(
+                    "A certificate file is required".to_string(),
+                ));
+            }
+        };
+        let private_key_pem = match self.private_key.first() {
+            Some(file) => decode_private_key_file(file)?,
+            None => {
+                return Err(Error::PrivateKeyInvalid(
+                    "A private key file is required".to_string(),
+                ));
+            }
+        };
+
+        parse_certificate_upload(&certificate_pem, &private_key_pem)
+    }
+}
+
+impl From<&CertificateForm> for CertificateForCreate {
+    fn from(form: &CertificateForm) -> Self {
+        CertificateForCreate {
+            nice_name: form.nice_name.trim().to_string(),
+            domain_names: form.domain_names(),
+            challenge: form.challenge,
+        }
+    }
+}
+
+// endregion: --- Signals
+
+// region:    --- Handlers
+
+pub async fn fragmant_certificate_rows(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    signals: std::result::Result<
+        DatastarQuery<CertificateListSignals>,
+        DatastarQueryError,
+    >,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_certificate_rows", "HANDLER");
+
+    let DatastarQuery(signals) = signals?;
+
+    render_rows(&ctx, &mm, &signals.search).await
+}
+
+pub async fn fragmant_certificate_new_form() -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_certificate_new_form", "HANDLER");
+
+    render_form(&CertificateForm::default(), false, None)
+}
+
+pub async fn fragmant_certificate_custom_form() -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_certificate_custom_form", "HANDLER");
+
+    render_form(&CustomCertificateForm::default(), true, None)
+}
+
+pub async fn fragmant_certificate_close_form() -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_certificate_close_form", "HANDLER");
+
+    render_fragmant("fragmants/certificate/form.html", &Context::new())
+}
+
+pub async fn fragmant_certificate_create(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    signals: std::result::Result<Json<CertificateFormSignals>, JsonRejection>,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_certificate_create", "HANDLER");
+
+    let CertificateFormSignals { search, form } = signals?.0;
+
+    let certificate_c = CertificateForCreate::from(&form);
+    if let Err(error) = validate_certificate_c(&certificate_c) {
+        return render_form_error(&form, false, error);
+    }
+
+    CertificateBmc::create(&ctx, &mm, certificate_c)
+        .await
+        .map_err(model::Error::from)?;
+
+    render_saved(&ctx, &mm, &search).await
+}
+
+pub async fn fragmant_certificate_create_custom(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    signals: std::result::Result<
+        Json<CustomCertificateFormSignals>,
+        JsonRejection,
+    >,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_certificate_create_custom", "HANDLER");
+
+    let CustomCertificateFormSignals { search, form } = signals?.0;
+
+    let bundle = match form.bundle() {
+        Ok(bundle) => bundle,
+        Err(error) => return render_form_error(&form, true, error),
+    };
+
+    CertificateBmc::create_custom(
+        &ctx,
+        &mm,
+        CertificateForUpload {
+            nice_name: form.nice_name.trim().to_string(),
+            bundle,
+        },
+    )
+    .await
+    .map_err(model::Error::from)?;
+
+    render_saved(&ctx, &mm, &search).await
+}
+
+pub async fn fragmant_certificate_delete(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+    signals: std::result::Result<Json<CertificateListSignals>, JsonRejection>,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_certificate_delete", "HANDLER");
+
+    let Json(signals) = signals?;
+
+    CertificateBmc::delete(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    render_rows(&ctx, &mm, &signals.search).await
+}
+
+pub async fn fragmant_dns_records(
+    State(manual_dns): State<ManualDnsProvider>,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_dns_records", "HANDLER");
+
+    render_dns_records(&manual_dns)
+}
+
+pub async fn fragmant_dns_record_confirm(
+    State(manual_dns): State<ManualDnsProvider>,
+    Path(id): Path<u64>,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_dns_record_confirm", "HANDLER");
+
+    if !manual_dns.confirm(id) {
+        return Err(Error::DnsRecordNotFound(id));
+    }
+
+    render_dns_records(&manual_dns)
+}
+
+// endregion: --- Handlers
+
+// region:    --- Render
+
+/// A row of the list, with the status shown for the certificate.
+#[derive(Serialize)]
+struct CertificateRow<'a> {
+    #[serde(flatten)]
+    certificate: &'a Certificate,
+    status: CertificateStatus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum CertificateStatus {
+    /// Not issued yet, the renewal task is on it.
+    Pending,
+    /// Not issued, the last attempt failed.
+    Failed,
+    Valid,
+    Expired,
+}
+
+impl CertificateStatus {
+    fn of(certificate: &Certificate) -> Self {
+        if !certificate.is_issued() {
+            return match certificate.renew_error {
+                Some(_) => CertificateStatus::Failed,
+                None => CertificateStatus::Pending,
+            };
+        }
+
+        let expired = certificate
+            .not_after
+            .as_deref()
+            .and_then(|not_after| TimeRfc3339::parse_utc(not_after).ok())
+            .is_none_or(|not_after| not_after < TimeRfc3339::now_utc());
+        if expired {
+            CertificateStatus::Expired
+        } else {
+            CertificateStatus::Valid
+        }
+    }
+}
+
+async fn render_rows(
+    ctx: &Ctx,
+    mm: &ModelManager,
+    search: &str,
+) -> Result<Html<String>> {
+    let search = search.trim().to_lowercase();
+    let certificates: Vec<Certificate> = CertificateBmc::list(ctx, mm)
+        .await
+        .map_err(model::Error::from)?
+        .into_iter()
+        .filter(|certificate| {
+            search.is_empty()
+                || certificate.nice_name.to_lowercase().contains(&search)
+                || certificate
+                    .domain_names
+                    .iter()
+                    .any(|domain| domain.contains(&search))
+        })
+        .collect();
+
+    let rows: Vec<CertificateRow> = certificates
+        .iter()
+        .map(|certificate| CertificateRow {
+            certificate,
+            status: CertificateStatus::of(certificate),
+        })
+        .collect();
+    let pending = rows
+        .iter()
+        .any(|row| row.status == CertificateStatus::Pending);
+
+    let mut context = Context::new();
+    context.insert("certificates", &rows);
+    context.insert("pending", &pending);
+    context.insert("search", &search);
+
+    render_fragmant("fragmants/certificate/rows.html", &context)
+}
+
+/// The rows and the closed form, once a certificate is saved.
+async fn render_saved(
+    ctx: &Ctx,
+    mm: &ModelManager,
+    search: &str,
+) -> Result<Html<String>> {
+    let Html(rows) = render_rows(ctx, mm, search).await?;
+    let Html(form) =
+        render_fragmant("fragmants/certificate/form.html", &Context::new())?;
+
+    Ok(Html(format!("{rows}{form}")))
+}
+
+fn render_dns_records(manual_dns: &ManualDnsProvider) -> Result<Html<String>> {
+    let mut context = Context::new();
+    context.insert("records", &manual_dns.records());
+
+    render_fragmant("fragmants/certificate/dns_records.html", &context)
+}
+
+/// `custom` selects the upload form over the Let's Encrypt one.
+fn render_form(
+    form: &impl Serialize,
+    custom: bool,
+    error: Option<String>,
+) -> Result<Html<String>> {
+    let mut context = Context::new();
+    context.insert(
+        "signals",
+        &serde_json::to_string(&serde_json::json!({
+            "form": form
+        }))?,
+    );
+    context.insert("custom", &custom);
+    context.insert("error", &error);
+
+    render_fragmant("fragmants/certificate/form.html", &context)
+}
+
+/// Render the form again with the validation message of `error`.
+fn render_form_error(
+    form: &impl Serialize,
+    custom: bool,
+    error: Error,
+) -> Result<Html<String>> {
+    let message = match error.client_status_and_error().1 {
+        ClientError::INVALID_FIELD { message, .. } => message,
+        _ => return Err(error),
+    };
+
+    render_form(form, custom, Some(message))
+}
+
+// endregion: --- Render
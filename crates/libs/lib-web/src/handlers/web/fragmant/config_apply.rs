@@ -0,0 +1,28 @@
+//! Outcome of the last nginx apply, shown on the `/proxy` page.
+
+use crate::error::Result;
+use crate::middleware::mw_auth::CtxW;
+use crate::tera::render_fragmant;
+
+use axum::extract::State;
+use axum::response::Html;
+use lib_core::model::config_apply::ConfigApplyBmc;
+use lib_core::model::{self, ModelManager};
+use tera::Context;
+use tracing::debug;
+
+pub async fn fragmant_config_apply_status(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_config_apply_status", "HANDLER");
+
+    let config_apply = ConfigApplyBmc::last(&ctx, &mm)
+        .await
+        .map_err(model::Error::from)?;
+
+    let mut context = Context::new();
+    context.insert("config_apply", &config_apply);
+
+    render_fragmant("fragmants/config_apply/status.html", &context)
+}
@@ -0,0 +1,330 @@
+//! Server rendered fragments of the `/404-host` page.
+//!
+//! Same signals as the `/proxy` page (`search` and `form`), patched into
+//! `#dead-host-rows` and `#dead-host-form`. The error page comes from a
+//! file input bound to `form.errorPage`.
+
+use crate::error::{ClientError, Error, Result};
+use crate::extractors::{DatastarQuery, DatastarQueryError};
+use crate::handlers::api::handlers_dead_host::{
+    validate_dead_host_c, validate_dead_host_u,
+};
+use crate::middleware::mw_auth::CtxW;
+use crate::tera::render_fragmant;
+use crate::utils::error_page::{
+    UploadedFile, decode_error_page, remove_error_page,
+    save_dead_host_error_page,
+};
+
+use axum::Json;
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{Path, State};
+use axum::response::Html;
+use lib_core::ctx::Ctx;
+use lib_core::model::dead_host::{
+    DeadHost, DeadHostBmc, DeadHostForCreate, DeadHostForUpdate,
+};
+use lib_core::model::{self, ModelManager};
+use serde::{Deserialize, Serialize};
+use tera::Context;
+use tracing::debug;
+
+// region:    --- Signals
+
+#[derive(Debug, Default, Deserialize)]
+pub struct DeadHostListSignals {
+    #[serde(default)]
+    search: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeadHostFormSignals {
+    #[serde(default)]
+    search: String,
+    form: DeadHostForm,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DeadHostForm {
+    /// Comma or whitespace separated.
+    domain_names: String,
+    /// Empty when no file is selected.
+    error_page: Vec<UploadedFile>,
+    /// Go back to the default page.
+    remove_error_page: bool,
+}
+
+impl DeadHostForm {
+    fn domain_names(&self) -> Vec<String> {
+        self.domain_names
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|domain| !domain.is_empty())
+            .map(str::to_lowercase)
+            .collect()
+    }
+
+    /// The decoded content of the selected file, if any.
+    fn error_page_content(&self) -> Result<Option<Vec<u8>>> {
+        self.error_page.first().map(decode_error_page).transpose()
+    }
+}
+
+impl From<&DeadHost> for DeadHostForm {
+    fn from(dead_host: &DeadHost) -> Self {
+        DeadHostForm {
+            domain_names: dead_host.domain_names.join(", "),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&DeadHostForm> for DeadHostForCreate {
+    fn from(form: &DeadHostForm) -> Self {
+        DeadHostForCreate {
+            domain_names: form.domain_names(),
+        }
+    }
+}
+
+impl From<&DeadHostForm> for DeadHostForUpdate {
+    fn from(form: &DeadHostForm) -> Self {
+        DeadHostForUpdate {
+            domain_names: Some(form.domain_names()),
+            enabled: None,
+        }
+    }
+}
+
+// endregion: --- Signals
+
+// region:    --- Handlers
+
+pub async fn fragmant_dead_host_rows(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    signals: std::result::Result<
+        DatastarQuery<DeadHostListSignals>,
+        DatastarQueryError,
+    >,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_dead_host_rows", "HANDLER");
+
+    let DatastarQuery(signals) = signals?;
+
+    render_rows(&ctx, &mm, &signals.search).await
+}
+
+pub async fn fragmant_dead_host_new_form() -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_dead_host_new_form", "HANDLER");
+
+    render_form(None, &DeadHostForm::default(), None, None)
+}
+
+pub async fn fragmant_dead_host_edit_form(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_dead_host_edit_form", "HANDLER");
+
+    let dead_host = DeadHostBmc::get(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    render_form(
+        Some(id),
+        &DeadHostForm::from(&dead_host),
+        dead_host.error_page.as_deref(),
+        None,
+    )
+}
+
+pub async fn fragmant_dead_host_close_form() -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_dead_host_close_form", "HANDLER");
+
+    render_fragmant("fragmants/dead_host/form.html", &Context::new())
+}
+
+pub async fn fragmant_dead_host_create(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    signals: std::result::Result<Json<DeadHostFormSignals>, JsonRejection>,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_dead_host_create", "HANDLER");
+
+    let DeadHostFormSignals { search, form } = signals?.0;
+
+    let dead_host_c = DeadHostForCreate::from(&form);
+    let content = match validate_dead_host_c(&dead_host_c)
+        .and_then(|_| form.error_page_content())
+    {
+        Ok(content) => content,
+        Err(error) => return render_form_error(None, &form, None, error),
+    };
+
+    let id = DeadHostBmc::create(&ctx, &mm, dead_host_c)
+        .await
+        .map_err(model::Error::from)?;
+    if let Some(content) = content {
+        let error_page = save_dead_host_error_page(id, &content).await?;
+        DeadHostBmc::set_error_page(&ctx, &mm, id, Some(error_page))
+            .await
+            .map_err(model::Error::from)?;
+    }
+
+    render_saved(&ctx, &mm, &search).await
+}
+
+pub async fn fragmant_dead_host_update(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+    signals: std::result::Result<Json<DeadHostFormSignals>, JsonRejection>,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_dead_host_update", "HANDLER");
+
+    let DeadHostFormSignals { search, form } = signals?.0;
+
+    let dead_host = DeadHostBmc::get(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    let dead_host_u = DeadHostForUpdate::from(&form);
+    let content = match validate_dead_host_u(&dead_host_u)
+        .and_then(|_| form.error_page_content())
+    {
+        Ok(content) => content,
+        Err(error) => {
+            return render_form_error(
+                Some(id),
+                &form,
+                dead_host.error_page.as_deref(),
+                error,
+            );
+        }
+    };
+
+    DeadHostBmc::update(&ctx, &mm, id, dead_host_u)
+        .await
+        .map_err(model::Error::from)?;
+    if let Some(content) = content {
+        let error_page = save_dead_host_error_page(id, &content).await?;
+        DeadHostBmc::set_error_page(&ctx, &mm, id, Some(error_page))
+            .await
+            .map_err(model::Error::from)?;
+    } else if form.remove_error_page
+        && let Some(error_page) = &dead_host.error_page
+    {
+        DeadHostBmc::set_error_page(&ctx, &mm, id, None)
+            .await
+            .map_err(model::Error::from)?;
+        remove_error_page(error_page).await?;
+    }
+
+    render_saved(&ctx, &mm, &search).await
+}
+
+pub async fn fragmant_dead_host_delete(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+    signals: std::result::Result<Json<DeadHostListSignals>, JsonRejection>,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_dead_host_delete", "HANDLER");
+
+    let Json(signals) = signals?;
+
+    let dead_host = DeadHostBmc::get(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+    DeadHostBmc::delete(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+    if let Some(error_page) = &dead_host.error_page {
+        remove_error_page(error_page).await?;
+    }
+
+    render_rows(&ctx, &mm, &signals.search).await
+}
+
+// endregion: --- Handlers
+
+// region:    --- Render
+
+async fn render_rows(
+    ctx: &Ctx,
+    mm: &ModelManager,
+    search: &str,
+) -> Result<Html<String>> {
+    let search = search.trim().to_lowercase();
+    let dead_hosts: Vec<DeadHost> = DeadHostBmc::list(ctx, mm)
+        .await
+        .map_err(model::Error::from)?
+        .into_iter()
+        .filter(|dead_host| {
+            search.is_empty()
+                || dead_host
+                    .domain_names
+                    .iter()
+                    .any(|domain| domain.contains(&search))
+        })
+        .collect();
+
+    let mut context = Context::new();
+    context.insert("dead_hosts", &dead_hosts);
+    context.insert("search", &search);
+
+    render_fragmant("fragmants/dead_host/rows.html", &context)
+}
+
+/// `error_page` is the current custom page of the edited host.
+fn render_form(
+    id: Option<i64>,
+    form: &DeadHostForm,
+    error_page: Option<&str>,
+    error: Option<String>,
+) -> Result<Html<String>> {
+    let mut context = Context::new();
+    context.insert("id", &id);
+    context.insert(
+        "signals",
+        &serde_json::to_string(&serde_json::json!({
+            "form": form
+        }))?,
+    );
+    context.insert("error_page", &error_page);
+    context.insert("error", &error);
+
+    render_fragmant("fragmants/dead_host/form.html", &context)
+}
+
+/// Render the form again with the validation message of `error`.
+fn render_form_error(
+    id: Option<i64>,
+    form: &DeadHostForm,
+    error_page: Option<&str>,
+    error: Error,
+) -> Result<Html<String>> {
+    let message = match error.client_status_and_error().1 {
+        ClientError::INVALID_FIELD { message, .. } => message,
+        _ => return Err(error),
+    };
+
+    render_form(id, form, error_page, Some(message))
+}
+
+/// After a create or an update: the refreshed rows and a closed form.
+async fn render_saved(
+    ctx: &Ctx,
+    mm: &ModelManager,
+    search: &str,
+) -> Result<Html<String>> {
+    let Html(rows) = render_rows(ctx, mm, search).await?;
+    let Html(form) =
+        render_fragmant("fragmants/dead_host/form.html", &Context::new())?;
+
+    Ok(Html(format!("{rows}{form}")))
+}
+
+// endregion: --- Render
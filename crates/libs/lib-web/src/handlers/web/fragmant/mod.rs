@@ -1 +1,8 @@
-
+pub mod access_list;
+pub mod certificate;
+pub mod config_apply;
+pub mod dead_host;
+pub mod proxy_host;
+pub mod redirection_host;
+pub mod stream_host;
+pub mod upstream_group;
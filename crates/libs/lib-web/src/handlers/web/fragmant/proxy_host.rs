@@ -0,0 +1,566 @@
+//! Server rendered fragments of the `/proxy` page.
+//!
+//! The page keeps its state in Datastar signals:
+//! - `search`: filter of the host list.
+//! - `form`: the fields of the add/edit form, with one entry of
+//!   `form.locations` per custom location.
+//!
+//! Every endpoint answers with html elements that Datastar patches in
+//! place by id (`#proxy-host-rows`, `#proxy-host-form`).
+
+use crate::error::{ClientError, Error, Result};
+use crate::extractors::{DatastarQuery, DatastarQueryError};
+use crate::handlers::api::handlers_proxy_host::{
+    validate_proxy_host_c, validate_proxy_host_u,
+};
+use crate::middleware::mw_auth::CtxW;
+use crate::tera::render_fragmant;
+
+use axum::Json;
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{Path, State};
+use axum::response::Html;
+use lib_core::ctx::Ctx;
+use lib_core::model::access_list::AccessListBmc;
+use lib_core::model::certificate::CertificateBmc;
+use lib_core::model::proxy_host::{
+    ForwardScheme, ProxyHeader, ProxyHost, ProxyHostBmc, ProxyHostForCreate,
+    ProxyHostForUpdate, ProxyLocation,
+};
+use lib_core::model::upstream_group::UpstreamGroupBmc;
+use lib_core::model::{self, ModelManager};
+use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, NoneAsEmptyString, PickFirst, serde_as};
+use tera::Context;
+use tracing::debug;
+
+// region:    --- Signals
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ProxyHostListSignals {
+    #[serde(default)]
+    search: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProxyHostFormSignals {
+    #[serde(default)]
+    search: String,
+    form: ProxyHostForm,
+}
+
+/// `/proxy-hosts[/{id}]/form/locations`, `id` of the edited host.
+#[derive(Debug, Deserialize)]
+pub struct ProxyHostFormPath {
+    id: Option<i64>,
+}
+
+/// `/proxy-hosts[/{id}]/form/locations/{index}`
+#[derive(Debug, Deserialize)]
+pub struct ProxyLocationFormPath {
+    id: Option<i64>,
+    index: usize,
+}
+
+#[serde_as]
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProxyHostForm {
+    /// Comma or whitespace separated.
+    domain_names: String,
+    forward_scheme: ForwardScheme,
+    forward_host: String,
+    /// Number inputs may bind a string.
+    #[serde_as(as = "PickFirst<(_, DisplayFromStr)>")]
+    forward_port: u16,
+
+    cache_assets: bool,
+    block_exploits: bool,
+    allow_websocket_upgrade: bool,
+
+    ssl_forced: bool,
+    http2_support: bool,
+    hsts_enabled: bool,
+    hsts_subdomains: bool,
+    /// Bound to a select, empty for http only.
+    #[serde_as(as = "NoneAsEmptyString")]
+    certificate_id: Option<i64>,
+
+    /// Bound to a select, empty for a public host.
+    #[serde_as(as = "NoneAsEmptyString")]
+    access_list_id: Option<i64>,
+    /// Bound to a select, empty for `forward_host:forward_port`.
+    #[serde_as(as = "NoneAsEmptyString")]
+    upstream_group_id: Option<i64>,
+
+    locations: Vec<ProxyLocationForm>,
+    /// Raw directives of the `server` block.
+    advanced: String,
+
+    /// Number inputs bind `""` when empty, for unlimited.
+    #[serde_as(as = "PickFirst<(_, NoneAsEmptyString)>")]
+    rate_limit: Option<u32>,
+    #[serde_as(as = "PickFirst<(_, DisplayFromStr)>")]
+    rate_limit_burst: u32,
+    rate_limit_nodelay: bool,
+    #[serde_as(as = "PickFirst<(_, NoneAsEmptyString)>")]
+    connection_limit: Option<u32>,
+
+    /// Empty for a TCP connect.
+    health_check_path: String,
+    #[serde_as(as = "PickFirst<(_, DisplayFromStr)>")]
+    health_check_interval: u32,
+}
+
+#[serde_as]
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProxyLocationForm {
+    path: String,
+    forward_scheme: ForwardScheme,
+    forward_host: String,
+    #[serde_as(as = "PickFirst<(_, DisplayFromStr)>")]
+    forward_port: u16,
+    /// One `Name: value` per line.
+    headers: String,
+    advanced: String,
+}
+
+impl Default for ProxyLocationForm {
+    fn default() -> Self {
+        ProxyLocationForm {
+            path: String::new(),
+            forward_scheme: ForwardScheme::default(),
+            forward_host: String::new(),
+            forward_port: 80,
+            headers: String::new(),
+            advanced: String::new(),
+        }
+    }
+}
+
+impl ProxyHostForm {
+    fn health_check_path(&self) -> Option<String> {
+        let path = self.health_check_path.trim();
+        (!path.is_empty()).then(|| path.to_string())
+    }
+
+    fn domain_names(&self) -> Vec<String> {
+        self.domain_names
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|domain| !domain.is_empty())
+            .map(str::to_lowercase)
+            .collect()
+    }
+
+    fn locations(&self) -> Result<Vec<ProxyLocation>> {
+        self.locations.iter().map(ProxyLocation::try_from).collect()
+    }
+}
+
+impl From<&ProxyLocation> for ProxyLocationForm {
+    fn from(location: &ProxyLocation) -> Self {
+        let headers: Vec<String> = location
+            .headers
+            .iter()
+            .map(|header| format!("{}: {}", header.name, header.value))
+            .collect();
+
+        ProxyLocationForm {
+            path: location.path.clone(),
+            forward_scheme: location.forward_scheme,
+            forward_host: location.forward_host.clone(),
+            forward_port: location.forward_port,
+            headers: headers.join("\n"),
+            advanced: location.advanced.clone(),
+        }
+    }
+}
+
+impl TryFrom<&ProxyLocationForm> for ProxyLocation {
+    type Error = Error;
+
+    fn try_from(form: &ProxyLocationForm) -> Result<Self> {
+        let headers = form
+            .headers
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                let (name, value) = line
+                    .split_once(':')
+                    .ok_or_else(|| Error::InvalidHeader(line.to_string()))?;
+                Ok(ProxyHeader {
+                    name: name.trim().to_string(),
+                    value: value.trim().to_string(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(ProxyLocation {
+            path: form.path.trim().to_string(),
+            forward_scheme: form.forward_scheme,
+            forward_host: form.forward_host.trim().to_string(),
+            forward_port: form.forward_port,
+            headers,
+            advanced: form.advanced.trim().to_string(),
+        })
+    }
+}
+
+impl From<&ProxyHost> for ProxyHostForm {
+    fn from(proxy_host: &ProxyHost) -> Self {
+        ProxyHostForm {
+            domain_names: proxy_host.domain_names.join(", "),
+            forward_scheme: proxy_host.forward_scheme,
+            forward_host: proxy_host.forward_host.clone(),
+            forward_port: proxy_host.forward_port,
+            cache_assets: proxy_host.cache_assets,
+            block_exploits: proxy_host.block_exploits,
+            allow_websocket_upgrade: proxy_host.allow_websocket_upgrade,
+            ssl_forced: proxy_host.ssl_forced,
+            http2_support: proxy_host.http2_support,
+            hsts_enabled: proxy_host.hsts_enabled,
+            hsts_subdomains: proxy_host.hsts_subdomains,
+            certificate_id: proxy_host.certificate_id,
+            access_list_id: proxy_host.access_list_id,
+            upstream_group_id: proxy_host.upstream_group_id,
+            locations: proxy_host
+                .locations
+                .iter()
+                .map(ProxyLocationForm::from)
+                .collect(),
+            advanced: proxy_host.advanced.clone(),
+            rate_limit: proxy_host.rate_limit,
+            rate_limit_burst: proxy_host.rate_limit_burst,
+            rate_limit_nodelay: proxy_host.rate_limit_nodelay,
+            connection_limit: proxy_host.connection_limit,
+            health_check_path: proxy_host
+                .health_check_path
+                .clone()
+                .unwrap_or_default(),
+            health_check_interval: proxy_host.health_check_interval,
+        }
+    }
+}
+
+impl TryFrom<&ProxyHostForm> for ProxyHostForCreate {
+    type Error = Error;
+
+    fn try_from(form: &ProxyHostForm) -> Result<Self> {
+        Ok(ProxyHostForCreate {
+            domain_names: form.domain_names(),
+            forward_scheme: form.forward_scheme,
+            forward_host: form.forward_host.clone(),
+            forward_port: form.forward_port,
+            cache_assets: form.cache_assets,
+            block_exploits: form.block_exploits,
+            allow_websocket_upgrade: form.allow_websocket_upgrade,
+            ssl_forced: form.ssl_forced,
+            http2_support: form.http2_support,
+            hsts_enabled: form.hsts_enabled,
+            hsts_subdomains: form.hsts_subdomains,
+            certificate_id: form.certificate_id,
+            access_list_id: form.access_list_id,
+            upstream_group_id: form.upstream_group_id,
+            locations: form.locations()?,
+            advanced: form.advanced.trim().to_string(),
+            rate_limit: form.rate_limit,
+            rate_limit_burst: form.rate_limit_burst,
+            rate_limit_nodelay: form.rate_limit_nodelay,
+            connection_limit: form.connection_limit,
+            health_check_path: form.health_check_path(),
+            health_check_interval: form.health_check_interval,
+        })
+    }
+}
+
+impl TryFrom<&ProxyHostForm> for ProxyHostForUpdate {
+    type Error = Error;
+
+    fn try_from(form: &ProxyHostForm) -> Result<Self> {
+        Ok(ProxyHostForUpdate {
+            domain_names: Some(form.domain_names()),
+            forward_scheme: Some(form.forward_scheme),
+            forward_host: Some(form.forward_host.clone()),
+            forward_port: Some(form.forward_port),
+            cache_assets: Some(form.cache_assets),
+            block_exploits: Some(form.block_exploits),
+            allow_websocket_upgrade: Some(form.allow_websocket_upgrade),
+            ssl_forced: Some(form.ssl_forced),
+            http2_support: Some(form.http2_support),
+            hsts_enabled: Some(form.hsts_enabled),
+            hsts_subdomains: Some(form.hsts_subdomains),
+            certificate_id: Some(form.certificate_id),
+            access_list_id: Some(form.access_list_id),
+            upstream_group_id: Some(form.upstream_group_id),
+            locations: Some(form.locations()?),
+            advanced: Some(form.advanced.trim().to_string()),
+            rate_limit: Some(form.rate_limit),
+            rate_limit_burst: Some(form.rate_limit_burst),
+            rate_limit_nodelay: Some(form.rate_limit_nodelay),
+            connection_limit: Some(form.connection_limit),
+            health_check_path: Some(form.health_check_path()),
+            health_check_interval: Some(form.health_check_interval),
+            enabled: None,
+        })
+    }
+}
+
+// endregion: --- Signals
+
+// region:    --- Handlers
+
+pub async fn fragmant_proxy_host_rows(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    signals: std::result::Result<
+        DatastarQuery<ProxyHostListSignals>,
+        DatastarQueryError,
+    >,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_proxy_host_rows", "HANDLER");
+
+    let DatastarQuery(signals) = signals?;
+
+    render_rows(&ctx, &mm, &signals.search).await
+}
+
+pub async fn fragmant_proxy_host_new_form(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_proxy_host_new_form", "HANDLER");
+
+    let form = ProxyHostForm {
+        forward_port: 80,
+        block_exploits: true,
+        health_check_interval: 30,
+        ..Default::default()
+    };
+
+    render_form(&ctx, &mm, None, &form, None).await
+}
+
+pub async fn fragmant_proxy_host_edit_form(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_proxy_host_edit_form", "HANDLER");
+
+    let proxy_host = ProxyHostBmc::get(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    render_form(&ctx, &mm, Some(id), &ProxyHostForm::from(&proxy_host), None)
+        .await
+}
+
+pub async fn fragmant_proxy_host_close_form() -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_proxy_host_close_form", "HANDLER");
+
+    render_fragmant("fragmants/proxy_host/form.html", &Context::new())
+}
+
+/// Render the form again with one more, empty, custom location.
+pub async fn fragmant_proxy_host_add_location(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(ProxyHostFormPath { id }): Path<ProxyHostFormPath>,
+    signals: std::result::Result<Json<ProxyHostFormSignals>, JsonRejection>,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_proxy_host_add_location", "HANDLER");
+
+    let ProxyHostFormSignals { mut form, .. } = signals?.0;
+    form.locations.push(ProxyLocationForm::default());
+
+    render_form(&ctx, &mm, id, &form, None).await
+}
+
+/// Render the form again without the custom location at `index`.
+pub async fn fragmant_proxy_host_remove_location(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(ProxyLocationFormPath { id, index }): Path<ProxyLocationFormPath>,
+    signals: std::result::Result<Json<ProxyHostFormSignals>, JsonRejection>,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_proxy_host_remove_location", "HANDLER");
+
+    let ProxyHostFormSignals { mut form, .. } = signals?.0;
+    if index < form.locations.len() {
+        form.locations.remove(index);
+    }
+
+    render_form(&ctx, &mm, id, &form, None).await
+}
+
+pub async fn fragmant_proxy_host_create(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    signals: std::result::Result<Json<ProxyHostFormSignals>, JsonRejection>,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_proxy_host_create", "HANDLER");
+
+    let ProxyHostFormSignals { search, form } = signals?.0;
+
+    let proxy_host_c =
+        match ProxyHostForCreate::try_from(&form).and_then(|proxy_host_c| {
+            validate_proxy_host_c(&proxy_host_c).map(|_| proxy_host_c)
+        }) {
+            Ok(proxy_host_c) => proxy_host_c,
+            Err(error) => {
+                return render_form_error(&ctx, &mm, None, &form, error).await;
+            }
+        };
+
+    ProxyHostBmc::create(&ctx, &mm, proxy_host_c)
+        .await
+        .map_err(model::Error::from)?;
+
+    render_saved(&ctx, &mm, &search).await
+}
+
+pub async fn fragmant_proxy_host_update(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+    signals: std::result::Result<Json<ProxyHostFormSignals>, JsonRejection>,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_proxy_host_update", "HANDLER");
+
+    let ProxyHostFormSignals { search, form } = signals?.0;
+
+    let proxy_host_u =
+        match ProxyHostForUpdate::try_from(&form).and_then(|proxy_host_u| {
+            validate_proxy_host_u(&proxy_host_u).map(|_| proxy_host_u)
+        }) {
+            Ok(proxy_host_u) => proxy_host_u,
+            Err(error) => {
+                return render_form_error(&ctx, &mm, Some(id), &form, error)
+                    .await;
+            }
+        };
+
+    ProxyHostBmc::update(&ctx, &mm, id, proxy_host_u)
+        .await
+        .map_err(model::Error::from)?;
+
+    render_saved(&ctx, &mm, &search).await
+}
+
+pub async fn fragmant_proxy_host_delete(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+    signals: std::result::Result<Json<ProxyHostListSignals>, JsonRejection>,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_proxy_host_delete", "HANDLER");
+
+    let Json(signals) = signals?;
+
+    ProxyHostBmc::delete(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    render_rows(&ctx, &mm, &signals.search).await
+}
+
+// endregion: --- Handlers
+
+// region:    --- Render
+
+async fn render_rows(
+    ctx: &Ctx,
+    mm: &ModelManager,
+    search: &str,
+) -> Result<Html<String>> {
+    let search = search.trim().to_lowercase();
+    let proxy_hosts: Vec<ProxyHost> = ProxyHostBmc::list(ctx, mm)
+        .await
+        .map_err(model::Error::from)?
+        .into_iter()
+        .filter(|proxy_host| {
+            search.is_empty()
+                || proxy_host.forward_host.to_lowercase().contains(&search)
+                || proxy_host
+                    .domain_names
+                    .iter()
+                    .any(|domain| domain.contains(&search))
+        })
+        .collect();
+
+    let mut context = Context::new();
+    context.insert("proxy_hosts", &proxy_hosts);
+    context.insert("search", &search);
+
+    render_fragmant("fragmants/proxy_host/rows.html", &context)
+}
+
+/// The access lists, the upstream groups and the certificates of the
+/// owner fill the selects of `form.accessListId`, `form.upstreamGroupId`
+/// and `form.certificateId`.
+async fn render_form(
+    ctx: &Ctx,
+    mm: &ModelManager,
+    id: Option<i64>,
+    form: &ProxyHostForm,
+    error: Option<String>,
+) -> Result<Html<String>> {
+    let access_lists = AccessListBmc::list(ctx, mm)
+        .await
+        .map_err(model::Error::from)?;
+    let upstream_groups = UpstreamGroupBmc::list(ctx, mm)
+        .await
+        .map_err(model::Error::from)?;
+    let certificates = CertificateBmc::list(ctx, mm)
+        .await
+        .map_err(model::Error::from)?;
+
+    let mut context = Context::new();
+    context.insert("access_lists", &access_lists);
+    context.insert("upstream_groups", &upstream_groups);
+    context.insert("certificates", &certificates);
+    context.insert("locations", &form.locations);
+    context.insert("id", &id);
+    context.insert(
+        "signals",
+        &serde_json::to_string(&serde_json::json!({
+            "form": form
+        }))?,
+    );
+    context.insert("error", &error);
+
+    render_fragmant("fragmants/proxy_host/form.html", &context)
+}
+
+/// Render the form again with the validation message of `error`.
+async fn render_form_error(
+    ctx: &Ctx,
+    mm: &ModelManager,
+    id: Option<i64>,
+    form: &ProxyHostForm,
+    error: Error,
+) -> Result<Html<String>> {
+    let message = match error.client_status_and_error().1 {
+        ClientError::INVALID_FIELD { message, .. } => message,
+        _ => return Err(error),
+    };
+
+    render_form(ctx, mm, id, form, Some(message)).await
+}
+
+/// After a create or an update: the refreshed rows and a closed form.
+async fn render_saved(
+    ctx: &Ctx,
+    mm: &ModelManager,
+    search: &str,
+) -> Result<Html<String>> {
+    let Html(rows) = render_rows(ctx, mm, search).await?;
+    let Html(form) =
+        render_fragmant("fragmants/proxy_host/form.html", &Context::new())?;
+
+    Ok(Html(format!("{rows}{form}")))
+}
+
+// endregion: --- Render
@@ -0,0 +1,325 @@
+//! Server rendered fragments of the `/redirection` page.
+//!
+//! Same signals as the `/proxy` page (`search` and `form`), patched into
+//! `#redirection-host-rows` and `#redirection-host-form`.
+
+use crate::error::{ClientError, Error, Result};
+use crate::extractors::{DatastarQuery, DatastarQueryError};
+use crate::handlers::api::handlers_redirection_host::{
+    validate_redirection_host_c, validate_redirection_host_u,
+};
+use crate::middleware::mw_auth::CtxW;
+use crate::tera::render_fragmant;
+
+use axum::Json;
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{Path, State};
+use axum::response::Html;
+use lib_core::ctx::Ctx;
+use lib_core::model::redirection_host::{
+    RedirectionHost, RedirectionHostBmc, RedirectionHostForCreate,
+    RedirectionHostForUpdate,
+};
+use lib_core::model::{self, ModelManager};
+use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, PickFirst, serde_as};
+use tera::Context;
+use tracing::debug;
+
+// region:    --- Signals
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RedirectionHostListSignals {
+    #[serde(default)]
+    search: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RedirectionHostFormSignals {
+    #[serde(default)]
+    search: String,
+    form: RedirectionHostForm,
+}
+
+#[serde_as]
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RedirectionHostForm {
+    /// Comma or whitespace separated.
+    domain_names: String,
+    forward_url: String,
+    /// Select values are strings.
+    #[serde_as(as = "PickFirst<(_, DisplayFromStr)>")]
+    redirect_code: u16,
+    preserve_path: bool,
+
+    ssl_forced: bool,
+    http2_support: bool,
+    hsts_enabled: bool,
+    hsts_subdomains: bool,
+}
+
+impl RedirectionHostForm {
+    fn domain_names(&self) -> Vec<String> {
+        self.domain_names
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|domain| !domain.is_empty())
+            .map(str::to_lowercase)
+            .collect()
+    }
+}
+
+impl From<&RedirectionHost> for RedirectionHostForm {
+    fn from(redirection_host: &RedirectionHost) -> Self {
+        RedirectionHostForm {
+            domain_names: redirection_host.domain_names.join(", "),
+            forward_url: redirection_host.forward_url.clone(),
+            redirect_code: redirection_host.redirect_code,
+            preserve_path: redirection_host.preserve_path,
+            ssl_forced: redirection_host.ssl_forced,
+            http2_support: redirection_host.http2_support,
+            hsts_enabled: redirection_host.hsts_enabled,
+            hsts_subdomains: redirection_host.hsts_subdomains,
+        }
+    }
+}
+
+impl From<&RedirectionHostForm> for RedirectionHostForCreate {
+    fn from(form: &RedirectionHostForm) -> Self {
+        RedirectionHostForCreate {
+            domain_names: form.domain_names(),
+            forward_url: form.forward_url.trim().to_string(),
+            redirect_code: form.redirect_code,
+            preserve_path: form.preserve_path,
+            ssl_forced: form.ssl_forced,
+            http2_support: form.http2_support,
+            hsts_enabled: form.hsts_enabled,
+            hsts_subdomains: form.hsts_subdomains,
+        }
+    }
+}
+
+impl From<&RedirectionHostForm> for RedirectionHostForUpdate {
+    fn from(form: &RedirectionHostForm) -> Self {
+        RedirectionHostForUpdate {
+            domain_names: Some(form.domain_names()),
+            forward_url: Some(form.forward_url.trim().to_string()),
+            redirect_code: Some(form.redirect_code),
+            preserve_path: Some(form.preserve_path),
+            ssl_forced: Some(form.ssl_forced),
+            http2_support: Some(form.http2_support),
+            hsts_enabled: Some(form.hsts_enabled),
+            hsts_subdomains: Some(form.hsts_subdomains),
+            enabled: None,
+        }
+    }
+}
+
+// endregion: --- Signals
+
+// region:    --- Handlers
+
+pub async fn fragmant_redirection_host_rows(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    signals: std::result::Result<
+        DatastarQuery<RedirectionHostListSignals>,
+        DatastarQueryError,
+    >,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_redirection_host_rows", "HANDLER");
+
+    let DatastarQuery(signals) = signals?;
+
+    render_rows(&ctx, &mm, &signals.search).await
+}
+
+pub async fn fragmant_redirection_host_new_form() -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_redirection_host_new_form", "HANDLER");
+
+    let form = RedirectionHostForm {
+        forward_url: "https://".to_string(),
+        redirect_code: 301,
+        preserve_path: true,
+        ..Default::default()
+    };
+
+    render_form(None, &form, None)
+}
+
+pub async fn fragmant_redirection_host_edit_form(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_redirection_host_edit_form", "HANDLER");
+
+    let redirection_host = RedirectionHostBmc::get(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    render_form(
+        Some(id),
+        &RedirectionHostForm::from(&redirection_host),
+        None,
+    )
+}
+
+pub async fn fragmant_redirection_host_close_form() -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_redirection_host_close_form", "HANDLER");
+
+    render_fragmant("fragmants/redirection_host/form.html", &Context::new())
+}
+
+pub async fn fragmant_redirection_host_create(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    signals: std::result::Result<
+        Json<RedirectionHostFormSignals>,
+        JsonRejection,
+    >,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_redirection_host_create", "HANDLER");
+
+    let RedirectionHostFormSignals { search, form } = signals?.0;
+
+    let redirection_host_c = RedirectionHostForCreate::from(&form);
+    if let Err(error) = validate_redirection_host_c(&redirection_host_c) {
+        return render_form_error(None, &form, error);
+    }
+
+    RedirectionHostBmc::create(&ctx, &mm, redirection_host_c)
+        .await
+        .map_err(model::Error::from)?;
+
+    render_saved(&ctx, &mm, &search).await
+}
+
+pub async fn fragmant_redirection_host_update(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+    signals: std::result::Result<
+        Json<RedirectionHostFormSignals>,
+        JsonRejection,
+    >,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_redirection_host_update", "HANDLER");
+
+    let RedirectionHostFormSignals { search, form } = signals?.0;
+
+    let redirection_host_u = RedirectionHostForUpdate::from(&form);
+    if let Err(error) = validate_redirection_host_u(&redirection_host_u) {
+        return render_form_error(Some(id), &form, error);
+    }
+
+    RedirectionHostBmc::update(&ctx, &mm, id, redirection_host_u)
+        .await
+        .map_err(model::Error::from)?;
+
+    render_saved(&ctx, &mm, &search).await
+}
+
+pub async fn fragmant_redirection_host_delete(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+    signals: std::result::Result<
+        Json<RedirectionHostListSignals>,
+        JsonRejection,
+    >,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_redirection_host_delete", "HANDLER");
+
+    let Json(signals) = signals?;
+
+    RedirectionHostBmc::delete(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    render_rows(&ctx, &mm, &signals.search).await
+}
+
+// endregion: --- Handlers
+
+// region:    --- Render
+
+async fn render_rows(
+    ctx: &Ctx,
+    mm: &ModelManager,
+    search: &str,
+) -> Result<Html<String>> {
+    let search = search.trim().to_lowercase();
+    let redirection_hosts: Vec<RedirectionHost> =
+        RedirectionHostBmc::list(ctx, mm)
+            .await
+            .map_err(model::Error::from)?
+            .into_iter()
+            .filter(|redirection_host| {
+                search.is_empty()
+                    || redirection_host
+                        .forward_url
+                        .to_lowercase()
+                        .contains(&search)
+                    || redirection_host
+                        .domain_names
+                        .iter()
+                        .any(|domain| domain.contains(&search))
+            })
+            .collect();
+
+    let mut context = Context::new();
+    context.insert("redirection_hosts", &redirection_hosts);
+    context.insert("search", &search);
+
+    render_fragmant("fragmants/redirection_host/rows.html", &context)
+}
+
+fn render_form(
+    id: Option<i64>,
+    form: &RedirectionHostForm,
+    error: Option<String>,
+) -> Result<Html<String>> {
+    let mut context = Context::new();
+    context.insert("id", &id);
+    context.insert(
+        "signals",
+        &serde_json::to_string(&serde_json::json!({
+            "form": form
+        }))?,
+    );
+    context.insert("error", &error);
+
+    render_fragmant("fragmants/redirection_host/form.html", &context)
+}
+
+/// Render the form again with the validation message of `error`.
+fn render_form_error(
+    id: Option<i64>,
+    form: &RedirectionHostForm,
+    error: Error,
+) -> Result<Html<String>> {
+    let message = match error.client_status_and_error().1 {
+        ClientError::INVALID_FIELD { message, .. } => message,
+        _ => return Err(error),
+    };
+
+    render_form(id, form, Some(message))
+}
+
+/// After a create or an update: the refreshed rows and a closed form.
+async fn render_saved(
+    ctx: &Ctx,
+    mm: &ModelManager,
+    search: &str,
+) -> Result<Html<String>> {
+    let Html(rows) = render_rows(ctx, mm, search).await?;
+    let Html(form) = render_fragmant(
+        "fragmants/redirection_host/form.html",
+        &Context::new(),
+    )?;
+
+    Ok(Html(format!("{rows}{form}")))
+}
+
+// endregion: --- Render
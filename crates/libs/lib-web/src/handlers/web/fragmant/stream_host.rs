@@ -0,0 +1,269 @@
+//! Server rendered fragments of the `/stream` page.
+//!
+//! Same signals as the `/proxy` page (`search` and `form`), patched into
+//! `#stream-host-rows` and `#stream-host-form`.
+
+use crate::error::{ClientError, Error, Result};
+use crate::extractors::{DatastarQuery, DatastarQueryError};
+use crate::handlers::api::handlers_stream_host::{
+    validate_stream_host_c, validate_stream_host_u,
+};
+use crate::middleware::mw_auth::CtxW;
+use crate::tera::render_fragmant;
+
+use axum::Json;
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{Path, State};
+use axum::response::Html;
+use lib_core::ctx::Ctx;
+use lib_core::model::stream_host::{
+    StreamHost, StreamHostBmc, StreamHostForCreate, StreamHostForUpdate,
+    StreamProtocol,
+};
+use lib_core::model::{self, ModelManager};
+use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, PickFirst, serde_as};
+use tera::Context;
+use tracing::debug;
+
+// region:    --- Signals
+
+#[derive(Debug, Default, Deserialize)]
+pub struct StreamHostListSignals {
+    #[serde(default)]
+    search: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StreamHostFormSignals {
+    #[serde(default)]
+    search: String,
+    form: StreamHostForm,
+}
+
+#[serde_as]
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct StreamHostForm {
+    /// Number inputs may bind a string.
+    #[serde_as(as = "PickFirst<(_, DisplayFromStr)>")]
+    incoming_port: u16,
+    protocol: StreamProtocol,
+    forward_host: String,
+    #[serde_as(as = "PickFirst<(_, DisplayFromStr)>")]
+    forward_port: u16,
+}
+
+impl From<&StreamHost> for StreamHostForm {
+    fn from(stream_host: &StreamHost) -> Self {
+        StreamHostForm {
+            incoming_port: stream_host.incoming_port,
+            protocol: stream_host.protocol,
+            forward_host: stream_host.forward_host.clone(),
+            forward_port: stream_host.forward_port,
+        }
+    }
+}
+
+impl From<&StreamHostForm> for StreamHostForCreate {
+    fn from(form: &StreamHostForm) -> Self {
+        StreamHostForCreate {
+            incoming_port: form.incoming_port,
+            protocol: form.protocol,
+            forward_host: form.forward_host.trim().to_string(),
+            forward_port: form.forward_port,
+        }
+    }
+}
+
+impl From<&StreamHostForm> for StreamHostForUpdate {
+    fn from(form: &StreamHostForm) -> Self {
+        StreamHostForUpdate {
+            incoming_port: Some(form.incoming_port),
+            protocol: Some(form.protocol),
+            forward_host: Some(form.forward_host.trim().to_string()),
+            forward_port: Some(form.forward_port),
+            enabled: None,
+        }
+    }
+}
+
+// endregion: --- Signals
+
+// region:    --- Handlers
+
+pub async fn fragmant_stream_host_rows(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    signals: std::result::Result<
+        DatastarQuery<StreamHostListSignals>,
+        DatastarQueryError,
+    >,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_stream_host_rows", "HANDLER");
+
+    let DatastarQuery(signals) = signals?;
+
+    render_rows(&ctx, &mm, &signals.search).await
+}
+
+pub async fn fragmant_stream_host_new_form() -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_stream_host_new_form", "HANDLER");
+
+    render_form(None, &StreamHostForm::default(), None)
+}
+
+pub async fn fragmant_stream_host_edit_form(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_stream_host_edit_form", "HANDLER");
+
+    let stream_host = StreamHostBmc::get(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    render_form(Some(id), &StreamHostForm::from(&stream_host), None)
+}
+
+pub async fn fragmant_stream_host_close_form() -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_stream_host_close_form", "HANDLER");
+
+    render_fragmant("fragmants/stream_host/form.html", &Context::new())
+}
+
+pub async fn fragmant_stream_host_create(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    signals: std::result::Result<Json<StreamHostFormSignals>, JsonRejection>,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_stream_host_create", "HANDLER");
+
+    let StreamHostFormSignals { search, form } = signals?.0;
+
+    let stream_host_c = StreamHostForCreate::from(&form);
+    if let Err(error) = validate_stream_host_c(&stream_host_c) {
+        return render_form_error(None, &form, error);
+    }
+
+    StreamHostBmc::create(&ctx, &mm, stream_host_c)
+        .await
+        .map_err(model::Error::from)?;
+
+    render_saved(&ctx, &mm, &search).await
+}
+
+pub async fn fragmant_stream_host_update(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+    signals: std::result::Result<Json<StreamHostFormSignals>, JsonRejection>,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_stream_host_update", "HANDLER");
+
+    let StreamHostFormSignals { search, form } = signals?.0;
+
+    let stream_host_u = StreamHostForUpdate::from(&form);
+    if let Err(error) = validate_stream_host_u(&stream_host_u) {
+        return render_form_error(Some(id), &form, error);
+    }
+
+    StreamHostBmc::update(&ctx, &mm, id, stream_host_u)
+        .await
+        .map_err(model::Error::from)?;
+
+    render_saved(&ctx, &mm, &search).await
+}
+
+pub async fn fragmant_stream_host_delete(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+    signals: std::result::Result<Json<StreamHostListSignals>, JsonRejection>,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_stream_host_delete", "HANDLER");
+
+    let Json(signals) = signals?;
+
+    StreamHostBmc::delete(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    render_rows(&ctx, &mm, &signals.search).await
+}
+
+// endregion: --- Handlers
+
+// region:    --- Render
+
+async fn render_rows(
+    ctx: &Ctx,
+    mm: &ModelManager,
+    search: &str,
+) -> Result<Html<String>> {
+    let search = search.trim().to_lowercase();
+    let stream_hosts: Vec<StreamHost> = StreamHostBmc::list(ctx, mm)
+        .await
+        .map_err(model::Error::from)?
+        .into_iter()
+        .filter(|stream_host| {
+            search.is_empty()
+                || stream_host.incoming_port.to_string().contains(&search)
+                || stream_host.forward_host.to_lowercase().contains(&search)
+        })
+        .collect();
+
+    let mut context = Context::new();
+    context.insert("stream_hosts", &stream_hosts);
+    context.insert("search", &search);
+
+    render_fragmant("fragmants/stream_host/rows.html", &context)
+}
+
+fn render_form(
+    id: Option<i64>,
+    form: &StreamHostForm,
+    error: Option<String>,
+) -> Result<Html<String>> {
+    let mut context = Context::new();
+    context.insert("id", &id);
+    context.insert(
+        "signals",
+        &serde_json::to_string(&serde_json::json!({
+            "form": form
+        }))?,
+    );
+    context.insert("error", &error);
+
+    render_fragmant("fragmants/stream_host/form.html", &context)
+}
+
+/// Render the form again with the validation message of `error`.
+fn render_form_error(
+    id: Option<i64>,
+    form: &StreamHostForm,
+    error: Error,
+) -> Result<Html<String>> {
+    let message = match error.client_status_and_error().1 {
+        ClientError::INVALID_FIELD { message, .. } => message,
+        _ => return Err(error),
+    };
+
+    render_form(id, form, Some(message))
+}
+
+/// After a create or an update: the refreshed rows and a closed form.
+async fn render_saved(
+    ctx: &Ctx,
+    mm: &ModelManager,
+    search: &str,
+) -> Result<Html<String>> {
+    let Html(rows) = render_rows(ctx, mm, search).await?;
+    let Html(form) =
+        render_fragmant("fragmants/stream_host/form.html", &Context::new())?;
+
+    Ok(Html(format!("{rows}{form}")))
+}
+
+// endregion: --- Render
@@ -0,0 +1,358 @@
+//! Server rendered fragments of the `/upstreams` page.
+//!
+//! Same signals as the `/proxy` page (`search` and `form`), patched into
+//! `#upstream-group-rows` and `#upstream-group-form`. The servers are edited
+//! as text, one `host:port` per line followed by the optional parameters,
+//! e.g. `10.0.0.5:3000 weight=3 max_fails=2 fail_timeout=30s backup`.
+
+use crate::error::{ClientError, Error, Result};
+use crate::extractors::{DatastarQuery, DatastarQueryError};
+use crate::handlers::api::handlers_upstream_group::{
+    validate_upstream_group_c, validate_upstream_group_u,
+};
+use crate::middleware::mw_auth::CtxW;
+use crate::tera::render_fragmant;
+
+use axum::Json;
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{Path, State};
+use axum::response::Html;
+use lib_core::ctx::Ctx;
+use lib_core::model::upstream_group::{
+    UpstreamBalance, UpstreamGroup, UpstreamGroupBmc, UpstreamGroupForCreate,
+    UpstreamGroupForUpdate, UpstreamGroupServer,
+};
+use lib_core::model::{self, ModelManager};
+use serde::{Deserialize, Serialize};
+use tera::Context;
+use tracing::debug;
+
+// region:    --- Signals
+
+#[derive(Debug, Default, Deserialize)]
+pub struct UpstreamGroupListSignals {
+    #[serde(default)]
+    search: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpstreamGroupFormSignals {
+    #[serde(default)]
+    search: String,
+    form: UpstreamGroupForm,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UpstreamGroupForm {
+    name: String,
+    balance: UpstreamBalance,
+    /// One `host:port [param...]` per line.
+    servers: String,
+}
+
+impl UpstreamGroupForm {
+    fn servers(&self) -> Result<Vec<UpstreamGroupServer>> {
+        lines(&self.servers).map(parse_server).collect()
+    }
+}
+
+impl From<&UpstreamGroup> for UpstreamGroupForm {
+    fn from(upstream_group: &UpstreamGroup) -> Self {
+        let servers: Vec<String> =
+            upstream_group.servers.iter().map(format_server).collect();
+
+        UpstreamGroupForm {
+            name: upstream_group.name.clone(),
+            balance: upstream_group.balance,
+            servers: servers.join("\n"),
+        }
+    }
+}
+
+impl TryFrom<&UpstreamGroupForm> for UpstreamGroupForCreate {
+    type Error = Error;
+
+    fn try_from(form: &UpstreamGroupForm) -> Result<Self> {
+        Ok(UpstreamGroupForCreate {
+            name: form.name.trim().to_string(),
+            balance: form.balance,
+            servers: form.servers()?,
+        })
+    }
+}
+
+impl TryFrom<&UpstreamGroupForm> for UpstreamGroupForUpdate {
+    type Error = Error;
+
+    fn try_from(form: &UpstreamGroupForm) -> Result<Self> {
+        Ok(UpstreamGroupForUpdate {
+            name: Some(form.name.trim().to_string()),
+            balance: Some(form.balance),
+            servers: Some(form.servers()?),
+        })
+    }
+}
+
+/// `host:port` then the parameters, an IPv6 host is written `[::1]:3000`.
+fn parse_server(line: &str) -> Result<UpstreamGroupServer> {
+    let invalid = || Error::InvalidUpstreamServer(line.to_string());
+
+    let mut words = line.split_whitespace();
+    let (host, port) = words
+        .next()
+        .and_then(|address| address.rsplit_once(':'))
+        .ok_or_else(invalid)?;
+    let host = host
+        .strip_prefix('[')
+        .and_then(|host| host.strip_suffix(']'))
+        .unwrap_or(host);
+
+    let mut server = UpstreamGroupServer {
+        host: host.to_string(),
+        port: port.parse().map_err(|_| invalid())?,
+        weight: None,
+        max_fails: None,
+        fail_timeout: None,
+        backup: false,
+    };
+    for word in words {
+        match word.split_once('=') {
+            Some(("weight", weight)) => {
+                server.weight = Some(weight.parse().map_err(|_| invalid())?)
+            }
+            Some(("max_fails", max_fails)) => {
+                server.max_fails =
+                    Some(max_fails.parse().map_err(|_| invalid())?)
+            }
+            Some(("fail_timeout", fail_timeout)) => {
+                let seconds =
+                    fail_timeout.strip_suffix('s').unwrap_or(fail_timeout);
+                server.fail_timeout =
+                    Some(seconds.parse().map_err(|_| invalid())?)
+            }
+            None if word == "backup" => server.backup = true,
+            _ => return Err(invalid()),
+        }
+    }
+
+    Ok(server)
+}
+
+fn format_server(server: &UpstreamGroupServer) -> String {
+    let mut line = if server.host.contains(':') {
+        format!("[{}]:{}", server.host, server.port)
+    } else {
+        format!("{}:{}", server.host, server.port)
+    };
+    if let Some(weight) = server.weight {
+        line.push_str(&format!(" weight={weight}"));
+    }
+    if let Some(max_fails) = server.max_fails {
+        line.push_str(&format!(" max_fails={max_fails}"));
+    }
+    if let Some(fail_timeout) = server.fail_timeout {
+        line.push_str(&format!(" fail_timeout={fail_timeout}s"));
+    }
+    if server.backup {
+        line.push_str(" backup");
+    }
+
+    line
+}
+
+/// The trimmed non-empty lines of a textarea.
+fn lines(text: &str) -> impl Iterator<Item = &str> {
+    text.lines().map(str::trim).filter(|line| !line.is_empty())
+}
+
+// endregion: --- Signals
+
+// region:    --- Handlers
+
+pub async fn fragmant_upstream_group_rows(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    signals: std::result::Result<
+        DatastarQuery<UpstreamGroupListSignals>,
+        DatastarQueryError,
+    >,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_upstream_group_rows", "HANDLER");
+
+    let DatastarQuery(signals) = signals?;
+
+    render_rows(&ctx, &mm, &signals.search).await
+}
+
+pub async fn fragmant_upstream_group_new_form() -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_upstream_group_new_form", "HANDLER");
+
+    render_form(None, &UpstreamGroupForm::default(), None)
+}
+
+pub async fn fragmant_upstream_group_edit_form(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_upstream_group_edit_form", "HANDLER");
+
+    let upstream_group = UpstreamGroupBmc::get(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    render_form(Some(id), &UpstreamGroupForm::from(&upstream_group), None)
+}
+
+pub async fn fragmant_upstream_group_close_form() -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_upstream_group_close_form", "HANDLER");
+
+    render_fragmant("fragmants/upstream_group/form.html", &Context::new())
+}
+
+pub async fn fragmant_upstream_group_create(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    signals: std::result::Result<Json<UpstreamGroupFormSignals>, JsonRejection>,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_upstream_group_create", "HANDLER");
+
+    let UpstreamGroupFormSignals { search, form } = signals?.0;
+
+    let upstream_group_c = match UpstreamGroupForCreate::try_from(&form)
+        .and_then(|upstream_group_c| {
+            validate_upstream_group_c(&upstream_group_c)
+                .map(|_| upstream_group_c)
+        }) {
+        Ok(upstream_group_c) => upstream_group_c,
+        Err(error) => return render_form_error(None, &form, error),
+    };
+
+    UpstreamGroupBmc::create(&ctx, &mm, upstream_group_c)
+        .await
+        .map_err(model::Error::from)?;
+
+    render_saved(&ctx, &mm, &search).await
+}
+
+pub async fn fragmant_upstream_group_update(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+    signals: std::result::Result<Json<UpstreamGroupFormSignals>, JsonRejection>,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_upstream_group_update", "HANDLER");
+
+    let UpstreamGroupFormSignals { search, form } = signals?.0;
+
+    let upstream_group_u = match UpstreamGroupForUpdate::try_from(&form)
+        .and_then(|upstream_group_u| {
+            validate_upstream_group_u(&upstream_group_u)
+                .map(|_| upstream_group_u)
+        }) {
+        Ok(upstream_group_u) => upstream_group_u,
+        Err(error) => return render_form_error(Some(id), &form, error),
+    };
+
+    UpstreamGroupBmc::update(&ctx, &mm, id, upstream_group_u)
+        .await
+        .map_err(model::Error::from)?;
+
+    render_saved(&ctx, &mm, &search).await
+}
+
+pub async fn fragmant_upstream_group_delete(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
+    Path(id): Path<i64>,
+    signals: std::result::Result<Json<UpstreamGroupListSignals>, JsonRejection>,
+) -> Result<Html<String>> {
+    debug!("{:<12} - fragmant_upstream_group_delete", "HANDLER");
+
+    let Json(signals) = signals?;
+
+    UpstreamGroupBmc::delete(&ctx, &mm, id)
+        .await
+        .map_err(model::Error::from)?;
+
+    render_rows(&ctx, &mm, &signals.search).await
+}
+
+// endregion: --- Handlers
+
+// region:    --- Render
+
+async fn render_rows(
+    ctx: &Ctx,
+    mm: &ModelManager,
+    search: &str,
+) -> Result<Html<String>> {
+    let search = search.trim().to_lowercase();
+    let upstream_groups: Vec<UpstreamGroup> = UpstreamGroupBmc::list(ctx, mm)
+        .await
+        .map_err(model::Error::from)?
+        .into_iter()
+        .filter(|upstream_group| {
+            search.is_empty()
+                || upstream_group.name.to_lowercase().contains(&search)
+                || upstream_group
+                    .servers
+                    .iter()
+                    .any(|server| server.host.contains(&search))
+        })
+        .collect();
+
+    let mut context = Context::new();
+    context.insert("upstream_groups", &upstream_groups);
+    context.insert("search", &search);
+
+    render_fragmant("fragmants/upstream_group/rows.html", &context)
+}
+
+fn render_form(
+    id: Option<i64>,
+    form: &UpstreamGroupForm,
+    error: Option<String>,
+) -> Result<Html<String>> {
+    let mut context = Context::new();
+    context.insert("id", &id);
+    context.insert(
+        "signals",
+        &serde_json::to_string(&serde_json::json!({
+            "form": form
+        }))?,
+    );
+    context.insert("error", &error);
+
+    render_fragmant("fragmants/upstream_group/form.html", &context)
+}
+
+/// Render the form again with the validation message of `error`.
+fn render_form_error(
+    id: Option<i64>,
+    form: &UpstreamGroupForm,
+    error: Error,
+) -> Result<Html<String>> {
+    let message = match error.client_status_and_error().1 {
+        ClientError::INVALID_FIELD { message, .. } => message,
+        _ => return Err(error),
+    };
+
+    render_form(id, form, Some(message))
+}
+
+/// After a create or an update: the refreshed rows and a closed form.
+async fn render_saved(
+    ctx: &Ctx,
+    mm: &ModelManager,
+    search: &str,
+) -> Result<Html<String>> {
+    let Html(rows) = render_rows(ctx, mm, search).await?;
+    let Html(form) =
+        render_fragmant("fragmants/upstream_group/form.html", &Context::new())?;
+
+    Ok(Html(format!("{rows}{form}")))
+}
+
+// endregion: --- Render
@@ -3,10 +3,16 @@ use axum::http::{StatusCode, Uri};
 use axum::response::{IntoResponse, Response};
 use tera::Context;
 
+pub mod access_list;
 pub mod auth;
+pub mod certificate;
 pub mod dashboard;
+pub mod dead_host;
 pub mod home;
 pub mod proxy;
+pub mod redirection;
+pub mod stream;
+pub mod upstream;
 
 pub mod fragmant;
 
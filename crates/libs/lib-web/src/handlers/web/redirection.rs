@@ -0,0 +1,11 @@
+use crate::{error::Result, tera::render};
+use axum::response::IntoResponse;
+use tera::Context;
+use tracing::debug;
+
+pub async fn render_redirection() -> Result<impl IntoResponse> {
+    debug!("{:<12} - web_redirection_handler", "HANDLER");
+
+    let context = Context::new();
+    render("routes/redirection.html", &context).map(IntoResponse::into_response)
+}
@@ -0,0 +1,11 @@
+use crate::{error::Result, tera::render};
+use axum::response::IntoResponse;
+use tera::Context;
+use tracing::debug;
+
+pub async fn render_upstream() -> Result<impl IntoResponse> {
+    debug!("{:<12} - web_upstream_handler", "HANDLER");
+
+    let context = Context::new();
+    render("routes/upstream.html", &context).map(IntoResponse::into_response)
+}
@@ -0,0 +1,126 @@
+//! Custom certificates, uploaded as PEM text (api) or files (fragments).
+
+use crate::error::{Error, Result};
+use crate::utils::error_page::UploadedFile;
+use lib_core::model::certificate::{self, CertificateBundle};
+use lib_utils::b64::b64_decode;
+
+const PEM_FILE_MAX_SIZE: usize = 64 * 1024;
+
+/// Check the uploaded chain against its key, see `CertificateBundle::parse`.
+pub fn parse_certificate_upload(
+    certificate_pem: &str,
+    private_key_pem: &str,
+) -> Result<CertificateBundle> {
+    CertificateBundle::parse(certificate_pem, private_key_pem).map_err(|ex| {
+        match ex {
+            certificate::Error::CertificatePemEmpty => {
+                Error::CertificateInvalid(
+                    "No certificate found in the PEM".to_string(),
+                )
+            }
+            certificate::Error::CertificatePemInvalid(message) => {
+                Error::CertificateInvalid(message)
+            }
+            certificate::Error::PrivateKeyInvalid(message) => {
+                Error::PrivateKeyInvalid(message)
+            }
+            certificate::Error::PrivateKeyMismatch => {
+                Error::CertificateKeyMismatch
+            }
+            certificate::Error::CertificateExpired { not_after } => {
+                Error::CertificateExpired { not_after }
+            }
+            certificate::Error::CertificateNotYetValid { not_before } => {
+                Error::CertificateNotYetValid { not_before }
+            }
+            certificate::Error::CertificateNotInChain { subject } => {
+                Error::CertificateChainInvalid(subject)
+            }
+            ex => Error::Model(ex.into()),
+        }
+    })
+}
+
+/// Decode an uploaded certificate (`.pem`, `.crt`) file.
+pub fn decode_certificate_file(file: &UploadedFile) -> Result<String> {
+    decode_pem_file(file).map_err(Error::CertificateInvalid)
+}
+
+/// Decode an uploaded private key (`.pem`, `.key`) file.
+pub fn decode_private_key_file(file: &UploadedFile) -> Result<String> {
+    decode_pem_file(file).map_err(Error::PrivateKeyInvalid)
+}
+
+fn decode_pem_file(file: &UploadedFile) -> std::result::Result<String, String> {
+    let content = b64_decode(&file.contents)
+        .map_err(|_| format!("'{}' can't be decoded", file.name))?;
+    if content.len() > PEM_FILE_MAX_SIZE {
+        return Err(format!("'{}' is larger than 64 KiB", file.name));
+    }
+
+    String::from_utf8(content)
+        .map_err(|_| format!("'{}' is not a PEM file", file.name))
+}
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+    use crate::error::Error as WebError;
+
+    #[test]
+    fn test_decode_certificate_file_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_file = UploadedFile {
+            name: "fullchain.pem".to_string(),
+            // "-----BEGIN CERTIFICATE-----"
+            contents: "LS0tLS1CRUdJTiBDRVJUSUZJQ0FURS0tLS0t".to_string(),
+            mime: None,
+        };
+
+        // -- Exec
+        let pem = decode_certificate_file(&fx_file)?;
+
+        // -- Check
+        assert_eq!(pem, "-----BEGIN CERTIFICATE-----");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_private_key_file_not_text_err() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_file = UploadedFile {
+            name: "privkey.der".to_string(),
+            // [0xff, 0xfe]
+            contents: "//4=".to_string(),
+            mime: None,
+        };
+
+        // -- Exec
+        let res = decode_private_key_file(&fx_file);
+
+        // -- Check
+        assert!(matches!(res, Err(WebError::PrivateKeyInvalid(_))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_certificate_upload_not_pem_err() -> Result<()> {
+        // -- Exec
+        let res = parse_certificate_upload("not a certificate", "");
+
+        // -- Check
+        assert!(matches!(res, Err(WebError::CertificateInvalid(_))));
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
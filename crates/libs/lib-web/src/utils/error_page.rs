@@ -0,0 +1,163 @@
+//! Error pages of the 404 hosts, stored as static files.
+//!
+//! Pages live in `STATIC_FOLDER/html/error_pages` so nginx can serve them
+//! with the rest of the static folder. The paths stored in the database
+//! are relative to `STATIC_FOLDER`.
+
+use crate::error::{Error, Result};
+use crate::tera::render_fragmant;
+use crate::web_config;
+use lib_utils::b64::b64_decode;
+use serde::{Deserialize, Serialize};
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use tera::Context;
+use tokio::fs;
+
+/// Relative to `STATIC_FOLDER`.
+const ERROR_PAGES_DIR: &str = "html/error_pages";
+
+/// Default page of the 404 hosts without a custom page.
+const DEFAULT_ERROR_PAGE: &str = "default_404.html";
+
+const ERROR_PAGE_MAX_SIZE: usize = 1024 * 1024;
+
+/// A file as sent by a Datastar `data-bind` file input.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct UploadedFile {
+    pub name: String,
+    /// Base64 encoded.
+    pub contents: String,
+    pub mime: Option<String>,
+}
+
+/// Decode and check an uploaded html page.
+pub fn decode_error_page(file: &UploadedFile) -> Result<Vec<u8>> {
+    let is_html = file.name.ends_with(".html")
+        || file.name.ends_with(".htm")
+        || file.mime.as_deref() == Some("text/html");
+    if !is_html {
+        return Err(Error::InvalidErrorPage(format!(
+            "'{}' is not an html file",
+            file.name
+        )));
+    }
+
+    let content = b64_decode(&file.contents).map_err(|_| {
+        Error::InvalidErrorPage(format!("'{}' can't be decoded", file.name))
+    })?;
+    if content.len() > ERROR_PAGE_MAX_SIZE {
+        return Err(Error::InvalidErrorPage(format!(
+            "'{}' is larger than 1 MiB",
+            file.name
+        )));
+    }
+    if std::str::from_utf8(&content).is_err() {
+        return Err(Error::InvalidErrorPage(format!(
+            "'{}' is not utf-8 text",
+            file.name
+        )));
+    }
+
+    Ok(content)
+}
+
+/// Write the custom page of the 404 host `id`, returns its path relative
+/// to `STATIC_FOLDER`.
+pub async fn save_dead_host_error_page(
+    id: i64,
+    content: &[u8],
+) -> Result<String> {
+    save_error_page(&format!("dead_host_{id}.html"), content).await
+}
+
+/// Write the default page rendered from `error404.html`, returns its path
+/// relative to `STATIC_FOLDER`.
+pub async fn save_default_error_page() -> Result<String> {
+    let mut context = Context::new();
+    context.insert("title", "Not Found");
+    context.insert("message", "The page you are looking for does not exist.");
+    let page = render_fragmant("error404.html", &context)?;
+
+    save_error_page(DEFAULT_ERROR_PAGE, page.0.as_bytes()).await
+}
+
+/// A missing file is not an error.
+pub async fn remove_error_page(path: &str) -> Result<()> {
+    match fs::remove_file(static_path(path)).await {
+        Err(ex) if ex.kind() != ErrorKind::NotFound => {
+            Err(Error::ErrorPageCantSave(ex.to_string()))
+        }
+        _ => Ok(()),
+    }
+}
+
+async fn save_error_page(file_name: &str, content: &[u8]) -> Result<String> {
+    let path = format!("{ERROR_PAGES_DIR}/{file_name}");
+
+    let full_path = static_path(&path);
+    if let Some(dir) = full_path.parent() {
+        fs::create_dir_all(dir)
+            .await
+            .map_err(|ex| Error::ErrorPageCantSave(ex.to_string()))?;
+    }
+    fs::write(&full_path, content)
+        .await
+        .map_err(|ex| Error::ErrorPageCantSave(ex.to_string()))?;
+
+    Ok(path)
+}
+
+fn static_path(path: &str) -> PathBuf {
+    PathBuf::from(&web_config().STATIC_FOLDER).join(path)
+}
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+    use crate::error::Error as WebError;
+
+    #[test]
+    fn test_decode_error_page_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_file = UploadedFile {
+            name: "gone.html".to_string(),
+            // "<h1>Gone</h1>"
+            contents: "PGgxPkdvbmU8L2gxPg==".to_string(),
+            mime: Some("text/html".to_string()),
+        };
+
+        // -- Exec
+        let content = decode_error_page(&fx_file)?;
+
+        // -- Check
+        assert_eq!(content, b"<h1>Gone</h1>");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_error_page_not_html_err() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_file = UploadedFile {
+            name: "logo.png".to_string(),
+            contents: "iVBORw0KGgo=".to_string(),
+            mime: Some("image/png".to_string()),
+        };
+
+        // -- Exec
+        let res = decode_error_page(&fx_file);
+
+        // -- Check
+        assert!(matches!(res, Err(WebError::InvalidErrorPage(_))));
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
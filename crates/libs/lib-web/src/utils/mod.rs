@@ -1 +1,4 @@
+pub mod certificate;
+pub mod error_page;
 pub mod token;
+pub mod validate;
@@ -0,0 +1,505 @@
+//! Input validation shared by the api and the fragmant handlers.
+
+use crate::error::{Error, Result};
+use lib_nginx::SnippetContext;
+use std::net::IpAddr;
+
+/// Proxied to the web-server by every host, see `lib_nginx`.
+const ACME_CHALLENGE_PATH: &str = "/.well-known/acme-challenge";
+
+/// At least one domain, each one a valid (optionally wildcard) hostname.
+pub fn validate_domain_names(domain_names: &[String]) -> Result<()> {
+    if domain_names.is_empty() {
+        return Err(Error::NoDomainName);
+    }
+
+    for domain in domain_names {
+        let hostname = domain.strip_prefix("*.").unwrap_or(domain);
+        if !is_hostname(hostname) {
+            return Err(Error::InvalidDomainName(domain.to_string()));
+        }
+    }
+
+    Ok(())
+}
+
+/// A hostname or an IPv4/IPv6 address.
+pub fn validate_forward_host(host: &str) -> Result<()> {
+    if host.parse::<IpAddr>().is_ok() || is_hostname(host) {
+        Ok(())
+    } else {
+        Err(Error::InvalidForwardHost(host.to_string()))
+    }
+}
+
+pub fn validate_port(port: u16) -> Result<()> {
+    if port == 0 {
+        return Err(Error::InvalidPort(port));
+    }
+
+    Ok(())
+}
+
+/// Port nginx listens on for a stream host.
+pub fn validate_incoming_port(port: u16) -> Result<()> {
+    validate_port(port).map_err(|_| Error::InvalidIncomingPort(port))
+}
+
+/// An absolute `http://` or `https://` url, e.g. a redirection target.
+pub fn validate_forward_url(url: &str) -> Result<()> {
+    let invalid = || Error::InvalidForwardUrl(url.to_string());
+
+    let rest = url
+        .strip_prefix("http://")
+        .or_else(|| url.strip_prefix("https://"))
+        .ok_or_else(invalid)?;
+    if rest.contains(|c: char| c.is_whitespace() || matches!(c, ';' | '"')) {
+        return Err(invalid());
+    }
+
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = match authority.strip_prefix('[') {
+        // IPv6, e.g. `[fd00::5]:8080`.
+        Some(ipv6) => {
+            let (host, port) = ipv6.split_once(']').ok_or_else(invalid)?;
+            host.parse::<IpAddr>().map_err(|_| invalid())?;
+            return match port {
+                "" => Ok(()),
+                port => validate_url_port(port).ok_or_else(invalid),
+            };
+        }
+        None => match authority.split_once(':') {
+            Some((host, port)) => {
+                validate_url_port(port).ok_or_else(invalid)?;
+                host
+            }
+            None => authority,
+        },
+    };
+
+    validate_forward_host(host).map_err(|_| invalid())
+}
+
+/// The status codes nginx `return` accepts with a url.
+pub fn validate_redirect_code(code: u16) -> Result<()> {
+    match code {
+        301 | 302 | 307 | 308 => Ok(()),
+        _ => Err(Error::InvalidRedirectCode(code)),
+    }
+}
+
+/// A user of an htpasswd file: no `:`, whitespace or control character.
+pub fn validate_username(username: &str) -> Result<()> {
+    if username.is_empty()
+        || username.len() > 255
+        || username
+            .chars()
+            .any(|c| c == ':' || c.is_whitespace() || c.is_control())
+    {
+        return Err(Error::InvalidUsername(username.to_string()));
+    }
+
+    Ok(())
+}
+
+/// Address of an allow/deny rule: an IPv4/IPv6 address, a CIDR or `all`.
+pub fn validate_access_address(address: &str) -> Result<()> {
+    let invalid = || Error::InvalidAccessAddress(address.to_string());
+    if address == "all" {
+        return Ok(());
+    }
+
+    let (ip, prefix) = match address.split_once('/') {
+        Some((ip, prefix)) => {
+            (ip, Some(prefix.parse::<u8>().map_err(|_| invalid())?))
+        }
+        None => (address, None),
+    };
+    let max_prefix = match ip.parse::<IpAddr>().map_err(|_| invalid())? {
+        IpAddr::V4(_) => 32,
+        IpAddr::V6(_) => 128,
+    };
+    if prefix.is_some_and(|prefix| prefix > max_prefix) {
+        return Err(invalid());
+    }
+
+    Ok(())
+}
+
+/// Prefixes of the custom locations of a proxy host, each one distinct
+/// from the others (a trailing `/` aside), from `location /` and from the
+/// acme challenges.
+pub fn validate_location_paths(paths: &[&str]) -> Result<()> {
+    for (i, path) in paths.iter().enumerate() {
+        if !path.starts_with('/')
+            || path.chars().any(|c| {
+                c.is_whitespace()
+                    || c.is_control()
+                    || matches!(c, '{' | '}' | ';' | '"' | '\'' | '#' | '$')
+            })
+        {
+            return Err(Error::InvalidLocationPath(path.to_string()));
+        }
+
+        let prefix = path.trim_end_matches('/');
+        if prefix.is_empty() || prefix.starts_with(ACME_CHALLENGE_PATH) {
+            return Err(Error::ReservedLocationPath(path.to_string()));
+        }
+        if let Some(other) = paths[..i]
+            .iter()
+            .find(|other| other.trim_end_matches('/') == prefix)
+        {
+            return Err(Error::ConflictingLocationPath {
+                path: path.to_string(),
+                other: other.to_string(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// A `proxy_set_header`: a token name and a single line value.
+pub fn validate_header(name: &str, value: &str) -> Result<()> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'))
+        || value.chars().any(char::is_control)
+    {
+        return Err(Error::InvalidHeader(name.to_string()));
+    }
+
+    Ok(())
+}
+
+/// Raw directives of a custom location, checked with the nginx parser.
+pub fn validate_location_snippet(path: &str, snippet: &str) -> Result<()> {
+    lib_nginx::parse_snippet(snippet, SnippetContext::Location).map_err(
+        |cause| Error::InvalidLocationSnippet {
+            path: path.to_string(),
+            cause,
+        },
+    )?;
+
+    Ok(())
+}
+
+/// Raw directives of the `server` block of a proxy host.
+pub fn validate_host_snippet(snippet: &str) -> Result<()> {
+    lib_nginx::parse_snippet(snippet, SnippetContext::Server)
+        .map_err(|cause| Error::InvalidHostSnippet { cause })?;
+
+    Ok(())
+}
+
+/// Path (and query) requested by the HTTP health checks.
+pub fn validate_health_check_path(path: &str) -> Result<()> {
+    if !path.starts_with('/')
+        || path.chars().any(|c| c.is_whitespace() || c.is_control())
+    {
+        return Err(Error::InvalidHealthCheckPath(path.to_string()));
+    }
+
+    Ok(())
+}
+
+/// Seconds between two health checks, see the `proxy_host` table.
+pub fn validate_health_check_interval(interval: u32) -> Result<()> {
+    if !(5..=3600).contains(&interval) {
+        return Err(Error::InvalidHealthCheckInterval(interval));
+    }
+
+    Ok(())
+}
+
+/// Requests per second of a client, see `lib_nginx::RateLimit`.
+pub fn validate_rate_limit(rate: u32) -> Result<()> {
+    if !(1..=10000).contains(&rate) {
+        return Err(Error::InvalidRateLimit(rate));
+    }
+
+    Ok(())
+}
+
+/// Requests of a client over the rate, delayed rather than refused.
+pub fn validate_rate_limit_burst(burst: u32) -> Result<()> {
+    if burst > 10000 {
+        return Err(Error::InvalidRateLimitBurst(burst));
+    }
+
+    Ok(())
+}
+
+/// Concurrent connections of a client.
+pub fn validate_connection_limit(limit: u32) -> Result<()> {
+    if !(1..=10000).contains(&limit) {
+        return Err(Error::InvalidConnectionLimit(limit));
+    }
+
+    Ok(())
+}
+
+/// `:port` part of a url, without the colon.
+fn validate_url_port(port: &str) -> Option<()> {
+    let port = port.strip_prefix(':').unwrap_or(port);
+    port.parse::<u16>()
+        .ok()
+        .filter(|port| *port != 0)
+        .map(|_| ())
+}
+
+/// RFC 1123 hostname: dot separated labels of 1 to 63 alphanumeric or `-`
+/// characters, not starting or ending with `-`, 253 characters at most.
+fn is_hostname(value: &str) -> bool {
+    let value = value.strip_suffix('.').unwrap_or(value);
+
+    !value.is_empty()
+        && value.len() <= 253
+        && value.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+
+    #[test]
+    fn test_validate_domain_names_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_domain_names = vec![
+            "example.com".to_string(),
+            "*.example.com".to_string(),
+            "my-app.internal".to_string(),
+            "localhost".to_string(),
+        ];
+
+        // -- Exec & Check
+        validate_domain_names(&fx_domain_names)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_domain_names_err() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_invalids = [
+            "",
+            "exa mple.com",
+            "-example.com",
+            "example..com",
+            "ex_ample.com",
+            "example.com;",
+            "*.*.example.com",
+        ];
+
+        for fx_invalid in fx_invalids {
+            // -- Exec
+            let res = validate_domain_names(&[fx_invalid.to_string()]);
+
+            // -- Check
+            assert!(
+                matches!(res, Err(crate::Error::InvalidDomainName(ref d)) if d == fx_invalid),
+                "{fx_invalid:?} should be invalid"
+            );
+        }
+        assert!(matches!(
+            validate_domain_names(&[]),
+            Err(crate::Error::NoDomainName)
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_forward_ok() -> Result<()> {
+        // -- Exec & Check
+        validate_forward_host("10.0.0.5")?;
+        validate_forward_host("fd00::5")?;
+        validate_forward_host("backend.svc")?;
+        validate_port(8080)?;
+
+        assert!(validate_forward_host("http://backend").is_err());
+        assert!(validate_port(0).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_access_address_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_valid = ["all", "10.0.0.1", "10.0.0.0/8", "fd00::/8", "::1/128"];
+        let fx_invalid = ["", "any", "10.0.0.0/33", "fd00::/129", "10.0.0/8"];
+
+        // -- Exec & Check
+        for address in fx_valid {
+            validate_access_address(address)?;
+        }
+        for address in fx_invalid {
+            assert!(
+                matches!(
+                    validate_access_address(address),
+                    Err(crate::Error::InvalidAccessAddress(_))
+                ),
+                "'{address}' should be invalid"
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_location_paths_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_paths = ["/api/", "/api/v2/", "/.well-known/", "/static"];
+
+        // -- Exec & Check
+        validate_location_paths(&fx_paths)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_location_paths_err() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_invalids: [&[&str]; 5] = [
+            &["api/"],
+            &["/api {"],
+            &["/"],
+            &["/.well-known/acme-challenge/x"],
+            &["/api/", "/static/", "/api"],
+        ];
+
+        for fx_invalid in fx_invalids {
+            // -- Exec
+            let res = validate_location_paths(fx_invalid);
+
+            // -- Check
+            assert!(
+                matches!(
+                    res,
+                    Err(crate::Error::InvalidLocationPath(_)
+                        | crate::Error::ReservedLocationPath(_)
+                        | crate::Error::ConflictingLocationPath { .. })
+                ),
+                "{fx_invalid:?} should be invalid"
+            );
+        }
+        assert!(matches!(
+            validate_location_paths(&["/api/", "/api"]),
+            Err(crate::Error::ConflictingLocationPath { ref path, ref other })
+                if path == "/api" && other == "/api/"
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_host_snippet_err() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_snippet = "client_max_body_size 50m;\nlisten 8080;\n";
+
+        // -- Exec
+        let res = validate_host_snippet(fx_snippet);
+
+        // -- Check
+        assert!(matches!(
+            res,
+            Err(crate::Error::InvalidHostSnippet {
+                cause: lib_nginx::Error::DirectiveNotAllowed {
+                    line: 2,
+                    col: 1,
+                    ..
+                }
+            })
+        ));
+        validate_host_snippet("")?;
+        validate_location_snippet("/api/", "proxy_read_timeout 300s;")?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_health_check_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_paths = ["/", "/healthz", "/status?full=1"];
+
+        // -- Exec & Check
+        for fx_path in fx_paths {
+            validate_health_check_path(fx_path)?;
+        }
+        for fx_invalid in ["healthz", "/health z", ""] {
+            assert!(matches!(
+                validate_health_check_path(fx_invalid),
+                Err(crate::Error::InvalidHealthCheckPath(_))
+            ));
+        }
+        validate_health_check_interval(30)?;
+        assert!(validate_health_check_interval(4).is_err());
+        assert!(validate_health_check_interval(3601).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_limits_ok() -> Result<()> {
+        // -- Exec & Check
+        validate_rate_limit(10000)?;
+        validate_rate_limit_burst(0)?;
+        validate_connection_limit(1)?;
+        assert!(matches!(
+            validate_rate_limit(0),
+            Err(crate::Error::InvalidRateLimit(0))
+        ));
+        assert!(matches!(
+            validate_rate_limit_burst(10001),
+            Err(crate::Error::InvalidRateLimitBurst(10001))
+        ));
+        assert!(matches!(
+            validate_connection_limit(0),
+            Err(crate::Error::InvalidConnectionLimit(0))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_forward_url_ok() -> Result<()> {
+        // -- Exec & Check
+        validate_forward_url("https://www.example.com")?;
+        validate_forward_url("http://example.com:8080/path?query=1")?;
+        validate_forward_url("https://[fd00::5]:8443/")?;
+        validate_forward_url("http://10.0.0.5")?;
+        validate_redirect_code(308)?;
+
+        for fx_invalid in [
+            "www.example.com",
+            "ftp://example.com",
+            "https://",
+            "https://exa mple.com",
+            "https://example.com:0",
+            "https://example.com/;rm",
+            "https://[fd00::5",
+        ] {
+            assert!(
+                matches!(validate_forward_url(fx_invalid), Err(crate::Error::InvalidForwardUrl(ref u)) if u == fx_invalid),
+                "{fx_invalid:?} should be invalid"
+            );
+        }
+        assert!(validate_redirect_code(303).is_err());
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
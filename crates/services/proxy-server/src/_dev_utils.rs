@@ -0,0 +1,166 @@
+//! Fixtures of the in-process tests: the records, a backend and the
+//! proxy listening on the loopback.
+
+use crate::{ProxyServer, RouteTable};
+use axum::Router;
+use axum::extract::ws::{WebSocket, WebSocketUpgrade};
+use axum::http::{HeaderMap, Uri};
+use axum::response::IntoResponse;
+use axum::routing::{any, get};
+use lib_core::model::certificate::{
+    Certificate, CertificateChallenge, CertificateProvider,
+};
+use lib_core::model::health_check::HealthStatus;
+use lib_core::model::proxy_host::{ForwardScheme, ProxyHost};
+use rcgen::{
+    BasicConstraints, CertificateParams, DnType, IsCa, Issuer, KeyPair,
+};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// Headers of the requests sent back by the backend, `(name, as)`.
+const SEEN_HEADERS: [(&str, &str); 5] = [
+    ("host", "x-seen-host"),
+    ("x-real-ip", "x-seen-real-ip"),
+    ("x-forwarded-for", "x-seen-forwarded-for"),
+    ("x-forwarded-proto", "x-seen-forwarded-proto"),
+    ("x-custom", "x-seen-custom"),
+];
+
+/// Answer delay of the `/slow` path of the backends.
+pub const SLOW_DELAY: Duration = Duration::from_millis(300);
+
+pub fn fx_proxy_host(id: i64, domain: &str, forward_port: u16) -> ProxyHost {
+    ProxyHost {
+        id,
+        owner_id: "demo1".to_string(),
+        domain_names: vec![domain.to_string()],
+        forward_scheme: ForwardScheme::Http,
+        forward_host: "127.0.0.1".to_string(),
+        forward_port,
+        cache_assets: false,
+        block_exploits: false,
+        allow_websocket_upgrade: false,
+        ssl_forced: false,
+        http2_support: false,
+        hsts_enabled: false,
+        hsts_subdomains: false,
+        certificate_id: None,
+        certificate_name: None,
+        access_list_id: None,
+        access_list_name: None,
+        upstream_group_id: None,
+        upstream_group_name: None,
+        locations: Vec::new(),
+        advanced: String::new(),
+        rate_limit: None,
+        rate_limit_burst: 0,
+        rate_limit_nodelay: false,
+        connection_limit: None,
+        health_check_path: None,
+        health_check_interval: 30,
+        health_status: HealthStatus::Unknown,
+        health_latency_ms: None,
+        health_check_time: None,
+        enabled: true,
+        ctime: String::new(),
+        mtime: String::new(),
+    }
+}
+
+/// An issued certificate for `domains` and the PEM of its CA.
+pub fn fx_certificate(
+    id: i64,
+    domains: &[&str],
+) -> Result<(Certificate, String)> {
+    let ca_key = KeyPair::generate()?;
+    let mut ca_params = CertificateParams::new(Vec::<String>::new())?;
+    ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    ca_params
+        .distinguished_name
+        .push(DnType::CommonName, "Test CA");
+    let ca_pem = ca_params.self_signed(&ca_key)?.pem();
+    let ca = Issuer::new(ca_params, ca_key);
+
+    let domain_names: Vec<String> =
+        domains.iter().map(|domain| domain.to_string()).collect();
+    let key = KeyPair::generate()?;
+    let leaf =
+        CertificateParams::new(domain_names.clone())?.signed_by(&key, &ca)?;
+
+    let certificate = Certificate {
+        id,
+        owner_id: "demo1".to_string(),
+        nice_name: "test".to_string(),
+        provider: CertificateProvider::Custom,
+        challenge: CertificateChallenge::Http01,
+        domain_names,
+        certificate_pem: format!("{}{ca_pem}", leaf.pem()),
+        private_key_pem: key.serialize_pem(),
+        issuer: "rcgen".to_string(),
+        not_before: None,
+        not_after: None,
+        renew_time: None,
+        renew_error: None,
+        ctime: String::new(),
+        mtime: String::new(),
+    };
+
+    Ok((certificate, ca_pem))
+}
+
+/// A backend answering `<name> <path>` with the proxy headers it got
+/// back in `x-seen-*` headers, after `SLOW_DELAY` for `/slow`, and
+/// echoing the websocket messages of `/ws`.
+pub async fn start_backend(name: &'static str) -> Result<SocketAddr> {
+    async fn echo(mut socket: WebSocket) {
+        while let Some(Ok(msg)) = socket.recv().await {
+            if socket.send(msg).await.is_err() {
+                break;
+            }
+        }
+    }
+
+    let app = Router::new()
+        .route(
+            "/ws",
+            get(|ws: WebSocketUpgrade| async move { ws.on_upgrade(echo) }),
+        )
+        .fallback(any(move |uri: Uri, headers: HeaderMap| async move {
+            if uri.path() == "/slow" {
+                tokio::time::sleep(SLOW_DELAY).await;
+            }
+            let mut seen = HeaderMap::new();
+            for (name, seen_name) in SEEN_HEADERS {
+                if let Some(value) = headers.get(name) {
+                    seen.insert(seen_name, value.clone());
+                }
+            }
+            (seen, format!("{name} {}", uri.path())).into_response()
+        }));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(async move { axum::serve(listener, app).await });
+
+    Ok(addr)
+}
+
+/// The proxy serving `table`, and its `(http, https)` addresses.
+pub async fn start_proxy(
+    table: RouteTable,
+) -> Result<(Arc<ProxyServer>, SocketAddr, SocketAddr)> {
+    let http = TcpListener::bind("127.0.0.1:0").await?;
+    let https = TcpListener::bind("127.0.0.1:0").await?;
+    let (http_addr, https_addr) = (http.local_addr()?, https.local_addr()?);
+
+    let server = Arc::new(ProxyServer::new(table, Some(https_addr.port()))?);
+    tokio::spawn(server.clone().serve_http(http));
+    tokio::spawn(server.clone().serve_https(https));
+
+    Ok((server, http_addr, https_addr))
+}
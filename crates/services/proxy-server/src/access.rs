@@ -0,0 +1,325 @@
+//! Access lists, checked as nginx does with the directives rendered by
+//! `lib_nginx::AccessConf`:
+//!
+//! - The ip rules are matched in order, an address matched by none of
+//!   them is denied.
+//! - The basic auth users are checked against their `hash_htpasswd` hash.
+//! - `satisfy` combines both when the list has rules and users.
+
+use hyper::HeaderMap;
+use hyper::header::AUTHORIZATION;
+use lib_auth::pwd::{self, ContentToHash};
+use lib_core::model::access_list::{
+    AccessList, AccessListSatisfy, AccessListUser, AccessRule, AccessRuleAction,
+};
+use lib_utils::b64::b64_decode;
+use std::net::IpAddr;
+use uuid::Uuid;
+
+/// Outcome of the check of a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Allowed,
+    /// 403, the address is denied.
+    Forbidden,
+    /// 401, with a `WWW-Authenticate` challenge.
+    Unauthorized,
+}
+
+#[derive(Debug, Clone)]
+pub struct Access {
+    /// The `realm` of the challenge, the name of the list.
+    pub realm: String,
+    satisfy: AccessListSatisfy,
+    rules: Vec<IpRule>,
+    /// `(username, pwd)`, with the `pwd` as stored.
+    users: Vec<(String, String)>,
+}
+
+impl Access {
+    /// `users` may hold the users of other lists.
+    pub fn new(access_list: &AccessList, users: &[AccessListUser]) -> Self {
+        Access {
+            realm: access_list.name.clone(),
+            satisfy: access_list.satisfy,
+            // Rules not parsing were rejected by the api, skipping one
+            // can only deny more since the unmatched addresses are.
+            rules: access_list.rules.iter().filter_map(IpRule::new).collect(),
+            users: users
+                .iter()
+                .filter(|user| user.access_list_id == access_list.id)
+                .map(|user| (user.username.clone(), user.pwd.clone()))
+                .collect(),
+        }
+    }
+
+    pub async fn check(&self, ip: IpAddr, headers: &HeaderMap) -> Verdict {
+        let ip_allowed = self.ip_allowed(ip);
+        if self.users.is_empty() {
+            return ip_verdict(ip_allowed);
+        }
+
+        match (self.satisfy, ip_allowed) {
+            (AccessListSatisfy::All, Some(false)) => Verdict::Forbidden,
+            (AccessListSatisfy::Any, Some(true)) => Verdict::Allowed,
+            _ => match self.auth_basic(headers).await {
+                true => Verdict::Allowed,
+                false => Verdict::Unauthorized,
+            },
+        }
+    }
+
+    /// `None` when the list has no rules.
+    fn ip_allowed(&self, ip: IpAddr) -> Option<bool> {
+        if self.rules.is_empty() {
+            return None;
+        }
+
+        let allowed = self
+            .rules
+            .iter()
+            .find(|rule| rule.matches(ip))
+            .is_some_and(|rule| rule.action == AccessRuleAction::Allow);
+        Some(allowed)
+    }
+
+    async fn auth_basic(&self, headers: &HeaderMap) -> bool {
+        let Some((username, password)) = basic_credentials(headers) else {
+            return false;
+        };
+        let Some((_, pwd_ref)) =
+            self.users.iter().find(|(name, _)| *name == username)
+        else {
+            return false;
+        };
+
+        // The htpasswd scheme reads its salt from the hash.
+        pwd::validate_pwd(
+            ContentToHash {
+                content: password,
+                salt: Uuid::nil(),
+            },
+            pwd_ref.clone(),
+        )
+        .await
+        .is_ok()
+    }
+}
+
+fn ip_verdict(ip_allowed: Option<bool>) -> Verdict {
+    match ip_allowed {
+        Some(false) => Verdict::Forbidden,
+        _ => Verdict::Allowed,
+    }
+}
+
+/// `(username, password)` of an `Authorization: Basic` header.
+fn basic_credentials(headers: &HeaderMap) -> Option<(String, String)> {
+    let value = headers.get(AUTHORIZATION)?.to_str().ok()?;
+    let (scheme, credentials) = value.split_once(' ')?;
+    if !scheme.eq_ignore_ascii_case("basic") {
+        return None;
+    }
+
+    let credentials =
+        String::from_utf8(b64_decode(credentials.trim()).ok()?).ok()?;
+    let (username, password) = credentials.split_once(':')?;
+
+    Some((username.to_string(), password.to_string()))
+}
+
+// region:    --- IpRule
+
+#[derive(Debug, Clone)]
+struct IpRule {
+    action: AccessRuleAction,
+    /// `None` for `all`.
+    network: Option<(IpAddr, u8)>,
+}
+
+impl IpRule {
+    /// `None` when the address does not parse.
+    fn new(rule: &AccessRule) -> Option<Self> {
+        let network = match rule.address.as_str() {
+            "all" => None,
+            address => {
+                let (ip, prefix) = match address.split_once('/') {
+                    Some((ip, prefix)) => {
+                        (ip.parse().ok()?, Some(prefix.parse().ok()?))
+                    }
+                    None => (address.parse().ok()?, None),
+                };
+                let max_prefix = match ip {
+                    IpAddr::V4(_) => 32,
+                    IpAddr::V6(_) => 128,
+                };
+                let prefix = prefix.unwrap_or(max_prefix);
+                if prefix > max_prefix {
+                    return None;
+                }
+                Some((ip, prefix))
+            }
+        };
+
+        Some(IpRule {
+            action: rule.action,
+            network,
+        })
+    }
+
+    fn matches(&self, ip: IpAddr) -> bool {
+        let Some((network, prefix)) = self.network else {
+            return true;
+        };
+
+        // An IPv4 client on a dual stack socket.
+        let ip = match ip {
+            IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+            ip => ip,
+        };
+
+        match (network, ip) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - u32::from(prefix));
+                let mask = mask.unwrap_or(0);
+                u32::from(network) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - u32::from(prefix));
+                let mask = mask.unwrap_or(0);
+                u128::from(network) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+// endregion: --- IpRule
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+    use hyper::header::HeaderValue;
+
+    fn fx_access_list(
+        satisfy: AccessListSatisfy,
+        rules: &[(AccessRuleAction, &str)],
+    ) -> AccessList {
+        AccessList {
+            id: 1,
+            owner_id: "demo1".to_string(),
+            name: "Staff only".to_string(),
+            satisfy,
+            rules: rules
+                .iter()
+                .map(|(action, address)| AccessRule {
+                    action: *action,
+                    address: address.to_string(),
+                })
+                .collect(),
+            usernames: Vec::new(),
+            ctime: String::new(),
+            mtime: String::new(),
+        }
+    }
+
+    async fn fx_user(username: &str, password: &str) -> Result<AccessListUser> {
+        Ok(AccessListUser {
+            access_list_id: 1,
+            username: username.to_string(),
+            pwd: pwd::hash_htpasswd(ContentToHash {
+                content: password.to_string(),
+                salt: Uuid::new_v4(),
+            })
+            .await?,
+        })
+    }
+
+    fn fx_basic(credentials: &str) -> Result<HeaderMap> {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!(
+                "Basic {}",
+                lib_utils::b64::b64_encode(credentials)
+            ))?,
+        );
+        Ok(headers)
+    }
+
+    #[tokio::test]
+    async fn test_access_ip_rules_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_access = Access::new(
+            &fx_access_list(
+                AccessListSatisfy::All,
+                &[
+                    (AccessRuleAction::Deny, "10.0.0.13"),
+                    (AccessRuleAction::Allow, "10.0.0.0/8"),
+                    (AccessRuleAction::Allow, "fd00::/8"),
+                ],
+            ),
+            &[],
+        );
+        let headers = HeaderMap::new();
+
+        // -- Exec & Check
+        for (ip, expected) in [
+            ("10.1.2.3", Verdict::Allowed),
+            ("::ffff:10.1.2.3", Verdict::Allowed),
+            ("10.0.0.13", Verdict::Forbidden),
+            ("192.168.1.1", Verdict::Forbidden),
+            ("fd12::1", Verdict::Allowed),
+            ("fe80::1", Verdict::Forbidden),
+        ] {
+            assert_eq!(fx_access.check(ip.parse()?, &headers).await, expected);
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_access_satisfy_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_rules = [(AccessRuleAction::Allow, "10.0.0.0/8")];
+        let fx_users = [fx_user("alice", "secret").await?];
+        let fx_all = Access::new(
+            &fx_access_list(AccessListSatisfy::All, &fx_rules),
+            &fx_users,
+        );
+        let fx_any = Access::new(
+            &fx_access_list(AccessListSatisfy::Any, &fx_rules),
+            &fx_users,
+        );
+        let fx_inside = "10.0.0.1".parse()?;
+        let fx_outside = "192.168.1.1".parse()?;
+        let fx_ok = fx_basic("alice:secret")?;
+        let fx_wrong = fx_basic("alice:wrong")?;
+
+        // -- Exec & Check
+        assert_eq!(fx_all.check(fx_inside, &fx_ok).await, Verdict::Allowed);
+        assert_eq!(
+            fx_all.check(fx_inside, &fx_wrong).await,
+            Verdict::Unauthorized
+        );
+        assert_eq!(fx_all.check(fx_outside, &fx_ok).await, Verdict::Forbidden);
+        assert_eq!(
+            fx_any.check(fx_inside, &HeaderMap::new()).await,
+            Verdict::Allowed
+        );
+        assert_eq!(fx_any.check(fx_outside, &fx_ok).await, Verdict::Allowed);
+        assert_eq!(
+            fx_any.check(fx_outside, &fx_wrong).await,
+            Verdict::Unauthorized
+        );
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -0,0 +1,49 @@
+use lib_utils::envs::{DefaultIfMissing, IfMissing, get_env_parse};
+use std::net::SocketAddr;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+pub fn proxy_config() -> &'static ProxyConfig {
+    static INSTANCE: OnceLock<ProxyConfig> = OnceLock::new();
+
+    INSTANCE.get_or_init(|| {
+        ProxyConfig::load_from_env().unwrap_or_else(|ex| {
+            panic!("FATAL - WHILE LOADING CONF - Cause: {ex:?}")
+        })
+    })
+}
+
+/// Configuration of the native data plane.
+#[allow(non_snake_case)]
+pub struct ProxyConfig {
+    /// Plain http listener, `0.0.0.0:80` by default.
+    pub PROXY_HTTP_ADDR: SocketAddr,
+    /// TLS listener, none when not set.
+    pub PROXY_HTTPS_ADDR: Option<SocketAddr>,
+    /// A forward target not accepting the connection within this delay
+    /// is skipped, as the nginx `proxy_connect_timeout`.
+    pub PROXY_CONNECT_TIMEOUT: Duration,
+    /// The standalone binary checks for a new apply of the web-server this
+    /// often, see `ProxyServer::spawn_poller`.
+    pub PROXY_POLL_INTERVAL: Duration,
+}
+
+impl ProxyConfig {
+    fn load_from_env() -> lib_utils::envs::Result<ProxyConfig> {
+        Ok(ProxyConfig {
+            PROXY_HTTP_ADDR: get_env_parse("SERVICE_PROXY_HTTP_ADDR")
+                .if_missing(SocketAddr::from(([0, 0, 0, 0], 80)))?,
+            PROXY_HTTPS_ADDR: get_env_parse("SERVICE_PROXY_HTTPS_ADDR")
+                .map(Some)
+                .default_if_missing()?,
+            PROXY_CONNECT_TIMEOUT: Duration::from_secs(
+                get_env_parse("SERVICE_PROXY_CONNECT_TIMEOUT_SECONDS")
+                    .if_missing(60)?,
+            ),
+            PROXY_POLL_INTERVAL: Duration::from_secs(
+                get_env_parse("SERVICE_PROXY_POLL_INTERVAL_SECONDS")
+                    .if_missing(2)?,
+            ),
+        })
+    }
+}
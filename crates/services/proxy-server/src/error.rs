@@ -0,0 +1,31 @@
+use lib_core::model::{
+    self, access_list, certificate, proxy_host, upstream_group,
+};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(thiserror::Error, Debug, strum_macros::Display)]
+pub enum Error {
+    CertificateInvalid {
+        id: i64,
+        cause: String,
+    },
+
+    // -- Modules
+    #[error(transparent)]
+    Model(#[from] model::Error),
+    #[error(transparent)]
+    ProxyHost(#[from] proxy_host::Error),
+    #[error(transparent)]
+    UpstreamGroup(#[from] upstream_group::Error),
+    #[error(transparent)]
+    AccessList(#[from] access_list::Error),
+    #[error(transparent)]
+    Certificate(#[from] certificate::Error),
+
+    // -- Externals
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Tls(#[from] rustls::Error),
+}
@@ -0,0 +1,97 @@
+//! The `block_exploits` rules of `lib_nginx`, matched on the same request
+//! parts as the nginx variables they are written for.
+
+use hyper::header::USER_AGENT;
+use hyper::{HeaderMap, Uri};
+use lib_nginx::BLOCK_EXPLOITS_RULES;
+use regex::{Regex, RegexBuilder};
+use std::sync::LazyLock;
+use tracing::warn;
+
+/// The nginx variables of the rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Variable {
+    QueryString,
+    RequestUri,
+    HttpUserAgent,
+}
+
+static RULES: LazyLock<Vec<(Variable, Regex)>> = LazyLock::new(|| {
+    BLOCK_EXPLOITS_RULES
+        .iter()
+        .filter_map(|(variable, regex)| {
+            let variable = match *variable {
+                "$query_string" => Variable::QueryString,
+                "$request_uri" => Variable::RequestUri,
+                "$http_user_agent" => Variable::HttpUserAgent,
+                _ => {
+                    warn!("{:<12} - rule on {variable} skipped", "EXPLOITS");
+                    return None;
+                }
+            };
+            match RegexBuilder::new(regex).case_insensitive(true).build() {
+                Ok(regex) => Some((variable, regex)),
+                Err(ex) => {
+                    warn!("{:<12} - rule {regex} skipped: {ex}", "EXPLOITS");
+                    None
+                }
+            }
+        })
+        .collect()
+});
+
+/// Whether the request matches one of the rules, to be refused with a
+/// `403`.
+pub fn is_exploit(uri: &Uri, headers: &HeaderMap) -> bool {
+    let query_string = uri.query().unwrap_or_default();
+    let request_uri = uri.path_and_query().map_or("/", |p| p.as_str());
+    let user_agent = headers
+        .get(USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+
+    RULES.iter().any(|(variable, regex)| {
+        let value = match variable {
+            Variable::QueryString => query_string,
+            Variable::RequestUri => request_uri,
+            Variable::HttpUserAgent => user_agent,
+        };
+        regex.is_match(value)
+    })
+}
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+    use hyper::header::HeaderValue;
+
+    #[test]
+    fn test_is_exploit_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mut fx_scanner = HeaderMap::new();
+        fx_scanner.insert(USER_AGENT, HeaderValue::from_static("sqlmap/1.7"));
+        let fx_uri = |uri: &'static str| Uri::from_static(uri);
+
+        // -- Exec & Check
+        assert_eq!(RULES.len(), BLOCK_EXPLOITS_RULES.len());
+        assert!(is_exploit(
+            &fx_uri("/items?id=1+UNION+SELECT+password("),
+            &HeaderMap::new()
+        ));
+        assert!(is_exploit(&fx_uri("/a/../etc/passwd"), &HeaderMap::new()));
+        assert!(is_exploit(&fx_uri("/"), &fx_scanner));
+        assert!(!is_exploit(
+            &fx_uri("/items?id=1&sort=name"),
+            &HeaderMap::new()
+        ));
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -0,0 +1,565 @@
+//! Native data plane, an alternative to nginx.
+//!
+//! Serves the proxy hosts of the database with hyper: host based routing,
+//! custom locations, upstream groups, access lists, exploit blocking,
+//! rate and connection limits, websocket upgrades and TLS termination
+//! with the certificates of the store. The redirection, stream and dead
+//! hosts are only served by nginx, as are the `advanced` snippets.
+//!
+//! The routes are swapped in whole on each change of the database, see
+//! `reload`. Run in the process writing the changes (the web-server with
+//! `SERVICE_PROXY_NATIVE`) they are reloaded on the `ModelEvent`s, the
+//! standalone binary polls the applies of the web-server in the database
+//! and reloads at once on `SIGHUP`.
+
+// region:    --- Modules
+
+mod access;
+mod config;
+mod error;
+mod exploits;
+mod limit;
+mod proxy;
+mod reload;
+mod table;
+mod tls;
+mod upstream;
+
+#[cfg(test)]
+mod _dev_utils;
+
+pub use self::config::proxy_config;
+pub use self::error::{Error, Result};
+pub use self::reload::spawn_proxy;
+pub use self::table::RouteTable;
+
+use crate::limit::Limits;
+use crate::proxy::Scheme;
+use arc_swap::ArcSwap;
+use hyper::service::service_fn;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::conn::auto;
+use lib_core::model::ModelManager;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::TlsConnector;
+use tracing::{debug, error, info};
+
+// endregion: --- Modules
+
+const ACCEPT_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+pub struct ProxyServer {
+    /// Shared with the certificate resolver of the https listener.
+    table: Arc<ArcSwap<RouteTable>>,
+    /// Port of the https listener, the forced SSL hosts are only
+    /// redirected when there is one.
+    https_port: Option<u16>,
+    connect_timeout: Duration,
+    tls_connector: TlsConnector,
+    /// Kept across the swaps of `table`.
+    limits: Limits,
+}
+
+impl ProxyServer {
+    pub fn new(table: RouteTable, https_port: Option<u16>) -> Result<Self> {
+        Ok(ProxyServer {
+            table: Arc::new(ArcSwap::from_pointee(table)),
+            https_port,
+            connect_timeout: proxy_config().PROXY_CONNECT_TIMEOUT,
+            tls_connector: tls::connector()?,
+            limits: Limits::default(),
+        })
+    }
+
+    /// The server of the routes of `mm`, for the configured listeners.
+    pub async fn load(mm: &ModelManager) -> Result<Self> {
+        let table = RouteTable::load(mm).await?;
+        info!("{:<12} - {} host name(s)", "ROUTES", table.len());
+
+        let https_port =
+            proxy_config().PROXY_HTTPS_ADDR.map(|addr| addr.port());
+        ProxyServer::new(table, https_port)
+    }
+
+    /// Serve the configured listeners, https only when there is one.
+    pub async fn serve(self: Arc<Self>) -> Result<()> {
+        let config = proxy_config();
+
+        let http = TcpListener::bind(config.PROXY_HTTP_ADDR).await?;
+        info!("{:<12} - http {:?}", "LISTENING", http.local_addr());
+        match config.PROXY_HTTPS_ADDR {
+            Some(addr) => {
+                let https = TcpListener::bind(addr).await?;
+                info!("{:<12} - https {:?}", "LISTENING", https.local_addr());
+                tokio::try_join!(
+                    self.clone().serve_http(http),
+                    self.serve_https(https)
+                )?;
+            }
+            None => self.serve_http(http).await?,
+        }
+
+        Ok(())
+    }
+
+    /// Serve plain http on `listener`.
+    pub async fn serve_http(
+        self: Arc<Self>,
+        listener: TcpListener,
+    ) -> Result<()> {
+        loop {
+            let (stream, client) = accept(&listener).await;
+            tokio::spawn(self.clone().serve_connection(
+                stream,
+                client,
+                Scheme::Http,
+            ));
+        }
+    }
+
+    /// Serve https on `listener`.
+    pub async fn serve_https(
+        self: Arc<Self>,
+        listener: TcpListener,
+    ) -> Result<()> {
+        let acceptor = tls::acceptor(self.table.clone())?;
+        loop {
+            let (stream, client) = accept(&listener).await;
+            let acceptor = acceptor.clone();
+            let server = self.clone();
+            tokio::spawn(async move {
+                match acceptor.accept(stream).await {
+                    Ok(stream) => {
+                        server
+                            .serve_connection(stream, client, Scheme::Https)
+                            .await
+                    }
+                    Err(ex) => {
+                        debug!("{:<12} - tls handshake: {ex}", "PROXY")
+                    }
+                }
+            });
+        }
+    }
+
+    /// HTTP/1.1, with upgrades, or HTTP/2 on `io`.
+    async fn serve_connection<T>(
+        self: Arc<Self>,
+        io: T,
+        client: SocketAddr,
+        scheme: Scheme,
+    ) where
+        T: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+    {
+        let service = service_fn(move |req| {
+            let server = self.clone();
+            async move {
+                Ok::<_, Infallible>(server.handle(req, client, scheme).await)
+            }
+        });
+
+        if let Err(ex) = auto::Builder::new(TokioExecutor::new())
+            .serve_connection_with_upgrades(TokioIo::new(io), service)
+            .await
+        {
+            debug!("{:<12} - connection of {client}: {ex}", "PROXY");
+        }
+    }
+}
+
+/// The next connection, the errors (e.g. too many open files) are logged
+/// and retried.
+async fn accept(listener: &TcpListener) -> (TcpStream, SocketAddr) {
+    loop {
+        match listener.accept().await {
+            Ok(accepted) => return accepted,
+            Err(ex) => {
+                error!("{:<12} - accept failed: {ex}", "PROXY");
+                tokio::time::sleep(ACCEPT_RETRY_DELAY).await;
+            }
+        }
+    }
+}
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+    use crate::_dev_utils::{
+        SLOW_DELAY, fx_certificate, fx_proxy_host, start_backend, start_proxy,
+    };
+    use futures::{SinkExt, StreamExt};
+    use lib_auth::pwd::{self, ContentToHash};
+    use lib_core::model::access_list::{
+        AccessList, AccessListSatisfy, AccessListUser,
+    };
+    use lib_core::model::proxy_host::{
+        ForwardScheme, ProxyHeader, ProxyLocation,
+    };
+    use lib_core::model::upstream_group::{
+        UpstreamBalance, UpstreamGroup, UpstreamGroupServer,
+    };
+    use reqwest::StatusCode;
+    use reqwest::header::{LOCATION, STRICT_TRANSPORT_SECURITY};
+    use tokio_tungstenite::tungstenite::Message;
+
+    const FX_DOMAIN: &str = "app.example.com";
+
+    /// A client resolving `FX_DOMAIN` to the proxy.
+    fn client(proxy: SocketAddr) -> Result<reqwest::Client> {
+        Ok(reqwest::Client::builder()
+            .resolve(FX_DOMAIN, proxy)
+            .redirect(reqwest::redirect::Policy::none())
+            .build()?)
+    }
+
+    #[tokio::test]
+    async fn test_proxy_routing_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_app = start_backend("app").await?;
+        let fx_api = start_backend("api").await?;
+        let mut fx_host = fx_proxy_host(1, FX_DOMAIN, fx_app.port());
+        fx_host.locations = vec![ProxyLocation {
+            path: "/api/".to_string(),
+            forward_scheme: ForwardScheme::Http,
+            forward_host: "127.0.0.1".to_string(),
+            forward_port: fx_api.port(),
+            headers: vec![ProxyHeader {
+                name: "X-Custom".to_string(),
+                value: "$host via $scheme".to_string(),
+            }],
+            advanced: String::new(),
+        }];
+        let (_, proxy, _) =
+            start_proxy(RouteTable::new(&[fx_host], &[], &[], &[], &[]))
+                .await?;
+        let client = client(proxy)?;
+        let url =
+            |path: &str| format!("http://{FX_DOMAIN}:{}{path}", proxy.port());
+
+        // -- Exec
+        let res = client.get(url("/")).send().await?;
+        let res_api = client.get(url("/api/users")).send().await?;
+        let res_unknown = client
+            .get(format!("http://127.0.0.1:{}/", proxy.port()))
+            .send()
+            .await?;
+
+        // -- Check
+        assert_eq!(res.headers()["x-seen-host"], FX_DOMAIN);
+        assert_eq!(res.headers()["x-seen-real-ip"], "127.0.0.1");
+        assert_eq!(res.headers()["x-seen-forwarded-proto"], "http");
+        assert_eq!(res.text().await?, "app /");
+        assert_eq!(
+            res_api.headers()["x-seen-custom"],
+            "app.example.com via http"
+        );
+        assert_eq!(res_api.text().await?, "api /api/users");
+        assert_eq!(res_unknown.status(), StatusCode::NOT_FOUND);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_proxy_upstream_next_server_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_app = start_backend("app").await?;
+        let fx_closed = TcpListener::bind("127.0.0.1:0").await?.local_addr()?;
+        let fx_group = UpstreamGroup {
+            id: 1,
+            owner_id: "demo1".to_string(),
+            name: "app".to_string(),
+            balance: UpstreamBalance::RoundRobin,
+            servers: [fx_closed, fx_app]
+                .iter()
+                .map(|addr| UpstreamGroupServer {
+                    host: "127.0.0.1".to_string(),
+                    port: addr.port(),
+                    weight: None,
+                    max_fails: None,
+                    fail_timeout: None,
+                    backup: false,
+                })
+                .collect(),
+            ctime: String::new(),
+            mtime: String::new(),
+        };
+        let mut fx_host = fx_proxy_host(1, FX_DOMAIN, 1);
+        fx_host.upstream_group_id = Some(fx_group.id);
+        let (_, proxy, _) = start_proxy(RouteTable::new(
+            &[fx_host],
+            &[fx_group],
+            &[],
+            &[],
+            &[],
+        ))
+        .await?;
+        let client = client(proxy)?;
+
+        // -- Exec & Check
+        for _ in 0..4 {
+            let res = client
+                .get(format!("http://{FX_DOMAIN}:{}/", proxy.port()))
+                .send()
+                .await?;
+            assert_eq!(res.text().await?, "app /");
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_proxy_access_list_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_app = start_backend("app").await?;
+        let fx_access_list = AccessList {
+            id: 1,
+            owner_id: "demo1".to_string(),
+            name: "Staff only".to_string(),
+            satisfy: AccessListSatisfy::All,
+            rules: Vec::new(),
+            usernames: vec!["alice".to_string()],
+            ctime: String::new(),
+            mtime: String::new(),
+        };
+        let fx_user = AccessListUser {
+            access_list_id: 1,
+            username: "alice".to_string(),
+            pwd: pwd::hash_htpasswd(ContentToHash {
+                content: "secret".to_string(),
+                salt: pwd::generate_random_uuid_v4().await?,
+            })
+            .await?,
+        };
+        let mut fx_host = fx_proxy_host(1, FX_DOMAIN, fx_app.port());
+        fx_host.access_list_id = Some(fx_access_list.id);
+        let (_, proxy, _) = start_proxy(RouteTable::new(
+            &[fx_host],
+            &[],
+            &[fx_access_list],
+            &[fx_user],
+            &[],
+        ))
+        .await?;
+        let client = client(proxy)?;
+        let url = format!("http://{FX_DOMAIN}:{}/", proxy.port());
+
+        // -- Exec
+        let res_anonymous = client.get(&url).send().await?;
+        let res_wrong = client
+            .get(&url)
+            .basic_auth("alice", Some("wrong"))
+            .send()
+            .await?;
+        let res_ok = client
+            .get(&url)
+            .basic_auth("alice", Some("secret"))
+            .send()
+            .await?;
+
+        // -- Check
+        assert_eq!(res_anonymous.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            res_anonymous.headers()["www-authenticate"],
+            "Basic realm=\"Staff only\""
+        );
+        assert_eq!(res_wrong.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(res_ok.text().await?, "app /");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_proxy_exploits_limits_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_app = start_backend("app").await?;
+        let mut fx_host = fx_proxy_host(1, FX_DOMAIN, fx_app.port());
+        fx_host.block_exploits = true;
+        fx_host.rate_limit = Some(1);
+        let mut fx_other_host =
+            fx_proxy_host(2, "other.example.com", fx_app.port());
+        fx_other_host.rate_limit = Some(1);
+        fx_other_host.rate_limit_burst = 5;
+        fx_other_host.rate_limit_nodelay = true;
+        fx_other_host.connection_limit = Some(1);
+        let (_, proxy, _) = start_proxy(RouteTable::new(
+            &[fx_host, fx_other_host],
+            &[],
+            &[],
+            &[],
+            &[],
+        ))
+        .await?;
+        let client = reqwest::Client::builder()
+            .resolve(FX_DOMAIN, proxy)
+            .resolve("other.example.com", proxy)
+            .build()?;
+        let url = |host: &str, path: &str| {
+            format!("http://{host}:{}{path}", proxy.port())
+        };
+
+        // -- Exec
+        let res_exploit = client
+            .get(url(FX_DOMAIN, "/?id=1+union+select+pwd("))
+            .send()
+            .await?;
+        let res_first = client.get(url(FX_DOMAIN, "/")).send().await?;
+        let res_over_rate = client.get(url(FX_DOMAIN, "/")).send().await?;
+        // Same rate, but a budget of its own.
+        let slow =
+            tokio::spawn(client.get(url("other.example.com", "/slow")).send());
+        tokio::time::sleep(SLOW_DELAY / 3).await;
+        let res_other =
+            client.get(url("other.example.com", "/")).send().await?;
+        let res_slow = slow.await??;
+
+        // -- Check
+        assert_eq!(res_exploit.status(), StatusCode::FORBIDDEN);
+        assert_eq!(res_first.text().await?, "app /");
+        assert_eq!(res_over_rate.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(res_slow.text().await?, "app /slow");
+        // In the burst, refused for the `/slow` one in progress.
+        assert_eq!(res_other.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_proxy_tls_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_app = start_backend("app").await?;
+        let (fx_certificate, fx_ca_pem) = fx_certificate(1, &[FX_DOMAIN])?;
+        let mut fx_host = fx_proxy_host(1, FX_DOMAIN, fx_app.port());
+        fx_host.ssl_forced = true;
+        fx_host.hsts_enabled = true;
+        let (_, proxy, proxy_https) = start_proxy(RouteTable::new(
+            &[fx_host],
+            &[],
+            &[],
+            &[],
+            &[fx_certificate],
+        ))
+        .await?;
+        let https_client = reqwest::Client::builder()
+            .resolve(FX_DOMAIN, proxy_https)
+            .add_root_certificate(reqwest::Certificate::from_pem(
+                fx_ca_pem.as_bytes(),
+            )?)
+            .build()?;
+
+        // -- Exec
+        let res_http = client(proxy)?
+            .get(format!("http://{FX_DOMAIN}:{}/a?b=c", proxy.port()))
+            .send()
+            .await?;
+        let res = https_client
+            .get(format!("https://{FX_DOMAIN}:{}/", proxy_https.port()))
+            .send()
+            .await?;
+
+        // -- Check
+        assert_eq!(res_http.status(), StatusCode::MOVED_PERMANENTLY);
+        assert_eq!(
+            res_http.headers()[LOCATION],
+            format!("https://{FX_DOMAIN}:{}/a?b=c", proxy_https.port())
+        );
+        assert_eq!(
+            res.headers()[STRICT_TRANSPORT_SECURITY],
+            "max-age=63072000"
+        );
+        assert_eq!(res.headers()["x-seen-forwarded-proto"], "https");
+        assert_eq!(res.text().await?, "app /");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_proxy_set_table_in_flight_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_old = start_backend("old").await?;
+        let fx_new = start_backend("new").await?;
+        let fx_table = |backend: SocketAddr| {
+            RouteTable::new(
+                &[fx_proxy_host(1, FX_DOMAIN, backend.port())],
+                &[],
+                &[],
+                &[],
+                &[],
+            )
+        };
+        let (server, proxy, _) = start_proxy(fx_table(fx_old)).await?;
+        let client = client(proxy)?;
+        let url =
+            |path: &str| format!("http://{FX_DOMAIN}:{}{path}", proxy.port());
+
+        // -- Exec
+        let in_flight = tokio::spawn(client.get(url("/slow")).send());
+        tokio::time::sleep(SLOW_DELAY / 3).await;
+        server.set_table(fx_table(fx_new));
+        let res = client.get(url("/")).send().await?;
+        let res_in_flight = in_flight.await??;
+
+        // -- Check
+        assert_eq!(res.text().await?, "new /");
+        assert_eq!(res_in_flight.text().await?, "old /slow");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_proxy_websocket_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_app = start_backend("app").await?;
+        let mut fx_host = fx_proxy_host(1, FX_DOMAIN, fx_app.port());
+        fx_host.allow_websocket_upgrade = true;
+        let fx_closed_host =
+            fx_proxy_host(2, "closed.example.com", fx_app.port());
+        let (_, proxy, _) = start_proxy(RouteTable::new(
+            &[fx_host, fx_closed_host],
+            &[],
+            &[],
+            &[],
+            &[],
+        ))
+        .await?;
+        let request = |host: &str| {
+            tokio_tungstenite::tungstenite::http::Request::builder()
+                .uri(format!("ws://127.0.0.1:{}/ws", proxy.port()))
+                .header("host", host)
+                .header("connection", "Upgrade")
+                .header("upgrade", "websocket")
+                .header("sec-websocket-version", "13")
+                .header(
+                    "sec-websocket-key",
+                    tokio_tungstenite::tungstenite::handshake::client::generate_key(),
+                )
+                .body(())
+        };
+
+        // -- Exec
+        let (mut socket, _) =
+            tokio_tungstenite::connect_async(request(FX_DOMAIN)?).await?;
+        socket.send(Message::text("hello")).await?;
+        let echo = socket.next().await.ok_or("Should echo")??;
+        let closed =
+            tokio_tungstenite::connect_async(request("closed.example.com")?)
+                .await;
+
+        // -- Check
+        assert_eq!(echo, Message::text("hello"));
+        assert!(closed.is_err());
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -0,0 +1,214 @@
+//! Rate and connection limits, checked as nginx does with the directives
+//! rendered by `lib_nginx::RateLimit` and `lib_nginx::ConnectionLimit`:
+//!
+//! - The requests of a client are counted per host, by its address.
+//! - The requests over the rate wait their turn, up to `burst` of them,
+//!   or are served at once with `nodelay`. The next ones get a `429`.
+//! - The requests of a client in progress on a host are capped, the
+//!   others get a `429`.
+//!
+//! The counts are kept by the `ProxyServer`, across the reloads of the
+//! routes, as nginx keeps its zones across its reloads.
+
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Clients counted before the idle ones are forgotten, about what a
+/// zone of nginx holds.
+const MAX_CLIENTS: usize = 16_000;
+
+/// `(host id, client address)`, a zone per host.
+type Key = (i64, IpAddr);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// Requests per second of a client.
+    pub rate: u32,
+    /// Requests over the rate delayed, the next ones refused.
+    pub burst: u32,
+    /// Serves the `burst` requests without delay.
+    pub nodelay: bool,
+}
+
+/// The requests of a client over the rate, drained at the rate.
+#[derive(Debug)]
+struct Bucket {
+    excess: f64,
+    last: Instant,
+    /// When `excess` is back to zero, the bucket can be forgotten.
+    drained: Instant,
+}
+
+#[derive(Debug, Default)]
+pub struct Limits {
+    buckets: Mutex<HashMap<Key, Bucket>>,
+    connections: Arc<Mutex<HashMap<Key, u32>>>,
+}
+
+impl Limits {
+    /// The delay before serving the request, `None` when it is refused.
+    pub fn check_rate(
+        &self,
+        host_id: i64,
+        client: IpAddr,
+        limit: RateLimit,
+    ) -> Option<Duration> {
+        let now = Instant::now();
+        let rate = f64::from(limit.rate.max(1));
+        let mut buckets =
+            self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if buckets.len() >= MAX_CLIENTS {
+            buckets.retain(|_, bucket| bucket.drained > now);
+        }
+
+        let excess = match buckets.entry((host_id, client.to_canonical())) {
+            Entry::Vacant(entry) => {
+                entry.insert(Bucket {
+                    excess: 0.0,
+                    last: now,
+                    drained: now,
+                });
+                return Some(Duration::ZERO);
+            }
+            Entry::Occupied(mut entry) => {
+                let bucket = entry.get_mut();
+                let elapsed = now.duration_since(bucket.last).as_secs_f64();
+                let excess = (bucket.excess - rate * elapsed).max(0.0) + 1.0;
+                if excess > f64::from(limit.burst) {
+                    return None;
+                }
+                bucket.excess = excess;
+                bucket.last = now;
+                bucket.drained = now + Duration::from_secs_f64(excess / rate);
+                excess
+            }
+        };
+
+        if limit.nodelay {
+            Some(Duration::ZERO)
+        } else {
+            Some(Duration::from_secs_f64(excess / rate))
+        }
+    }
+
+    /// Count a request in progress until the guard is dropped, `None`
+    /// when the client has `limit` of them already.
+    pub fn acquire_connection(
+        &self,
+        host_id: i64,
+        client: IpAddr,
+        limit: u32,
+    ) -> Option<ConnectionGuard> {
+        let key = (host_id, client.to_canonical());
+        let mut connections =
+            self.connections.lock().unwrap_or_else(|e| e.into_inner());
+        let count = connections.entry(key).or_insert(0);
+        if *count >= limit {
+            return None;
+        }
+        *count += 1;
+
+        Some(ConnectionGuard {
+            connections: self.connections.clone(),
+            key,
+        })
+    }
+}
+
+/// A request in progress, see `Limits::acquire_connection`.
+#[derive(Debug)]
+pub struct ConnectionGuard {
+    connections: Arc<Mutex<HashMap<Key, u32>>>,
+    key: Key,
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        let mut connections =
+            self.connections.lock().unwrap_or_else(|e| e.into_inner());
+        if let Entry::Occupied(mut entry) = connections.entry(self.key) {
+            *entry.get_mut() -= 1;
+            if *entry.get() == 0 {
+                entry.remove();
+            }
+        }
+    }
+}
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+
+    const FX_CLIENT: IpAddr = IpAddr::V4(std::net::Ipv4Addr::LOCALHOST);
+
+    #[test]
+    fn test_limits_rate_burst_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let limits = Limits::default();
+        let fx_delayed = RateLimit {
+            rate: 10,
+            burst: 2,
+            nodelay: false,
+        };
+        let fx_nodelay = RateLimit {
+            nodelay: true,
+            ..fx_delayed
+        };
+
+        // -- Exec
+        let res: Vec<Option<Duration>> = (0..4)
+            .map(|_| limits.check_rate(1, FX_CLIENT, fx_delayed))
+            .collect();
+        let res_nodelay: Vec<Option<Duration>> = (0..4)
+            .map(|_| limits.check_rate(2, FX_CLIENT, fx_nodelay))
+            .collect();
+
+        // -- Check
+        // The first one at once, two in the burst, the last one refused.
+        assert_eq!(res[0], Some(Duration::ZERO));
+        assert!(res[1].is_some_and(|delay| delay > Duration::from_millis(90)));
+        assert!(res[2].is_some_and(|delay| delay > Duration::from_millis(190)));
+        assert_eq!(res[3], None);
+        assert_eq!(
+            res_nodelay[..3],
+            [
+                Some(Duration::ZERO),
+                Some(Duration::ZERO),
+                Some(Duration::ZERO)
+            ]
+        );
+        assert_eq!(res_nodelay[3], None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_limits_connection_guard_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let limits = Limits::default();
+
+        // -- Exec
+        let first = limits.acquire_connection(1, FX_CLIENT, 1);
+        let second = limits.acquire_connection(1, FX_CLIENT, 1);
+        let other_host = limits.acquire_connection(2, FX_CLIENT, 1);
+        drop(first);
+        let after_drop = limits.acquire_connection(1, FX_CLIENT, 1);
+
+        // -- Check
+        assert!(second.is_none());
+        assert!(other_host.is_some());
+        assert!(after_drop.is_some());
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
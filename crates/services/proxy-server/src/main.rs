@@ -0,0 +1,34 @@
+use lib_core::model::ModelManager;
+use proxy_server::{ProxyServer, proxy_config};
+use std::sync::Arc;
+use tokio::signal::unix::{SignalKind, signal};
+use tracing::{error, info};
+use tracing_subscriber::EnvFilter;
+
+#[tokio::main]
+async fn main() -> proxy_server::Result<()> {
+    tracing_subscriber::fmt()
+        .without_time() // For early local development.
+        .with_target(false)
+        .with_env_filter(EnvFilter::from_default_env())
+        .init();
+
+    let mm = ModelManager::new().await?;
+    let server = Arc::new(ProxyServer::load(&mm).await?);
+
+    // The changes are made by another process, seen from its applies, or
+    // reloaded at once on `SIGHUP`.
+    server.spawn_poller(mm.clone(), proxy_config().PROXY_POLL_INTERVAL);
+    let mut hangups = signal(SignalKind::hangup())?;
+    let reloaded = server.clone();
+    tokio::spawn(async move {
+        while hangups.recv().await.is_some() {
+            info!("{:<12} - SIGHUP", "PROXY");
+            if let Err(ex) = reloaded.reload(&mm).await {
+                error!("{:<12} - reload failed: {ex:?}", "PROXY");
+            }
+        }
+    });
+
+    server.serve().await
+}
@@ -0,0 +1,462 @@
+//! The request handler, the equivalent of the nginx `server` block
+//! rendered for each proxy host:
+//!
+//! - The host name selects the route, 404 when no host has it.
+//! - A forced SSL host with a certificate is redirected to https.
+//! - The requests matching the `block_exploits` rules get a `403`.
+//! - The rate and connection limits of the host are checked, see
+//!   `limit`.
+//! - The access list of the host is checked.
+//! - The request goes to the upstream of the matching location with the
+//!   default proxy headers (`Host`, `X-Real-IP`, `X-Forwarded-For` and
+//!   `X-Forwarded-Proto`) and the headers of the location. The next
+//!   server of the upstream is tried when one can not be connected to.
+//! - Websocket upgrades pass through when the host allows them.
+//!
+//! The `advanced` snippets of the host and of its locations are nginx
+//! directives, they are not applied here.
+
+use crate::ProxyServer;
+use crate::access::Verdict;
+use crate::exploits::is_exploit;
+use crate::upstream::{Server, Upstream};
+use bytes::Bytes;
+use http_body_util::combinators::BoxBody;
+use http_body_util::{BodyExt, Full};
+use hyper::body::Incoming;
+use hyper::client::conn::http1::{self, SendRequest};
+use hyper::header::{
+    CONNECTION, HOST, LOCATION, STRICT_TRANSPORT_SECURITY, UPGRADE,
+    WWW_AUTHENTICATE,
+};
+use hyper::header::{HeaderName, HeaderValue};
+use hyper::http::uri::PathAndQuery;
+use hyper::{HeaderMap, Request, Response, StatusCode, Uri, Version};
+use hyper_util::rt::TokioIo;
+use lib_core::model::proxy_host::{ForwardScheme, ProxyHeader};
+use rustls::pki_types::ServerName;
+use std::net::SocketAddr;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+use tracing::{debug, warn};
+
+pub type ProxyBody = BoxBody<Bytes, hyper::Error>;
+
+/// Scheme of the listener a request came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scheme {
+    Http,
+    Https,
+}
+
+impl Scheme {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Scheme::Http => "http",
+            Scheme::Https => "https",
+        }
+    }
+}
+
+/// Not forwarded, they are about the connection the request came on.
+const HOP_BY_HOP_HEADERS: [&str; 8] = [
+    "connection",
+    "keep-alive",
+    "proxy-connection",
+    "proxy-authenticate",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+];
+
+/// As the nginx `add_header Strict-Transport-Security`, two years.
+const HSTS_MAX_AGE: u32 = 63072000;
+
+impl ProxyServer {
+    pub(crate) async fn handle(
+        &self,
+        mut req: Request<Incoming>,
+        client: SocketAddr,
+        scheme: Scheme,
+    ) -> Response<ProxyBody> {
+        let Some(host) = request_host(&req) else {
+            return status_response(StatusCode::BAD_REQUEST);
+        };
+        // The whole request is served by the table it started with.
+        let table = self.table.load_full();
+        let Some(route) = table.host(&host).cloned() else {
+            return status_response(StatusCode::NOT_FOUND);
+        };
+
+        if scheme == Scheme::Http
+            && route.ssl_forced
+            && let Some(https_port) = self.https_port
+            && table.certificate(&host).is_some()
+        {
+            return redirect_https(&host, https_port, req.uri());
+        }
+
+        if route.block_exploits && is_exploit(req.uri(), req.headers()) {
+            return status_response(StatusCode::FORBIDDEN);
+        }
+        if let Some(rate_limit) = route.rate_limit {
+            match self.limits.check_rate(route.id, client.ip(), rate_limit) {
+                Some(delay) if !delay.is_zero() => {
+                    tokio::time::sleep(delay).await
+                }
+                Some(_) => (),
+                None => return status_response(StatusCode::TOO_MANY_REQUESTS),
+            }
+        }
+        // Held until the response is sent, or the upgraded connection
+        // closed.
+        let connection = match route.connection_limit {
+            Some(limit) => {
+                match self.limits.acquire_connection(
+                    route.id,
+                    client.ip(),
+                    limit,
+                ) {
+                    Some(guard) => Some(guard),
+                    None => {
+                        return status_response(StatusCode::TOO_MANY_REQUESTS);
+                    }
+                }
+            }
+            None => None,
+        };
+
+        if let Some(access) = &route.access {
+            match access.check(client.ip(), req.headers()).await {
+                Verdict::Allowed => (),
+                Verdict::Forbidden => {
+                    return status_response(StatusCode::FORBIDDEN);
+                }
+                Verdict::Unauthorized => {
+                    let mut res = status_response(StatusCode::UNAUTHORIZED);
+                    let challenge = format!(
+                        "Basic realm=\"{}\"",
+                        access.realm.replace('"', "'")
+                    );
+                    if let Ok(value) = HeaderValue::from_str(&challenge) {
+                        res.headers_mut().insert(WWW_AUTHENTICATE, value);
+                    }
+                    return res;
+                }
+            }
+        }
+
+        let upgrade = (route.allow_websocket_upgrade
+            && is_upgrade(req.headers()))
+        .then(|| hyper::upgrade::on(&mut req));
+
+        let (upstream, headers) = route.upstream(req.uri().path());
+        let req = match upstream_request(
+            req,
+            &host,
+            client,
+            scheme,
+            headers,
+            upgrade.is_some(),
+        ) {
+            Ok(req) => req,
+            Err(status) => return status_response(status),
+        };
+
+        let mut res = match self.forward(upstream, client, req).await {
+            Ok(res) => res,
+            Err(status) => return status_response(status),
+        };
+
+        let connection = match upgrade {
+            Some(client_upgrade)
+                if res.status() == StatusCode::SWITCHING_PROTOCOLS =>
+            {
+                let upstream_upgrade = hyper::upgrade::on(&mut res);
+                tokio::spawn(async move {
+                    let _connection = connection;
+                    match tokio::try_join!(client_upgrade, upstream_upgrade) {
+                        Ok((client_io, upstream_io)) => {
+                            let _ = tokio::io::copy_bidirectional(
+                                &mut TokioIo::new(client_io),
+                                &mut TokioIo::new(upstream_io),
+                            )
+                            .await;
+                        }
+                        Err(ex) => {
+                            debug!("{:<12} - upgrade failed: {ex}", "PROXY")
+                        }
+                    }
+                });
+                None
+            }
+            _ => {
+                remove_hop_by_hop_headers(res.headers_mut());
+                connection
+            }
+        };
+
+        if scheme == Scheme::Https && route.hsts_enabled {
+            let mut hsts = format!("max-age={HSTS_MAX_AGE}");
+            if route.hsts_subdomains {
+                hsts.push_str("; includeSubDomains");
+            }
+            if let Ok(value) = HeaderValue::from_str(&hsts) {
+                res.headers_mut().insert(STRICT_TRANSPORT_SECURITY, value);
+            }
+        }
+
+        res.map(|body| {
+            body.map_frame(move |frame| {
+                let _connection = &connection;
+                frame
+            })
+            .boxed()
+        })
+    }
+
+    /// Send `req` to the first server of `upstream` accepting the
+    /// connection.
+    async fn forward(
+        &self,
+        upstream: &Upstream,
+        client: SocketAddr,
+        req: Request<Incoming>,
+    ) -> Result<Response<Incoming>, StatusCode> {
+        for server in upstream.servers(client.ip()) {
+            let mut sender = match self.connect(upstream.scheme, server).await {
+                Ok(sender) => sender,
+                Err(ex) => {
+                    warn!(
+                        "{:<12} - {}:{} failed: {ex}",
+                        "PROXY", server.host, server.port
+                    );
+                    server.failed();
+                    continue;
+                }
+            };
+
+            // The body is sent, no other server can be tried from here.
+            let _in_flight = server.start();
+            return match sender.send_request(req).await {
+                Ok(res) => {
+                    server.succeeded();
+                    Ok(res)
+                }
+                Err(ex) => {
+                    warn!(
+                        "{:<12} - {}:{} failed: {ex}",
+                        "PROXY", server.host, server.port
+                    );
+                    server.failed();
+                    Err(StatusCode::BAD_GATEWAY)
+                }
+            };
+        }
+
+        Err(StatusCode::BAD_GATEWAY)
+    }
+
+    async fn connect(
+        &self,
+        scheme: ForwardScheme,
+        server: &Server,
+    ) -> std::io::Result<SendRequest<Incoming>> {
+        let stream = tokio::time::timeout(
+            self.connect_timeout,
+            TcpStream::connect((server.host.as_str(), server.port)),
+        )
+        .await
+        .map_err(|_| std::io::ErrorKind::TimedOut)??;
+        let _ = stream.set_nodelay(true);
+
+        match scheme {
+            ForwardScheme::Http => handshake(stream).await,
+            ForwardScheme::Https => {
+                let server_name = ServerName::try_from(server.host.clone())
+                    .map_err(std::io::Error::other)?;
+                let stream =
+                    self.tls_connector.connect(server_name, stream).await?;
+                handshake(stream).await
+            }
+        }
+    }
+}
+
+async fn handshake<T>(io: T) -> std::io::Result<SendRequest<Incoming>>
+where
+    T: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+{
+    let (sender, conn) = http1::handshake(TokioIo::new(io))
+        .await
+        .map_err(std::io::Error::other)?;
+    tokio::spawn(async move {
+        if let Err(ex) = conn.with_upgrades().await {
+            debug!("{:<12} - upstream connection: {ex}", "PROXY");
+        }
+    });
+
+    Ok(sender)
+}
+
+/// The request for the upstream: origin-form uri, HTTP/1.1, no hop by hop
+/// headers and the proxy headers set.
+fn upstream_request(
+    req: Request<Incoming>,
+    host: &str,
+    client: SocketAddr,
+    scheme: Scheme,
+    location_headers: &[ProxyHeader],
+    upgrade: bool,
+) -> Result<Request<Incoming>, StatusCode> {
+    let (mut parts, body) = req.into_parts();
+
+    let upgrade_value = parts.headers.get(UPGRADE).cloned();
+    remove_hop_by_hop_headers(&mut parts.headers);
+
+    let client_ip = client.ip().to_canonical().to_string();
+    let forwarded_for = match parts.headers.get("x-forwarded-for") {
+        Some(value) => format!(
+            "{}, {client_ip}",
+            value.to_str().map_err(|_| StatusCode::BAD_REQUEST)?
+        ),
+        None => client_ip.clone(),
+    };
+    let mut headers = vec![
+        ("host".to_string(), host.to_string()),
+        ("x-real-ip".to_string(), client_ip),
+        ("x-forwarded-for".to_string(), forwarded_for),
+        ("x-forwarded-proto".to_string(), scheme.as_str().to_string()),
+    ];
+    for header in location_headers {
+        let name = header.name.to_lowercase();
+        let value = expand(&header.value, &headers);
+        match headers.iter_mut().find(|(n, _)| *n == name) {
+            Some(default) => default.1 = value,
+            None => headers.push((name, value)),
+        }
+    }
+    for (name, value) in headers {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        let value = HeaderValue::from_str(&value)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        parts.headers.insert(name, value);
+    }
+    if upgrade && let Some(value) = upgrade_value {
+        parts.headers.insert(UPGRADE, value);
+        parts
+            .headers
+            .insert(CONNECTION, HeaderValue::from_static("upgrade"));
+    }
+
+    let path_and_query = parts
+        .uri
+        .path_and_query()
+        .cloned()
+        .unwrap_or_else(|| PathAndQuery::from_static("/"));
+    parts.uri = Uri::from(path_and_query);
+    parts.version = Version::HTTP_11;
+
+    Ok(Request::from_parts(parts, body))
+}
+
+/// The nginx variables of a header value with a default proxy header
+/// equivalent, e.g. `$host`. The others are kept as is.
+fn expand(value: &str, headers: &[(String, String)]) -> String {
+    let header = |name: &str| {
+        headers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+            .unwrap_or_default()
+    };
+
+    value
+        .replace("$proxy_add_x_forwarded_for", header("x-forwarded-for"))
+        .replace("$remote_addr", header("x-real-ip"))
+        .replace("$scheme", header("x-forwarded-proto"))
+        .replace("$host", header("host"))
+}
+
+/// Lowercase, without the port, from the uri for HTTP/2 or the `Host`
+/// header.
+fn request_host(req: &Request<Incoming>) -> Option<String> {
+    let authority = match req.uri().host() {
+        Some(host) => host,
+        None => req.headers().get(HOST)?.to_str().ok()?,
+    };
+
+    let host = match authority.strip_prefix('[') {
+        Some(ipv6) => ipv6.split_once(']').map(|(host, _)| host)?,
+        None => authority.split(':').next()?,
+    };
+    let host = host.trim_end_matches('.').to_lowercase();
+
+    (!host.is_empty()).then_some(host)
+}
+
+fn is_upgrade(headers: &HeaderMap) -> bool {
+    headers.contains_key(UPGRADE)
+        && headers.get_all(CONNECTION).iter().any(|value| {
+            value.to_str().is_ok_and(|value| {
+                value
+                    .split(',')
+                    .any(|token| token.trim().eq_ignore_ascii_case("upgrade"))
+            })
+        })
+}
+
+fn remove_hop_by_hop_headers(headers: &mut HeaderMap) {
+    // The headers listed in `Connection` are hop by hop as well.
+    let listed: Vec<String> = headers
+        .get_all(CONNECTION)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|name| name.trim().to_lowercase())
+        .collect();
+
+    for name in HOP_BY_HOP_HEADERS
+        .iter()
+        .copied()
+        .chain(listed.iter().map(String::as_str))
+    {
+        headers.remove(name);
+    }
+}
+
+fn redirect_https(
+    host: &str,
+    https_port: u16,
+    uri: &Uri,
+) -> Response<ProxyBody> {
+    let path_and_query = uri.path_and_query().map_or("/", |p| p.as_str());
+    let location = match https_port {
+        443 => format!("https://{host}{path_and_query}"),
+        port => format!("https://{host}:{port}{path_and_query}"),
+    };
+
+    let mut res = status_response(StatusCode::MOVED_PERMANENTLY);
+    if let Ok(value) = HeaderValue::from_str(&location) {
+        res.headers_mut().insert(LOCATION, value);
+    }
+    res
+}
+
+fn status_response(status: StatusCode) -> Response<ProxyBody> {
+    let body = format!(
+        "{} {}\n",
+        status.as_u16(),
+        status.canonical_reason().unwrap_or_default()
+    );
+
+    let mut res = Response::new(
+        Full::new(Bytes::from(body))
+            .map_err(|never| match never {})
+            .boxed(),
+    );
+    *res.status_mut() = status;
+    res
+}
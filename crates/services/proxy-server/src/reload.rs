@@ -0,0 +1,221 @@
+//! Hot reload of the routes.
+//!
+//! The Bmcs publish a `ModelEvent` after each write, on the ones touching
+//! the routes the table is read again and swapped in whole. A request
+//! loads the table once, when it starts, so the in-flight requests finish
+//! on the table they started with while the new ones, on the same
+//! connections, use the new one. A table failing to load keeps the
+//! current one.
+//!
+//! The events are only published in the process writing the changes. The
+//! standalone binary polls the `config_apply` runs instead: the web-server
+//! records one after every change, deletions included.
+//!
+//! The state of the upstream servers (failures, connections in flight)
+//! starts over with each table.
+
+use crate::{ProxyServer, Result, RouteTable};
+use lib_core::ctx::Ctx;
+use lib_core::model::config_apply::ConfigApplyBmc;
+use lib_core::model::{ModelEvent, ModelManager};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::{RecvError, TryRecvError};
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+
+/// Coalesces the events of a burst of writes into one reload.
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Serve the routes of `mm` on the configured listeners, reloaded on
+/// each change.
+pub fn spawn_proxy(mm: ModelManager) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let server = match ProxyServer::load(&mm).await {
+            Ok(server) => Arc::new(server),
+            Err(ex) => {
+                error!("{:<12} - routes not loaded: {ex:?}", "PROXY");
+                return;
+            }
+        };
+        server.spawn_reloader(mm);
+
+        if let Err(ex) = server.serve().await {
+            error!("{:<12} - proxy stopped: {ex:?}", "PROXY");
+        }
+    })
+}
+
+impl ProxyServer {
+    /// Reload the routes after each change of `mm`, and once when
+    /// started for the changes made since they were read.
+    pub fn spawn_reloader(
+        self: &Arc<Self>,
+        mm: ModelManager,
+    ) -> JoinHandle<()> {
+        let server = self.clone();
+        let mut events = mm.subscribe();
+
+        tokio::spawn(async move {
+            loop {
+                if let Err(ex) = server.reload(&mm).await {
+                    error!("{:<12} - reload failed: {ex:?}", "PROXY");
+                }
+
+                loop {
+                    match events.recv().await {
+                        Ok(event) if !touches_routes(event) => {}
+                        // A lagged receiver missed changes, reload anyway.
+                        Ok(_) | Err(RecvError::Lagged(_)) => break,
+                        Err(RecvError::Closed) => return,
+                    }
+                }
+                tokio::time::sleep(DEBOUNCE).await;
+                loop {
+                    match events.try_recv() {
+                        Ok(_) | Err(TryRecvError::Lagged(_)) => {}
+                        Err(TryRecvError::Empty) => break,
+                        Err(TryRecvError::Closed) => return,
+                    }
+                }
+            }
+        })
+    }
+
+    /// Reload the routes when a new apply was recorded in `mm`, checked
+    /// every `interval`, and once when started.
+    ///
+    /// For a proxy running apart from the web-server, which does not see
+    /// its `ModelEvent`s.
+    pub fn spawn_poller(
+        self: &Arc<Self>,
+        mm: ModelManager,
+        interval: Duration,
+    ) -> JoinHandle<()> {
+        let server = self.clone();
+
+        tokio::spawn(async move {
+            let ctx = Ctx::root_ctx();
+            // Id of the last apply, `None` until read once.
+            let mut seen: Option<Option<i64>> = None;
+            loop {
+                match ConfigApplyBmc::last(&ctx, &mm).await {
+                    Ok(last) => {
+                        let last = last.map(|config_apply| config_apply.id);
+                        if seen != Some(last) {
+                            seen = Some(last);
+                            if let Err(ex) = server.reload(&mm).await {
+                                error!(
+                                    "{:<12} - reload failed: {ex:?}",
+                                    "PROXY"
+                                );
+                            }
+                        }
+                    }
+                    Err(ex) => {
+                        error!("{:<12} - poll failed: {ex:?}", "PROXY");
+                    }
+                }
+                tokio::time::sleep(interval).await;
+            }
+        })
+    }
+
+    /// Read the routes of `mm` again and swap them in.
+    pub async fn reload(&self, mm: &ModelManager) -> Result<()> {
+        let table = RouteTable::load(mm).await?;
+        info!("{:<12} - {} host name(s)", "ROUTES", table.len());
+        self.set_table(table);
+
+        Ok(())
+    }
+
+    /// Serve the new requests with `table`.
+    pub fn set_table(&self, table: RouteTable) {
+        self.table.store(Arc::new(table));
+    }
+}
+
+/// The redirection, stream and dead hosts are not served natively.
+fn touches_routes(event: ModelEvent) -> bool {
+    match event {
+        ModelEvent::ProxyHostChanged { .. }
+        | ModelEvent::UpstreamGroupChanged { .. }
+        | ModelEvent::AccessListChanged { .. }
+        | ModelEvent::CertificateChanged { .. } => true,
+        ModelEvent::RedirectionHostChanged { .. }
+        | ModelEvent::StreamHostChanged { .. }
+        | ModelEvent::DeadHostChanged { .. } => false,
+    }
+}
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+    use crate::_dev_utils::{start_backend, start_proxy};
+    use lib_core::_dev_utils;
+    use lib_core::model::config_apply::{
+        ConfigApplyForCreate, ConfigApplyStatus,
+    };
+    use lib_core::model::proxy_host::{ProxyHostBmc, ProxyHostForCreate};
+    use sqlx::{Pool, Sqlite};
+
+    const FX_DOMAIN: &str = "poll.example.com";
+
+    #[sqlx::test(migrations = false)]
+    async fn test_poller_other_process_ok(pool: Pool<Sqlite>) -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test_db(pool.clone()).await?;
+        // Same database, its own events, as another process.
+        let mm_proxy = ModelManager::new_with_pool(pool).await?;
+        let ctx = Ctx::new("demo1")?;
+        let fx_app = start_backend("app").await?;
+        let (server, proxy, _) =
+            start_proxy(RouteTable::load(&mm_proxy).await?).await?;
+        server.spawn_poller(mm_proxy, Duration::from_millis(50));
+        // Past the first check, done when started.
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let client = reqwest::Client::builder()
+            .resolve(FX_DOMAIN, proxy)
+            .build()?;
+        let url = format!("http://{FX_DOMAIN}:{}/", proxy.port());
+
+        // -- Exec
+        ProxyHostBmc::create(
+            &ctx,
+            &mm,
+            ProxyHostForCreate {
+                forward_port: fx_app.port(),
+                .._dev_utils::fx_proxy_host_c(FX_DOMAIN)
+            },
+        )
+        .await?;
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let res_before_apply = client.get(&url).send().await?;
+        ConfigApplyBmc::create(
+            &Ctx::root_ctx(),
+            &mm,
+            ConfigApplyForCreate {
+                generation: None,
+                status: ConfigApplyStatus::Failed,
+                output: String::new(),
+            },
+        )
+        .await?;
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let res = client.get(&url).send().await?;
+
+        // -- Check
+        assert_eq!(res_before_apply.status(), reqwest::StatusCode::NOT_FOUND);
+        assert_eq!(res.text().await?, "app /");
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -0,0 +1,354 @@
+//! The routing table, built from the records nginx is configured from.
+//!
+//! A request is routed by its host name, exact names first then the
+//! longest `*.` wildcard, and then by the longest location prefix of its
+//! path, `/` being the forward target of the host itself.
+
+use crate::Result;
+use crate::access::Access;
+use crate::limit::RateLimit;
+use crate::tls;
+use crate::upstream::Upstream;
+use lib_core::ctx::Ctx;
+use lib_core::model::ModelManager;
+use lib_core::model::access_list::{AccessList, AccessListBmc, AccessListUser};
+use lib_core::model::certificate::{Certificate, CertificateBmc};
+use lib_core::model::proxy_host::{ProxyHeader, ProxyHost, ProxyHostBmc};
+use lib_core::model::upstream_group::{UpstreamGroup, UpstreamGroupBmc};
+use rustls::sign::CertifiedKey;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::warn;
+
+#[derive(Debug, Default)]
+pub struct RouteTable {
+    hosts: DomainMap<Arc<HostRoute>>,
+    certificates: DomainMap<Arc<CertifiedKey>>,
+}
+
+/// The route of a proxy host.
+#[derive(Debug)]
+pub struct HostRoute {
+    pub id: i64,
+    pub ssl_forced: bool,
+    pub hsts_enabled: bool,
+    pub hsts_subdomains: bool,
+    pub allow_websocket_upgrade: bool,
+    /// Refuses the requests matching the `block_exploits` rules.
+    pub block_exploits: bool,
+    /// Public when `None`.
+    pub access: Option<Access>,
+    pub rate_limit: Option<RateLimit>,
+    /// Requests of a client in progress.
+    pub connection_limit: Option<u32>,
+    /// Longest path first.
+    locations: Vec<LocationRoute>,
+    upstream: Upstream,
+}
+
+/// A custom location of a proxy host.
+#[derive(Debug)]
+pub struct LocationRoute {
+    pub path: String,
+    /// Replace the default proxy headers of the same name.
+    pub headers: Vec<ProxyHeader>,
+    pub upstream: Upstream,
+}
+
+impl RouteTable {
+    /// Read the records of all the users.
+    pub async fn load(mm: &ModelManager) -> Result<Self> {
+        let ctx = Ctx::root_ctx();
+
+        let proxy_hosts = ProxyHostBmc::list(&ctx, mm).await?;
+        let upstream_groups = UpstreamGroupBmc::list(&ctx, mm).await?;
+        let access_lists = AccessListBmc::list(&ctx, mm).await?;
+        let users = AccessListBmc::list_users(&ctx, mm).await?;
+        let certificates = CertificateBmc::list(&ctx, mm).await?;
+
+        Ok(RouteTable::new(
+            &proxy_hosts,
+            &upstream_groups,
+            &access_lists,
+            &users,
+            &certificates,
+        ))
+    }
+
+    /// The disabled hosts and the certificates not issued yet are left
+    /// out, as they are from the nginx configuration.
+    pub fn new(
+        proxy_hosts: &[ProxyHost],
+        upstream_groups: &[UpstreamGroup],
+        access_lists: &[AccessList],
+        users: &[AccessListUser],
+        certificates: &[Certificate],
+    ) -> Self {
+        let upstream_groups: HashMap<i64, &UpstreamGroup> = upstream_groups
+            .iter()
+            .map(|upstream_group| (upstream_group.id, upstream_group))
+            .collect();
+        let access_lists: HashMap<i64, &AccessList> = access_lists
+            .iter()
+            .map(|access_list| (access_list.id, access_list))
+            .collect();
+
+        let mut table = RouteTable::default();
+        for proxy_host in proxy_hosts.iter().filter(|host| host.enabled) {
+            let upstream = match proxy_host.upstream_group_id {
+                Some(id) => match upstream_groups.get(&id) {
+                    Some(upstream_group) => Upstream::group(
+                        proxy_host.forward_scheme,
+                        upstream_group,
+                    ),
+                    None => {
+                        warn!(
+                            "{:<12} - host {} skipped, no upstream group {id}",
+                            "ROUTES", proxy_host.id
+                        );
+                        continue;
+                    }
+                },
+                None => Upstream::single(
+                    proxy_host.forward_scheme,
+                    &proxy_host.forward_host,
+                    proxy_host.forward_port,
+                ),
+            };
+
+            let mut locations: Vec<LocationRoute> = proxy_host
+                .locations
+                .iter()
+                .map(|location| LocationRoute {
+                    path: location.path.clone(),
+                    headers: location.headers.clone(),
+                    upstream: Upstream::single(
+                        location.forward_scheme,
+                        &location.forward_host,
+                        location.forward_port,
+                    ),
+                })
+                .collect();
+            locations.sort_by_key(|location| Reverse(location.path.len()));
+
+            let route = Arc::new(HostRoute {
+                id: proxy_host.id,
+                ssl_forced: proxy_host.ssl_forced,
+                hsts_enabled: proxy_host.hsts_enabled,
+                hsts_subdomains: proxy_host.hsts_subdomains,
+                allow_websocket_upgrade: proxy_host.allow_websocket_upgrade,
+                block_exploits: proxy_host.block_exploits,
+                access: proxy_host
+                    .access_list_id
+                    .and_then(|id| access_lists.get(&id))
+                    .map(|access_list| Access::new(access_list, users)),
+                rate_limit: proxy_host.rate_limit.map(|rate| RateLimit {
+                    rate,
+                    burst: proxy_host.rate_limit_burst,
+                    nodelay: proxy_host.rate_limit_nodelay,
+                }),
+                connection_limit: proxy_host.connection_limit,
+                locations,
+                upstream,
+            });
+            for domain in &proxy_host.domain_names {
+                table.hosts.insert(domain, route.clone());
+            }
+        }
+
+        for certificate in certificates.iter().filter(|c| c.is_issued()) {
+            match tls::certified_key(certificate) {
+                Ok(certified_key) => {
+                    let certified_key = Arc::new(certified_key);
+                    for domain in &certificate.domain_names {
+                        table
+                            .certificates
+                            .insert(domain, certified_key.clone());
+                    }
+                }
+                Err(ex) => {
+                    warn!("{:<12} - certificate skipped: {ex}", "ROUTES")
+                }
+            }
+        }
+
+        table
+    }
+
+    pub fn host(&self, name: &str) -> Option<&Arc<HostRoute>> {
+        self.hosts.get(name)
+    }
+
+    pub fn certificate(&self, name: &str) -> Option<&Arc<CertifiedKey>> {
+        self.certificates.get(name)
+    }
+
+    /// Number of host names routed.
+    pub fn len(&self) -> usize {
+        self.hosts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hosts.len() == 0
+    }
+}
+
+impl HostRoute {
+    /// The upstream of `path`, and the headers of its location.
+    pub fn upstream(&self, path: &str) -> (&Upstream, &[ProxyHeader]) {
+        match self
+            .locations
+            .iter()
+            .find(|location| path.starts_with(&location.path))
+        {
+            Some(location) => (&location.upstream, &location.headers),
+            None => (&self.upstream, &[]),
+        }
+    }
+}
+
+// region:    --- DomainMap
+
+/// Values by host name, as the nginx `server_name`: `*.example.com`
+/// matches the sub-domains of `example.com` at any depth.
+#[derive(Debug)]
+struct DomainMap<T> {
+    exact: HashMap<String, T>,
+    /// `(".example.com", value)`, longest suffix first.
+    wildcards: Vec<(String, T)>,
+}
+
+impl<T> Default for DomainMap<T> {
+    fn default() -> Self {
+        DomainMap {
+            exact: HashMap::new(),
+            wildcards: Vec::new(),
+        }
+    }
+}
+
+impl<T> DomainMap<T> {
+    /// The first value of a name is kept.
+    fn insert(&mut self, domain: &str, value: T) {
+        let domain = domain.to_lowercase();
+        match domain.strip_prefix('*') {
+            Some(suffix) => {
+                if self.wildcards.iter().any(|(s, _)| s == suffix) {
+                    return;
+                }
+                let at = self
+                    .wildcards
+                    .iter()
+                    .position(|(s, _)| s.len() < suffix.len())
+                    .unwrap_or(self.wildcards.len());
+                self.wildcards.insert(at, (suffix.to_string(), value));
+            }
+            None => {
+                self.exact.entry(domain).or_insert(value);
+            }
+        }
+    }
+
+    /// `name` lowercased, without port.
+    fn get(&self, name: &str) -> Option<&T> {
+        self.exact.get(name).or_else(|| {
+            self.wildcards
+                .iter()
+                .find(|(suffix, _)| name.ends_with(suffix.as_str()))
+                .map(|(_, value)| value)
+        })
+    }
+
+    fn len(&self) -> usize {
+        self.exact.len() + self.wildcards.len()
+    }
+}
+
+// endregion: --- DomainMap
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+    use crate::_dev_utils::fx_proxy_host;
+    use lib_core::model::proxy_host::{ForwardScheme, ProxyLocation};
+
+    #[test]
+    fn test_domain_map_get_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mut fx_map = DomainMap::default();
+        fx_map.insert("*.example.com", "wildcard");
+        fx_map.insert("*.api.example.com", "api");
+        fx_map.insert("WWW.example.com", "www");
+        fx_map.insert("www.example.com", "duplicate");
+
+        // -- Exec & Check
+        assert_eq!(fx_map.get("www.example.com"), Some(&"www"));
+        assert_eq!(fx_map.get("a.b.example.com"), Some(&"wildcard"));
+        assert_eq!(fx_map.get("v1.api.example.com"), Some(&"api"));
+        assert_eq!(fx_map.get("example.com"), None);
+        assert_eq!(fx_map.get("example.org"), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_route_table_new_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mut fx_host = fx_proxy_host(1, "app.example.com", 3000);
+        fx_host.locations = vec![
+            ProxyLocation {
+                path: "/api/".to_string(),
+                forward_scheme: ForwardScheme::Http,
+                forward_host: "10.0.0.2".to_string(),
+                forward_port: 4000,
+                headers: Vec::new(),
+                advanced: String::new(),
+            },
+            ProxyLocation {
+                path: "/api/v2/".to_string(),
+                forward_scheme: ForwardScheme::Https,
+                forward_host: "10.0.0.3".to_string(),
+                forward_port: 4443,
+                headers: Vec::new(),
+                advanced: String::new(),
+            },
+        ];
+        let mut fx_disabled = fx_proxy_host(2, "off.example.com", 3000);
+        fx_disabled.enabled = false;
+        let mut fx_orphan = fx_proxy_host(3, "orphan.example.com", 3000);
+        fx_orphan.upstream_group_id = Some(99);
+
+        // -- Exec
+        let table = RouteTable::new(
+            &[fx_host, fx_disabled, fx_orphan],
+            &[],
+            &[],
+            &[],
+            &[],
+        );
+
+        // -- Check
+        assert_eq!(table.len(), 1);
+        assert!(table.host("off.example.com").is_none());
+        let route = table.host("app.example.com").ok_or("Should route")?;
+        let fx_client = "10.0.0.1".parse()?;
+        let port = |path: &str| {
+            let (upstream, _) = route.upstream(path);
+            upstream.servers(fx_client)[0].port
+        };
+        assert_eq!(port("/"), 3000);
+        assert_eq!(port("/api/users"), 4000);
+        assert_eq!(port("/api/v2/users"), 4443);
+        assert_eq!(port("/apix"), 3000);
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -0,0 +1,142 @@
+//! TLS with rustls, on both sides:
+//!
+//! - The https listener presents the issued certificate of the store
+//!   matching the SNI of the client, and offers `h2` and `http/1.1`.
+//! - The `https` forward targets are connected to without verifying
+//!   their certificate, as nginx does by default (`proxy_ssl_verify off`),
+//!   the backends behind a proxy often have self-signed ones.
+
+use crate::table::RouteTable;
+use crate::{Error, Result};
+use arc_swap::ArcSwap;
+use lib_core::model::certificate::Certificate;
+use rustls::client::danger::{
+    HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier,
+};
+use rustls::crypto::{CryptoProvider, ring};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use rustls::server::{ClientHello, ResolvesServerCert};
+use rustls::sign::CertifiedKey;
+use rustls::{
+    ClientConfig, DigitallySignedStruct, ServerConfig, SignatureScheme,
+};
+use std::sync::Arc;
+use tokio_rustls::{TlsAcceptor, TlsConnector};
+
+/// The chain and key of an issued certificate of the store.
+pub fn certified_key(certificate: &Certificate) -> Result<CertifiedKey> {
+    let invalid = |cause: String| Error::CertificateInvalid {
+        id: certificate.id,
+        cause,
+    };
+
+    let chain =
+        CertificateDer::pem_slice_iter(certificate.certificate_pem.as_bytes())
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|ex| invalid(ex.to_string()))?;
+    let key =
+        PrivateKeyDer::from_pem_slice(certificate.private_key_pem.as_bytes())
+            .map_err(|ex| invalid(ex.to_string()))?;
+
+    CertifiedKey::from_der(chain, key, &ring::default_provider())
+        .map_err(|ex| invalid(ex.to_string()))
+}
+
+pub fn acceptor(table: Arc<ArcSwap<RouteTable>>) -> Result<TlsAcceptor> {
+    let mut config = ServerConfig::builder_with_provider(provider())
+        .with_safe_default_protocol_versions()?
+        .with_no_client_auth()
+        .with_cert_resolver(Arc::new(TableCertResolver(table)));
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+pub fn connector() -> Result<TlsConnector> {
+    let config = ClientConfig::builder_with_provider(provider())
+        .with_safe_default_protocol_versions()?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(NoVerifier))
+        .with_no_client_auth();
+
+    Ok(TlsConnector::from(Arc::new(config)))
+}
+
+fn provider() -> Arc<CryptoProvider> {
+    Arc::new(ring::default_provider())
+}
+
+// region:    --- TableCertResolver
+
+/// No certificate, so no handshake, for the names without one.
+#[derive(Debug)]
+struct TableCertResolver(Arc<ArcSwap<RouteTable>>);
+
+impl ResolvesServerCert for TableCertResolver {
+    fn resolve(
+        &self,
+        client_hello: ClientHello<'_>,
+    ) -> Option<Arc<CertifiedKey>> {
+        let name = client_hello.server_name()?.to_lowercase();
+        self.0.load().certificate(&name).cloned()
+    }
+}
+
+// endregion: --- TableCertResolver
+
+// region:    --- NoVerifier
+
+/// Accepts any certificate, the signatures of the handshake are still
+/// checked.
+#[derive(Debug)]
+struct NoVerifier;
+
+impl ServerCertVerifier for NoVerifier {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> std::result::Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &ring::default_provider().signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &ring::default_provider().signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        ring::default_provider()
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+// endregion: --- NoVerifier
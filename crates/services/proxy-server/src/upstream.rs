@@ -0,0 +1,317 @@
+//! Forward targets of the routes, a single address or the servers of an
+//! upstream group balanced as the nginx `upstream` block does:
+//!
+//! - `round_robin` spreads the requests by weight.
+//! - `least_conn` picks the server with the fewest requests in flight
+//!   for its weight. A request is in flight until its response head.
+//! - `ip_hash` keeps a client address on the same server.
+//!
+//! A server failing `max_fails` times (1 by default) within
+//! `fail_timeout` seconds (10 by default) is skipped for `fail_timeout`.
+//! The backup servers are only tried when all the others failed.
+
+use lib_core::model::proxy_host::ForwardScheme;
+use lib_core::model::upstream_group::{UpstreamBalance, UpstreamGroup};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+const DEFAULT_MAX_FAILS: u32 = 1;
+const DEFAULT_FAIL_TIMEOUT: u32 = 10;
+
+/// Where the requests of a route are forwarded to.
+#[derive(Debug)]
+pub struct Upstream {
+    pub scheme: ForwardScheme,
+    balance: UpstreamBalance,
+    servers: Vec<Server>,
+    /// Round-robin position, in weight units.
+    next: AtomicUsize,
+}
+
+#[derive(Debug)]
+pub struct Server {
+    pub host: String,
+    pub port: u16,
+    weight: usize,
+    backup: bool,
+    max_fails: u32,
+    fail_timeout: Duration,
+    in_flight: AtomicUsize,
+    /// `(failures, first failure)` of the current `fail_timeout` window.
+    failures: Mutex<(u32, Option<Instant>)>,
+}
+
+impl Upstream {
+    pub fn single(scheme: ForwardScheme, host: &str, port: u16) -> Self {
+        Upstream {
+            scheme,
+            balance: UpstreamBalance::RoundRobin,
+            servers: vec![Server::new(host, port)],
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn group(
+        scheme: ForwardScheme,
+        upstream_group: &UpstreamGroup,
+    ) -> Self {
+        let servers = upstream_group
+            .servers
+            .iter()
+            .map(|server| Server {
+                weight: server.weight.unwrap_or(1).max(1) as usize,
+                backup: server.backup,
+                max_fails: server.max_fails.unwrap_or(DEFAULT_MAX_FAILS),
+                fail_timeout: Duration::from_secs(
+                    server.fail_timeout.unwrap_or(DEFAULT_FAIL_TIMEOUT).into(),
+                ),
+                ..Server::new(&server.host, server.port)
+            })
+            .collect();
+
+        Upstream {
+            scheme,
+            balance: upstream_group.balance,
+            servers,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// The servers to try in order for a request of `client_ip`: the
+    /// balanced primary one, the other primary ones, then the backups.
+    /// The servers marked down come last, nginx also tries them when all
+    /// the others are down.
+    pub fn servers(&self, client_ip: IpAddr) -> Vec<&Server> {
+        let primaries: Vec<&Server> = self
+            .servers
+            .iter()
+            .filter(|server| !server.backup)
+            .collect();
+        let mut servers = Vec::with_capacity(self.servers.len());
+        if let Some(first) = self.pick(&primaries, client_ip) {
+            servers.extend(primaries[first..].iter().copied());
+            servers.extend(primaries[..first].iter().copied());
+        }
+        servers.extend(self.servers.iter().filter(|server| server.backup));
+
+        let now = Instant::now();
+        servers.sort_by_key(|server| server.is_down(now));
+        servers
+    }
+
+    fn pick(&self, primaries: &[&Server], client_ip: IpAddr) -> Option<usize> {
+        if primaries.is_empty() {
+            return None;
+        }
+
+        let index = match self.balance {
+            UpstreamBalance::RoundRobin => {
+                let total: usize =
+                    primaries.iter().map(|server| server.weight).sum();
+                let mut position =
+                    self.next.fetch_add(1, Ordering::Relaxed) % total;
+                primaries
+                    .iter()
+                    .position(|server| {
+                        let found = position < server.weight;
+                        position = position.saturating_sub(server.weight);
+                        found
+                    })
+                    .unwrap_or(0)
+            }
+            UpstreamBalance::LeastConn => primaries
+                .iter()
+                .enumerate()
+                .min_by(|(_, a), (_, b)| {
+                    // a.in_flight / a.weight < b.in_flight / b.weight
+                    let a_load = a.in_flight.load(Ordering::Relaxed) * b.weight;
+                    let b_load = b.in_flight.load(Ordering::Relaxed) * a.weight;
+                    a_load.cmp(&b_load)
+                })
+                .map(|(index, _)| index)
+                .unwrap_or(0),
+            UpstreamBalance::IpHash => {
+                let mut hasher = DefaultHasher::new();
+                client_ip.hash(&mut hasher);
+                hasher.finish() as usize % primaries.len()
+            }
+        };
+
+        Some(index)
+    }
+}
+
+impl Server {
+    fn new(host: &str, port: u16) -> Self {
+        Server {
+            host: host.to_string(),
+            port,
+            weight: 1,
+            backup: false,
+            max_fails: DEFAULT_MAX_FAILS,
+            fail_timeout: Duration::from_secs(DEFAULT_FAIL_TIMEOUT.into()),
+            in_flight: AtomicUsize::new(0),
+            failures: Mutex::new((0, None)),
+        }
+    }
+
+    /// Count a request in flight until the guard is dropped.
+    pub fn start(&self) -> InFlight<'_> {
+        self.in_flight.fetch_add(1, Ordering::Relaxed);
+        InFlight(self)
+    }
+
+    pub fn failed(&self) {
+        let now = Instant::now();
+        let mut failures =
+            self.failures.lock().unwrap_or_else(|e| e.into_inner());
+        match failures.1 {
+            Some(since) if now.duration_since(since) < self.fail_timeout => {
+                failures.0 += 1
+            }
+            _ => *failures = (1, Some(now)),
+        }
+    }
+
+    pub fn succeeded(&self) {
+        let mut failures =
+            self.failures.lock().unwrap_or_else(|e| e.into_inner());
+        *failures = (0, None);
+    }
+
+    /// `max_fails=0` never marks the server down.
+    fn is_down(&self, now: Instant) -> bool {
+        let failures = self.failures.lock().unwrap_or_else(|e| e.into_inner());
+        match *failures {
+            (count, Some(since)) => {
+                self.max_fails > 0
+                    && count >= self.max_fails
+                    && now.duration_since(since) < self.fail_timeout
+            }
+            _ => false,
+        }
+    }
+}
+
+pub struct InFlight<'a>(&'a Server);
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.0.in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+    use lib_core::model::upstream_group::UpstreamGroupServer;
+
+    fn fx_upstream(
+        balance: UpstreamBalance,
+        servers: &[(&str, Option<u32>, bool)],
+    ) -> Upstream {
+        Upstream::group(
+            ForwardScheme::Http,
+            &UpstreamGroup {
+                id: 1,
+                owner_id: "demo1".to_string(),
+                name: "app".to_string(),
+                balance,
+                servers: servers
+                    .iter()
+                    .map(|(host, weight, backup)| UpstreamGroupServer {
+                        host: host.to_string(),
+                        port: 3000,
+                        weight: *weight,
+                        max_fails: None,
+                        fail_timeout: None,
+                        backup: *backup,
+                    })
+                    .collect(),
+                ctime: String::new(),
+                mtime: String::new(),
+            },
+        )
+    }
+
+    fn first_host(upstream: &Upstream, client_ip: &str) -> Result<String> {
+        let servers = upstream.servers(client_ip.parse()?);
+        let first = servers.first().ok_or("Should have a server")?;
+        Ok(first.host.clone())
+    }
+
+    #[test]
+    fn test_upstream_round_robin_weight_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_upstream = fx_upstream(
+            UpstreamBalance::RoundRobin,
+            &[("a", Some(2), false), ("b", None, false), ("c", None, true)],
+        );
+
+        // -- Exec
+        let mut hosts = Vec::new();
+        for _ in 0..6 {
+            hosts.push(first_host(&fx_upstream, "10.0.0.1")?);
+        }
+
+        // -- Check
+        assert_eq!(hosts, ["a", "a", "b", "a", "a", "b"]);
+        let servers = fx_upstream.servers("10.0.0.1".parse()?);
+        let order: Vec<&str> =
+            servers.iter().map(|s| s.host.as_str()).collect();
+        assert_eq!(order, ["a", "b", "c"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_upstream_failed_server_skipped_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_upstream = fx_upstream(
+            UpstreamBalance::IpHash,
+            &[("a", None, false), ("b", None, false)],
+        );
+        let fx_client = "10.0.0.1";
+        let sticky = first_host(&fx_upstream, fx_client)?;
+        assert_eq!(first_host(&fx_upstream, fx_client)?, sticky);
+
+        // -- Exec
+        let servers = fx_upstream.servers(fx_client.parse()?);
+        servers[0].failed();
+
+        // -- Check
+        assert_ne!(first_host(&fx_upstream, fx_client)?, sticky);
+        servers[0].succeeded();
+        assert_eq!(first_host(&fx_upstream, fx_client)?, sticky);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_upstream_least_conn_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_upstream = fx_upstream(
+            UpstreamBalance::LeastConn,
+            &[("a", None, false), ("b", None, false)],
+        );
+
+        // -- Exec
+        let servers = fx_upstream.servers("10.0.0.1".parse()?);
+        let _in_flight = servers[0].start();
+
+        // -- Check
+        assert_ne!(first_host(&fx_upstream, "10.0.0.1")?, servers[0].host);
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
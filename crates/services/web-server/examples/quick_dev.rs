@@ -19,6 +19,21 @@ async fn main() -> Result<()> {
     );
     req_login.await?.print().await?;
 
+    // -- Proxy hosts
+    let req_create_proxy_host = hc.do_post(
+        "/api/proxy-hosts",
+        json!({
+            "domainNames": ["app.example.com"],
+            "forwardScheme": "http",
+            "forwardHost": "127.0.0.1",
+            "forwardPort": 3000
+        }),
+    );
+    req_create_proxy_host.await?.print().await?;
+
+    let req_list_proxy_hosts = hc.do_get("/api/proxy-hosts");
+    req_list_proxy_hosts.await?.print().await?;
+
     // -- Logoff
     let req_logoff = hc.do_post(
         "/api/logoff",
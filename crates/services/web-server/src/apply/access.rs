@@ -0,0 +1,50 @@
+//! User files of the access lists, staged in each generation:
+//!
+//! ```text
+//! <generation>/
+//!     access/
+//!         access_list_<id>.htpasswd    `user:hash` lines
+//! ```
+//!
+//! Like the certificates, they are referred to relative to `nginx.conf`.
+//! Unlike them, the files are read by the nginx workers on each request,
+//! through the `current` symlink, so they are readable by all. They only
+//! hold hashes.
+
+use crate::apply::Result;
+use crate::apply::generation::StagedFile;
+use lib_auth::pwd::htpasswd_hash;
+use lib_core::model::access_list::AccessListUser;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+const ACCESS_DIR: &str = "access";
+
+/// Path of the user file of the access list `id`, relative to the
+/// generation.
+pub fn user_file_path(id: i64) -> PathBuf {
+    Path::new(ACCESS_DIR).join(format!("access_list_{id}.htpasswd"))
+}
+
+/// The user files of the access lists with `users`.
+pub fn user_files(users: &[AccessListUser]) -> Result<Vec<StagedFile>> {
+    let mut files: BTreeMap<i64, String> = BTreeMap::new();
+    for user in users {
+        let line = format!("{}:{}\n", user.username, htpasswd_hash(&user.pwd)?);
+        files
+            .entry(user.access_list_id)
+            .or_default()
+            .push_str(&line);
+    }
+
+    let files = files
+        .into_iter()
+        .map(|(id, content)| StagedFile {
+            path: user_file_path(id),
+            content,
+            private: false,
+        })
+        .collect();
+
+    Ok(files)
+}
@@ -0,0 +1,52 @@
+//! Certificates of the store, staged in each generation:
+//!
+//! ```text
+//! <generation>/
+//!     certs/
+//!         certificate_<id>.pem    leaf and chain
+//!         certificate_<id>.key    private key, owner only
+//! ```
+//!
+//! The hosts refer to them with paths relative to `nginx.conf`, which
+//! nginx resolves from the staged generation for `nginx -t` and through
+//! the `current` symlink once swapped in. A renewal is then applied like
+//! any other change, and rolled back with it.
+
+use crate::apply::generation::StagedFile;
+use lib_core::model::certificate::Certificate;
+use std::path::{Path, PathBuf};
+
+const CERTS_DIR: &str = "certs";
+
+/// `(certificate, key)` paths of the certificate `id`, relative to the
+/// generation.
+pub fn certificate_paths(id: i64) -> (PathBuf, PathBuf) {
+    let dir = Path::new(CERTS_DIR);
+    (
+        dir.join(format!("certificate_{id}.pem")),
+        dir.join(format!("certificate_{id}.key")),
+    )
+}
+
+/// The files of the issued `certificates`.
+pub fn certificate_files(certificates: &[Certificate]) -> Vec<StagedFile> {
+    certificates
+        .iter()
+        .filter(|c| c.is_issued())
+        .flat_map(|certificate| {
+            let (cert_path, key_path) = certificate_paths(certificate.id);
+            [
+                StagedFile {
+                    path: cert_path,
+                    content: certificate.certificate_pem.clone(),
+                    private: true,
+                },
+                StagedFile {
+                    path: key_path,
+                    content: certificate.private_key_pem.clone(),
+                    private: true,
+                },
+            ]
+        })
+        .collect()
+}
@@ -0,0 +1,61 @@
+use lib_auth::pwd;
+use lib_core::model::{
+    access_list, certificate, config_apply, dead_host, proxy_host,
+    redirection_host, stream_host, upstream_group,
+};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(thiserror::Error, Debug, strum_macros::Display)]
+pub enum Error {
+    MainConfCantRead {
+        path: String,
+        cause: String,
+    },
+    MainConfInvalid {
+        path: String,
+        cause: lib_nginx::Error,
+    },
+    MainConfCantInclude {
+        cause: lib_nginx::Error,
+    },
+    LocationSnippetInvalid {
+        host_id: i64,
+        path: String,
+        cause: lib_nginx::Error,
+    },
+    HostSnippetInvalid {
+        host_id: i64,
+        cause: lib_nginx::Error,
+    },
+    NginxCantRun {
+        bin: String,
+        cause: String,
+    },
+
+    // -- Modules
+    #[error(transparent)]
+    AccessList(#[from] access_list::Error),
+    #[error(transparent)]
+    ProxyHost(#[from] proxy_host::Error),
+    #[error(transparent)]
+    UpstreamGroup(#[from] upstream_group::Error),
+    #[error(transparent)]
+    RedirectionHost(#[from] redirection_host::Error),
+    #[error(transparent)]
+    StreamHost(#[from] stream_host::Error),
+    #[error(transparent)]
+    DeadHost(#[from] dead_host::Error),
+    #[error(transparent)]
+    Certificate(#[from] certificate::Error),
+    #[error(transparent)]
+    ConfigApply(#[from] config_apply::Error),
+    #[error(transparent)]
+    Web(#[from] lib_web::Error),
+    #[error(transparent)]
+    Pwd(#[from] pwd::Error),
+
+    // -- Externals
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
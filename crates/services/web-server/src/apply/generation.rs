@@ -0,0 +1,573 @@
+//! On disk layout of the generated configuration:
+//!
+//! ```text
+//! <NGINX_CONF_DIR>/
+//!     current -> generations/<generation>
+//!     generations/
+//!         <generation>/
+//!             nginx.conf
+//!             hosts/<host>.conf
+//!             streams/<host>.conf
+//!             <staged files>, e.g. certs/certificate_<id>.pem
+//! ```
+//!
+//! `hosts` are included from the `http` context, `streams` from a
+//! `stream` context which only exists when there is a stream host.
+//! The other staged files are referred to relative to `nginx.conf`.
+//!
+//! A new generation is staged next to the current one and validated with
+//! `nginx -t`. Only then the `current` symlink is swapped, with a rename
+//! so that nginx never sees a half written configuration.
+
+use crate::apply::{Error, Result};
+use crate::config::ApplyConfig;
+use chrono::Utc;
+use lib_core::model::config_apply::ConfigApplyStatus;
+use lib_nginx::{Directive, append_to_block};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use tokio::process::Command;
+use tracing::debug;
+
+const GENERATIONS_DIR: &str = "generations";
+const CURRENT_LINK: &str = "current";
+const HOSTS_DIR: &str = "hosts";
+const STREAMS_DIR: &str = "streams";
+const NGINX_CONF: &str = "nginx.conf";
+
+/// One rendered host file.
+pub struct HostFile {
+    /// File name in the `hosts` or `streams` directory, e.g.
+    /// `proxy_host_1.conf`.
+    pub name: String,
+    pub content: String,
+}
+
+/// Another file of the generation, e.g. a certificate.
+pub struct StagedFile {
+    /// Path relative to the generation, e.g. `certs/certificate_1.pem`.
+    pub path: PathBuf,
+    pub content: String,
+    /// Readable by the owner only, as its directory.
+    pub private: bool,
+}
+
+#[derive(Debug)]
+pub struct ApplyOutcome {
+    pub generation: Option<String>,
+    pub status: ConfigApplyStatus,
+    /// Output of nginx, the error for `Invalid` and `ReloadFailed`.
+    pub output: String,
+}
+
+/// Stage `hosts`, `streams` and `files` as a new generation, validate it,
+/// swap it in and reload.
+///
+/// `main` is the text of the main `nginx.conf`, written as is with only
+/// the `include` of the hosts (and streams) added.
+///
+/// A failed validation or reload is not an `Err`, it is reported in the
+/// outcome and the previous generation stays (or is put back) in place.
+pub async fn apply_generation(
+    config: &ApplyConfig,
+    main: &str,
+    hosts: &[HostFile],
+    streams: &[HostFile],
+    files: &[StagedFile],
+) -> Result<ApplyOutcome> {
+    // -- Stage
+    fs::create_dir_all(config.NGINX_CONF_DIR.join(GENERATIONS_DIR)).await?;
+    let conf_dir = fs::canonicalize(&config.NGINX_CONF_DIR).await?;
+
+    let generation = Utc::now().format("%Y%m%dT%H%M%S%.6fZ").to_string();
+    let staging = conf_dir.join(GENERATIONS_DIR).join(&generation);
+    write_host_files(&staging.join(HOSTS_DIR), hosts).await?;
+    let include = |dir: &str| {
+        Directive::new("include")
+            .arg(format!("{}/*.conf", staging.join(dir).display()))
+    };
+    let mut main = append_to_block(main, "http", include(HOSTS_DIR))
+        .map_err(|cause| Error::MainConfCantInclude { cause })?;
+
+    if !streams.is_empty() {
+        write_host_files(&staging.join(STREAMS_DIR), streams).await?;
+        main = append_to_block(&main, "stream", include(STREAMS_DIR))
+            .map_err(|cause| Error::MainConfCantInclude { cause })?;
+    }
+    write_staged_files(&staging, files).await?;
+    fs::write(staging.join(NGINX_CONF), main).await?;
+
+    // -- Validate
+    let test = match run_nginx(config, &["-t"], &staging.join(NGINX_CONF)).await
+    {
+        Ok(test) => test,
+        Err(ex) => {
+            fs::remove_dir_all(&staging).await?;
+            return Err(ex);
+        }
+    };
+    if !test.success {
+        fs::remove_dir_all(&staging).await?;
+        return Ok(ApplyOutcome {
+            generation: None,
+            status: ConfigApplyStatus::Invalid,
+            output: test.output,
+        });
+    }
+
+    // -- Swap
+    let current = conf_dir.join(CURRENT_LINK);
+    let previous = fs::read_link(&current).await.ok();
+    swap_current(&conf_dir, &Path::new(GENERATIONS_DIR).join(&generation))
+        .await?;
+
+    // -- Reload
+    let reload =
+        run_nginx(config, &["-s", "reload"], &current.join(NGINX_CONF)).await;
+    if !matches!(reload, Ok(NginxRun { success: true, .. })) {
+        // Put the previous generation back.
+        match &previous {
+            Some(previous) => swap_current(&conf_dir, previous).await?,
+            None => fs::remove_file(&current).await?,
+        }
+        fs::remove_dir_all(&staging).await?;
+
+        return Ok(ApplyOutcome {
+            generation: None,
+            status: ConfigApplyStatus::ReloadFailed,
+            output: reload?.output,
+        });
+    }
+
+    prune(&conf_dir, &generation, config.NGINX_KEEP_GENERATIONS).await?;
+
+    Ok(ApplyOutcome {
+        generation: Some(generation),
+        status: ConfigApplyStatus::Applied,
+        output: test.output,
+    })
+}
+
+/// What `<bin> -v` prints, e.g. `nginx version: nginx/1.25.3`. `None`
+/// when it can't run.
+pub async fn nginx_version(config: &ApplyConfig) -> Option<String> {
+    let output = Command::new(&config.NGINX_BIN).arg("-v").output().await;
+    match output {
+        Ok(output) if output.status.success() => {
+            // Printed on stderr.
+            Some(String::from_utf8_lossy(&output.stderr).trim().to_string())
+        }
+        _ => None,
+    }
+}
+
+// region:    --- Support
+
+async fn write_host_files(dir: &Path, files: &[HostFile]) -> Result<()> {
+    fs::create_dir_all(dir).await?;
+    for file in files {
+        fs::write(dir.join(&file.name), &file.content).await?;
+    }
+
+    Ok(())
+}
+
+async fn write_staged_files(
+    staging: &Path,
+    files: &[StagedFile],
+) -> Result<()> {
+    for file in files {
+        let path = staging.join(&file.path);
+        let Some(dir) = path.parent() else {
+            continue;
+        };
+        fs::create_dir_all(dir).await?;
+        fs::write(&path, &file.content).await?;
+        if file.private {
+            fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))
+                .await?;
+            fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+struct NginxRun {
+    success: bool,
+    /// stdout and stderr, nginx writes its diagnostics to stderr.
+    output: String,
+}
+
+/// Run `<bin> <args> -c <conf>`.
+async fn run_nginx(
+    config: &ApplyConfig,
+    args: &[&str],
+    conf: &Path,
+) -> Result<NginxRun> {
+    debug!("{:<12} - run_nginx {:?}", "APPLY", config.NGINX_BIN);
+
+    let output = Command::new(&config.NGINX_BIN)
+        .args(args)
+        .arg("-c")
+        .arg(conf)
+        .output()
+        .await
+        .map_err(|ex| Error::NginxCantRun {
+            bin: config.NGINX_BIN.display().to_string(),
+            cause: ex.to_string(),
+        })?;
+
+    let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+    text.push_str(&String::from_utf8_lossy(&output.stderr));
+
+    Ok(NginxRun {
+        success: output.status.success(),
+        output: text.trim().to_string(),
+    })
+}
+
+/// Atomically point the `current` symlink to `target` (relative to
+/// `conf_dir`).
+async fn swap_current(conf_dir: &Path, target: &Path) -> Result<()> {
+    let tmp_link = conf_dir.join(format!("{CURRENT_LINK}.tmp"));
+    let _ = fs::remove_file(&tmp_link).await;
+    fs::symlink(target, &tmp_link).await?;
+    fs::rename(&tmp_link, conf_dir.join(CURRENT_LINK)).await?;
+
+    Ok(())
+}
+
+/// Remove the oldest generations, keeping `keep` of them (`current`
+/// included).
+async fn prune(conf_dir: &Path, current: &str, keep: usize) -> Result<()> {
+    let mut generations = Vec::new();
+    let mut entries = fs::read_dir(conf_dir.join(GENERATIONS_DIR)).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        generations.push(entry.file_name().to_string_lossy().into_owned());
+    }
+    // Names are timestamps, newest last.
+    generations.sort();
+
+    let obsolete = generations.len().saturating_sub(keep.max(1));
+    for generation in &generations[..obsolete] {
+        if generation != current {
+            let path: PathBuf = conf_dir.join(GENERATIONS_DIR).join(generation);
+            fs::remove_dir_all(path).await?;
+        }
+    }
+
+    Ok(())
+}
+
+// endregion: --- Support
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+    use lib_nginx::{NginxConfig, Render};
+
+    fn fx_main() -> String {
+        NginxConfig::default().render()
+    }
+
+    /// A fresh conf dir and an nginx stub script which fails for the
+    /// arguments matching `fail_on` (a shell `case` pattern).
+    async fn fx_config(name: &str, fail_on: &str) -> Result<ApplyConfig> {
+        let dir = std::env::temp_dir()
+            .join(format!("web-server-apply-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir).await;
+        fs::create_dir_all(&dir).await?;
+
+        let bin = dir.join("nginx-stub.sh");
+        fs::write(
+            &bin,
+            format!(
+                "#!/bin/sh\ncase \"$*\" in\n  {fail_on}) echo \"nginx: [emerg] stub failure for $*\" >&2; exit 1;;\nesac\necho \"nginx: stub ok $*\" >&2\n"
+            ),
+        )
+        .await?;
+        fs::set_permissions(&bin, std::fs::Permissions::from_mode(0o755))
+            .await?;
+
+        Ok(ApplyConfig {
+            NGINX_BIN: bin,
+            NGINX_CONF_DIR: dir.join("conf"),
+            NGINX_MAIN_CONF: None,
+            NGINX_KEEP_GENERATIONS: 2,
+        })
+    }
+
+    fn fx_hosts(content: &str) -> Vec<HostFile> {
+        vec![HostFile {
+            name: "proxy_host_1.conf".to_string(),
+            content: content.to_string(),
+        }]
+    }
+
+    async fn current_host(config: &ApplyConfig) -> Result<String> {
+        let path = config
+            .NGINX_CONF_DIR
+            .join(CURRENT_LINK)
+            .join(HOSTS_DIR)
+            .join("proxy_host_1.conf");
+        Ok(fs::read_to_string(path).await?)
+    }
+
+    #[tokio::test]
+    async fn test_apply_generation_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let config = fx_config("ok", "never").await?;
+
+        // -- Exec
+        let mut outcomes = Vec::new();
+        for content in ["server { }\n", "server { listen 81; }\n", "# v3\n"] {
+            outcomes.push(
+                apply_generation(
+                    &config,
+                    &fx_main(),
+                    &fx_hosts(content),
+                    &[],
+                    &[],
+                )
+                .await?,
+            );
+        }
+
+        // -- Check
+        assert!(
+            outcomes
+                .iter()
+                .all(|outcome| outcome.status == ConfigApplyStatus::Applied)
+        );
+        assert_eq!(current_host(&config).await?, "# v3\n");
+        let main = fs::read_to_string(
+            config.NGINX_CONF_DIR.join("current/nginx.conf"),
+        )
+        .await?;
+        assert!(main.contains("/hosts/*.conf;"));
+        assert!(!main.contains("stream {"));
+        // Pruned to NGINX_KEEP_GENERATIONS.
+        let mut entries =
+            fs::read_dir(config.NGINX_CONF_DIR.join(GENERATIONS_DIR)).await?;
+        let mut count = 0;
+        while entries.next_entry().await?.is_some() {
+            count += 1;
+        }
+        assert_eq!(count, 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_apply_generation_main_as_is_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let config = fx_config("main", "never").await?;
+        let fx_main = "# Hand written.
+error_log /var/log/nginx/error.log;
+error_log /var/log/nginx/debug.log debug;
+events {}
+http {
+  keepalive_timeout 1m;
+}
+";
+
+        // -- Exec
+        apply_generation(&config, fx_main, &fx_hosts("# v1\n"), &[], &[])
+            .await?;
+
+        // -- Check
+        let main = fs::read_to_string(
+            config.NGINX_CONF_DIR.join("current/nginx.conf"),
+        )
+        .await?;
+        let (head, tail) = main
+            .split_once("    include ")
+            .ok_or("Should include the hosts")?;
+        assert_eq!(head, &fx_main[..fx_main.len() - 2]);
+        assert!(tail.ends_with("/hosts/*.conf;\n}\n"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_apply_generation_streams_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let config = fx_config("streams", "never").await?;
+
+        // -- Exec
+        apply_generation(
+            &config,
+            &fx_main(),
+            &[],
+            &fx_hosts("server { listen 5432; }\n"),
+            &[],
+        )
+        .await?;
+
+        // -- Check
+        let main = fs::read_to_string(
+            config.NGINX_CONF_DIR.join("current/nginx.conf"),
+        )
+        .await?;
+        assert!(main.contains("stream {"));
+        assert!(main.contains("/streams/*.conf;"));
+        assert!(
+            config
+                .NGINX_CONF_DIR
+                .join("current/streams/proxy_host_1.conf")
+                .exists()
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_apply_generation_staged_files_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let config = fx_config("staged", "never").await?;
+        let fx_files = [StagedFile {
+            path: "certs/certificate_1.key".into(),
+            content: "fx-key".to_string(),
+            private: true,
+        }];
+
+        // -- Exec
+        apply_generation(
+            &config,
+            &fx_main(),
+            &fx_hosts("# v1\n"),
+            &[],
+            &fx_files,
+        )
+        .await?;
+
+        // -- Check
+        let path = config
+            .NGINX_CONF_DIR
+            .join(CURRENT_LINK)
+            .join("certs/certificate_1.key");
+        assert_eq!(fs::read_to_string(&path).await?, "fx-key");
+        let mode = fs::metadata(&path).await?.permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        let mode = fs::metadata(path.parent().ok_or("no parent")?)
+            .await?
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o700);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_apply_generation_invalid_keeps_previous() -> Result<()> {
+        // -- Setup & Fixtures
+        let config = fx_config("invalid", "-t*").await?;
+        let ok_config = ApplyConfig {
+            NGINX_BIN: "true".into(),
+            NGINX_CONF_DIR: config.NGINX_CONF_DIR.clone(),
+            NGINX_MAIN_CONF: None,
+            NGINX_KEEP_GENERATIONS: 2,
+        };
+        apply_generation(&ok_config, &fx_main(), &fx_hosts("# v1\n"), &[], &[])
+            .await?;
+
+        // -- Exec
+        let outcome = apply_generation(
+            &config,
+            &fx_main(),
+            &fx_hosts("# v2\n"),
+            &[],
+            &[],
+        )
+        .await?;
+
+        // -- Check
+        assert_eq!(outcome.status, ConfigApplyStatus::Invalid);
+        assert!(outcome.output.contains("[emerg] stub failure for -t -c"));
+        assert_eq!(current_host(&config).await?, "# v1\n");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_apply_generation_reload_failed_rolls_back() -> Result<()> {
+        // -- Setup & Fixtures
+        let config = fx_config("reload", "-s*").await?;
+        let ok_config = ApplyConfig {
+            NGINX_BIN: "true".into(),
+            NGINX_CONF_DIR: config.NGINX_CONF_DIR.clone(),
+            NGINX_MAIN_CONF: None,
+            NGINX_KEEP_GENERATIONS: 2,
+        };
+        apply_generation(&ok_config, &fx_main(), &fx_hosts("# v1\n"), &[], &[])
+            .await?;
+
+        // -- Exec
+        let outcome = apply_generation(
+            &config,
+            &fx_main(),
+            &fx_hosts("# v2\n"),
+            &[],
+            &[],
+        )
+        .await?;
+
+        // -- Check
+        assert_eq!(outcome.status, ConfigApplyStatus::ReloadFailed);
+        assert!(outcome.output.contains("stub failure for -s reload"));
+        assert_eq!(current_host(&config).await?, "# v1\n");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_nginx_version_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let config = fx_config("version", "never").await?;
+        let mut missing_config = fx_config("version-missing", "never").await?;
+        missing_config.NGINX_BIN = "/nonexistent/nginx".into();
+
+        // -- Exec
+        let version = nginx_version(&config).await;
+        let missing_version = nginx_version(&missing_config).await;
+
+        // -- Check
+        assert_eq!(version.as_deref(), Some("nginx: stub ok -v"));
+        assert_eq!(missing_version, None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_apply_generation_missing_bin_err() -> Result<()> {
+        // -- Setup & Fixtures
+        let mut config = fx_config("missing", "never").await?;
+        config.NGINX_BIN = "/nonexistent/nginx".into();
+
+        // -- Exec
+        let res = apply_generation(
+            &config,
+            &fx_main(),
+            &fx_hosts("# v1\n"),
+            &[],
+            &[],
+        )
+        .await;
+
+        // -- Check
+        assert!(matches!(res, Err(crate::apply::Error::NginxCantRun { .. })));
+        assert!(!config.NGINX_CONF_DIR.join(CURRENT_LINK).exists());
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -0,0 +1,926 @@
+//! Writes the hosts to nginx and reloads it.
+//!
+//! Every change of the model (see `ModelEvent`) triggers a new apply:
+//! the enabled hosts are rendered into a new generation which is checked
+//! with `nginx -t` before being swapped in and reloaded. Each run is
+//! recorded with `ConfigApplyBmc`, the UI shows the last one.
+//!
+//! A proxy host which can't be rendered is left out of the generation and
+//! the run is recorded as `Failed`, naming it.
+//!
+//! The upstream groups are rendered in their own files, next to the hosts
+//! forwarding to them.
+//!
+//! The issued certificates and the user files of the access lists are
+//! staged with the hosts (see `certs` and `access`), a renewal is applied
+//! like any other change.
+
+// region:    --- Modules
+
+mod access;
+mod certs;
+mod error;
+mod generation;
+
+pub use self::error::{Error, Result};
+
+use crate::config::{ApplyConfig, acme_config, apply_config};
+use generation::{ApplyOutcome, HostFile, apply_generation, nginx_version};
+use lib_core::ctx::Ctx;
+use lib_core::model::ModelManager;
+use lib_core::model::access_list::{
+    self, AccessList, AccessListBmc, AccessListSatisfy,
+};
+use lib_core::model::certificate::CertificateBmc;
+use lib_core::model::config_apply::{
+    ConfigApplyBmc, ConfigApplyForCreate, ConfigApplyStatus,
+};
+use lib_core::model::dead_host::{DeadHost, DeadHostBmc};
+use lib_core::model::proxy_host::{self, ProxyHost, ProxyHostBmc};
+use lib_core::model::redirection_host::{RedirectionHost, RedirectionHostBmc};
+use lib_core::model::stream_host::{self, StreamHost, StreamHostBmc};
+use lib_core::model::upstream_group::{
+    UpstreamBalance, UpstreamGroup, UpstreamGroupBmc,
+};
+use lib_nginx::{
+    AccessConf, AccessRule, AuthBasic, CacheZone, ConnectionLimit,
+    ConnectionUpgradeMap, DeadHostConf, ErrorPage, Header, Http2Syntax,
+    LimitZones, NginxConfig, ProxyHostConf, ProxyLocationConf, RateLimit,
+    RedirectionHostConf, Render, Satisfy, SslConf, StreamHostConf, Upstream,
+    UpstreamServer, parse,
+};
+use lib_web::utils::error_page::save_default_error_page;
+use lib_web::web_config;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use tokio::sync::broadcast::error::{RecvError, TryRecvError};
+use tokio::task::JoinHandle;
+use tracing::{debug, error};
+
+// endregion: --- Modules
+
+/// Changes arriving within this delay are applied together.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Apply once at start, then after every model change.
+pub fn spawn_applier(mm: ModelManager) -> JoinHandle<()> {
+    let mut events = mm.subscribe();
+
+    tokio::spawn(async move {
+        // `http2 on;` for the nginx which deprecates `listen ... http2`.
+        // Probed once, nginx is not upgraded under a running manager.
+        let http2_syntax = nginx_version(apply_config())
+            .await
+            .map(|version| Http2Syntax::for_nginx_version(&version))
+            .unwrap_or_default();
+
+        loop {
+            if let Err(ex) = apply(&mm, apply_config(), http2_syntax).await {
+                error!("{:<12} - apply failed: {ex:?}", "APPLY");
+            }
+
+            match events.recv().await {
+                // A lagged receiver missed changes, apply anyway.
+                Ok(_) | Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => break,
+            }
+            tokio::time::sleep(DEBOUNCE).await;
+            loop {
+                match events.try_recv() {
+                    Ok(_) | Err(TryRecvError::Lagged(_)) => {}
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Closed) => return,
+                }
+            }
+        }
+    })
+}
+
+/// Render the enabled hosts, apply them and record the outcome.
+pub async fn apply(
+    mm: &ModelManager,
+    config: &ApplyConfig,
+    http2_syntax: Http2Syntax,
+) -> Result<ConfigApplyStatus> {
+    debug!("{:<12} - apply", "APPLY");
+
+    let ctx = Ctx::root_ctx();
+
+    let access_lists: HashMap<i64, AccessList> = AccessListBmc::list(&ctx, mm)
+        .await?
+        .into_iter()
+        .map(|access_list| (access_list.id, access_list))
+        .collect();
+    let access_list_users = AccessListBmc::list_users(&ctx, mm).await?;
+
+    let certificates = CertificateBmc::list(&ctx, mm).await?;
+    // Only the issued ones are staged, and can be served.
+    let issued_certificates: HashSet<i64> = certificates
+        .iter()
+        .filter(|certificate| certificate.is_issued())
+        .map(|certificate| certificate.id)
+        .collect();
+
+    // A host which can't be rendered (e.g. a location saved before its
+    // validation) is left out, the others are still applied.
+    let mut skipped: Vec<Error> = Vec::new();
+    let mut proxy_hosts: Vec<ProxyHost> = Vec::new();
+    let mut proxy_host_confs: Vec<ProxyHostConf> = Vec::new();
+    let mut hosts: Vec<HostFile> = Vec::new();
+    for host in ProxyHostBmc::list(&ctx, mm).await? {
+        if !host.enabled {
+            continue;
+        }
+        match proxy_host_conf(
+            &host,
+            &access_lists,
+            &issued_certificates,
+            http2_syntax,
+        ) {
+            Ok(conf) => {
+                hosts.push(HostFile {
+                    name: format!("proxy_host_{}.conf", host.id),
+                    content: conf.render(),
+                });
+                proxy_hosts.push(host);
+                proxy_host_confs.push(conf);
+            }
+            Err(ex) => {
+                error!("{:<12} - proxy host left out: {ex:?}", "APPLY");
+                skipped.push(ex);
+            }
+        }
+    }
+    // The zones and the map are declared once, at the `http` level.
+    let limit_zones = LimitZones::new(&proxy_host_confs);
+    if !limit_zones.is_empty() {
+        hosts.push(HostFile {
+            name: "limit_zones.conf".to_string(),
+            content: limit_zones.render(),
+        });
+    }
+    if proxy_hosts.iter().any(|host| host.cache_assets) {
+        // nginx resolves a relative path against its own prefix.
+        tokio::fs::create_dir_all(&config.NGINX_CONF_DIR).await?;
+        let path = tokio::fs::canonicalize(&config.NGINX_CONF_DIR)
+            .await?
+            .join("cache_assets");
+        hosts.push(HostFile {
+            name: "cache_assets.conf".to_string(),
+            content: CacheZone::new(path.display().to_string()).render(),
+        });
+    }
+    if proxy_hosts.iter().any(|host| host.allow_websocket_upgrade) {
+        hosts.push(HostFile {
+            name: "websocket_upgrade.conf".to_string(),
+            content: ConnectionUpgradeMap.render(),
+        });
+    }
+    hosts.extend(UpstreamGroupBmc::list(&ctx, mm).await?.iter().map(
+        |upstream_group| HostFile {
+            name: format!("{}.conf", upstream_name(upstream_group.id)),
+            content: upstream_conf(upstream_group).render(),
+        },
+    ));
+    hosts.extend(
+        RedirectionHostBmc::list(&ctx, mm)
+            .await?
+            .iter()
+            .filter(|host| host.enabled)
+            .map(|host| HostFile {
+                name: format!("redirection_host_{}.conf", host.id),
+                content: redirection_host_conf(host).render(),
+            }),
+    );
+    hosts.extend(dead_host_files(mm, &ctx).await?);
+
+    let streams: Vec<HostFile> = StreamHostBmc::list(&ctx, mm)
+        .await?
+        .iter()
+        .filter(|host| host.enabled)
+        .map(|host| HostFile {
+            name: format!("stream_host_{}.conf", host.id),
+            content: stream_host_conf(host).render(),
+        })
+        .collect();
+
+    let outcome = async {
+        let mut files = certs::certificate_files(&certificates);
+        files.extend(access::user_files(&access_list_users)?);
+        let main = load_main_conf(config).await?;
+        apply_generation(config, &main, &hosts, &streams, &files).await
+    }
+    .await;
+    let mut outcome = outcome.unwrap_or_else(|ex| ApplyOutcome {
+        generation: None,
+        status: ConfigApplyStatus::Failed,
+        output: format!("{ex:?}"),
+    });
+    if !skipped.is_empty() {
+        // Applied without them is still a failure to show.
+        if outcome.status == ConfigApplyStatus::Applied {
+            outcome.status = ConfigApplyStatus::Failed;
+        }
+        let mut output: Vec<String> = skipped
+            .iter()
+            .map(|ex| format!("left out, {ex:?}"))
+            .collect();
+        output.push(outcome.output);
+        outcome.output = output.join("\n");
+    }
+
+    let status = outcome.status;
+    ConfigApplyBmc::create(
+        &ctx,
+        mm,
+        ConfigApplyForCreate {
+            generation: outcome.generation,
+            status,
+            output: outcome.output,
+        },
+    )
+    .await?;
+
+    Ok(status)
+}
+
+// region:    --- Support
+
+/// The text of the configured main `nginx.conf`, or of the crate
+/// defaults.
+///
+/// The configured file is only checked for syntax, it is written as the
+/// operator wrote it.
+async fn load_main_conf(config: &ApplyConfig) -> Result<String> {
+    let Some(path) = &config.NGINX_MAIN_CONF else {
+        let mut main = NginxConfig::default();
+        if let Some(http) = &mut main.http {
+            http.mime_types = Some("/etc/nginx/mime.types".to_string());
+        }
+        return Ok(main.render());
+    };
+
+    let text = tokio::fs::read_to_string(path).await.map_err(|ex| {
+        Error::MainConfCantRead {
+            path: path.display().to_string(),
+            cause: ex.to_string(),
+        }
+    })?;
+
+    parse(&text).map_err(|cause| Error::MainConfInvalid {
+        path: path.display().to_string(),
+        cause,
+    })?;
+
+    Ok(text)
+}
+
+/// Served over https when its certificate is issued, the ssl flags are
+/// ignored otherwise.
+fn proxy_host_conf(
+    host: &ProxyHost,
+    access_lists: &HashMap<i64, AccessList>,
+    issued_certificates: &HashSet<i64>,
+    http2_syntax: Http2Syntax,
+) -> Result<ProxyHostConf> {
+    let locations = host
+        .locations
+        .iter()
+        .map(|location| {
+            Ok(ProxyLocationConf {
+                path: location.path.clone(),
+                forward_scheme: forward_scheme(location.forward_scheme),
+                forward_host: location.forward_host.clone(),
+                forward_port: location.forward_port,
+                headers: location
+                    .headers
+                    .iter()
+                    .map(|header| Header::new(&header.name, &header.value))
+                    .collect(),
+                // Validated when saved, checked again as nginx would fail.
+                advanced: parse(&location.advanced).map_err(|cause| {
+                    Error::LocationSnippetInvalid {
+                        host_id: host.id,
+                        path: location.path.clone(),
+                        cause,
+                    }
+                })?,
+            })
+        })
+        .collect::<Result<_>>()?;
+    let advanced =
+        parse(&host.advanced).map_err(|cause| Error::HostSnippetInvalid {
+            host_id: host.id,
+            cause,
+        })?;
+
+    Ok(ProxyHostConf {
+        domain_names: host.domain_names.clone(),
+        forward_scheme: forward_scheme(host.forward_scheme),
+        forward_host: host.forward_host.clone(),
+        forward_port: host.forward_port,
+        acme_challenge_pass: Some(acme_config().ACME_CHALLENGE_PASS.clone()),
+        access: host
+            .access_list_id
+            .and_then(|id| access_lists.get(&id))
+            .map(access_conf),
+        ssl: host
+            .certificate_id
+            .filter(|id| issued_certificates.contains(id))
+            .map(|id| ssl_conf(host, id, http2_syntax)),
+        block_exploits: host.block_exploits,
+        cache_assets: host.cache_assets,
+        allow_websocket_upgrade: host.allow_websocket_upgrade,
+        advanced,
+        // A zone each, the hosts do not share the budget of a client.
+        rate_limit: host.rate_limit.map(|rate| RateLimit {
+            zone: format!("proxy_host_{}", host.id),
+            rate,
+            burst: host.rate_limit_burst,
+            nodelay: host.rate_limit_nodelay,
+        }),
+        connection_limit: host.connection_limit.map(|limit| ConnectionLimit {
+            zone: format!("proxy_host_{}", host.id),
+            limit,
+        }),
+        upstream: host.upstream_group_id.map(upstream_name),
+        locations,
+    })
+}
+
+fn ssl_conf(
+    host: &ProxyHost,
+    certificate_id: i64,
+    http2_syntax: Http2Syntax,
+) -> SslConf {
+    let (certificate, certificate_key) =
+        certs::certificate_paths(certificate_id);
+
+    SslConf {
+        certificate: certificate.display().to_string(),
+        certificate_key: certificate_key.display().to_string(),
+        forced: host.ssl_forced,
+        http2: host.http2_support,
+        http2_syntax,
+        hsts: host.hsts_enabled,
+        hsts_subdomains: host.hsts_subdomains,
+    }
+}
+
+/// Name of the `upstream` of a group, unique across the owners.
+fn upstream_name(upstream_group_id: i64) -> String {
+    format!("upstream_group_{upstream_group_id}")
+}
+
+fn upstream_conf(upstream_group: &UpstreamGroup) -> Upstream {
+    Upstream {
+        balance: match upstream_group.balance {
+            UpstreamBalance::RoundRobin => None,
+            UpstreamBalance::LeastConn => {
+                Some(lib_nginx::UpstreamBalance::LeastConn)
+            }
+            UpstreamBalance::IpHash => Some(lib_nginx::UpstreamBalance::IpHash),
+        },
+        servers: upstream_group
+            .servers
+            .iter()
+            .map(|server| {
+                // An IPv6 address is bracketed before the port.
+                let address = if server.host.contains(':') {
+                    format!("[{}]:{}", server.host, server.port)
+                } else {
+                    format!("{}:{}", server.host, server.port)
+                };
+                UpstreamServer {
+                    address,
+                    weight: server.weight,
+                    max_fails: server.max_fails,
+                    fail_timeout: server.fail_timeout,
+                    backup: server.backup,
+                }
+            })
+            .collect(),
+        ..Upstream::new(upstream_name(upstream_group.id))
+    }
+}
+
+fn forward_scheme(
+    scheme: proxy_host::ForwardScheme,
+) -> lib_nginx::ForwardScheme {
+    match scheme {
+        proxy_host::ForwardScheme::Http => lib_nginx::ForwardScheme::Http,
+        proxy_host::ForwardScheme::Https => lib_nginx::ForwardScheme::Https,
+    }
+}
+
+fn access_conf(access_list: &AccessList) -> AccessConf {
+    AccessConf {
+        satisfy: match access_list.satisfy {
+            AccessListSatisfy::All => Satisfy::All,
+            AccessListSatisfy::Any => Satisfy::Any,
+        },
+        rules: access_list
+            .rules
+            .iter()
+            .map(|rule| match rule.action {
+                access_list::AccessRuleAction::Allow => {
+                    AccessRule::Allow(rule.address.clone())
+                }
+                access_list::AccessRuleAction::Deny => {
+                    AccessRule::Deny(rule.address.clone())
+                }
+            })
+            .collect(),
+        auth_basic: (!access_list.usernames.is_empty()).then(|| AuthBasic {
+            realm: access_list.name.clone(),
+            user_file: access::user_file_path(access_list.id)
+                .display()
+                .to_string(),
+        }),
+    }
+}
+
+fn redirection_host_conf(host: &RedirectionHost) -> RedirectionHostConf {
+    RedirectionHostConf {
+        domain_names: host.domain_names.clone(),
+        forward_url: host.forward_url.clone(),
+        redirect_code: host.redirect_code,
+        preserve_path: host.preserve_path,
+        acme_challenge_pass: Some(acme_config().ACME_CHALLENGE_PASS.clone()),
+    }
+}
+
+/// The 404 hosts, with the default error page for the hosts without a
+/// custom one.
+async fn dead_host_files(
+    mm: &ModelManager,
+    ctx: &Ctx,
+) -> Result<Vec<HostFile>> {
+    let dead_hosts: Vec<DeadHost> = DeadHostBmc::list(ctx, mm)
+        .await?
+        .into_iter()
+        .filter(|host| host.enabled)
+        .collect();
+    if dead_hosts.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let static_root = tokio::fs::canonicalize(&web_config().STATIC_FOLDER)
+        .await?
+        .display()
+        .to_string();
+    let default_page =
+        if dead_hosts.iter().any(|host| host.error_page.is_none()) {
+            Some(save_default_error_page().await?)
+        } else {
+            None
+        };
+
+    let files = dead_hosts
+        .iter()
+        .map(|host| {
+            let file = host.error_page.clone().or_else(|| default_page.clone());
+            let conf = DeadHostConf {
+                domain_names: host.domain_names.clone(),
+                error_page: file.map(|file| ErrorPage {
+                    static_root: static_root.clone(),
+                    file,
+                }),
+                acme_challenge_pass: Some(
+                    acme_config().ACME_CHALLENGE_PASS.clone(),
+                ),
+            };
+            HostFile {
+                name: format!("dead_host_{}.conf", host.id),
+                content: conf.render(),
+            }
+        })
+        .collect();
+
+    Ok(files)
+}
+
+fn stream_host_conf(host: &StreamHost) -> StreamHostConf {
+    StreamHostConf {
+        incoming_port: host.incoming_port,
+        protocol: match host.protocol {
+            stream_host::StreamProtocol::Tcp => lib_nginx::StreamProtocol::Tcp,
+            stream_host::StreamProtocol::Udp => lib_nginx::StreamProtocol::Udp,
+            stream_host::StreamProtocol::Both => {
+                lib_nginx::StreamProtocol::Both
+            }
+        },
+        forward_host: host.forward_host.clone(),
+        forward_port: host.forward_port,
+    }
+}
+
+// endregion: --- Support
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+    use axum::Router;
+    use axum::extract::ws::{WebSocket, WebSocketUpgrade};
+    use axum::routing::get;
+    use futures::{SinkExt, StreamExt};
+    use lib_core::_dev_utils;
+    use lib_core::model::health_check::HealthStatus;
+    use lib_core::model::proxy_host::{ProxyHostForCreate, ProxyLocation};
+    use lib_nginx::Listen;
+    use sqlx::{Pool, Sqlite};
+    use std::os::unix::fs::PermissionsExt;
+    use std::time::Duration;
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio_tungstenite::tungstenite::Message;
+
+    /// A fresh conf dir and an nginx stub script which accepts everything.
+    async fn fx_stub_config(name: &str) -> Result<ApplyConfig> {
+        let dir = std::env::temp_dir()
+            .join(format!("web-server-apply-{name}-{}", std::process::id()));
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+        tokio::fs::create_dir_all(&dir).await?;
+
+        let bin = dir.join("nginx-stub.sh");
+        tokio::fs::write(&bin, "#!/bin/sh\necho \"nginx: stub ok $*\" >&2\n")
+            .await?;
+        tokio::fs::set_permissions(
+            &bin,
+            std::fs::Permissions::from_mode(0o755),
+        )
+        .await?;
+
+        Ok(ApplyConfig {
+            NGINX_BIN: bin,
+            NGINX_CONF_DIR: dir.join("conf"),
+            NGINX_MAIN_CONF: None,
+            NGINX_KEEP_GENERATIONS: 2,
+        })
+    }
+
+    fn fx_proxy_host(certificate_id: Option<i64>) -> ProxyHost {
+        ProxyHost {
+            id: 1,
+            owner_id: "demo1".to_string(),
+            domain_names: vec!["secure.example.com".to_string()],
+            forward_scheme: proxy_host::ForwardScheme::Http,
+            forward_host: "10.0.0.5".to_string(),
+            forward_port: 3000,
+            cache_assets: false,
+            block_exploits: false,
+            allow_websocket_upgrade: false,
+            ssl_forced: true,
+            http2_support: true,
+            hsts_enabled: true,
+            hsts_subdomains: false,
+            certificate_id,
+            certificate_name: None,
+            access_list_id: None,
+            access_list_name: None,
+            upstream_group_id: None,
+            upstream_group_name: None,
+            locations: Vec::new(),
+            advanced: String::new(),
+            rate_limit: None,
+            rate_limit_burst: 0,
+            rate_limit_nodelay: false,
+            connection_limit: None,
+            health_check_path: None,
+            health_check_interval: 30,
+            health_status: HealthStatus::Unknown,
+            health_latency_ms: None,
+            health_check_time: None,
+            enabled: true,
+            ctime: String::new(),
+            mtime: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_proxy_host_conf_ssl_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_issued = HashSet::from([7]);
+
+        // -- Exec
+        let conf = proxy_host_conf(
+            &fx_proxy_host(Some(7)),
+            &HashMap::new(),
+            &fx_issued,
+            Http2Syntax::ListenParam,
+        )?;
+        // A pending certificate is not staged yet.
+        let conf_pending = proxy_host_conf(
+            &fx_proxy_host(Some(8)),
+            &HashMap::new(),
+            &fx_issued,
+            Http2Syntax::ListenParam,
+        )?;
+
+        // -- Check
+        let res = conf.render();
+        assert!(res.contains("return 301 https://$host$request_uri;"));
+        assert!(res.contains("listen 443 ssl http2;"));
+        assert!(res.contains("ssl_certificate certs/certificate_7.pem;"));
+        assert!(res.contains("ssl_certificate_key certs/certificate_7.key;"));
+        assert!(res.contains(
+            "add_header Strict-Transport-Security max-age=63072000 always;"
+        ));
+        assert_eq!(conf_pending.ssl, None);
+        assert!(!conf_pending.render().contains("443"));
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_apply_invalid_host_left_out_ok(
+        pool: Pool<Sqlite>,
+    ) -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test_db(pool).await?;
+        let ctx = Ctx::new("demo1")?;
+        let fx_config = fx_stub_config("invalid-host").await?;
+        let good_id = ProxyHostBmc::create(
+            &ctx,
+            &mm,
+            _dev_utils::fx_proxy_host_c("good.example.com"),
+        )
+        .await?;
+        // Saved before the snippets were validated.
+        let bad_host_id = ProxyHostBmc::create(
+            &ctx,
+            &mm,
+            ProxyHostForCreate {
+                advanced: "gzip on".to_string(),
+                .._dev_utils::fx_proxy_host_c("bad-host.example.com")
+            },
+        )
+        .await?;
+        let bad_location_id = ProxyHostBmc::create(
+            &ctx,
+            &mm,
+            ProxyHostForCreate {
+                locations: vec![ProxyLocation {
+                    path: "/api/".to_string(),
+                    forward_scheme: proxy_host::ForwardScheme::Http,
+                    forward_host: "10.0.0.6".to_string(),
+                    forward_port: 8080,
+                    headers: Vec::new(),
+                    advanced: "proxy_read_timeout {".to_string(),
+                }],
+                .._dev_utils::fx_proxy_host_c("bad-location.example.com")
+            },
+        )
+        .await?;
+
+        // -- Exec
+        let status = apply(&mm, &fx_config, Http2Syntax::default()).await?;
+
+        // -- Check
+        assert_eq!(status, ConfigApplyStatus::Failed);
+        let last = ConfigApplyBmc::last(&ctx, &mm)
+            .await?
+            .ok_or("Should record the apply")?;
+        assert_eq!(last.status, ConfigApplyStatus::Failed);
+        assert!(last.generation.is_some());
+        assert!(last.output.contains(&format!(
+            "HostSnippetInvalid {{ host_id: {bad_host_id},"
+        )));
+        let reason =
+            format!("LocationSnippetInvalid {{ host_id: {bad_location_id},");
+        assert!(last.output.contains(&reason));
+        let hosts_dir = fx_config.NGINX_CONF_DIR.join("current/hosts");
+        assert!(
+            hosts_dir
+                .join(format!("proxy_host_{good_id}.conf"))
+                .exists()
+        );
+        assert!(
+            !hosts_dir
+                .join(format!("proxy_host_{bad_host_id}.conf"))
+                .exists()
+        );
+        assert!(
+            !hosts_dir
+                .join(format!("proxy_host_{bad_location_id}.conf"))
+                .exists()
+        );
+        // The reason is shown by the status fragment.
+        let tera = tera::Tera::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../../frontend/templates/fragmants/config_apply/*.html"
+        ))?;
+        let mut context = tera::Context::new();
+        context.insert("config_apply", &last);
+        let fragment = tera.render("status.html", &context)?;
+        assert!(fragment.contains("Failed"));
+        assert!(fragment.contains(&format!("left out, {reason}")));
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_apply_cache_assets_absolute_path_ok(
+        pool: Pool<Sqlite>,
+    ) -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test_db(pool).await?;
+        let ctx = Ctx::new("demo1")?;
+        let mut fx_config = fx_stub_config("cache-assets").await?;
+        // Relative to the working directory, as the default
+        // `SERVICE_NGINX_CONF_DIR`.
+        let cwd = std::env::current_dir()?;
+        let mut relative = std::path::PathBuf::new();
+        for _ in cwd.components().skip(1) {
+            relative.push("..");
+        }
+        fx_config.NGINX_CONF_DIR =
+            relative.join(fx_config.NGINX_CONF_DIR.strip_prefix("/")?);
+        ProxyHostBmc::create(
+            &ctx,
+            &mm,
+            ProxyHostForCreate {
+                cache_assets: true,
+                .._dev_utils::fx_proxy_host_c("cached.example.com")
+            },
+        )
+        .await?;
+
+        // -- Exec
+        let status = apply(&mm, &fx_config, Http2Syntax::default()).await?;
+
+        // -- Check
+        assert_eq!(status, ConfigApplyStatus::Applied);
+        let conf_dir =
+            tokio::fs::canonicalize(&fx_config.NGINX_CONF_DIR).await?;
+        let cache = tokio::fs::read_to_string(
+            conf_dir.join("current/hosts/cache_assets.conf"),
+        )
+        .await?;
+        assert!(cache.contains(&format!(
+            "proxy_cache_path {}/cache_assets ",
+            conf_dir.display()
+        )));
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_apply_websocket_upgrade_ok(pool: Pool<Sqlite>) -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test_db(pool).await?;
+        let ctx = Ctx::new("demo1")?;
+        let fx_config = fx_stub_config("websocket").await?;
+        let open_id = ProxyHostBmc::create(
+            &ctx,
+            &mm,
+            ProxyHostForCreate {
+                allow_websocket_upgrade: true,
+                .._dev_utils::fx_proxy_host_c("open.example.com")
+            },
+        )
+        .await?;
+        let closed_id = ProxyHostBmc::create(
+            &ctx,
+            &mm,
+            _dev_utils::fx_proxy_host_c("closed.example.com"),
+        )
+        .await?;
+
+        // -- Exec
+        let status = apply(&mm, &fx_config, Http2Syntax::default()).await?;
+
+        // -- Check
+        assert_eq!(status, ConfigApplyStatus::Applied);
+        let hosts_dir = fx_config.NGINX_CONF_DIR.join("current/hosts");
+        let map =
+            tokio::fs::read_to_string(hosts_dir.join("websocket_upgrade.conf"))
+                .await?;
+        assert!(map.contains("map $http_upgrade $connection_upgrade {"));
+        let open = tokio::fs::read_to_string(
+            hosts_dir.join(format!("proxy_host_{open_id}.conf")),
+        )
+        .await?;
+        assert!(open.contains("proxy_set_header Upgrade $http_upgrade;"));
+        assert!(
+            open.contains("proxy_set_header Connection $connection_upgrade;")
+        );
+        assert!(open.contains("proxy_http_version 1.1;"));
+        let closed = tokio::fs::read_to_string(
+            hosts_dir.join(format!("proxy_host_{closed_id}.conf")),
+        )
+        .await?;
+        assert!(!closed.contains("Upgrade"));
+
+        Ok(())
+    }
+
+    /// Runs the `nginx` of the `PATH` in front of a websocket echo
+    /// backend, run with `cargo test -- --ignored`.
+    #[tokio::test]
+    #[ignore = "needs nginx on the PATH"]
+    async fn test_websocket_upgrade_nginx_e2e_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        async fn echo(mut socket: WebSocket) {
+            while let Some(Ok(msg)) = socket.recv().await {
+                if socket.send(msg).await.is_err() {
+                    break;
+                }
+            }
+        }
+        let fx_app = Router::new().route(
+            "/ws",
+            get(|ws: WebSocketUpgrade| async move { ws.on_upgrade(echo) }),
+        );
+        let fx_backend = TcpListener::bind("127.0.0.1:0").await?;
+        let fx_backend_port = fx_backend.local_addr()?.port();
+        tokio::spawn(async move { axum::serve(fx_backend, fx_app).await });
+
+        let port = TcpListener::bind("127.0.0.1:0").await?.local_addr()?.port();
+        let dir = std::env::temp_dir()
+            .join(format!("web-server-websocket-{}", std::process::id()));
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+        tokio::fs::create_dir_all(dir.join("logs")).await?;
+
+        let mut servers = String::new();
+        for (domain, allow) in
+            [("open.example.com", true), ("closed.example.com", false)]
+        {
+            let mut fx_host = fx_proxy_host(None);
+            fx_host.domain_names = vec![domain.to_string()];
+            fx_host.forward_host = "127.0.0.1".to_string();
+            fx_host.forward_port = fx_backend_port;
+            fx_host.allow_websocket_upgrade = allow;
+            let mut server = proxy_host_conf(
+                &fx_host,
+                &HashMap::new(),
+                &HashSet::new(),
+                Http2Syntax::default(),
+            )?
+            .server();
+            server.listen = vec![Listen {
+                address: Some("127.0.0.1".to_string()),
+                ..Listen::port(port)
+            }];
+            servers.push_str(&server.render());
+        }
+        tokio::fs::write(
+            dir.join("nginx.conf"),
+            format!(
+                "daemon off;\nevents {{}}\nhttp {{\naccess_log off;\n{}{servers}}}\n",
+                ConnectionUpgradeMap.render()
+            ),
+        )
+        .await?;
+
+        let mut nginx = tokio::process::Command::new("nginx")
+            .arg("-p")
+            .arg(&dir)
+            .arg("-c")
+            .arg(dir.join("nginx.conf"))
+            .kill_on_drop(true)
+            .spawn()?;
+        for _ in 0..50 {
+            if TcpStream::connect(("127.0.0.1", port)).await.is_ok() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        let request = |host: &str| {
+            tokio_tungstenite::tungstenite::http::Request::builder()
+                .uri(format!("ws://127.0.0.1:{port}/ws"))
+                .header("host", host)
+                .header("connection", "Upgrade")
+                .header("upgrade", "websocket")
+                .header("sec-websocket-version", "13")
+                .header(
+                    "sec-websocket-key",
+                    tokio_tungstenite::tungstenite::handshake::client::generate_key(),
+                )
+                .body(())
+        };
+
+        // -- Exec
+        let (mut socket, _) =
+            tokio_tungstenite::connect_async(request("open.example.com")?)
+                .await?;
+        socket.send(Message::text("hello")).await?;
+        let echo = socket.next().await.ok_or("Should echo")??;
+        let closed =
+            tokio_tungstenite::connect_async(request("closed.example.com")?)
+                .await;
+        nginx.kill().await?;
+
+        // -- Check
+        assert_eq!(echo, Message::text("hello"));
+        assert!(closed.is_err());
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -0,0 +1,200 @@
+use lib_utils::envs::{
+    DefaultIfMissing, Error, IfMissing, get_env, get_env_parse,
+};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+pub fn apply_config() -> &'static ApplyConfig {
+    static INSTANCE: OnceLock<ApplyConfig> = OnceLock::new();
+
+    INSTANCE.get_or_init(|| {
+        ApplyConfig::load_from_env().unwrap_or_else(|ex| {
+            panic!("FATAL - WHILE LOADING CONF - Cause: {ex:?}")
+        })
+    })
+}
+
+/// Configuration of the nginx apply pipeline.
+#[allow(non_snake_case)]
+pub struct ApplyConfig {
+    /// nginx binary, run as `<bin> -t -c <file>` and
+    /// `<bin> -s reload -c <file>`.
+    pub NGINX_BIN: PathBuf,
+    /// Directory holding the `generations/` and the `current` symlink.
+    pub NGINX_CONF_DIR: PathBuf,
+    /// Optional `nginx.conf` used as base for the generated one, copied
+    /// as is with the `include` of the hosts added.
+    pub NGINX_MAIN_CONF: Option<PathBuf>,
+    /// Number of generations kept on disk, the current one included.
+    pub NGINX_KEEP_GENERATIONS: usize,
+}
+
+impl ApplyConfig {
+    fn load_from_env() -> lib_utils::envs::Result<ApplyConfig> {
+        Ok(ApplyConfig {
+            NGINX_BIN: get_env("SERVICE_NGINX_BIN")
+                .if_missing("nginx".to_string())?
+                .into(),
+            NGINX_CONF_DIR: get_env("SERVICE_NGINX_CONF_DIR")?.into(),
+            NGINX_MAIN_CONF: get_env("SERVICE_NGINX_MAIN_CONF")
+                .map(|path| Some(path.into()))
+                .default_if_missing()?,
+            NGINX_KEEP_GENERATIONS: get_env_parse(
+                "SERVICE_NGINX_KEEP_GENERATIONS",
+            )
+            .if_missing(5)?,
+        })
+    }
+}
+
+pub fn acme_config() -> &'static AcmeConfig {
+    static INSTANCE: OnceLock<AcmeConfig> = OnceLock::new();
+
+    INSTANCE.get_or_init(|| {
+        AcmeConfig::load_from_env().unwrap_or_else(|ex| {
+            panic!("FATAL - WHILE LOADING CONF - Cause: {ex:?}")
+        })
+    })
+}
+
+/// Configuration of the certificate issuance and renewal.
+#[allow(non_snake_case)]
+pub struct AcmeConfig {
+    /// Directory of the ACME server, Let's Encrypt by default.
+    pub ACME_DIRECTORY_URL: String,
+    /// Contact of the ACME account, for the expiry notices.
+    pub ACME_CONTACT_EMAIL: Option<String>,
+    /// Where nginx proxies `/.well-known/acme-challenge/`, this server
+    /// on the loopback by default.
+    pub ACME_CHALLENGE_PASS: String,
+    /// Publishes the TXT records of the dns-01 certificates.
+    pub ACME_DNS_PROVIDER: AcmeDnsProvider,
+}
+
+/// `SERVICE_ACME_DNS_PROVIDER`, `manual` by default.
+pub enum AcmeDnsProvider {
+    /// Records shown on the certificates page, confirmed by the user.
+    Manual,
+    /// Dynamic updates, `SERVICE_ACME_RFC2136_*`.
+    Rfc2136 {
+        server: SocketAddr,
+        zone: String,
+        /// `(name, base64 secret)`, `hmac-sha256`.
+        tsig_key: Option<(String, String)>,
+        propagation_delay: Duration,
+    },
+}
+
+impl AcmeDnsProvider {
+    fn load_from_env() -> lib_utils::envs::Result<AcmeDnsProvider> {
+        let provider = get_env("SERVICE_ACME_DNS_PROVIDER")
+            .if_missing("manual".to_string())?;
+
+        match provider.as_str() {
+            "manual" => Ok(AcmeDnsProvider::Manual),
+            "rfc2136" => Ok(AcmeDnsProvider::Rfc2136 {
+                server: get_env_parse("SERVICE_ACME_RFC2136_SERVER")?,
+                zone: get_env("SERVICE_ACME_RFC2136_ZONE")?,
+                tsig_key: get_env("SERVICE_ACME_RFC2136_TSIG_KEY_NAME")
+                    .map(Some)
+                    .default_if_missing()?
+                    .map(|name| {
+                        get_env("SERVICE_ACME_RFC2136_TSIG_SECRET")
+                            .map(|secret| (name, secret))
+                    })
+                    .transpose()?,
+                propagation_delay: Duration::from_secs(
+                    get_env_parse("SERVICE_ACME_RFC2136_PROPAGATION_SECONDS")
+                        .if_missing(60)?,
+                ),
+            }),
+            _ => Err(Error::WrongFormat("SERVICE_ACME_DNS_PROVIDER")),
+        }
+    }
+}
+
+impl AcmeConfig {
+    fn load_from_env() -> lib_utils::envs::Result<AcmeConfig> {
+        let challenge_pass = format!(
+            "http://127.0.0.1:{}",
+            lib_web::web_config().HOST_PORT.port()
+        );
+
+        Ok(AcmeConfig {
+            ACME_DIRECTORY_URL: get_env("SERVICE_ACME_DIRECTORY_URL")
+                .if_missing(
+                    "https://acme-v02.api.letsencrypt.org/directory"
+                        .to_string(),
+                )?,
+            ACME_CONTACT_EMAIL: get_env("SERVICE_ACME_CONTACT_EMAIL")
+                .map(Some)
+                .default_if_missing()?,
+            ACME_CHALLENGE_PASS: get_env("SERVICE_ACME_CHALLENGE_PASS")
+                .if_missing(challenge_pass)?,
+            ACME_DNS_PROVIDER: AcmeDnsProvider::load_from_env()?,
+        })
+    }
+}
+
+pub fn health_config() -> &'static HealthConfig {
+    static INSTANCE: OnceLock<HealthConfig> = OnceLock::new();
+
+    INSTANCE.get_or_init(|| {
+        HealthConfig::load_from_env().unwrap_or_else(|ex| {
+            panic!("FATAL - WHILE LOADING CONF - Cause: {ex:?}")
+        })
+    })
+}
+
+/// Configuration of the health checks of the proxy hosts, the path and
+/// the interval are set per host.
+#[allow(non_snake_case)]
+pub struct HealthConfig {
+    /// Number of results kept per host.
+    pub HEALTH_CHECK_KEEP: usize,
+    /// A target not answering within this delay is offline.
+    pub HEALTH_CHECK_TIMEOUT: Duration,
+}
+
+impl HealthConfig {
+    fn load_from_env() -> lib_utils::envs::Result<HealthConfig> {
+        Ok(HealthConfig {
+            HEALTH_CHECK_KEEP: get_env_parse("SERVICE_HEALTH_CHECK_KEEP")
+                .if_missing(20)?,
+            HEALTH_CHECK_TIMEOUT: Duration::from_secs(
+                get_env_parse("SERVICE_HEALTH_CHECK_TIMEOUT_SECONDS")
+                    .if_missing(5)?,
+            ),
+        })
+    }
+}
+
+pub fn native_proxy_config() -> &'static NativeProxyConfig {
+    static INSTANCE: OnceLock<NativeProxyConfig> = OnceLock::new();
+
+    INSTANCE.get_or_init(|| {
+        NativeProxyConfig::load_from_env().unwrap_or_else(|ex| {
+            panic!("FATAL - WHILE LOADING CONF - Cause: {ex:?}")
+        })
+    })
+}
+
+/// Configuration of the native data plane run in this process, its
+/// listeners are the `SERVICE_PROXY_*` of `proxy_server::proxy_config`.
+#[allow(non_snake_case)]
+pub struct NativeProxyConfig {
+    /// Also serve the proxy hosts with the native data plane, reloaded
+    /// on each change. `false` by default.
+    pub PROXY_NATIVE: bool,
+}
+
+impl NativeProxyConfig {
+    fn load_from_env() -> lib_utils::envs::Result<NativeProxyConfig> {
+        Ok(NativeProxyConfig {
+            PROXY_NATIVE: get_env_parse("SERVICE_PROXY_NATIVE")
+                .if_missing(false)?,
+        })
+    }
+}
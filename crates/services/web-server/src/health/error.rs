@@ -0,0 +1,14 @@
+use lib_core::model::{health_check, proxy_host, upstream_group};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    // -- Modules
+    #[error(transparent)]
+    ProxyHost(#[from] proxy_host::Error),
+    #[error(transparent)]
+    UpstreamGroup(#[from] upstream_group::Error),
+    #[error(transparent)]
+    HealthCheck(#[from] health_check::Error),
+}
@@ -0,0 +1,396 @@
+//! Probes the forward targets of the enabled proxy hosts.
+//!
+//! Each host is checked every `health_check_interval` seconds, with a
+//! TCP connect or, when it has a `health_check_path`, a HTTP GET of the
+//! path answered by a 2xx or 3xx. A host forwarding to an upstream group
+//! is online while one of the servers of the group is.
+//!
+//! The results are recorded with `HealthCheckBmc`, which keeps the last
+//! `HEALTH_CHECK_KEEP` of each host for the list and the api.
+
+// region:    --- Modules
+
+mod error;
+
+pub use self::error::Result;
+
+use crate::config::{HealthConfig, health_config};
+use lib_core::ctx::Ctx;
+use lib_core::model::ModelManager;
+use lib_core::model::health_check::{HealthCheckBmc, HealthCheckForCreate};
+use lib_core::model::proxy_host::{ForwardScheme, ProxyHost, ProxyHostBmc};
+use lib_core::model::upstream_group::{UpstreamGroup, UpstreamGroupBmc};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tracing::{debug, error};
+
+// endregion: --- Modules
+
+/// Delay between two looks for the due hosts.
+const TICK: Duration = Duration::from_secs(1);
+
+/// Check the due hosts on every tick.
+pub fn spawn_health_checker(mm: ModelManager) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let config = health_config();
+        let http = http_client(config);
+        let mut next_checks: HashMap<i64, Instant> = HashMap::new();
+
+        loop {
+            if let Err(ex) =
+                check_due(&mm, config, &http, &mut next_checks).await
+            {
+                error!("{:<12} - health check failed: {ex:?}", "HEALTH");
+            }
+            tokio::time::sleep(TICK).await;
+        }
+    })
+}
+
+/// Probe, in the background, the enabled hosts whose next check is due.
+async fn check_due(
+    mm: &ModelManager,
+    config: &'static HealthConfig,
+    http: &reqwest::Client,
+    next_checks: &mut HashMap<i64, Instant>,
+) -> Result<()> {
+    let ctx = Ctx::root_ctx();
+    let now = Instant::now();
+
+    let hosts: Vec<ProxyHost> = ProxyHostBmc::list(&ctx, mm)
+        .await?
+        .into_iter()
+        .filter(|host| host.enabled)
+        .collect();
+    next_checks.retain(|id, _| hosts.iter().any(|host| host.id == *id));
+
+    let due: Vec<ProxyHost> = hosts
+        .into_iter()
+        .filter(|host| next_checks.get(&host.id).is_none_or(|at| *at <= now))
+        .collect();
+    if due.is_empty() {
+        return Ok(());
+    }
+    debug!("{:<12} - check {} host(s)", "HEALTH", due.len());
+
+    let upstream_groups: HashMap<i64, UpstreamGroup> =
+        UpstreamGroupBmc::list(&ctx, mm)
+            .await?
+            .into_iter()
+            .map(|upstream_group| (upstream_group.id, upstream_group))
+            .collect();
+
+    for host in due {
+        let interval = Duration::from_secs(host.health_check_interval.into());
+        next_checks.insert(host.id, now + interval);
+
+        let probe = Probe {
+            kind: match &host.health_check_path {
+                Some(path) => ProbeKind::Http {
+                    scheme: host.forward_scheme,
+                    path: path.clone(),
+                },
+                None => ProbeKind::Tcp,
+            },
+            targets: targets(&host, &upstream_groups),
+            // A probe never outlives the next one of the host.
+            timeout: config.HEALTH_CHECK_TIMEOUT.min(interval),
+        };
+        let mm = mm.clone();
+        let http = http.clone();
+        tokio::spawn(async move {
+            let health_check_c = probe.run(host.id, &http).await;
+            let res = HealthCheckBmc::create(
+                &Ctx::root_ctx(),
+                &mm,
+                health_check_c,
+                config.HEALTH_CHECK_KEEP,
+            )
+            .await;
+            if let Err(ex) = res {
+                error!(
+                    "{:<12} - host {} not recorded: {ex:?}",
+                    "HEALTH", host.id
+                );
+            }
+        });
+    }
+
+    Ok(())
+}
+
+// region:    --- Probe
+
+struct Probe {
+    kind: ProbeKind,
+    /// `(host, port)`, the probe passes when one of them answers.
+    targets: Vec<(String, u16)>,
+    timeout: Duration,
+}
+
+enum ProbeKind {
+    Tcp,
+    Http { scheme: ForwardScheme, path: String },
+}
+
+impl Probe {
+    async fn run(
+        &self,
+        proxy_host_id: i64,
+        http: &reqwest::Client,
+    ) -> HealthCheckForCreate {
+        let mut error = None;
+        for (host, port) in &self.targets {
+            let start = Instant::now();
+            let res = match &self.kind {
+                ProbeKind::Tcp => self.connect(host, *port).await,
+                ProbeKind::Http { scheme, path } => {
+                    self.get(http, *scheme, host, *port, path).await
+                }
+            };
+            match res {
+                Ok(()) => {
+                    let latency = start.elapsed().as_millis();
+                    return HealthCheckForCreate {
+                        proxy_host_id,
+                        healthy: true,
+                        latency_ms: Some(
+                            u32::try_from(latency).unwrap_or(u32::MAX),
+                        ),
+                        error: None,
+                    };
+                }
+                Err(ex) => error = Some(format!("{host}:{port}: {ex}")),
+            }
+        }
+
+        HealthCheckForCreate {
+            proxy_host_id,
+            healthy: false,
+            latency_ms: None,
+            error: error.or_else(|| Some("No target".to_string())),
+        }
+    }
+
+    async fn connect(
+        &self,
+        host: &str,
+        port: u16,
+    ) -> std::result::Result<(), String> {
+        match tokio::time::timeout(
+            self.timeout,
+            TcpStream::connect((host, port)),
+        )
+        .await
+        {
+            Ok(Ok(_)) => Ok(()),
+            Ok(Err(ex)) => Err(ex.to_string()),
+            Err(_) => Err("timeout".to_string()),
+        }
+    }
+
+    async fn get(
+        &self,
+        http: &reqwest::Client,
+        scheme: ForwardScheme,
+        host: &str,
+        port: u16,
+        path: &str,
+    ) -> std::result::Result<(), String> {
+        let host = if host.contains(':') {
+            format!("[{host}]")
+        } else {
+            host.to_string()
+        };
+        let res = http
+            .get(format!("{scheme}://{host}:{port}{path}"))
+            .timeout(self.timeout)
+            .send()
+            .await
+            .map_err(|ex| match ex.is_timeout() {
+                true => "timeout".to_string(),
+                false => ex.to_string(),
+            })?;
+
+        let status = res.status();
+        if status.is_success() || status.is_redirection() {
+            Ok(())
+        } else {
+            Err(format!("HTTP {status}"))
+        }
+    }
+}
+
+/// The forward target of `host`, or the servers of its upstream group.
+fn targets(
+    host: &ProxyHost,
+    upstream_groups: &HashMap<i64, UpstreamGroup>,
+) -> Vec<(String, u16)> {
+    match host.upstream_group_id {
+        Some(id) => upstream_groups
+            .get(&id)
+            .map(|upstream_group| {
+                upstream_group
+                    .servers
+                    .iter()
+                    .map(|server| (server.host.clone(), server.port))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        None => vec![(host.forward_host.clone(), host.forward_port)],
+    }
+}
+
+/// Redirects are a valid answer, and the backends behind a proxy often
+/// have self-signed certificates.
+fn http_client(config: &HealthConfig) -> reqwest::Client {
+    reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .danger_accept_invalid_certs(true)
+        .timeout(config.HEALTH_CHECK_TIMEOUT)
+        .build()
+        .unwrap_or_default()
+}
+
+// endregion: --- Probe
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// A server answering every request with `status_line`.
+    async fn fx_http_server(status_line: &'static str) -> Result<u16> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let port = listener.local_addr()?.port();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buf = [0; 1024];
+                let _ = stream.read(&mut buf).await;
+                let res = format!(
+                    "HTTP/1.1 {status_line}\r\ncontent-length: 0\r\n\
+                    connection: close\r\n\r\n"
+                );
+                let _ = stream.write_all(res.as_bytes()).await;
+            }
+        });
+
+        Ok(port)
+    }
+
+    /// A port nothing listens on.
+    async fn fx_closed_port() -> Result<u16> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        Ok(listener.local_addr()?.port())
+    }
+
+    fn fx_probe(kind: ProbeKind, ports: &[u16]) -> Probe {
+        Probe {
+            kind,
+            targets: ports
+                .iter()
+                .map(|port| ("127.0.0.1".to_string(), *port))
+                .collect(),
+            timeout: Duration::from_secs(2),
+        }
+    }
+
+    fn fx_http_kind() -> ProbeKind {
+        ProbeKind::Http {
+            scheme: ForwardScheme::Http,
+            path: "/healthz".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_probe_tcp_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_open = fx_http_server("200 OK").await?;
+        let fx_closed = fx_closed_port().await?;
+        let http = reqwest::Client::new();
+
+        // -- Exec
+        let open = fx_probe(ProbeKind::Tcp, &[fx_open]).run(1, &http).await;
+        let closed = fx_probe(ProbeKind::Tcp, &[fx_closed]).run(1, &http).await;
+
+        // -- Check
+        assert!(open.healthy);
+        assert!(open.latency_ms.is_some());
+        assert!(!closed.healthy);
+        assert!(
+            closed
+                .error
+                .is_some_and(|error| error.contains("127.0.0.1"))
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_probe_http_status_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_ok = fx_http_server("200 OK").await?;
+        let fx_redirect = fx_http_server("302 Found").await?;
+        let fx_unavailable = fx_http_server("503 Service Unavailable").await?;
+        let http = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()?;
+
+        // -- Exec & Check
+        assert!(
+            fx_probe(fx_http_kind(), &[fx_ok])
+                .run(1, &http)
+                .await
+                .healthy
+        );
+        assert!(
+            fx_probe(fx_http_kind(), &[fx_redirect])
+                .run(1, &http)
+                .await
+                .healthy
+        );
+        let unavailable = fx_probe(fx_http_kind(), &[fx_unavailable])
+            .run(1, &http)
+            .await;
+        assert!(!unavailable.healthy);
+        assert!(unavailable.error.is_some_and(|error| error.contains("503")));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_probe_any_target_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_closed = fx_closed_port().await?;
+        let fx_ok = fx_http_server("204 No Content").await?;
+        let http = reqwest::Client::new();
+
+        // -- Exec
+        let res = fx_probe(fx_http_kind(), &[fx_closed, fx_ok])
+            .run(1, &http)
+            .await;
+
+        // -- Check
+        assert!(res.healthy);
+        assert!(
+            fx_probe(ProbeKind::Tcp, &[])
+                .run(1, &http)
+                .await
+                .error
+                .is_some()
+        );
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -1,9 +1,15 @@
+mod apply;
+mod config;
 mod error;
+mod health;
+mod renew;
 mod routes_api;
 mod routes_web;
 
-use crate::routes_web::routes_static;
+use crate::routes_web::{routes_acme, routes_static};
 
+use lib_core::acme::Http01Challenges;
+use lib_core::acme::dns::ManualDnsProvider;
 use lib_core::model::ModelManager;
 use lib_web::{
     handlers::web,
@@ -23,9 +29,25 @@ pub use self::error::{Error, Result};
 pub async fn routes() -> Result<Router> {
     let model_manager = ModelManager::new().await?;
 
+    apply::spawn_applier(model_manager.clone());
+    health::spawn_health_checker(model_manager.clone());
+    if config::native_proxy_config().PROXY_NATIVE {
+        proxy_server::spawn_proxy(model_manager.clone());
+    }
+
+    // Shared by the renewal task and the challenge routes.
+    let acme_challenges = Http01Challenges::default();
+    let manual_dns = ManualDnsProvider::default();
+    renew::spawn_renewer(
+        model_manager.clone(),
+        acme_challenges.clone(),
+        manual_dns.clone(),
+    );
+
     let router = Router::new()
         .nest("/api", routes_api::routes(model_manager.clone()))
-        .merge(routes_web::routes(model_manager.clone()))
+        .merge(routes_web::routes(model_manager.clone(), manual_dns))
+        .merge(routes_acme::routes(acme_challenges))
         .layer(
             ServiceBuilder::new()
                 .layer(middleware::from_fn(mw_req_stamp_resolver))
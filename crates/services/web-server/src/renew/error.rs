@@ -0,0 +1,13 @@
+use lib_core::acme;
+use lib_core::model::certificate;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    // -- Modules
+    #[error(transparent)]
+    Certificate(#[from] certificate::Error),
+    #[error(transparent)]
+    Acme(#[from] acme::Error),
+}
@@ -0,0 +1,451 @@
+//! Issues the ACME certificates of the store and renews them before they
+//! expire.
+//!
+//! The store is checked periodically and whenever a certificate changes
+//! (e.g. a new one is requested from the UI). Every attempt is recorded
+//! on the certificate, a failed one is retried after `RETRY_DELAY`.
+//! A stored certificate triggers a new nginx apply (see `apply`).
+//!
+//! dns-01 certificates use the provider of `ACME_DNS_PROVIDER`. With the
+//! manual one, an issuance waits for the user to confirm the records
+//! shown on the certificates page.
+//!
+//! Each certificate is issued in its own task, so such a wait does not
+//! hold back the others. A check skips the certificates still in flight.
+
+// region:    --- Modules
+
+mod error;
+
+pub use self::error::Result;
+
+use crate::config::{AcmeConfig, AcmeDnsProvider, acme_config};
+use chrono::{DateTime, TimeDelta, Utc};
+use lib_core::acme::dns::{
+    DnsProvider, ManualDnsProvider, Rfc2136Provider, TsigKey,
+};
+use lib_core::acme::{AcmeClient, ChallengeSolver, Http01Challenges};
+use lib_core::ctx::Ctx;
+use lib_core::model::certificate::{
+    Certificate, CertificateBmc, CertificateChallenge, CertificateForIssue,
+    CertificateProvider,
+};
+use lib_core::model::{ModelEvent, ModelManager};
+use lib_utils::time::TimeRfc3339;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast::Receiver;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use tracing::{debug, error};
+
+// endregion: --- Modules
+
+/// Certificates are renewed this long before they expire.
+const RENEW_BEFORE: TimeDelta = TimeDelta::days(30);
+/// Delay before a failed issuance is attempted again.
+const RETRY_DELAY: TimeDelta = TimeDelta::hours(6);
+/// Delay between two checks when nothing changes.
+const CHECK_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Ids of the certificates being issued.
+#[derive(Debug, Clone, Default)]
+pub struct InFlight(Arc<Mutex<HashSet<i64>>>);
+
+impl InFlight {
+    /// `false` when `id` already is in flight.
+    fn insert(&self, id: i64) -> bool {
+        self.0.lock().map(|mut ids| ids.insert(id)).unwrap_or(false)
+    }
+
+    fn contains(&self, id: i64) -> bool {
+        self.0.lock().map(|ids| ids.contains(&id)).unwrap_or(false)
+    }
+
+    fn remove(&self, id: i64) {
+        if let Ok(mut ids) = self.0.lock() {
+            ids.remove(&id);
+        }
+    }
+}
+
+/// Renew at start, then on every check.
+pub fn spawn_renewer(
+    mm: ModelManager,
+    challenges: Http01Challenges,
+    manual_dns: ManualDnsProvider,
+) -> JoinHandle<()> {
+    let mut events = mm.subscribe();
+    let in_flight = InFlight::default();
+
+    tokio::spawn(async move {
+        loop {
+            let res =
+                renew(&mm, &challenges, &manual_dns, &in_flight, acme_config())
+                    .await;
+            if let Err(ex) = res {
+                error!("{:<12} - renew failed: {ex:?}", "RENEW");
+            }
+
+            tokio::select! {
+                _ = tokio::time::sleep(CHECK_INTERVAL) => {}
+                closed = certificate_changed(&mut events) => {
+                    if closed {
+                        break;
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// Start the issuance of the due certificates, each one stores its
+/// certificate or its error when done.
+pub async fn renew(
+    mm: &ModelManager,
+    challenges: &Http01Challenges,
+    manual_dns: &ManualDnsProvider,
+    in_flight: &InFlight,
+    config: &AcmeConfig,
+) -> Result<()> {
+    let ctx = Ctx::root_ctx();
+    let now = Utc::now();
+
+    let due: Vec<Certificate> = CertificateBmc::list(&ctx, mm)
+        .await?
+        .into_iter()
+        .filter(|certificate| {
+            is_due(certificate, now) && !in_flight.contains(certificate.id)
+        })
+        .collect();
+    if due.is_empty() {
+        return Ok(());
+    }
+    debug!("{:<12} - renew {} certificate(s)", "RENEW", due.len());
+
+    let contact: Vec<String> =
+        config.ACME_CONTACT_EMAIL.iter().cloned().collect();
+    let (client, dns_provider) = match async {
+        let client = AcmeClient::load_or_register(
+            &ctx,
+            mm,
+            &config.ACME_DIRECTORY_URL,
+            &contact,
+        )
+        .await?;
+        let dns_provider = dns_provider(&config.ACME_DNS_PROVIDER, manual_dns)?;
+        Ok::<_, lib_core::acme::Error>((client, dns_provider))
+    }
+    .await
+    {
+        Ok(loaded) => loaded,
+        Err(ex) => {
+            let renew_error = format!("{ex:?}");
+            for certificate in &due {
+                CertificateBmc::set_renew_error(
+                    &ctx,
+                    mm,
+                    certificate.id,
+                    renew_error.clone(),
+                )
+                .await?;
+            }
+            return Err(ex.into());
+        }
+    };
+
+    let client = Arc::new(client);
+    let dns_provider: Arc<dyn DnsProvider> = Arc::from(dns_provider);
+    for certificate in due {
+        if !in_flight.insert(certificate.id) {
+            continue;
+        }
+        let (mm, challenges, in_flight) =
+            (mm.clone(), challenges.clone(), in_flight.clone());
+        let (client, dns_provider) = (client.clone(), dns_provider.clone());
+        tokio::spawn(async move {
+            let id = certificate.id;
+            let res = issue(
+                &mm,
+                &client,
+                &challenges,
+                dns_provider.as_ref(),
+                certificate,
+            )
+            .await;
+            in_flight.remove(id);
+            if let Err(ex) = res {
+                error!("{:<12} - certificate {id} not stored: {ex:?}", "RENEW");
+            }
+        });
+    }
+
+    Ok(())
+}
+
+/// Issue `certificate` and store it, or its error.
+async fn issue(
+    mm: &ModelManager,
+    client: &AcmeClient,
+    challenges: &Http01Challenges,
+    dns_provider: &dyn DnsProvider,
+    certificate: Certificate,
+) -> Result<()> {
+    let ctx = Ctx::root_ctx();
+    let solver = match certificate.challenge {
+        CertificateChallenge::Http01 => ChallengeSolver::Http01(challenges),
+        CertificateChallenge::Dns01 => ChallengeSolver::Dns01(dns_provider),
+    };
+    match client.issue(&certificate.domain_names, solver).await {
+        Ok(issued) => {
+            CertificateBmc::set_issued(
+                &ctx,
+                mm,
+                certificate.id,
+                CertificateForIssue {
+                    certificate_pem: issued.certificate_pem,
+                    private_key_pem: issued.private_key_pem,
+                },
+            )
+            .await?
+        }
+        Err(ex) => {
+            error!(
+                "{:<12} - certificate {} failed: {ex:?}",
+                "RENEW", certificate.id
+            );
+            CertificateBmc::set_renew_error(
+                &ctx,
+                mm,
+                certificate.id,
+                format!("{ex:?}"),
+            )
+            .await?
+        }
+    }
+
+    Ok(())
+}
+
+// region:    --- Support
+
+fn dns_provider(
+    config: &AcmeDnsProvider,
+    manual_dns: &ManualDnsProvider,
+) -> lib_core::acme::Result<Box<dyn DnsProvider>> {
+    match config {
+        AcmeDnsProvider::Manual => Ok(Box::new(manual_dns.clone())),
+        AcmeDnsProvider::Rfc2136 {
+            server,
+            zone,
+            tsig_key,
+            propagation_delay,
+        } => {
+            let tsig_key = tsig_key
+                .as_ref()
+                .map(|(name, secret)| TsigKey::new(name, secret))
+                .transpose()?;
+            Ok(Box::new(Rfc2136Provider::new(
+                *server,
+                zone,
+                tsig_key,
+                *propagation_delay,
+            )))
+        }
+    }
+}
+
+/// An ACME certificate not issued yet, or expiring within `RENEW_BEFORE`,
+/// unless its last attempt failed less than `RETRY_DELAY` ago.
+fn is_due(certificate: &Certificate, now: DateTime<Utc>) -> bool {
+    if certificate.provider != CertificateProvider::Acme {
+        return false;
+    }
+
+    let parse = |time: &Option<String>| {
+        time.as_deref()
+            .and_then(|time| TimeRfc3339::parse_utc(time).ok())
+            .map(|time| time.inner())
+    };
+
+    if certificate.renew_error.is_some()
+        && let Some(renew_time) = parse(&certificate.renew_time)
+        && now < renew_time + RETRY_DELAY
+    {
+        return false;
+    }
+
+    match parse(&certificate.not_after) {
+        Some(not_after) => now >= not_after - RENEW_BEFORE,
+        None => true,
+    }
+}
+
+/// Wait for a `CertificateChanged`, returns `true` once the model is
+/// gone.
+async fn certificate_changed(events: &mut Receiver<ModelEvent>) -> bool {
+    loop {
+        match events.recv().await {
+            Ok(ModelEvent::CertificateChanged { .. }) => return false,
+            Ok(_) => {}
+            // Missed events, check anyway.
+            Err(RecvError::Lagged(_)) => return false,
+            Err(RecvError::Closed) => return true,
+        }
+    }
+}
+
+// endregion: --- Support
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+    use lib_core::_dev_utils;
+    use lib_core::model::certificate::CertificateForCreate;
+    use sqlx::{Pool, Sqlite};
+
+    fn fx_certificate(
+        not_after: Option<DateTime<Utc>>,
+        renew_time: Option<DateTime<Utc>>,
+        renew_error: Option<&str>,
+    ) -> Certificate {
+        let format =
+            |time: DateTime<Utc>| TimeRfc3339::from(time).format_time();
+        Certificate {
+            id: 1,
+            owner_id: "demo1".to_string(),
+            nice_name: String::new(),
+            provider: CertificateProvider::Acme,
+            challenge: CertificateChallenge::Http01,
+            domain_names: vec!["app.example.com".to_string()],
+            certificate_pem: String::new(),
+            private_key_pem: String::new(),
+            issuer: String::new(),
+            not_before: None,
+            not_after: not_after.map(format),
+            renew_time: renew_time.map(format),
+            renew_error: renew_error.map(str::to_string),
+            ctime: String::new(),
+            mtime: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_is_due_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let now = Utc::now();
+        let fx_pending = fx_certificate(None, None, None);
+        let fx_valid =
+            fx_certificate(Some(now + TimeDelta::days(60)), Some(now), None);
+        let fx_expiring =
+            fx_certificate(Some(now + TimeDelta::days(29)), None, None);
+        let mut fx_custom = fx_expiring.clone();
+        fx_custom.provider = CertificateProvider::Custom;
+
+        // -- Exec & Check
+        assert!(is_due(&fx_pending, now));
+        assert!(!is_due(&fx_valid, now));
+        assert!(is_due(&fx_expiring, now));
+        assert!(!is_due(&fx_custom, now));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_due_retry_delay_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let now = Utc::now();
+        let fx_failed_recently =
+            fx_certificate(None, Some(now - TimeDelta::hours(1)), Some("x"));
+        let fx_failed_long_ago =
+            fx_certificate(None, Some(now - TimeDelta::hours(7)), Some("x"));
+
+        // -- Exec & Check
+        assert!(!is_due(&fx_failed_recently, now));
+        assert!(is_due(&fx_failed_long_ago, now));
+
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_renew_manual_dns_not_blocking_ok(
+        pool: Pool<Sqlite>,
+    ) -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test_db(pool).await?;
+        let ctx = Ctx::new("demo1")?;
+        let challenges = Http01Challenges::default();
+        let http01_port = _dev_utils::serve_http01(challenges.clone()).await?;
+        let (_, dns_records) = _dev_utils::start_dns_stand_in(
+            "example.com",
+            "acme-update",
+            // "test-secret-for-the-dns-stand-in"
+            "dGVzdC1zZWNyZXQtZm9yLXRoZS1kbnMtc3RhbmQtaW4=",
+        )
+        .await?;
+        let fx_config = AcmeConfig {
+            ACME_DIRECTORY_URL: _dev_utils::start_acme_stand_in(
+                http01_port,
+                dns_records,
+            )
+            .await?,
+            ACME_CONTACT_EMAIL: None,
+            ACME_CHALLENGE_PASS: String::new(),
+            ACME_DNS_PROVIDER: AcmeDnsProvider::Manual,
+        };
+        let manual_dns = ManualDnsProvider::default();
+        let in_flight = InFlight::default();
+        // Waits for records nobody confirms.
+        let dns_id = CertificateBmc::create(
+            &ctx,
+            &mm,
+            CertificateForCreate {
+                nice_name: String::new(),
+                domain_names: vec!["*.example.com".to_string()],
+                challenge: CertificateChallenge::Dns01,
+            },
+        )
+        .await?;
+        let http_id = CertificateBmc::create(
+            &ctx,
+            &mm,
+            CertificateForCreate {
+                nice_name: String::new(),
+                domain_names: vec!["app.example.com".to_string()],
+                challenge: CertificateChallenge::Http01,
+            },
+        )
+        .await?;
+
+        // -- Exec
+        renew(&mm, &challenges, &manual_dns, &in_flight, &fx_config).await?;
+        let mut issued = false;
+        for _ in 0..50 {
+            issued = CertificateBmc::get(&ctx, &mm, http_id).await?.is_issued();
+            if issued {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        // The order still waiting is not started again.
+        renew(&mm, &challenges, &manual_dns, &in_flight, &fx_config).await?;
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        // -- Check
+        assert!(issued);
+        assert!(!CertificateBmc::get(&ctx, &mm, dns_id).await?.is_issued());
+        assert!(in_flight.contains(dns_id));
+        assert!(!in_flight.contains(http_id));
+        assert_eq!(manual_dns.records().len(), 1);
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -3,12 +3,26 @@ use lib_core::model::ModelManager;
 use lib_web::handlers::api::fallback;
 
 // region:    --- Modules
+mod routes_access_list;
+mod routes_certificate;
+mod routes_dead_host;
 mod routes_login;
+mod routes_proxy_host;
+mod routes_redirection_host;
+mod routes_stream_host;
+mod routes_upstream_group;
 
 // endregion: --- Modules
 
 pub fn routes(mm: ModelManager) -> Router {
     Router::new()
-        .merge(routes_login::routes(mm))
+        .merge(routes_login::routes(mm.clone()))
+        .merge(routes_access_list::routes(mm.clone()))
+        .merge(routes_certificate::routes(mm.clone()))
+        .merge(routes_dead_host::routes(mm.clone()))
+        .merge(routes_proxy_host::routes(mm.clone()))
+        .merge(routes_redirection_host::routes(mm.clone()))
+        .merge(routes_stream_host::routes(mm.clone()))
+        .merge(routes_upstream_group::routes(mm))
         .fallback(fallback)
 }
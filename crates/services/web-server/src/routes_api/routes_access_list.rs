@@ -0,0 +1,22 @@
+use axum::routing::get;
+use axum::{Router, middleware};
+use lib_core::model::ModelManager;
+use lib_web::handlers::api::handlers_access_list;
+use lib_web::middleware::mw_auth::mw_ctx_require;
+
+pub fn routes(mm: ModelManager) -> Router {
+    Router::new()
+        .route(
+            "/access-lists",
+            get(handlers_access_list::api_list_access_lists_handler)
+                .post(handlers_access_list::api_create_access_list_handler),
+        )
+        .route(
+            "/access-lists/{id}",
+            get(handlers_access_list::api_get_access_list_handler)
+                .patch(handlers_access_list::api_update_access_list_handler)
+                .delete(handlers_access_list::api_delete_access_list_handler),
+        )
+        .with_state(mm)
+        .route_layer(middleware::from_fn(mw_ctx_require))
+}
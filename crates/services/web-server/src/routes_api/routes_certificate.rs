@@ -0,0 +1,25 @@
+use axum::routing::{get, post};
+use axum::{Router, middleware};
+use lib_core::model::ModelManager;
+use lib_web::handlers::api::handlers_certificate;
+use lib_web::middleware::mw_auth::mw_ctx_require;
+
+pub fn routes(mm: ModelManager) -> Router {
+    Router::new()
+        .route(
+            "/certificates",
+            get(handlers_certificate::api_list_certificates_handler)
+                .post(handlers_certificate::api_create_certificate_handler),
+        )
+        .route(
+            "/certificates/custom",
+            post(handlers_certificate::api_create_custom_certificate_handler),
+        )
+        .route(
+            "/certificates/{id}",
+            get(handlers_certificate::api_get_certificate_handler)
+                .delete(handlers_certificate::api_delete_certificate_handler),
+        )
+        .with_state(mm)
+        .route_layer(middleware::from_fn(mw_ctx_require))
+}
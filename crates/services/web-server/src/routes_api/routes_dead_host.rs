@@ -0,0 +1,29 @@
+use axum::routing::{get, put};
+use axum::{Router, middleware};
+use lib_core::model::ModelManager;
+use lib_web::handlers::api::handlers_dead_host;
+use lib_web::middleware::mw_auth::mw_ctx_require;
+
+pub fn routes(mm: ModelManager) -> Router {
+    Router::new()
+        .route(
+            "/dead-hosts",
+            get(handlers_dead_host::api_list_dead_hosts_handler)
+                .post(handlers_dead_host::api_create_dead_host_handler),
+        )
+        .route(
+            "/dead-hosts/{id}",
+            get(handlers_dead_host::api_get_dead_host_handler)
+                .patch(handlers_dead_host::api_update_dead_host_handler)
+                .delete(handlers_dead_host::api_delete_dead_host_handler),
+        )
+        .route(
+            "/dead-hosts/{id}/error-page",
+            put(handlers_dead_host::api_set_dead_host_error_page_handler)
+                .delete(
+                    handlers_dead_host::api_remove_dead_host_error_page_handler,
+                ),
+        )
+        .with_state(mm)
+        .route_layer(middleware::from_fn(mw_ctx_require))
+}
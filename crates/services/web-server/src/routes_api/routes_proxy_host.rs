@@ -0,0 +1,152 @@
+use axum::routing::get;
+use axum::{Router, middleware};
+use lib_core::model::ModelManager;
+use lib_web::handlers::api::handlers_proxy_host;
+use lib_web::middleware::mw_auth::mw_ctx_require;
+
+pub fn routes(mm: ModelManager) -> Router {
+    Router::new()
+        .route(
+            "/proxy-hosts",
+            get(handlers_proxy_host::api_list_proxy_hosts_handler)
+                .post(handlers_proxy_host::api_create_proxy_host_handler),
+        )
+        .route(
+            "/proxy-hosts/{id}",
+            get(handlers_proxy_host::api_get_proxy_host_handler)
+                .patch(handlers_proxy_host::api_update_proxy_host_handler)
+                .delete(handlers_proxy_host::api_delete_proxy_host_handler),
+        )
+        .route(
+            "/proxy-hosts/{id}/health-checks",
+            get(handlers_proxy_host::api_list_proxy_host_health_checks_handler),
+        )
+        .with_state(mm)
+        .route_layer(middleware::from_fn(mw_ctx_require))
+}
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = std::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+
+    use super::*;
+    use axum::body::{Body, to_bytes};
+    use axum::http::{Request, StatusCode, header};
+    use lib_auth::token::generate_web_token;
+    use lib_core::_dev_utils;
+    use lib_core::ctx::Ctx;
+    use lib_core::model::proxy_host::{ProxyHostBmc, ProxyHostForCreate};
+    use lib_core::model::upstream_group::{
+        UpstreamBalance, UpstreamGroupBmc, UpstreamGroupForCreate,
+        UpstreamGroupServer,
+    };
+    use lib_core::model::user::UserBmc;
+    use lib_web::middleware::mw_auth::mw_ctx_resolver;
+    use lib_web::middleware::mw_req_stamp::mw_req_stamp_resolver;
+    use lib_web::middleware::mw_res_map::mw_reponse_map;
+    use serde_json::{Value, json};
+    use sqlx::{Pool, Sqlite};
+    use tower::{ServiceBuilder, ServiceExt};
+    use tower_cookies::CookieManagerLayer;
+
+    /// The routes behind the middlewares of the server.
+    fn fx_app(mm: ModelManager) -> Router {
+        routes(mm.clone()).layer(
+            ServiceBuilder::new()
+                .layer(middleware::from_fn(mw_req_stamp_resolver))
+                .layer(CookieManagerLayer::new())
+                .layer(middleware::from_fn_with_state(mm, mw_ctx_resolver))
+                .layer(middleware::map_response(mw_reponse_map)),
+        )
+    }
+
+    /// `PATCH /proxy-hosts/{id}` as `demo1`.
+    async fn patch_proxy_host(
+        mm: &ModelManager,
+        id: i64,
+        body: Value,
+    ) -> Result<(StatusCode, Value)> {
+        let user = UserBmc::first_by_user_id(&Ctx::root_ctx(), mm, "demo1")
+            .await?
+            .ok_or("Should have demo1")?;
+        let token = generate_web_token(&user.user_id, user.token_salt)?;
+
+        let req = Request::patch(format!("/proxy-hosts/{id}"))
+            .header(header::COOKIE, format!("auth-token={token}"))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))?;
+        let res = fx_app(mm.clone()).oneshot(req).await?;
+
+        let status = res.status();
+        let body = to_bytes(res.into_body(), usize::MAX).await?;
+
+        Ok((status, serde_json::from_slice(&body)?))
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_api_proxy_host_detach_group_ok(
+        pool: Pool<Sqlite>,
+    ) -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test_db(pool).await?;
+        let ctx = Ctx::new("demo1")?;
+        let group_id = UpstreamGroupBmc::create(
+            &ctx,
+            &mm,
+            UpstreamGroupForCreate {
+                name: "backends".to_string(),
+                balance: UpstreamBalance::RoundRobin,
+                servers: vec![UpstreamGroupServer {
+                    host: "10.0.0.5".to_string(),
+                    port: 8080,
+                    weight: None,
+                    max_fails: None,
+                    fail_timeout: None,
+                    backup: false,
+                }],
+            },
+        )
+        .await?;
+        // The forward host is not checked while a group replaces it.
+        let id = ProxyHostBmc::create(
+            &ctx,
+            &mm,
+            ProxyHostForCreate {
+                forward_host: String::new(),
+                upstream_group_id: Some(group_id),
+                .._dev_utils::fx_proxy_host_c("detach.example.com")
+            },
+        )
+        .await?;
+
+        // -- Exec
+        let (detach_status, detach_body) =
+            patch_proxy_host(&mm, id, json!({ "upstreamGroupId": null }))
+                .await?;
+        let (status, _) = patch_proxy_host(
+            &mm,
+            id,
+            json!({ "upstreamGroupId": null, "forwardHost": "10.0.0.6" }),
+        )
+        .await?;
+
+        // -- Check
+        assert_eq!(detach_status, StatusCode::BAD_REQUEST);
+        assert_eq!(detach_body["error"]["message"], "INVALID_FIELD");
+        assert_eq!(
+            detach_body["error"]["data"]["detail"]["field"],
+            "forwardHost"
+        );
+        assert_eq!(status, StatusCode::OK);
+        let proxy_host = ProxyHostBmc::get(&ctx, &mm, id).await?;
+        assert_eq!(proxy_host.upstream_group_id, None);
+        assert_eq!(proxy_host.forward_host, "10.0.0.6");
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -0,0 +1,22 @@
+use axum::routing::get;
+use axum::{Router, middleware};
+use lib_core::model::ModelManager;
+use lib_web::handlers::api::handlers_redirection_host;
+use lib_web::middleware::mw_auth::mw_ctx_require;
+
+pub fn routes(mm: ModelManager) -> Router {
+    Router::new()
+        .route(
+            "/redirection-hosts",
+            get(handlers_redirection_host::api_list_redirection_hosts_handler)
+                .post(handlers_redirection_host::api_create_redirection_host_handler),
+        )
+        .route(
+            "/redirection-hosts/{id}",
+            get(handlers_redirection_host::api_get_redirection_host_handler)
+                .patch(handlers_redirection_host::api_update_redirection_host_handler)
+                .delete(handlers_redirection_host::api_delete_redirection_host_handler),
+        )
+        .with_state(mm)
+        .route_layer(middleware::from_fn(mw_ctx_require))
+}
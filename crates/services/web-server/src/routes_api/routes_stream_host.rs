@@ -0,0 +1,22 @@
+use axum::routing::get;
+use axum::{Router, middleware};
+use lib_core::model::ModelManager;
+use lib_web::handlers::api::handlers_stream_host;
+use lib_web::middleware::mw_auth::mw_ctx_require;
+
+pub fn routes(mm: ModelManager) -> Router {
+    Router::new()
+        .route(
+            "/stream-hosts",
+            get(handlers_stream_host::api_list_stream_hosts_handler)
+                .post(handlers_stream_host::api_create_stream_host_handler),
+        )
+        .route(
+            "/stream-hosts/{id}",
+            get(handlers_stream_host::api_get_stream_host_handler)
+                .patch(handlers_stream_host::api_update_stream_host_handler)
+                .delete(handlers_stream_host::api_delete_stream_host_handler),
+        )
+        .with_state(mm)
+        .route_layer(middleware::from_fn(mw_ctx_require))
+}
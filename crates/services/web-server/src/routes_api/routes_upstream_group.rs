@@ -0,0 +1,28 @@
+use axum::routing::get;
+use axum::{Router, middleware};
+use lib_core::model::ModelManager;
+use lib_web::handlers::api::handlers_upstream_group;
+use lib_web::middleware::mw_auth::mw_ctx_require;
+
+pub fn routes(mm: ModelManager) -> Router {
+    Router::new()
+        .route(
+            "/upstream-groups",
+            get(handlers_upstream_group::api_list_upstream_groups_handler)
+                .post(
+                    handlers_upstream_group::api_create_upstream_group_handler,
+                ),
+        )
+        .route(
+            "/upstream-groups/{id}",
+            get(handlers_upstream_group::api_get_upstream_group_handler)
+                .patch(
+                    handlers_upstream_group::api_update_upstream_group_handler,
+                )
+                .delete(
+                    handlers_upstream_group::api_delete_upstream_group_handler,
+                ),
+        )
+        .with_state(mm)
+        .route_layer(middleware::from_fn(mw_ctx_require))
+}
@@ -1,20 +1,34 @@
 use axum::{Router, routing::get};
+use lib_core::acme::dns::ManualDnsProvider;
 use lib_core::model::ModelManager;
-use lib_web::handlers::web::{auth, dashboard, home, proxy};
+use lib_web::handlers::web::{
+    access_list, auth, certificate, dashboard, dead_host, home, proxy,
+    redirection, stream, upstream,
+};
 
 // region:    --- Modules
+pub mod routes_acme;
 mod routes_fragmant;
 pub mod routes_static;
 
 // endregion: --- Modules
 
-pub fn routes(mm: ModelManager) -> Router {
+pub fn routes(mm: ModelManager, manual_dns: ManualDnsProvider) -> Router {
     Router::new()
         .route("/", get(home::render_home))
         .route("/login", get(auth::render_login))
         .route("/register", get(auth::render_register))
         .route("/dashboard", get(dashboard::render_dashboard))
         .route("/proxy", get(proxy::render_proxy))
-        .nest_service("/fragmant", routes_fragmant::routes(mm.clone()))
+        .route("/redirection", get(redirection::render_redirection))
+        .route("/stream", get(stream::render_stream))
+        .route("/404-host", get(dead_host::render_dead_host))
+        .route("/upstreams", get(upstream::render_upstream))
+        .route("/access-lists", get(access_list::render_access_list))
+        .route("/certificates", get(certificate::render_certificates))
+        .nest_service(
+            "/fragmant",
+            routes_fragmant::routes(mm.clone(), manual_dns),
+        )
         .with_state(mm)
 }
@@ -0,0 +1,14 @@
+use axum::{Router, routing::get};
+use lib_core::acme::Http01Challenges;
+use lib_web::handlers::acme::acme_challenge_handler;
+
+/// Must stay reachable over plain http on every domain, nginx forwards
+/// `/.well-known/acme-challenge/` here.
+pub fn routes(challenges: Http01Challenges) -> Router {
+    Router::new()
+        .route(
+            "/.well-known/acme-challenge/{token}",
+            get(acme_challenge_handler),
+        )
+        .with_state(challenges)
+}
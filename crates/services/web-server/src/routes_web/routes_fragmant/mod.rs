@@ -1,6 +1,198 @@
-use axum::Router;
+use axum::routing::{delete, get, post, put};
+use axum::{Router, middleware};
+use lib_core::acme::dns::ManualDnsProvider;
 use lib_core::model::ModelManager;
+use lib_web::handlers::web::fragmant::{
+    access_list, certificate, config_apply, dead_host, proxy_host,
+    redirection_host, stream_host, upstream_group,
+};
+use lib_web::middleware::mw_auth::mw_ctx_require;
 
-pub fn routes(_mm: ModelManager) -> Router {
+pub fn routes(mm: ModelManager, manual_dns: ManualDnsProvider) -> Router {
     Router::new()
+        .route("/proxy-hosts", post(proxy_host::fragmant_proxy_host_create))
+        .route(
+            "/proxy-hosts/rows",
+            get(proxy_host::fragmant_proxy_host_rows),
+        )
+        .route(
+            "/proxy-hosts/form",
+            get(proxy_host::fragmant_proxy_host_new_form)
+                .delete(proxy_host::fragmant_proxy_host_close_form),
+        )
+        .route(
+            "/proxy-hosts/{id}",
+            put(proxy_host::fragmant_proxy_host_update)
+                .delete(proxy_host::fragmant_proxy_host_delete),
+        )
+        .route(
+            "/proxy-hosts/form/locations",
+            post(proxy_host::fragmant_proxy_host_add_location),
+        )
+        .route(
+            "/proxy-hosts/form/locations/{index}",
+            delete(proxy_host::fragmant_proxy_host_remove_location),
+        )
+        .route(
+            "/proxy-hosts/{id}/form/locations",
+            post(proxy_host::fragmant_proxy_host_add_location),
+        )
+        .route(
+            "/proxy-hosts/{id}/form/locations/{index}",
+            delete(proxy_host::fragmant_proxy_host_remove_location),
+        )
+        .route(
+            "/proxy-hosts/{id}/form",
+            get(proxy_host::fragmant_proxy_host_edit_form),
+        )
+        .route(
+            "/redirection-hosts",
+            post(redirection_host::fragmant_redirection_host_create),
+        )
+        .route(
+            "/redirection-hosts/rows",
+            get(redirection_host::fragmant_redirection_host_rows),
+        )
+        .route(
+            "/redirection-hosts/form",
+            get(redirection_host::fragmant_redirection_host_new_form)
+                .delete(redirection_host::fragmant_redirection_host_close_form),
+        )
+        .route(
+            "/redirection-hosts/{id}",
+            put(redirection_host::fragmant_redirection_host_update)
+                .delete(redirection_host::fragmant_redirection_host_delete),
+        )
+        .route(
+            "/redirection-hosts/{id}/form",
+            get(redirection_host::fragmant_redirection_host_edit_form),
+        )
+        .route(
+            "/stream-hosts",
+            post(stream_host::fragmant_stream_host_create),
+        )
+        .route(
+            "/stream-hosts/rows",
+            get(stream_host::fragmant_stream_host_rows),
+        )
+        .route(
+            "/stream-hosts/form",
+            get(stream_host::fragmant_stream_host_new_form)
+                .delete(stream_host::fragmant_stream_host_close_form),
+        )
+        .route(
+            "/stream-hosts/{id}",
+            put(stream_host::fragmant_stream_host_update)
+                .delete(stream_host::fragmant_stream_host_delete),
+        )
+        .route(
+            "/stream-hosts/{id}/form",
+            get(stream_host::fragmant_stream_host_edit_form),
+        )
+        .route("/dead-hosts", post(dead_host::fragmant_dead_host_create))
+        .route("/dead-hosts/rows", get(dead_host::fragmant_dead_host_rows))
+        .route(
+            "/dead-hosts/form",
+            get(dead_host::fragmant_dead_host_new_form)
+                .delete(dead_host::fragmant_dead_host_close_form),
+        )
+        .route(
+            "/dead-hosts/{id}",
+            put(dead_host::fragmant_dead_host_update)
+                .delete(dead_host::fragmant_dead_host_delete),
+        )
+        .route(
+            "/dead-hosts/{id}/form",
+            get(dead_host::fragmant_dead_host_edit_form),
+        )
+        .route(
+            "/upstream-groups",
+            post(upstream_group::fragmant_upstream_group_create),
+        )
+        .route(
+            "/upstream-groups/rows",
+            get(upstream_group::fragmant_upstream_group_rows),
+        )
+        .route(
+            "/upstream-groups/form",
+            get(upstream_group::fragmant_upstream_group_new_form)
+                .delete(upstream_group::fragmant_upstream_group_close_form),
+        )
+        .route(
+            "/upstream-groups/{id}",
+            put(upstream_group::fragmant_upstream_group_update)
+                .delete(upstream_group::fragmant_upstream_group_delete),
+        )
+        .route(
+            "/upstream-groups/{id}/form",
+            get(upstream_group::fragmant_upstream_group_edit_form),
+        )
+        .route(
+            "/access-lists",
+            post(access_list::fragmant_access_list_create),
+        )
+        .route(
+            "/access-lists/rows",
+            get(access_list::fragmant_access_list_rows),
+        )
+        .route(
+            "/access-lists/form",
+            get(access_list::fragmant_access_list_new_form)
+                .delete(access_list::fragmant_access_list_close_form),
+        )
+        .route(
+            "/access-lists/{id}",
+            put(access_list::fragmant_access_list_update)
+                .delete(access_list::fragmant_access_list_delete),
+        )
+        .route(
+            "/access-lists/{id}/form",
+            get(access_list::fragmant_access_list_edit_form),
+        )
+        .route(
+            "/certificates",
+            post(certificate::fragmant_certificate_create),
+        )
+        .route(
+            "/certificates/rows",
+            get(certificate::fragmant_certificate_rows),
+        )
+        .route(
+            "/certificates/form",
+            get(certificate::fragmant_certificate_new_form)
+                .delete(certificate::fragmant_certificate_close_form),
+        )
+        .route(
+            "/certificates/custom",
+            post(certificate::fragmant_certificate_create_custom),
+        )
+        .route(
+            "/certificates/custom/form",
+            get(certificate::fragmant_certificate_custom_form),
+        )
+        .route(
+            "/certificates/{id}",
+            delete(certificate::fragmant_certificate_delete),
+        )
+        .route(
+            "/config-apply/status",
+            get(config_apply::fragmant_config_apply_status),
+        )
+        .with_state(mm)
+        .merge(routes_dns_records(manual_dns))
+        .route_layer(middleware::from_fn(mw_ctx_require))
+}
+
+/// Records of the manual DNS provider, shared with the renewal task.
+fn routes_dns_records(manual_dns: ManualDnsProvider) -> Router {
+    Router::new()
+        .route(
+            "/certificates/dns-records",
+            get(certificate::fragmant_dns_records),
+        )
+        .route(
+            "/certificates/dns-records/{id}/confirm",
+            post(certificate::fragmant_dns_record_confirm),
+        )
+        .with_state(manual_dns)
 }
@@ -0,0 +1,33 @@
+-- Proxy host
+CREATE TABLE "proxy_host" (
+  serial_id INTEGER PRIMARY KEY AUTOINCREMENT,
+  owner_serial_id INTEGER NOT NULL,
+  -- source
+  domain_names TEXT NOT NULL, -- json array of domain names
+  -- destination
+  forward_scheme TEXT NOT NULL DEFAULT 'http',
+  forward_host TEXT NOT NULL,
+  forward_port INTEGER NOT NULL,
+  -- options
+  cache_assets INTEGER NOT NULL DEFAULT 0,
+  block_exploits INTEGER NOT NULL DEFAULT 0,
+  allow_websocket_upgrade INTEGER NOT NULL DEFAULT 0,
+  -- ssl
+  ssl_forced INTEGER NOT NULL DEFAULT 0,
+  http2_support INTEGER NOT NULL DEFAULT 0,
+  hsts_enabled INTEGER NOT NULL DEFAULT 0,
+  hsts_subdomains INTEGER NOT NULL DEFAULT 0,
+
+  enabled INTEGER NOT NULL DEFAULT 1,
+  -- timestamps
+  ctime TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+  mtime TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+
+  CHECK (forward_scheme IN ('http', 'https')),
+  CHECK (forward_port BETWEEN 1 AND 65535),
+
+  FOREIGN KEY(owner_serial_id)
+    REFERENCES users (serial_id)
+    ON UPDATE CASCADE
+    ON DELETE RESTRICT
+) STRICT;
@@ -0,0 +1,14 @@
+-- Config apply
+-- One row per run of the nginx apply pipeline.
+CREATE TABLE "config_apply" (
+  serial_id INTEGER PRIMARY KEY AUTOINCREMENT,
+  -- generation directory name, NULL when nothing was staged
+  generation TEXT,
+  status TEXT NOT NULL,
+  -- nginx output (validation or reload errors)
+  output TEXT NOT NULL DEFAULT '',
+  -- timestamps
+  ctime TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+
+  CHECK (status IN ('Applied', 'Invalid', 'ReloadFailed', 'Failed'))
+) STRICT;
@@ -0,0 +1,28 @@
+-- Redirection host
+CREATE TABLE "redirection_host" (
+  serial_id INTEGER PRIMARY KEY AUTOINCREMENT,
+  owner_serial_id INTEGER NOT NULL,
+  -- source
+  domain_names TEXT NOT NULL, -- json array of domain names
+  -- destination
+  forward_url TEXT NOT NULL,
+  redirect_code INTEGER NOT NULL DEFAULT 301,
+  preserve_path INTEGER NOT NULL DEFAULT 1,
+  -- ssl
+  ssl_forced INTEGER NOT NULL DEFAULT 0,
+  http2_support INTEGER NOT NULL DEFAULT 0,
+  hsts_enabled INTEGER NOT NULL DEFAULT 0,
+  hsts_subdomains INTEGER NOT NULL DEFAULT 0,
+
+  enabled INTEGER NOT NULL DEFAULT 1,
+  -- timestamps
+  ctime TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+  mtime TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+
+  CHECK (redirect_code IN (301, 302, 307, 308)),
+
+  FOREIGN KEY(owner_serial_id)
+    REFERENCES users (serial_id)
+    ON UPDATE CASCADE
+    ON DELETE RESTRICT
+) STRICT;
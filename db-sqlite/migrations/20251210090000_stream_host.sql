@@ -0,0 +1,25 @@
+-- Stream host
+CREATE TABLE "stream_host" (
+  serial_id INTEGER PRIMARY KEY AUTOINCREMENT,
+  owner_serial_id INTEGER NOT NULL,
+  -- source
+  incoming_port INTEGER NOT NULL,
+  protocol TEXT NOT NULL DEFAULT 'tcp',
+  -- destination
+  forward_host TEXT NOT NULL,
+  forward_port INTEGER NOT NULL,
+
+  enabled INTEGER NOT NULL DEFAULT 1,
+  -- timestamps
+  ctime TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+  mtime TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+
+  CHECK (incoming_port BETWEEN 1 AND 65535),
+  CHECK (protocol IN ('tcp', 'udp', 'both')),
+  CHECK (forward_port BETWEEN 1 AND 65535),
+
+  FOREIGN KEY(owner_serial_id)
+    REFERENCES users (serial_id)
+    ON UPDATE CASCADE
+    ON DELETE RESTRICT
+) STRICT;
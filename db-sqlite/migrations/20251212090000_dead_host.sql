@@ -0,0 +1,19 @@
+-- 404 host
+CREATE TABLE "dead_host" (
+  serial_id INTEGER PRIMARY KEY AUTOINCREMENT,
+  owner_serial_id INTEGER NOT NULL,
+  -- source
+  domain_names TEXT NOT NULL, -- json array of domain names
+  -- custom error page, path relative to the static folder
+  error_page TEXT,
+
+  enabled INTEGER NOT NULL DEFAULT 1,
+  -- timestamps
+  ctime TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+  mtime TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+
+  FOREIGN KEY(owner_serial_id)
+    REFERENCES users (serial_id)
+    ON UPDATE CASCADE
+    ON DELETE RESTRICT
+) STRICT;
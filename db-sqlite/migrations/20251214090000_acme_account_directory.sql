@@ -0,0 +1,3 @@
+-- Acme account, one per directory (e.g. Let's Encrypt production, staging)
+ALTER TABLE "acme_account" ADD COLUMN directory_url TEXT NOT NULL DEFAULT '';
+CREATE INDEX acme_account_directory_url_idx ON acme_account (directory_url);
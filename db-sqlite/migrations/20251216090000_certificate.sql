@@ -0,0 +1,30 @@
+-- Certificate
+CREATE TABLE "certificate" (
+  serial_id INTEGER PRIMARY KEY AUTOINCREMENT,
+  owner_serial_id INTEGER NOT NULL,
+  nice_name TEXT NOT NULL DEFAULT '',
+  -- 'acme' are issued and renewed by the server, 'custom' are uploaded
+  provider TEXT NOT NULL DEFAULT 'acme',
+  -- requested domains, then the SAN list of the issued certificate
+  domain_names TEXT NOT NULL, -- json array of domain names
+  -- empty until issued
+  certificate_pem TEXT NOT NULL DEFAULT '', -- leaf first, then the chain
+  private_key_pem TEXT NOT NULL DEFAULT '',
+  issuer TEXT NOT NULL DEFAULT '',
+  not_before TEXT,
+  not_after TEXT,
+  -- renewal
+  renew_time TEXT, -- last issuance attempt
+  renew_error TEXT, -- error of the last attempt, NULL when it succeeded
+
+  -- timestamps
+  ctime TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+  mtime TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+
+  CHECK (provider IN ('acme', 'custom')),
+
+  FOREIGN KEY(owner_serial_id)
+    REFERENCES users (serial_id)
+    ON UPDATE CASCADE
+    ON DELETE RESTRICT
+) STRICT;
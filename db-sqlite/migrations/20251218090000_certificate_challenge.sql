@@ -0,0 +1,3 @@
+-- Certificate, how the ACME authorizations are validated
+ALTER TABLE "certificate" ADD COLUMN challenge TEXT NOT NULL DEFAULT 'http-01'
+  CHECK (challenge IN ('http-01', 'dns-01'));
@@ -0,0 +1,43 @@
+-- Access list
+CREATE TABLE "access_list" (
+  serial_id INTEGER PRIMARY KEY AUTOINCREMENT,
+  owner_serial_id INTEGER NOT NULL,
+  name TEXT NOT NULL,
+  -- 'all' requires both the ip rules and the basic auth, 'any' either one
+  satisfy TEXT NOT NULL DEFAULT 'all',
+  rules TEXT NOT NULL DEFAULT '[]', -- json array of {action, address}, in order
+
+  -- timestamps
+  ctime TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+  mtime TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+
+  CHECK (satisfy IN ('all', 'any')),
+
+  FOREIGN KEY(owner_serial_id)
+    REFERENCES users (serial_id)
+    ON UPDATE CASCADE
+    ON DELETE RESTRICT
+) STRICT;
+
+-- Basic auth user of an access list
+CREATE TABLE "access_list_user" (
+  serial_id INTEGER PRIMARY KEY AUTOINCREMENT,
+  access_list_serial_id INTEGER NOT NULL,
+  username TEXT NOT NULL,
+  pwd TEXT NOT NULL, -- htpasswd scheme of lib-auth, readable by nginx
+
+  -- timestamps
+  ctime TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+  mtime TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+
+  UNIQUE (access_list_serial_id, username),
+
+  FOREIGN KEY(access_list_serial_id)
+    REFERENCES access_list (serial_id)
+    ON UPDATE CASCADE
+    ON DELETE CASCADE
+) STRICT;
+
+-- Proxy host, public when NULL
+ALTER TABLE "proxy_host" ADD COLUMN access_list_serial_id INTEGER
+  REFERENCES access_list (serial_id) ON DELETE RESTRICT;
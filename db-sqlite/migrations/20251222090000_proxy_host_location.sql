@@ -0,0 +1,2 @@
+-- Custom locations of a proxy host, rendered before `location /`
+ALTER TABLE "proxy_host" ADD COLUMN locations TEXT NOT NULL DEFAULT '[]'; -- json array of {path, forwardScheme, forwardHost, forwardPort, headers, advanced}, in order
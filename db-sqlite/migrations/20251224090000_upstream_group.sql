@@ -0,0 +1,47 @@
+-- Upstream group, a load balanced set of backends for proxy hosts
+CREATE TABLE "upstream_group" (
+  serial_id INTEGER PRIMARY KEY AUTOINCREMENT,
+  owner_serial_id INTEGER NOT NULL,
+  name TEXT NOT NULL,
+  balance TEXT NOT NULL DEFAULT 'round_robin',
+
+  -- timestamps
+  ctime TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+  mtime TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+
+  CHECK (balance IN ('round_robin', 'least_conn', 'ip_hash')),
+
+  FOREIGN KEY(owner_serial_id)
+    REFERENCES users (serial_id)
+    ON UPDATE CASCADE
+    ON DELETE RESTRICT
+) STRICT;
+
+-- Backend of an upstream group, NULL parameters keep the nginx defaults
+CREATE TABLE "upstream_group_server" (
+  serial_id INTEGER PRIMARY KEY AUTOINCREMENT,
+  upstream_group_serial_id INTEGER NOT NULL,
+  position INTEGER NOT NULL, -- order of the servers in the group
+  host TEXT NOT NULL,
+  port INTEGER NOT NULL,
+  weight INTEGER,
+  max_fails INTEGER,
+  fail_timeout INTEGER, -- seconds
+  backup INTEGER NOT NULL DEFAULT 0,
+
+  -- timestamps
+  ctime TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+  mtime TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+
+  CHECK (port BETWEEN 1 AND 65535),
+  CHECK (weight IS NULL OR weight >= 1),
+
+  FOREIGN KEY(upstream_group_serial_id)
+    REFERENCES upstream_group (serial_id)
+    ON UPDATE CASCADE
+    ON DELETE CASCADE
+) STRICT;
+
+-- Proxy host, forwards to forward_host:forward_port when NULL
+ALTER TABLE "proxy_host" ADD COLUMN upstream_group_serial_id INTEGER
+  REFERENCES upstream_group (serial_id) ON DELETE RESTRICT;
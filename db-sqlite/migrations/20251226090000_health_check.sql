@@ -0,0 +1,25 @@
+-- Proxy host, probed by a TCP connect when health_check_path is NULL,
+-- by a HTTP GET of the path otherwise
+ALTER TABLE "proxy_host" ADD COLUMN health_check_path TEXT;
+ALTER TABLE "proxy_host" ADD COLUMN health_check_interval INTEGER NOT NULL
+  DEFAULT 30 CHECK (health_check_interval BETWEEN 5 AND 3600); -- seconds
+
+-- Health check, one row per probe, only the last ones of each host are kept
+CREATE TABLE "health_check" (
+  serial_id INTEGER PRIMARY KEY AUTOINCREMENT,
+  proxy_host_serial_id INTEGER NOT NULL,
+  healthy INTEGER NOT NULL,
+  latency_ms INTEGER, -- NULL when the target did not answer
+  error TEXT,
+
+  -- timestamps
+  ctime TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+
+  FOREIGN KEY(proxy_host_serial_id)
+    REFERENCES proxy_host (serial_id)
+    ON UPDATE CASCADE
+    ON DELETE CASCADE
+) STRICT;
+
+CREATE INDEX "health_check_proxy_host_idx"
+  ON "health_check" (proxy_host_serial_id, serial_id);
@@ -0,0 +1,3 @@
+-- Proxy host, served over https with the certificate, http only when NULL
+ALTER TABLE "proxy_host" ADD COLUMN certificate_serial_id INTEGER
+  REFERENCES certificate (serial_id) ON DELETE RESTRICT;
@@ -0,0 +1,2 @@
+-- Proxy host, raw nginx directives appended to its server block
+ALTER TABLE "proxy_host" ADD COLUMN advanced TEXT NOT NULL DEFAULT '';
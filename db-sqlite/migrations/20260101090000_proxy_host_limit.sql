@@ -0,0 +1,5 @@
+-- Proxy host, limits per client address, unlimited when NULL
+ALTER TABLE "proxy_host" ADD COLUMN rate_limit INTEGER; -- requests per second
+ALTER TABLE "proxy_host" ADD COLUMN rate_limit_burst INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE "proxy_host" ADD COLUMN rate_limit_nodelay INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE "proxy_host" ADD COLUMN connection_limit INTEGER; -- concurrent connections
@@ -0,0 +1,7 @@
+import { defaultSetup } from "$utils/defaultSetup.js";
+
+function setup() {
+  defaultSetup();
+}
+
+setup();